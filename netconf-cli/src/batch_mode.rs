@@ -0,0 +1,150 @@
+use std::{io::BufRead, net::IpAddr, path::PathBuf};
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::{cli_manager::CliManager, command_handler::CommandHandler};
+
+/// Runner of the non-interactive (batch) mode, executing the commands one by one
+/// and keeping track of their failures.
+///
+/// Stops on the first failed command, unless asked to keep going.
+/// Any pending NETCONF session is closed when the runner is finished.
+pub struct BatchRunner {
+    cli_manager: CliManager,
+    command_handler: CommandHandler,
+    keep_going: bool,
+    executed: usize,
+    failures: Vec<String>,
+    stopped: bool,
+}
+
+impl BatchRunner {
+    pub fn new(keep_going: bool, config_path: Option<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            cli_manager: CliManager::new_batch(config_path)?,
+            command_handler: CommandHandler::new(),
+            keep_going,
+            executed: 0,
+            failures: vec![],
+            stopped: false,
+        })
+    }
+
+    /// Open the NETCONF session, used by all the following commands.
+    ///
+    /// Failure to connect stops the batch, regardless of the keep-going mode.
+    pub fn connect(&mut self, address: IpAddr, port: u16, user: &str, password: Option<&str>) {
+        let mut tokens = vec![
+            "send".to_string(),
+            "hello".to_string(),
+            format!("--address={}", address),
+            format!("--port={}", port),
+            format!("--user={}", user),
+        ];
+        if let Some(password) = password {
+            tokens.push(format!("--password={}", password));
+        }
+        // do not report the password
+        let description = format!("connect {}@{}:{}", user, address, port);
+        self.connect_with("--host", &description, tokens);
+    }
+
+    /// Open the NETCONF session to the server of named profile, used by all the following commands.
+    ///
+    /// Failure to connect stops the batch, regardless of the keep-going mode.
+    pub fn connect_profile(&mut self, profile: &str) {
+        let tokens = vec!["connect".to_string(), profile.to_string()];
+        let description = tokens.join(" ");
+        self.connect_with("--profile", &description, tokens);
+    }
+
+    fn connect_with(&mut self, origin: &str, description: &str, tokens: Vec<String>) {
+        let result = self
+            .command_handler
+            .handle_tokens(&mut self.cli_manager, tokens);
+        self.record(origin, description, result);
+        if !self.failures.is_empty() {
+            self.stopped = true;
+        }
+    }
+
+    /// Execute a single command given as separate tokens, e.g. from the application arguments.
+    pub fn run_tokens(&mut self, origin: &str, tokens: Vec<String>) {
+        if self.stopped {
+            return;
+        }
+        let description = tokens.join(" ");
+        let result = self
+            .command_handler
+            .handle_tokens(&mut self.cli_manager, tokens);
+        self.record(origin, &description, result);
+    }
+
+    /// Execute all the commands of the script, one per line.
+    ///
+    /// Empty lines & comments starting with `#` are skipped.
+    pub fn run_script(&mut self, name: &str, script: impl BufRead) -> Result<()> {
+        for (index, line) in script.lines().enumerate() {
+            if self.stopped {
+                break;
+            }
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let origin = format!("{}:{}", name, index + 1);
+            let result = self
+                .command_handler
+                .handle_line(&mut self.cli_manager, line);
+            self.record(&origin, line, result);
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, origin: &str, description: &str, result: Result<()>) {
+        self.executed += 1;
+        if let Err(err) = result {
+            let err = err.to_string();
+            eprintln!("{}: {}", origin, err.trim().yellow());
+            self.failures.push(format!(
+                "{}: {} - {}",
+                origin,
+                description,
+                first_line(&err)
+            ));
+            if !self.keep_going {
+                self.stopped = true;
+            }
+        }
+    }
+
+    /// Print the summary of executed commands & close the pending session.
+    ///
+    /// Returns `true` if all the commands succeeded.
+    pub fn finish(self) -> bool {
+        let summary = format!(
+            "Executed {} command(s), {} failed",
+            self.executed,
+            self.failures.len()
+        );
+        match self.failures.is_empty() {
+            true => eprintln!("{}", summary.green()),
+            false => {
+                eprintln!("{}", summary.bright_red());
+                for failure in self.failures.iter() {
+                    eprintln!("  {}", failure);
+                }
+                if self.stopped && !self.keep_going {
+                    eprintln!("Stopped after the first failure, use --keep-going to continue with the following commands.");
+                }
+            }
+        }
+        self.failures.is_empty()
+    }
+}
+
+fn first_line(s: &str) -> &str {
+    s.trim().lines().next().unwrap_or_default()
+}
@@ -1,32 +1,78 @@
 mod cli_config;
+mod config_file;
+mod credentials;
+mod redacting_history;
 
+mod completion;
 mod custom_completer;
 mod custom_hinter;
 
 mod custom_prompt;
 use custom_prompt::CustomPrompt;
 
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use clap::CommandFactory;
+use crossterm::event::{KeyCode, KeyModifiers};
 use netconf_client::NetconfSession;
-use reedline::{FileBackedHistory, Reedline, Signal, HISTORY_SIZE};
+use reedline::{
+    default_emacs_keybindings, ColumnarMenu, Emacs, FileBackedHistory, Reedline, ReedlineEvent,
+    ReedlineMenu, Signal, HISTORY_SIZE,
+};
 
-pub use self::cli_config::{CliConfig, DumpXmlFormat};
+pub use self::cli_config::{CliConfig, DumpXmlFormat, LogLevel};
+use self::completion::{CompletionContext, SharedCompletionContext};
+pub use self::config_file::{default_config_path, Profile};
+use self::config_file::{save_settings, ConfigFile};
+pub use self::credentials::Credentials;
+use self::custom_completer::CustomCompleter;
+use self::custom_hinter::CustomHinter;
+use self::redacting_history::RedactingHistory;
+use crate::command_handler::RootArgs;
 
 const HISTORY_FILE: &str = "netconf-cli-history.txt";
+const CREDENTIALS_STORE_FILE: &str = "credentials.age";
+const COMPLETION_MENU: &str = "completion_menu";
 
 pub struct CliManager {
-    editor: Reedline,
+    /// Line editor of the interactive mode, none in the batch mode.
+    editor: Option<Reedline>,
     prompt: CustomPrompt,
     config: CliConfig,
-    pending_session: Option<NetconfSession>,
+    /// Opened NETCONF sessions by their names.
+    sessions: BTreeMap<String, NetconfSession>,
+    /// Name of the session targeted by commands without explicit `--session`.
+    active_session: Option<String>,
+    profiles: BTreeMap<String, Profile>,
+    credentials: Credentials,
+    /// Live values offered by the completion of the line editor.
+    completion_context: SharedCompletionContext,
+    /// Configuration file the settings are persisted into, none in the batch mode.
+    settings_file: Option<PathBuf>,
 }
 
 pub trait CliManagerCommandApi {
     fn config(&self) -> &CliConfig;
     fn config_mut(&mut self) -> &mut CliConfig;
     fn prompt_mut(&mut self) -> &mut CustomPrompt;
-    fn pending_session(&self) -> Option<&NetconfSession>;
-    fn pending_session_mut(&mut self) -> Option<&mut NetconfSession>;
-    fn set_pending_session(&mut self, session: Option<NetconfSession>);
+    /// Session of the given name, or the active one if no name is given.
+    fn session(&self, name: Option<&str>) -> Option<&NetconfSession>;
+    fn session_mut(&mut self, name: Option<&str>) -> Option<&mut NetconfSession>;
+    fn sessions(&self) -> &BTreeMap<String, NetconfSession>;
+    fn active_session(&self) -> Option<&str>;
+    /// Store newly opened session under the name, making it the active one.
+    fn add_session(&mut self, name: String, session: NetconfSession);
+    fn set_active_session(&mut self, name: Option<String>);
+    /// Forget the session, without closing it. Active session is unset if removed.
+    fn remove_session(&mut self, name: &str) -> Option<NetconfSession>;
+    fn profiles(&self) -> &BTreeMap<String, Profile>;
+    fn credentials_mut(&mut self) -> &mut Credentials;
+    /// Persist current settings into the configuration file, if any.
+    fn save_settings(&self) -> anyhow::Result<()>;
 }
 
 impl CliManagerCommandApi for CliManager {
@@ -42,79 +88,194 @@ impl CliManagerCommandApi for CliManager {
         &mut self.prompt
     }
 
-    fn pending_session(&self) -> Option<&NetconfSession> {
-        self.pending_session.as_ref()
+    fn session(&self, name: Option<&str>) -> Option<&NetconfSession> {
+        let name = name.or(self.active_session.as_deref())?;
+        self.sessions.get(name)
+    }
+
+    fn session_mut(&mut self, name: Option<&str>) -> Option<&mut NetconfSession> {
+        let name = name.or(self.active_session.as_deref())?;
+        self.sessions.get_mut(name)
+    }
+
+    fn sessions(&self) -> &BTreeMap<String, NetconfSession> {
+        &self.sessions
+    }
+
+    fn active_session(&self) -> Option<&str> {
+        self.active_session.as_deref()
     }
 
-    fn pending_session_mut(&mut self) -> Option<&mut NetconfSession> {
-        self.pending_session.as_mut()
+    fn add_session(&mut self, name: String, session: NetconfSession) {
+        self.sessions.insert(name.clone(), session);
+        self.set_active_session(Some(name));
     }
 
-    fn set_pending_session(&mut self, session: Option<NetconfSession>) {
-        self.pending_session = session;
-        let new_prompt = self.pending_session().map(|session| {
+    fn set_active_session(&mut self, name: Option<String>) {
+        self.active_session = name;
+        let new_prompt = self.session(None).map(|session| {
             format!(
-                "session-id:{}@{}",
+                "{} session-id:{}@{}",
+                self.active_session.as_deref().unwrap_or_default(),
                 session.session_id().unwrap_or(0),
                 session.target_string()
             )
         });
         self.prompt_mut().set_sub_mode(new_prompt);
     }
+
+    fn remove_session(&mut self, name: &str) -> Option<NetconfSession> {
+        let session = self.sessions.remove(name);
+        if self.active_session.as_deref() == Some(name) {
+            self.set_active_session(None);
+        }
+        session
+    }
+
+    fn profiles(&self) -> &BTreeMap<String, Profile> {
+        &self.profiles
+    }
+
+    fn credentials_mut(&mut self) -> &mut Credentials {
+        &mut self.credentials
+    }
+
+    fn save_settings(&self) -> anyhow::Result<()> {
+        match &self.settings_file {
+            Some(path) => save_settings(path, &self.config),
+            None => Ok(()),
+        }
+    }
 }
 
 impl CliManager {
-    pub fn new() -> anyhow::Result<Self> {
-        let editor_history = Box::new(FileBackedHistory::with_file(
+    /// Manager of the interactive mode, with settings & profiles of the configuration file.
+    ///
+    /// Changed settings are persisted into the same file.
+    pub fn new(config_path: Option<PathBuf>) -> anyhow::Result<Self> {
+        let editor_history = Box::new(RedactingHistory::new(FileBackedHistory::with_file(
             HISTORY_SIZE,
             HISTORY_FILE.into(),
-        )?);
-
-        // let commands = vec![
-        //     "test".into(),
-        //     "hello world".into(),
-        //     "hello world reedline".into(),
-        //     "this is the reedline crate".into(),
-        // ];
-        // let completer = Box::new(DefaultCompleter::new_with_wordlen(commands.clone(), 2));
-        // Use the interactive menu to select options from the completer
-        // let completion_menu = Box::new(CompletionMenu::default());
+        )?));
 
-        // let completer = CustomCompleter::new();
-        // dbg!(&completer);
-        // let completion_menu = Box::new(CompletionMenu::default());
+        let completion_context: SharedCompletionContext =
+            Arc::new(Mutex::new(CompletionContext::default()));
+        let completer = Box::new(CustomCompleter::new(
+            RootArgs::command(),
+            completion_context.clone(),
+        ));
+        let hinter = Box::new(CustomHinter::new(RootArgs::command()));
+        let completion_menu = Box::new(ColumnarMenu::default().with_name(COMPLETION_MENU));
 
-        // let hinter = CustomHinter {};
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(
+            KeyModifiers::NONE,
+            KeyCode::Tab,
+            ReedlineEvent::UntilFound(vec![
+                ReedlineEvent::Menu(COMPLETION_MENU.to_string()),
+                ReedlineEvent::MenuNext,
+            ]),
+        );
 
         let editor = Reedline::create()
             .with_history(editor_history)
-            // .with_completer(completer)
-            // .with_hinter(Box::new(hinter));
-        // .with_menu(completion_menu);
-        ;
+            .with_completer(completer)
+            .with_hinter(hinter)
+            .with_menu(ReedlineMenu::EngineCompleter(completion_menu))
+            .with_edit_mode(Box::new(Emacs::new(keybindings)));
         let prompt = CustomPrompt::new();
 
-        let config = CliConfig::new();
+        let (config, profiles) = load_config(config_path.as_ref())?;
 
         Ok(Self {
-            editor,
+            editor: Some(editor),
             prompt,
             config,
-            pending_session: None,
+            sessions: BTreeMap::new(),
+            active_session: None,
+            profiles,
+            credentials: Credentials::new(credentials_store_path(config_path.as_ref())),
+            completion_context,
+            settings_file: config_path,
+        })
+    }
+
+    /// Manager of the non-interactive (batch) mode, without any line editor & history.
+    ///
+    /// Settings & profiles of the configuration file are used, but changed settings are not persisted.
+    pub fn new_batch(config_path: Option<PathBuf>) -> anyhow::Result<Self> {
+        let (config, profiles) = load_config(config_path.as_ref())?;
+        Ok(Self {
+            editor: None,
+            prompt: CustomPrompt::new(),
+            config,
+            sessions: BTreeMap::new(),
+            active_session: None,
+            profiles,
+            credentials: Credentials::new(credentials_store_path(config_path.as_ref())),
+            completion_context: Default::default(),
+            settings_file: None,
         })
     }
 
     pub fn get_user_input(&mut self) -> anyhow::Result<Signal> {
-        let r = self.editor.read_line(&self.prompt)?;
+        self.update_completion_context();
+        let editor = match &mut self.editor {
+            Some(editor) => editor,
+            None => anyhow::bail!("No user input in batch mode!"),
+        };
+        let r = editor.read_line(&self.prompt)?;
         Ok(r)
     }
 }
 
+impl CliManager {
+    /// Refresh the live values offered by completion, after the previous command.
+    fn update_completion_context(&self) {
+        let active_session = self.session(None);
+        let server_capabilities = active_session.and_then(|x| x.server_capabilities());
+        let context = CompletionContext {
+            profiles: self.profiles.keys().cloned().collect(),
+            sessions: self.sessions.keys().cloned().collect(),
+            session_ids: self
+                .sessions
+                .values()
+                .filter_map(|x| x.session_id())
+                .map(|x| x.to_string())
+                .collect(),
+            datastores: server_capabilities
+                .map(|caps| caps.datastores().iter().map(|x| x.to_string()).collect()),
+            modules: server_capabilities
+                .map(|caps| caps.modules().map(|x| x.module.clone()).collect())
+                .unwrap_or_default(),
+        };
+        *self.completion_context.lock().unwrap() = context;
+    }
+}
+
+fn load_config(path: Option<&PathBuf>) -> anyhow::Result<(CliConfig, BTreeMap<String, Profile>)> {
+    let mut config = CliConfig::new();
+    let file = match path {
+        Some(path) => ConfigFile::load(path)?,
+        None => ConfigFile::default(),
+    };
+    file.apply_settings(&mut config)?;
+    crate::logging::set_level(config.log_level());
+    Ok((config, file.profiles))
+}
+
+/// Credentials store is expected next to the configuration file.
+fn credentials_store_path(config_path: Option<&PathBuf>) -> Option<PathBuf> {
+    config_path.map(|path| path.with_file_name(CREDENTIALS_STORE_FILE))
+}
+
 impl Drop for CliManager {
     fn drop(&mut self) {
-        let _ = self.editor.sync_history();
+        if let Some(editor) = &mut self.editor {
+            let _ = editor.sync_history();
+        }
 
-        if let Some(session) = &mut self.pending_session {
+        for session in self.sessions.values_mut() {
             let _ = session.request_close_session();
         }
     }
@@ -1,20 +1,38 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use anyhow::bail;
-use netconf_client::types::Capability;
+use netconf_client::{diff::ListHints, schema::SchemaHints, types::Capability};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum DumpXmlFormat {
     None,
     Raw,
     Pretty,
+    /// RFC 7951 style JSON of the data, see [`netconf_client::json::JsonEncoder`].
+    Json,
+    Yaml,
+}
+
+/// Verbosity of the diagnostics logged to stderr - of NETCONF sessions, RPCs & SSH.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
 }
 
 pub struct CliConfig {
     request_dump_mode: DumpXmlFormat,
     response_dump_mode: DumpXmlFormat,
     client_capabilities: Vec<Capability>,
-    // log_level: LogLevel, // TODO
+    /// Lists & leaf-lists known to the configuration diff.
+    list_hints: ListHints,
+    /// Modules, lists & leaf types known to the JSON/YAML dumps.
+    schema_hints: SchemaHints,
+    log_level: LogLevel,
 }
 
 impl CliConfig {
@@ -23,6 +41,9 @@ impl CliConfig {
             request_dump_mode: DumpXmlFormat::None,
             response_dump_mode: DumpXmlFormat::Pretty,
             client_capabilities: vec![Capability::Base, Capability::Base11],
+            list_hints: ListHints::new(),
+            schema_hints: SchemaHints::new(),
+            log_level: LogLevel::Warn,
         }
     }
 
@@ -45,6 +66,30 @@ impl CliConfig {
     pub fn client_capabilities(&self) -> &[Capability] {
         &self.client_capabilities
     }
+
+    pub fn list_hints(&self) -> &ListHints {
+        &self.list_hints
+    }
+
+    pub fn set_list_hints(&mut self, hints: ListHints) {
+        self.list_hints = hints;
+    }
+
+    pub fn schema_hints(&self) -> &SchemaHints {
+        &self.schema_hints
+    }
+
+    pub fn set_schema_hints(&mut self, hints: SchemaHints) {
+        self.schema_hints = hints;
+    }
+
+    pub fn log_level(&self) -> LogLevel {
+        self.log_level
+    }
+
+    pub fn set_log_level(&mut self, level: LogLevel) {
+        self.log_level = level;
+    }
 }
 
 impl FromStr for DumpXmlFormat {
@@ -55,13 +100,64 @@ impl FromStr for DumpXmlFormat {
             "none" => Ok(Self::None),
             "raw" => Ok(Self::Raw),
             "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
             s => bail!("Unsupported dump mode: \"{}\"", s),
         }
     }
 }
 
+impl Display for DumpXmlFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DumpXmlFormat::None => "none",
+            DumpXmlFormat::Raw => "raw",
+            DumpXmlFormat::Pretty => "pretty",
+            DumpXmlFormat::Json => "json",
+            DumpXmlFormat::Yaml => "yaml",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 impl DumpXmlFormat {
     pub fn values() -> Vec<&'static str> {
-        vec!["none", "raw", "pretty"]
+        vec!["none", "raw", "pretty", "json", "yaml"]
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            s => bail!("Unsupported log level: \"{}\"", s),
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl LogLevel {
+    pub fn values() -> Vec<&'static str> {
+        vec!["off", "error", "warn", "info", "debug", "trace"]
     }
 }
@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+
+use clap::{Arg, Command};
+use netconf_client::types::Datastore;
+
+/// Values of the live CLI state offered by completion - profiles, opened sessions & details of the active one.
+///
+/// Shared between [`super::CliManager`] updating it before each line is read, and the line editor.
+#[derive(Debug, Default, Clone)]
+pub struct CompletionContext {
+    pub profiles: Vec<String>,
+    pub sessions: Vec<String>,
+    pub session_ids: Vec<String>,
+    /// Datastores advertised by server of the active session, if any.
+    pub datastores: Option<Vec<String>>,
+    /// YANG modules advertised by server of the active session.
+    pub modules: Vec<String>,
+}
+
+pub type SharedCompletionContext = Arc<Mutex<CompletionContext>>;
+
+impl CompletionContext {
+    /// Values offered for the argument of the command at `path`.
+    ///
+    /// Dynamic values of the live session take precedence over the static possible values of the argument.
+    pub fn values(&self, path: &[&str], arg: &Arg) -> Vec<String> {
+        match (path, arg.get_id()) {
+            (_, "session") | ([.., "session", _], "name") => self.sessions.clone(),
+            (_, "profile") => self.profiles.clone(),
+            (_, "session-id") => self.session_ids.clone(),
+            ([.., "modules"], "name") => self.modules.clone(),
+            ([.., "diff"], "old" | "new") => match &self.datastores {
+                Some(datastores) => datastores.clone(),
+                None => Datastore::values().iter().map(|x| x.to_string()).collect(),
+            },
+            _ => {
+                let values: Vec<String> = arg
+                    .get_possible_values()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|x| !x.is_hide_set())
+                    .map(|x| x.get_name().to_string())
+                    .collect();
+                match &self.datastores {
+                    Some(datastores) if is_datastore_arg(&values) => values
+                        .into_iter()
+                        .filter(|x| datastores.contains(x))
+                        .collect(),
+                    _ => values,
+                }
+            }
+        }
+    }
+}
+
+fn is_datastore_arg(values: &[String]) -> bool {
+    let datastores = Datastore::values();
+    !values.is_empty() && values.iter().all(|x| datastores.contains(&x.as_str()))
+}
+
+/// State of the (partially typed) command line, walked through the `clap` command tree.
+pub struct ParsedLine<'a> {
+    /// Innermost (sub)command matched.
+    pub command: &'a Command<'static>,
+    /// Names of the matched (sub)commands, from the root.
+    pub path: Vec<&'a str>,
+    /// Global options of the parent commands, applicable to the innermost one as well.
+    globals: Vec<&'a Arg<'static>>,
+    /// Ids of the options given already.
+    pub present: Vec<&'a str>,
+    /// Number of the positional values given already.
+    pub positionals: usize,
+    /// Option still waiting for its value.
+    pub pending_option: Option<&'a Arg<'static>>,
+}
+
+impl<'a> ParsedLine<'a> {
+    pub fn parse(root: &'a Command<'static>, tokens: &[&str]) -> Self {
+        let mut state = Self {
+            command: root,
+            path: vec![],
+            globals: vec![],
+            present: vec![],
+            positionals: 0,
+            pending_option: None,
+        };
+        for token in tokens {
+            if state.pending_option.take().is_some() {
+                continue;
+            }
+            if let Some(option) = token.strip_prefix("--") {
+                let (name, has_value) = match option.split_once('=') {
+                    Some((name, _)) => (name, true),
+                    None => (option, false),
+                };
+                let arg = state.options().find(|x| x.get_long() == Some(name));
+                if let Some(arg) = arg {
+                    state.present.push(arg.get_id());
+                    if arg.is_takes_value_set() && !has_value {
+                        state.pending_option = Some(arg);
+                    }
+                }
+                continue;
+            }
+            match state.command.find_subcommand(*token) {
+                Some(subcommand) => {
+                    state
+                        .globals
+                        .extend(state.command.get_arguments().filter(|x| x.is_global_set()));
+                    state.command = subcommand;
+                    state.path.push(subcommand.get_name());
+                    state.positionals = 0;
+                }
+                None => state.positionals += 1,
+            }
+        }
+        state
+    }
+
+    /// All the options applicable to the command, incl. the global ones of its parents.
+    pub fn options(&self) -> impl Iterator<Item = &'a Arg<'static>> + '_ {
+        let own = self
+            .command
+            .get_arguments()
+            .filter(|x| x.get_long().is_some() && x.get_id() != "version");
+        let inherited = self
+            .globals
+            .iter()
+            .copied()
+            .filter(|x| !matches!(x.get_id(), "help" | "version"));
+        own.chain(inherited)
+    }
+
+    /// Whether the command requires a subcommand - `clap` derive marks the non-optional ones
+    /// with the (deprecated) `SubcommandRequiredElseHelp` setting.
+    #[allow(deprecated)]
+    pub fn requires_subcommand(&self) -> bool {
+        self.command.is_subcommand_required_set()
+            || self
+                .command
+                .is_set(clap::AppSettings::SubcommandRequiredElseHelp)
+    }
+
+    /// Positional arguments not given yet.
+    pub fn missing_positionals(&self) -> impl Iterator<Item = &'a Arg<'static>> {
+        self.command.get_positionals().skip(self.positionals)
+    }
+}
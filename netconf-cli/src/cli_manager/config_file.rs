@@ -0,0 +1,343 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use netconf_client::{
+    diff::ListHints, schema::SchemaHints, transport::TcpTransport, types::Capability,
+    NetconfSession, Password, SshAuthentication, SshClient,
+};
+use serde::Deserialize;
+use toml_edit::{table, value, Document};
+
+use super::{CliConfig, Credentials};
+
+const CONFIG_FILE: &str = "netconf-cli/config.toml";
+
+/// Default location of the configuration file, e.g. `~/.config/netconf-cli/config.toml` on Linux.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// Content of the TOML configuration file - persisted CLI settings & named device profiles.
+///
+/// ```toml
+/// [settings]
+/// request-dump = "none"
+/// response-dump = "pretty"
+/// log-level = "warn"
+///
+/// [profiles.lab-router]
+/// host = "10.0.0.1"
+/// user = "admin"
+/// auth = "key"
+/// key-path = "~/.ssh/id_ed25519"
+/// timeout = 10
+///
+/// [profiles.mock]
+/// host = "127.0.0.1"
+/// port = 8830
+/// transport = "tcp"
+/// user = "test"
+///
+/// [diff]
+/// lists = { interface = ["name"] }
+/// leaf-lists = ["dns"]
+///
+/// [schema]
+/// yang = ["~/yang"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default)]
+    pub diff: DiffSettings,
+    #[serde(default)]
+    pub schema: SchemaSettings,
+}
+
+/// [`CliConfig`] settings persisted between runs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    pub request_dump: Option<String>,
+    pub response_dump: Option<String>,
+    pub log_level: Option<String>,
+}
+
+/// Lists & leaf-lists lined up by the configuration diff, in lack of the YANG schema.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DiffSettings {
+    /// Key leaves of the lists, by the list name.
+    #[serde(default)]
+    pub lists: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub leaf_lists: Vec<String>,
+}
+
+/// YANG modules providing the lists & leaf types to the JSON/YAML dumps and the configuration diff.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SchemaSettings {
+    /// YANG files, or directories of `*.yang` files.
+    #[serde(default)]
+    pub yang: Vec<PathBuf>,
+}
+
+/// Transport of the NETCONF session of the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransportKind {
+    #[default]
+    Ssh,
+    /// Plain TCP without any authentication & encryption, e.g. for testing against `netconf-mock-server`.
+    Tcp,
+}
+
+/// SSH authentication method of the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    #[default]
+    Password,
+    Key,
+    Agent,
+}
+
+impl Display for AuthMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AuthMethod::Password => "password",
+            AuthMethod::Key => "key",
+            AuthMethod::Agent => "agent",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Named connection profile of the NETCONF server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    /// Host name or IP address of the server.
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub transport: TransportKind,
+    pub user: String,
+    #[serde(default)]
+    pub auth: AuthMethod,
+    /// Password of the `password` authentication, looked up in the credentials if left out.
+    pub password: Option<String>,
+    /// Private key of the `key` authentication.
+    pub key_path: Option<PathBuf>,
+    pub key_passphrase: Option<String>,
+    /// Client capabilities (URNs) advertised in `<hello>`, instead of the configured ones.
+    pub capabilities: Option<Vec<String>>,
+    /// Timeout of the connection & SSH operations, in seconds.
+    pub timeout: Option<u64>,
+}
+
+fn default_port() -> u16 {
+    830
+}
+
+impl ConfigFile {
+    /// Load the configuration file, falling back to defaults if there is no such file.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml_edit::easy::from_str(&content)
+                .with_context(|| format!("Invalid configuration file {}", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err)
+                .with_context(|| format!("Failed to read configuration file {}", path.display())),
+        }
+    }
+
+    /// Apply the persisted settings on top of the default CLI configuration.
+    pub fn apply_settings(&self, config: &mut CliConfig) -> Result<()> {
+        if let Some(mode) = &self.settings.request_dump {
+            config.set_request_dump_mode(mode.parse()?);
+        }
+        if let Some(mode) = &self.settings.response_dump {
+            config.set_response_dump_mode(mode.parse()?);
+        }
+        if let Some(level) = &self.settings.log_level {
+            config.set_log_level(level.parse()?);
+        }
+
+        let mut hints = ListHints::new();
+        for (name, keys) in self.diff.lists.iter() {
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            hints = hints.list(name, &keys);
+        }
+        for name in self.diff.leaf_lists.iter() {
+            hints = hints.leaf_list(name);
+        }
+
+        if !self.schema.yang.is_empty() {
+            let paths: Vec<PathBuf> = self.schema.yang.iter().map(|x| expand_home(x)).collect();
+            let schema_hints = load_yang(&paths)?;
+            hints = hints.merge(schema_hints.list_hints());
+            config.set_schema_hints(schema_hints);
+        }
+        config.set_list_hints(hints);
+        Ok(())
+    }
+}
+
+/// Schema hints of the YANG files, or directories of `*.yang` files.
+pub fn load_yang(paths: &[PathBuf]) -> Result<SchemaHints> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let entries = fs::read_dir(path)
+                .with_context(|| format!("Failed to read directory {}", path.display()))?;
+            for entry in entries {
+                let file = entry?.path();
+                if file.extension().is_some_and(|x| x == "yang") {
+                    files.push(file);
+                }
+            }
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+
+    let mut sources = vec![];
+    for file in files.iter() {
+        let source = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read YANG file {}", file.display()))?;
+        sources.push(source);
+    }
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    SchemaHints::from_yang(&sources)
+}
+
+/// Store the current CLI settings into the configuration file.
+///
+/// Rest of the file - profiles, comments & formatting, is kept intact.
+pub fn save_settings(path: &Path, config: &CliConfig) -> Result<()> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let mut document: Document = content
+        .parse()
+        .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+    if !document.contains_key("settings") {
+        document["settings"] = table();
+    }
+    document["settings"]["request-dump"] = value(config.request_dump_mode().to_string());
+    document["settings"]["response-dump"] = value(config.response_dump_mode().to_string());
+    document["settings"]["log-level"] = value(config.log_level().to_string());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, document.to_string())
+        .with_context(|| format!("Failed to write configuration file {}", path.display()))
+}
+
+impl Profile {
+    /// Create (not yet connected) NETCONF session to the server of this profile.
+    ///
+    /// Profile capabilities take precedence over the `default_capabilities`, if specified.
+    /// Password left out of the profile is looked up in the `credentials`, for the SSH transport only.
+    pub fn to_session(
+        &self,
+        default_capabilities: &[Capability],
+        credentials: &mut Credentials,
+    ) -> Result<NetconfSession> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .with_context(|| format!("Failed to resolve host \"{}\"", self.host))?
+            .next()
+            .with_context(|| format!("No address found for host \"{}\"", self.host))?
+            .ip();
+
+        let capabilities = match &self.capabilities {
+            Some(urns) => urns
+                .iter()
+                .map(|urn| Capability::from_urn(urn))
+                .collect::<Result<Vec<_>>>()?,
+            None => default_capabilities.to_vec(),
+        };
+        let timeout = self.timeout.map(Duration::from_secs);
+
+        if self.transport == TransportKind::Tcp {
+            let mut transport = TcpTransport::new(SocketAddr::new(address, self.port));
+            if let Some(timeout) = timeout {
+                transport.set_timeout(Some(timeout));
+            }
+            return Ok(NetconfSession::with_transport(
+                Box::new(transport),
+                capabilities,
+            ));
+        }
+
+        let user = self.user.clone();
+        let auth = match self.auth {
+            AuthMethod::Password => {
+                let password = match &self.password {
+                    Some(password) => password.as_str().into(),
+                    None => credentials.password(&self.user, &self.host)?,
+                };
+                SshAuthentication::UserPassword(user, password)
+            }
+            AuthMethod::Key => match &self.key_path {
+                Some(path) => SshAuthentication::KeyFile(
+                    user,
+                    expand_home(path),
+                    self.key_passphrase.as_deref().map(Password::from),
+                ),
+                None => bail!("Missing \"key-path\" for key authentication!"),
+            },
+            AuthMethod::Agent => SshAuthentication::Agent(user),
+        };
+
+        let mut client = SshClient::new(address, self.port, auth);
+        if let Some(timeout) = timeout {
+            client.set_timeout(timeout);
+        }
+        Ok(NetconfSession::with_transport(
+            Box::new(client),
+            capabilities,
+        ))
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.transport {
+            TransportKind::Ssh => write!(
+                f,
+                "{}@{}:{} ({} auth)",
+                self.user, self.host, self.port, self.auth
+            ),
+            TransportKind::Tcp => write!(f, "{}:{} (plain TCP)", self.host, self.port),
+        }
+    }
+}
+
+/// Expand leading `~` of the path to the home directory of the user.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
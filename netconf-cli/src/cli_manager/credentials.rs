@@ -0,0 +1,81 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use age::secrecy::SecretString;
+use anyhow::{Context, Result};
+use netconf_client::Password;
+use zeroize::Zeroizing;
+
+/// Environment variable with the password, used for any user & host.
+const PASSWORD_ENV: &str = "NETCONF_CLI_PASSWORD";
+/// Environment variable with the passphrase of the credentials store.
+const STORE_PASSPHRASE_ENV: &str = "NETCONF_CLI_STORE_PASSPHRASE";
+
+/// Source of the passwords left out of the commands & profiles.
+///
+/// Passwords are looked up in this order:
+/// 1. `NETCONF_CLI_PASSWORD` environment variable
+/// 2. entry `"user@host"` of the credentials store - TOML file encrypted by [age](https://age-encryption.org) passphrase,
+///    e.g. `age -p -o credentials.age credentials.toml`
+/// 3. hidden prompt on the terminal
+pub struct Credentials {
+    store_path: Option<PathBuf>,
+    /// Content of the store, decrypted on the first lookup.
+    store: Option<BTreeMap<String, Password>>,
+}
+
+impl Credentials {
+    pub fn new(store_path: Option<PathBuf>) -> Self {
+        Self {
+            store_path,
+            store: None,
+        }
+    }
+
+    pub fn password(&mut self, user: &str, host: &str) -> Result<Password> {
+        if let Ok(password) = env::var(PASSWORD_ENV) {
+            return Ok(password.into());
+        }
+
+        let key = format!("{}@{}", user, host);
+        if let Some(password) = self.store()?.and_then(|store| store.get(&key)) {
+            return Ok(password.clone());
+        }
+
+        let password = rpassword::prompt_password(format!("Password for {}: ", key))
+            .context("Failed to read the password")?;
+        Ok(password.into())
+    }
+
+    fn store(&mut self) -> Result<Option<&BTreeMap<String, Password>>> {
+        if self.store.is_none() {
+            match &self.store_path {
+                Some(path) if path.exists() => self.store = Some(load_store(path)?),
+                _ => return Ok(None),
+            }
+        }
+        Ok(self.store.as_ref())
+    }
+}
+
+fn load_store(path: &PathBuf) -> Result<BTreeMap<String, Password>> {
+    let encrypted = fs::read(path)
+        .with_context(|| format!("Failed to read credentials store {}", path.display()))?;
+    let passphrase = match env::var(STORE_PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => rpassword::prompt_password(format!("Passphrase of {}: ", path.display()))
+            .context("Failed to read the passphrase")?,
+    };
+
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+    let content = Zeroizing::new(
+        age::decrypt(&identity, &encrypted)
+            .with_context(|| format!("Failed to decrypt credentials store {}", path.display()))?,
+    );
+    let content = std::str::from_utf8(&content)?;
+    let entries: BTreeMap<String, String> = toml_edit::easy::from_str(content)
+        .with_context(|| format!("Invalid credentials store {}", path.display()))?;
+    Ok(entries
+        .into_iter()
+        .map(|(key, password)| (key, password.into()))
+        .collect())
+}
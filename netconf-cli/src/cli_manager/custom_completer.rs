@@ -1,32 +1,78 @@
+use clap::Command;
 use reedline::{Completer, Span, Suggestion};
 
-#[derive(Debug)]
-pub struct CustomCompleter {}
+use super::completion::{ParsedLine, SharedCompletionContext};
+
+/// Completion of the commands, options & their values, driven by the `clap` command tree
+/// and the live state of the CLI.
+pub struct CustomCompleter {
+    root: Command<'static>,
+    context: SharedCompletionContext,
+}
 
 impl CustomCompleter {
-    // pub fn new() -> Self {
-    //     Self {}
-    // }
+    pub fn new(root: Command<'static>, context: SharedCompletionContext) -> Self {
+        Self { root, context }
+    }
 }
 
 impl Completer for CustomCompleter {
-    fn complete(&mut self, line: &str, _pos: usize) -> Vec<reedline::Suggestion> {
-        dbg!(&line, _pos);
-        if line.is_empty() {
-            let root_commands = vec!["hello", "lock", "unlock"];
-            let vv = root_commands
-                .iter()
-                .map(|x| Suggestion {
-                    value: x.to_string(),
-                    description: None,
-                    extra: None,
-                    span: Span { start: 0, end: 0 },
-                    append_whitespace: true,
-                })
-                .collect();
-            dbg!(&vv);
-            return vv;
+    fn complete(&mut self, line: &str, pos: usize) -> Vec<Suggestion> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |x| x + 1);
+        let partial = &line[start..];
+        let tokens: Vec<&str> = line[..start].split_whitespace().collect();
+        let state = ParsedLine::parse(&self.root, &tokens);
+        let context = self.context.lock().unwrap().clone();
+
+        let mut candidates: Vec<(String, Option<String>)> = vec![];
+        if let Some(arg) = state.pending_option {
+            candidates.extend(
+                context
+                    .values(&state.path, arg)
+                    .into_iter()
+                    .map(|x| (x, None)),
+            );
+        } else if partial.starts_with('-') {
+            candidates.extend(
+                state
+                    .options()
+                    .filter(|x| !state.present.contains(&x.get_id()))
+                    .map(|x| {
+                        let name = format!("--{}", x.get_long().unwrap_or_default());
+                        (name, x.get_help().map(str::to_string))
+                    }),
+            );
+        } else {
+            candidates.extend(
+                state
+                    .command
+                    .get_subcommands()
+                    .map(|x| (x.get_name().to_string(), x.get_about().map(str::to_string))),
+            );
+            if let Some(arg) = state.missing_positionals().next() {
+                candidates.extend(
+                    context
+                        .values(&state.path, arg)
+                        .into_iter()
+                        .map(|x| (x, None)),
+                );
+            }
         }
-        vec![]
+
+        candidates
+            .into_iter()
+            .filter(|(value, _)| value.starts_with(partial))
+            .map(|(value, description)| Suggestion {
+                value,
+                description,
+                extra: None,
+                span: Span {
+                    start,
+                    end: line.len(),
+                },
+                append_whitespace: true,
+            })
+            .collect()
     }
 }
@@ -1,26 +1,99 @@
-use reedline::Hinter;
+use clap::Command;
+use colored::Colorize;
+use reedline::{Hinter, History};
 
-pub struct CustomHinter {}
+use super::completion::ParsedLine;
+
+/// Inline hint of the arguments still missing in the command - required positionals, options & subcommand.
+///
+/// Hints are informative only, there is nothing to be accepted into the line.
+pub struct CustomHinter {
+    root: Command<'static>,
+}
+
+impl CustomHinter {
+    pub fn new(root: Command<'static>) -> Self {
+        Self { root }
+    }
+
+    fn missing_arguments(&self, line: &str) -> Vec<String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let state = ParsedLine::parse(&self.root, &tokens);
+
+        // do not hint after a word being typed, unless it is complete (sub)command or option
+        let word_complete = line.ends_with(char::is_whitespace)
+            || state.pending_option.is_some()
+            || state.path.last() == tokens.last();
+        if !word_complete {
+            return vec![];
+        }
+
+        if let Some(arg) = state.pending_option {
+            return vec![value_placeholder(arg.get_id(), arg.get_value_names())];
+        }
+
+        let mut missing: Vec<String> = state
+            .missing_positionals()
+            .filter(|x| x.is_required_set())
+            .map(|x| value_placeholder(x.get_id(), x.get_value_names()))
+            .collect();
+        missing.extend(
+            state
+                .options()
+                .filter(|x| x.is_required_set() && !state.present.contains(&x.get_id()))
+                .map(|x| {
+                    format!(
+                        "--{} {}",
+                        x.get_long().unwrap_or_default(),
+                        value_placeholder(x.get_id(), x.get_value_names())
+                    )
+                }),
+        );
+        if state.requires_subcommand() {
+            missing.push("<command>".to_string());
+        }
+        missing
+    }
+}
+
+fn value_placeholder(id: &str, value_names: Option<&[&str]>) -> String {
+    match value_names {
+        Some([name, ..]) => format!("<{}>", name),
+        _ => format!("<{}>", id),
+    }
+}
 
 impl Hinter for CustomHinter {
     fn handle(
         &mut self,
         line: &str,
         pos: usize,
-        history: &dyn reedline::History,
+        _history: &dyn History,
         use_ansi_coloring: bool,
     ) -> String {
-        dbg!(line, pos, use_ansi_coloring);
-        let x = history.string_at_cursor();
-        dbg!(&x);
-        "".to_string()
+        if line.trim().is_empty() || pos < line.len() {
+            return String::new();
+        }
+        let missing = self.missing_arguments(line);
+        if missing.is_empty() {
+            return String::new();
+        }
+        let separator = match line.ends_with(char::is_whitespace) {
+            true => "",
+            false => " ",
+        };
+        let hint = format!("{}{}", separator, missing.join(" "));
+        match use_ansi_coloring {
+            true => hint.dimmed().to_string(),
+            false => hint,
+        }
     }
 
     fn complete_hint(&self) -> String {
-        "complete".to_string()
+        String::new()
     }
 
     fn next_hint_token(&self) -> String {
-        "next".to_string()
+        String::new()
     }
 }
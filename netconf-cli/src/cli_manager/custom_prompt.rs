@@ -25,11 +25,11 @@ impl CustomPrompt {
 }
 
 impl Prompt for CustomPrompt {
-    fn render_prompt_left(&self) -> std::borrow::Cow<str> {
+    fn render_prompt_left(&self) -> std::borrow::Cow<'_, str> {
         Cow::Borrowed(&self.prompt)
     }
 
-    fn render_prompt_right(&self) -> std::borrow::Cow<str> {
+    fn render_prompt_right(&self) -> std::borrow::Cow<'_, str> {
         let time = OffsetDateTime::now_utc();
         Cow::Owned(format!("{}", time))
     }
@@ -37,19 +37,19 @@ impl Prompt for CustomPrompt {
     fn render_prompt_indicator(
         &self,
         _prompt_mode: reedline::PromptEditMode,
-    ) -> std::borrow::Cow<str> {
+    ) -> std::borrow::Cow<'_, str> {
         // TODO - customize per prompt mode?
         Cow::Borrowed("$ ")
     }
 
-    fn render_prompt_multiline_indicator(&self) -> std::borrow::Cow<str> {
+    fn render_prompt_multiline_indicator(&self) -> std::borrow::Cow<'_, str> {
         Cow::Borrowed("| ")
     }
 
     fn render_prompt_history_search_indicator(
         &self,
         history_search: reedline::PromptHistorySearch,
-    ) -> std::borrow::Cow<str> {
+    ) -> std::borrow::Cow<'_, str> {
         // NOTE/TODO - copied from DefaultPrompt implementation of `reedline` crate!
         let prefix = match history_search.status {
             PromptHistorySearchStatus::Passing => "",
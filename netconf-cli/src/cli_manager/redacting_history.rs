@@ -0,0 +1,93 @@
+use std::collections::vec_deque::Iter;
+
+use reedline::{FileBackedHistory, History, HistoryNavigationQuery};
+
+/// Options of the commands carrying secrets, never to be stored in the history.
+const SECRET_OPTIONS: &[&str] = &["--password"];
+
+/// File backed history, with secret arguments stripped from the entries before being stored.
+///
+/// Commands recalled from the history then ask for the stripped secrets again.
+pub struct RedactingHistory {
+    inner: FileBackedHistory,
+}
+
+impl RedactingHistory {
+    pub fn new(inner: FileBackedHistory) -> Self {
+        Self { inner }
+    }
+}
+
+/// Strip secret options (incl. their values) from the command line.
+///
+/// Returns `None` if the line cannot be safely redacted, e.g. due to mismatched quotes.
+fn redact(line: &str) -> Option<String> {
+    if !SECRET_OPTIONS.iter().any(|option| line.contains(option)) {
+        return Some(line.to_string());
+    }
+    let mut tokens = shlex::split(line)?.into_iter();
+    let mut kept = vec![];
+    while let Some(token) = tokens.next() {
+        if SECRET_OPTIONS.contains(&token.as_str()) {
+            let _ = tokens.next();
+            continue;
+        }
+        let is_secret_assignment = SECRET_OPTIONS.iter().any(|option| {
+            token
+                .strip_prefix(option)
+                .is_some_and(|rest| rest.starts_with('='))
+        });
+        if !is_secret_assignment {
+            kept.push(token);
+        }
+    }
+    shlex::try_join(kept.iter().map(String::as_str)).ok()
+}
+
+impl History for RedactingHistory {
+    fn append(&mut self, entry: &str) {
+        if let Some(entry) = redact(entry) {
+            self.inner.append(&entry);
+        }
+    }
+
+    fn iter_chronologic(&self) -> Iter<'_, String> {
+        self.inner.iter_chronologic()
+    }
+
+    fn back(&mut self) {
+        self.inner.back()
+    }
+
+    fn forward(&mut self) {
+        self.inner.forward()
+    }
+
+    fn string_at_cursor(&self) -> Option<String> {
+        self.inner.string_at_cursor()
+    }
+
+    fn set_navigation(&mut self, navigation: HistoryNavigationQuery) {
+        self.inner.set_navigation(navigation)
+    }
+
+    fn get_navigation(&self) -> HistoryNavigationQuery {
+        self.inner.get_navigation()
+    }
+
+    fn query_entries(&self, search: &str) -> Vec<String> {
+        self.inner.query_entries(search)
+    }
+
+    fn max_values(&self) -> usize {
+        self.inner.max_values()
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.inner.sync()
+    }
+
+    fn reset_cursor(&mut self) {
+        self.inner.reset_cursor()
+    }
+}
@@ -1,6 +1,10 @@
+mod backup_commands;
 mod cli_config_commands;
+mod diff_commands;
 mod netconf_commands;
+mod record_commands;
 mod root_commands;
+mod session_commands;
 mod show_commands;
 
 pub use root_commands::{CommandHandler, RootArgs};
@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use colored::Colorize;
+use netconf_client::{
+    backup::{ConfigBackup, RestoreMethod, RestoreOptions},
+    types::Datastore,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    cli_manager::CliManagerCommandApi, command_handler::netconf_commands::target_session_name,
+};
+
+#[derive(Args, Debug)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+pub struct BackupCommand {
+    /// Datastores to back up, each into its own file.
+    #[clap(possible_values = Datastore::values(), default_value = "running")]
+    datastores: Vec<Datastore>,
+    /// Directory of the backup files, named "<session>_<datastore>_<timestamp>.xml".
+    #[clap(long, default_value = ".")]
+    dir: PathBuf,
+    /// Name of the targeted session, the active one by default.
+    #[clap(long)]
+    session: Option<String>,
+}
+
+impl BackupCommand {
+    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+        let name = target_session_name(cli_api, self.session.as_deref())?;
+        let session = cli_api.session_mut(Some(&name)).unwrap();
+        let now = OffsetDateTime::now_utc();
+        let timestamp = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            now.year(),
+            now.month() as u8,
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+        // file names must not contain colons, e.g. of IPv6 addresses
+        let file_stamp = timestamp.replace(['-', ':'], "");
+        let prefix: String = name
+            .chars()
+            .map(|x| {
+                if x.is_alphanumeric() || x == '-' {
+                    x
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create directory {}", self.dir.display()))?;
+        for datastore in self.datastores.iter() {
+            let backup = session.backup(datastore.clone(), &timestamp)?;
+            let path = self
+                .dir
+                .join(format!("{}_{}_{}.xml", prefix, datastore, file_stamp));
+            std::fs::write(&path, backup.to_xml()?)
+                .with_context(|| format!("Failed to write file {}", path.display()))?;
+            println!(
+                "Backup of {} datastore saved to {}",
+                datastore,
+                path.display().to_string().bold()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+pub struct RestoreCommand {
+    /// Backup file, as written by the "backup" command.
+    file: PathBuf,
+    /// Loading of the backup into the candidate datastore - <edit-config> replacing the configuration, or <copy-config>.
+    #[clap(long, default_value = "replace", possible_values = RestoreMethod::values())]
+    method: RestoreMethod,
+    /// Timeout of the confirmed commit in seconds, used if supported by server.
+    #[clap(long, default_value = "600")]
+    confirm_timeout: u32,
+    /// Restore despite the missing or older YANG modules of server.
+    #[clap(long)]
+    force: bool,
+    /// Name of the targeted session, the active one by default.
+    #[clap(long)]
+    session: Option<String>,
+}
+
+impl RestoreCommand {
+    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+        let content = std::fs::read_to_string(&self.file)
+            .with_context(|| format!("Failed to read file {}", self.file.display()))?;
+        let backup = ConfigBackup::from_xml(&content)
+            .with_context(|| format!("Invalid backup file {}", self.file.display()))?;
+
+        let name = target_session_name(cli_api, self.session.as_deref())?;
+        let options = RestoreOptions {
+            method: self.method,
+            confirm_timeout: self.confirm_timeout,
+            force: self.force,
+            list_hints: cli_api.config().list_hints().clone(),
+        };
+        let session = cli_api.session_mut(Some(&name)).unwrap();
+        let report = session.restore(&backup, &options)?;
+
+        for problem in report.incompatible_modules.iter() {
+            println!("{} {}", "Warning:".yellow(), problem);
+        }
+        println!(
+            "Restored {} backup of {} taken at {} ({}, {}).",
+            backup.datastore,
+            backup.device,
+            backup.timestamp,
+            if report.validated {
+                "validated"
+            } else {
+                "not validated"
+            },
+            if report.confirmed_commit {
+                "checked & confirmed commit"
+            } else {
+                "commit"
+            }
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,25 @@
 use anyhow::Result;
 use clap::Subcommand;
 
-use crate::cli_manager::{CliManagerCommandApi, DumpXmlFormat};
+use crate::cli_manager::{CliManagerCommandApi, DumpXmlFormat, LogLevel};
 
 #[derive(Subcommand, Debug)]
 #[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
 pub enum CliConfigCommand {
     /// Perform dump of XML to console when exchanging messages with NETCONF server.
+    /// The setting is persisted in the configuration file.
     XmlDump {
         #[clap(long, possible_values = DumpXmlFormat::values())]
         request: DumpXmlFormat,
         #[clap(long, possible_values = DumpXmlFormat::values())]
         response: DumpXmlFormat,
     },
+    /// Verbosity of the diagnostics logged to stderr - sessions, RPCs (message-id, operation, duration & sizes) and SSH.
+    /// The setting is persisted in the configuration file.
+    LogLevel {
+        #[clap(possible_values = LogLevel::values())]
+        level: LogLevel,
+    },
 }
 
 impl CliConfigCommand {
@@ -22,7 +29,11 @@ impl CliConfigCommand {
                 cli_api.config_mut().set_request_dump_mode(*request);
                 cli_api.config_mut().set_response_dump_mode(*response);
             }
+            CliConfigCommand::LogLevel { level } => {
+                cli_api.config_mut().set_log_level(*level);
+                crate::logging::set_level(*level);
+            }
         }
-        Ok(())
+        cli_api.save_settings()
     }
 }
@@ -0,0 +1,214 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use colored::Colorize;
+use netconf_client::{
+    backup::ConfigBackup,
+    diff::{ConfigDiff, ListHints},
+    messages::{get_config::GetConfigRequest, raw_to_pretty_xml, NetconfResponse},
+    types::{Datastore, XmlNode},
+};
+
+use crate::{
+    cli_manager::CliManagerCommandApi,
+    command_handler::netconf_commands::{
+        dump_response, json_encoder, target_session_name, ResponseDump,
+    },
+};
+
+#[derive(Args, Debug)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+pub struct DiffCommand {
+    /// Old configuration - datastore of the active session (e.g. "running"),
+    /// of the named session (e.g. "r1:running"), or XML file (e.g. "file:cfg.xml"), incl. the backup files.
+    old: String,
+    /// New configuration, compared to the old one.
+    new: String,
+    #[clap(long, default_value = "unified", possible_values = DiffFormat::values())]
+    format: DiffFormat,
+    /// Lines of the unchanged context around the changes of the unified diff.
+    #[clap(long, default_value = "3")]
+    context: usize,
+    /// List entries identified by the key leaves, e.g. "interface=name" or "route=prefix,next-hop".
+    #[clap(long = "list", value_name = "LIST=KEYS", multiple_occurrences = true)]
+    lists: Vec<String>,
+    /// Leaf-list entries identified by their values.
+    #[clap(long = "leaf-list", value_name = "NAME", multiple_occurrences = true)]
+    leaf_lists: Vec<String>,
+}
+
+/// Rendering of the configuration diff.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiffFormat {
+    /// Unified diff of the pretty printed XML.
+    Unified,
+    /// Changed elements & their ancestors.
+    Tree,
+    /// Content of the `<config>` of `<edit-config>`, changing the old configuration into the new one.
+    Patch,
+}
+
+impl FromStr for DiffFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unified" => Ok(Self::Unified),
+            "tree" => Ok(Self::Tree),
+            "patch" => Ok(Self::Patch),
+            s => bail!("Unsupported diff format: \"{}\"", s),
+        }
+    }
+}
+
+impl Display for DiffFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DiffFormat::Unified => "unified",
+            DiffFormat::Tree => "tree",
+            DiffFormat::Patch => "patch",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl DiffFormat {
+    pub fn values() -> Vec<&'static str> {
+        vec!["unified", "tree", "patch"]
+    }
+}
+
+/// Origin of the compared configuration.
+enum ConfigSource {
+    Datastore {
+        session: Option<String>,
+        datastore: Datastore,
+    },
+    File(PathBuf),
+}
+
+impl FromStr for ConfigSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("file:") {
+            return Ok(ConfigSource::File(path.into()));
+        }
+        // session names of IPv6 addresses contain colons as well
+        let (session, datastore) = match s.rsplit_once(':') {
+            Some((session, datastore)) => (Some(session.to_string()), datastore),
+            None => (None, s),
+        };
+        if datastore.is_empty() {
+            bail!("Missing datastore of \"{}\"", s);
+        }
+        Ok(ConfigSource::Datastore {
+            session,
+            datastore: datastore.parse()?,
+        })
+    }
+}
+
+impl ConfigSource {
+    /// Top-level elements of the configuration.
+    fn load(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<Vec<XmlNode>> {
+        match self {
+            ConfigSource::Datastore { session, datastore } => {
+                let name = target_session_name(cli_api, session.as_deref())?;
+                let session = cli_api.session_mut(Some(&name)).unwrap();
+                let request =
+                    GetConfigRequest::new(session.new_message_id(), datastore.clone(), None);
+                let response = session.dispatch_request(request)?;
+                if !response.typed.succeeded() {
+                    let encoder = json_encoder(cli_api, cli_api.session(Some(&name)));
+                    let dump = ResponseDump::new(cli_api.config().response_dump_mode());
+                    let _ = dump_response(&dump, &encoder, &response);
+                    bail!("NETCONF server replied with <rpc-error>!");
+                }
+                Ok(XmlNode::parse(response.data()?)?.children)
+            }
+            ConfigSource::File(path) => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read file {}", path.display()))?;
+                let mut nodes = XmlNode::parse_fragment(&content)
+                    .with_context(|| format!("Invalid XML file {}", path.display()))?;
+                // unwrap the <data> of saved reply or backup, or the <config> of edit-config
+                match nodes.as_slice() {
+                    [node] if node.name == "data" || node.name == "config" => {
+                        Ok(nodes.remove(0).children)
+                    }
+                    [node] if node.name == "config-backup" => {
+                        Ok(ConfigBackup::from_xml(&content)?.config)
+                    }
+                    _ => Ok(nodes),
+                }
+            }
+        }
+    }
+}
+
+impl DiffCommand {
+    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+        let mut hints = cli_api.config().list_hints().clone();
+        for list in self.lists.iter() {
+            hints = parse_list(hints, list)?;
+        }
+        for name in self.leaf_lists.iter() {
+            hints = hints.leaf_list(name);
+        }
+
+        let old = self.old.parse::<ConfigSource>()?.load(cli_api)?;
+        let new = self.new.parse::<ConfigSource>()?.load(cli_api)?;
+        let diff = ConfigDiff::new(&old, &new, &hints);
+        if diff.is_empty() {
+            println!("No differences found.");
+            return Ok(());
+        }
+
+        match self.format {
+            DiffFormat::Unified => {
+                for line in diff.to_unified(&self.old, &self.new, self.context).lines() {
+                    let line = match line {
+                        x if x.starts_with("---") || x.starts_with("+++") => x.bold(),
+                        x if x.starts_with("@@") => x.cyan(),
+                        x if x.starts_with('+') => x.green(),
+                        x if x.starts_with('-') => x.red(),
+                        x => x.normal(),
+                    };
+                    println!("{}", line);
+                }
+            }
+            DiffFormat::Tree => {
+                for line in diff.to_tree().lines() {
+                    let line = match line.chars().next() {
+                        Some('+') => line.green(),
+                        Some('-') => line.red(),
+                        Some('~') => line.yellow(),
+                        _ => line.normal(),
+                    };
+                    println!("{}", line);
+                }
+            }
+            DiffFormat::Patch => {
+                let patch = XmlNode::fragment_to_xml(&diff.to_edit_config())?;
+                println!("{}", raw_to_pretty_xml(&patch)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add the list of "name=key1,key2" format into the hints.
+fn parse_list(hints: ListHints, list: &str) -> Result<ListHints> {
+    match list.split_once('=') {
+        Some((name, keys)) if !name.is_empty() && !keys.is_empty() => {
+            let keys: Vec<&str> = keys.split(',').collect();
+            Ok(hints.list(name, &keys))
+        }
+        _ => bail!(
+            "Invalid list \"{}\", expected e.g. \"interface=name\"",
+            list
+        ),
+    }
+}
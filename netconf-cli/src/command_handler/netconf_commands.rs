@@ -1,13 +1,20 @@
-use anyhow::{bail, Result};
-use clap::{ArgGroup, Subcommand};
+use anyhow::{bail, Context, Result};
+use clap::{ArgGroup, Args, Subcommand};
 use colored::{Color, Colorize};
 use netconf_client::{
+    framing::encode_message,
+    json::{to_yaml, JsonDecoder, JsonEncoder},
     messages::{
         cancel_commit::CancelCommitRequest,
         close_session::CloseSessionRequest,
+        commit::{CommitRequest, CommitType, ConfirmedCommitParams},
         copy_config::CopyConfigRequest,
         delete_config::DeleteConfigRequest,
         discard_changes::DiscardChangesRequest,
+        edit_config::{
+            DefaultOperation, EditConfigContent, EditConfigParams, EditConfigRequest, ErrorOption,
+            TestOption,
+        },
         get::GetRequest,
         get_config::GetConfigRequest,
         hello::HelloRequest,
@@ -16,14 +23,23 @@ use netconf_client::{
         raw_to_pretty_xml,
         unlock::UnlockRequest,
         validate::{ValidateRequest, ValidateSource},
-        FullResponse, NetconfResponse, ToRawXml,
+        FullResponse, NetconfRequest, NetconfResponse,
     },
-    types::{Capability, ConfigWaypoint, Datastore, Filter, FilterPayload},
-    NetconfSession, SshAuthentication,
+    types::{Capability, ConfigWaypoint, Datastore, Filter, FilterPayload, XmlNode},
+    xpath::{SelectedNode, Selection, XPath},
+    BaseCapability, NetconfSession, SshAuthentication,
+};
+use std::{
+    fmt::Display,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
-use std::net::IpAddr;
 
-use crate::cli_manager::{CliManagerCommandApi, DumpXmlFormat};
+use crate::{
+    cli_manager::{CliManagerCommandApi, DumpXmlFormat},
+    output::{print_paged, XmlView},
+};
 
 pub const NO_SESSION_ERROR_STR: &str = "There is no opened NETCONF session!";
 
@@ -43,8 +59,9 @@ pub enum NetconfCommand {
         port: u16,
         #[clap(long)]
         user: String,
+        /// Password is looked up in the environment & credentials store, or prompted for, if left out.
         #[clap(long)]
-        password: String,
+        password: Option<String>,
     },
     /// The <lock> operation allows the client to lock the entire configuration datastore system of a device.
     Lock {
@@ -58,6 +75,8 @@ pub enum NetconfCommand {
     },
     /// Retrieve running configuration and device state information.
     Get {
+        #[clap(flatten)]
+        output: OutputArgs,
         #[clap(subcommand)]
         filter: Option<FilterCommand>,
     },
@@ -66,11 +85,46 @@ pub enum NetconfCommand {
         /// Datastore containing the requested configuration.
         #[clap(possible_values = Datastore::values())]
         source: Datastore,
+        #[clap(flatten)]
+        output: OutputArgs,
         /// Optional filter to retrieve specified part of config.
         #[clap(subcommand)]
         filter: Option<FilterCommand>,
     },
-    EditConfig {},
+    /// Load all or part of a specified configuration into the target datastore.
+    #[clap(group(
+        ArgGroup::new("content")
+            .required(true)
+            .multiple(false)
+            .args(&["config", "config-file", "url"]),
+    ))]
+    EditConfig {
+        /// Datastore being edited.
+        #[clap(possible_values = Datastore::values())]
+        target: Datastore,
+        /// Configuration changes - raw XML content of the <config> element, or JSON/YAML of the --format.
+        #[clap(long)]
+        config: Option<String>,
+        /// File with the configuration changes - raw XML content of the <config> element, or JSON/YAML of the --format.
+        #[clap(long)]
+        config_file: Option<PathBuf>,
+        /// URL of the configuration changes, for `:url` capability enabled servers.
+        #[clap(long)]
+        url: Option<String>,
+        #[clap(long, possible_values = DefaultOperation::values())]
+        default_operation: Option<DefaultOperation>,
+        #[clap(long, possible_values = TestOption::values())]
+        test_option: Option<TestOption>,
+        #[clap(long, possible_values = ErrorOption::values())]
+        error_option: Option<ErrorOption>,
+        /// Format of the configuration changes - JSON of RFC 7951 style (incl. "@operation" annotations) and YAML
+        /// are converted into XML. By the extension of --config-file, XML otherwise.
+        #[clap(long, possible_values = ContentFormat::values())]
+        format: Option<ContentFormat>,
+        /// Print the XML content of the <config> element only, do not send the request.
+        #[clap(long)]
+        show_xml: bool,
+    },
     /// Create or replace an entire configuration datastore with the contents of another complete configuration datastore.
     #[clap(group(
         ArgGroup::new("from")
@@ -113,18 +167,28 @@ pub enum NetconfCommand {
         url: Option<String>,
     },
     /// Terminate remote session via <kill-session> operation & session-id.
-    KillSession {
-        session_id: u32,
+    KillSession { session_id: u32 },
+    /// Commit the candidate configuration as the device's new current configuration.
+    Commit {
+        /// Confirmed commit, reverted unless confirmed by a subsequent commit within the timeout.
+        #[clap(long)]
+        confirmed: bool,
+        /// Timeout of the confirmed commit in seconds, 600 by default.
+        #[clap(long, requires = "confirmed")]
+        confirm_timeout: Option<u32>,
+        /// Make the confirmed commit persistent, identified by the given token.
+        #[clap(long, requires = "confirmed")]
+        persist: Option<String>,
+        /// Token of the persistent confirmed commit being confirmed.
+        #[clap(long, requires = "confirmed")]
+        persist_id: Option<String>,
     },
-    Commit {},
     /// The <discard-changes> operation can be used to revert the candidate configuration to the current running configuration.
     DiscardChanges {},
     /// Cancels an ongoing confirmed commit.  If the <persist-id>
     /// parameter is not given, the <cancel-commit> operation MUST be
     /// issued on the same session that issued the confirmed commit.
-    CancelCommit {
-        persist_id: Option<u32>,
-    },
+    CancelCommit { persist_id: Option<u32> },
     /// Validates the contents of the specified configuration.
     Validate {
         #[clap(subcommand)]
@@ -134,38 +198,194 @@ pub enum NetconfCommand {
     CloseSession {},
 }
 
+/// Presentation of the retrieved data.
+#[derive(Args, Debug)]
+pub struct OutputArgs {
+    /// Format of the response dump, instead of the "config xml-dump" one.
+    #[clap(long, possible_values = DumpXmlFormat::values())]
+    output: Option<DumpXmlFormat>,
+    /// Collapse elements of the pretty XML nested deeper than N levels, counted from the <rpc-reply>.
+    #[clap(long, value_name = "N")]
+    depth: Option<usize>,
+    /// Show namespaces of the pretty XML by names of their YANG modules.
+    #[clap(long)]
+    short_ns: bool,
+    /// Save the response into the file instead of printing it - in the --output format, pretty XML by default.
+    #[clap(long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// Show only the nodes selected by XPath 1.0 expression, evaluated over the retrieved data by client.
+    /// Also given by the "| select <XPATH>" suffix of the command.
+    #[clap(long, value_name = "XPATH", global = true)]
+    select: Option<String>,
+}
+
+/// Presentation of the response - format & view of the dump, or file to save it into instead of printing.
+#[derive(Debug, Clone)]
+pub struct ResponseDump {
+    pub format: DumpXmlFormat,
+    pub view: XmlView,
+    pub out: Option<PathBuf>,
+    /// XPath of the nodes of retrieved data to be dumped, instead of the whole response.
+    pub select: Option<String>,
+}
+
+impl ResponseDump {
+    /// Dump of the `format`, printed in full.
+    pub fn new(format: DumpXmlFormat) -> Self {
+        Self {
+            format,
+            view: XmlView::default(),
+            out: None,
+            select: None,
+        }
+    }
+}
+
+/// Object-safe view of a [`NetconfRequest`] of any type, built once from the command
+/// to be dumped, validated & dispatched.
+pub trait DynRequest {
+    fn raw_xml(&self) -> Result<String>;
+    fn validate(&self, server_capabilities: &[Capability]) -> Result<()>;
+    /// Dispatch the request within the session, failing on `<rpc-error>` reply.
+    fn dispatch(
+        self: Box<Self>,
+        session: &mut NetconfSession,
+        dump: &ResponseDump,
+        encoder: &JsonEncoder,
+    ) -> Result<()>;
+}
+
+impl<R: NetconfRequest + 'static> DynRequest for R {
+    fn raw_xml(&self) -> Result<String> {
+        self.to_raw_xml()
+    }
+
+    fn validate(&self, server_capabilities: &[Capability]) -> Result<()> {
+        self.validate_request(server_capabilities)
+    }
+
+    fn dispatch(
+        self: Box<Self>,
+        session: &mut NetconfSession,
+        dump: &ResponseDump,
+        encoder: &JsonEncoder,
+    ) -> Result<()> {
+        let response = session.dispatch_request(*self)?;
+        check_response(dump, encoder, &response)
+    }
+}
+
 impl NetconfCommand {
-    pub fn to_raw_xml(
+    /// Dump of the response, as requested by the command - in the `format` by default.
+    fn response_dump(
+        &self,
+        format: DumpXmlFormat,
+        session: Option<&NetconfSession>,
+    ) -> Result<ResponseDump> {
+        Ok(match self {
+            NetconfCommand::Get { output, .. } | NetconfCommand::GetConfig { output, .. } => {
+                let select = match (self.client_side_xpath(session), &output.select) {
+                    (Some(_), Some(_)) => bail!(
+                        "Server lacks the :xpath capability, filter the data by --select only!"
+                    ),
+                    (Some(xpath), None) => Some(xpath.to_string()),
+                    (None, select) => select.clone(),
+                };
+                let format = output.output.unwrap_or(format);
+                let pretty = matches!(format, DumpXmlFormat::Pretty | DumpXmlFormat::None);
+                if !pretty && (output.depth.is_some() || output.short_ns) {
+                    bail!(
+                        "--depth & --short-ns apply to the pretty XML only, not to the \"{}\" output!",
+                        format
+                    );
+                }
+                ResponseDump {
+                    format,
+                    view: XmlView {
+                        depth: output.depth,
+                        short_namespaces: output.short_ns,
+                    },
+                    out: output.out.clone(),
+                    select,
+                }
+            }
+            _ => ResponseDump::new(format),
+        })
+    }
+
+    /// XPath of the filter to be evaluated by client instead, as server of the `session` lacks the `:xpath` capability.
+    fn client_side_xpath(&self, session: Option<&NetconfSession>) -> Option<&str> {
+        let filter = match self {
+            NetconfCommand::Get { filter, .. } | NetconfCommand::GetConfig { filter, .. } => filter,
+            _ => return None,
+        };
+        match (filter, session.and_then(|x| x.server_capabilities())) {
+            (Some(FilterCommand::XPath { value }), Some(caps))
+                if !caps.contains(&Capability::XPath) =>
+            {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Filter of the request sent to server - none in case of the [`Self::client_side_xpath()`].
+    fn filter_payload(
+        &self,
+        filter: &Option<FilterCommand>,
+        session: Option<&NetconfSession>,
+    ) -> Option<FilterPayload> {
+        match self.client_side_xpath(session) {
+            Some(_) => None,
+            None => filter.as_ref().map(|f| f.into()),
+        }
+    }
+
+    /// Build the request of the command, with the given message-id.
+    ///
+    /// YANG modules advertised by server of the `session` resolve the namespaces of JSON/YAML content.
+    pub fn to_request(
         &self,
         cli_api: &impl CliManagerCommandApi,
+        session: Option<&NetconfSession>,
         message_id: String,
-    ) -> Result<String> {
-        match self {
-            NetconfCommand::Hello {
-                address: _,
-                port: _,
-                user: _,
-                password: _,
-            } => {
+    ) -> Result<Box<dyn DynRequest>> {
+        let request: Box<dyn DynRequest> = match self {
+            NetconfCommand::Hello { .. } => {
                 let capabilities = cli_api.config().client_capabilities().to_owned();
-                let request = HelloRequest::new(capabilities);
-                request.to_raw_xml()
+                Box::new(HelloRequest::new(capabilities))
             }
             NetconfCommand::Lock { target } => {
-                LockRequest::new(message_id, target.clone()).to_raw_xml()
+                Box::new(LockRequest::new(message_id, target.clone()))
             }
             NetconfCommand::Unlock { target } => {
-                UnlockRequest::new(message_id, target.clone()).to_raw_xml()
+                Box::new(UnlockRequest::new(message_id, target.clone()))
             }
-            NetconfCommand::Get { filter } => {
-                let filter = filter.as_ref().map(|f| f.into());
-                GetRequest::new(message_id, filter).to_raw_xml()
+            NetconfCommand::Get { filter, .. } => {
+                let filter = self.filter_payload(filter, session);
+                Box::new(GetRequest::new(message_id, filter))
             }
-            NetconfCommand::GetConfig { source, filter } => {
-                let filter = filter.as_ref().map(|f| f.into());
-                GetConfigRequest::new(message_id, source.clone(), filter).to_raw_xml()
+            NetconfCommand::GetConfig { source, filter, .. } => {
+                let filter = self.filter_payload(filter, session);
+                Box::new(GetConfigRequest::new(message_id, source.clone(), filter))
+            }
+            NetconfCommand::EditConfig {
+                target,
+                default_operation,
+                test_option,
+                error_option,
+                ..
+            } => {
+                let config = self.edit_config_content(cli_api, session)?;
+                let params = EditConfigParams {
+                    target: target.clone(),
+                    default_operation: *default_operation,
+                    test_option: *test_option,
+                    error_option: *error_option,
+                    config,
+                };
+                Box::new(EditConfigRequest::new(message_id, params))
             }
-            NetconfCommand::EditConfig {} => todo!(),
             NetconfCommand::CopyConfig {
                 from_datastore,
                 from_url,
@@ -174,55 +394,140 @@ impl NetconfCommand {
             } => {
                 let source = args_to_config_waypoint(from_datastore, from_url)?;
                 let target = args_to_config_waypoint(to_datastore, to_url)?;
-                CopyConfigRequest::new(message_id, target, source).to_raw_xml()
+                Box::new(CopyConfigRequest::new(message_id, target, source))
             }
             NetconfCommand::DeleteConfig { datastore, url } => {
                 let target = args_to_config_waypoint(datastore, url)?;
-                DeleteConfigRequest::new(message_id, target).to_raw_xml()
+                Box::new(DeleteConfigRequest::new(message_id, target))
             }
             NetconfCommand::KillSession { session_id } => {
-                KillSessionRequest::new(message_id, *session_id).to_raw_xml()
+                Box::new(KillSessionRequest::new(message_id, *session_id))
             }
-            NetconfCommand::Commit {} => todo!(),
-            NetconfCommand::DiscardChanges {} => {
-                DiscardChangesRequest::new(message_id).to_raw_xml()
+            NetconfCommand::Commit {
+                confirmed,
+                confirm_timeout,
+                persist,
+                persist_id,
+            } => {
+                let commit_type = match confirmed {
+                    true => CommitType::Confirmed(ConfirmedCommitParams {
+                        confirm_timeout: *confirm_timeout,
+                        persist: persist.clone(),
+                        persist_id: persist_id.clone(),
+                    }),
+                    false => CommitType::Plain,
+                };
+                Box::new(CommitRequest::new(message_id, commit_type))
             }
+            NetconfCommand::DiscardChanges {} => Box::new(DiscardChangesRequest::new(message_id)),
             NetconfCommand::CancelCommit { persist_id } => {
-                CancelCommitRequest::new(message_id, *persist_id).to_raw_xml()
+                Box::new(CancelCommitRequest::new(message_id, *persist_id))
             }
             NetconfCommand::Validate { source } => {
                 let source = source.to_validate_source();
-                ValidateRequest::new(message_id, source).to_raw_xml()
+                Box::new(ValidateRequest::new(message_id, source))
             }
-            NetconfCommand::CloseSession {} => CloseSessionRequest::new(message_id).to_raw_xml(),
-        }
+            NetconfCommand::CloseSession {} => Box::new(CloseSessionRequest::new(message_id)),
+        };
+        Ok(request)
     }
 
-    pub fn dump_command_xml(
+    /// Content of the `<edit-config>` command, JSON & YAML converted into XML.
+    fn edit_config_content(
         &self,
-        cli_api: &mut impl CliManagerCommandApi,
-        dump_mode: DumpXmlFormat,
-        header: &str,
-        message_id: &str,
-    ) -> Result<()> {
-        let dump_string = match dump_mode {
-            DumpXmlFormat::None => None,
-            DumpXmlFormat::Raw => Some(self.to_raw_xml(cli_api, message_id.to_string())?),
-            DumpXmlFormat::Pretty => {
-                let raw = self.to_raw_xml(cli_api, message_id.to_string())?;
-                Some(raw_to_pretty_xml(&raw)?)
+        cli_api: &impl CliManagerCommandApi,
+        session: Option<&NetconfSession>,
+    ) -> Result<EditConfigContent> {
+        let (config, config_file, url, format) = match self {
+            NetconfCommand::EditConfig {
+                config,
+                config_file,
+                url,
+                format,
+                ..
+            } => (config, config_file, url, format),
+            _ => bail!("Not an <edit-config> command!"),
+        };
+        let (content, format) = match (config, config_file, url) {
+            (Some(config), _, _) => (config.to_owned(), format.unwrap_or(ContentFormat::Xml)),
+            (_, Some(path), _) => {
+                let content = std::fs::read_to_string(path).with_context(|| {
+                    format!("Failed to read configuration file {}", path.display())
+                })?;
+                let format = match format {
+                    Some(format) => *format,
+                    None => ContentFormat::from_path(path),
+                };
+                (content, format)
             }
+            (_, _, Some(url)) => return Ok(EditConfigContent::Url(url.to_owned())),
+            _ => bail!("Failed to parse configuration content!"),
         };
-        if let Some(s) = dump_string {
-            println!("{}", header.green());
-            println!("{}", &s);
+
+        let decoder = JsonDecoder::new(cli_api.config().schema_hints().clone());
+        let decoder = match session.and_then(|x| x.server_capabilities()) {
+            Some(caps) => decoder.with_capabilities(caps),
+            None => decoder,
+        };
+        match format {
+            ContentFormat::Xml => Ok(EditConfigContent::Config(content)),
+            ContentFormat::Json => EditConfigContent::from_json(&content, &decoder),
+            ContentFormat::Yaml => EditConfigContent::from_yaml(&content, &decoder),
         }
+    }
+
+    /// Print the exact bytes of the request as it would be written into the session of given name, or the active one -
+    /// incl. its next message-id & the message framing.
+    ///
+    /// Without any session, the message-id of a new session & the `:base:1.0` framing is used.
+    /// The `:base:1.1` framing with chunk headers is forced by `chunked`.
+    pub fn dump(
+        &self,
+        cli_api: &impl CliManagerCommandApi,
+        session: Option<&str>,
+        chunked: bool,
+    ) -> Result<()> {
+        let session = match session {
+            Some(_) => cli_api.session(Some(&target_session_name(cli_api, session)?)),
+            None => cli_api.session(None),
+        };
+        let message_id = session.map_or_else(|| "1".to_string(), |x| x.next_message_id());
+        let base = match (chunked, self, session) {
+            (true, _, _) => BaseCapability::Base11,
+            // <hello> is exchanged before the framing is negotiated
+            (false, NetconfCommand::Hello { .. }, _) | (false, _, None) => BaseCapability::Base,
+            (false, _, Some(session)) => session.base_capability(),
+        };
+
+        let request = self.to_request(cli_api, session, message_id)?;
+        let framed = encode_message(&request.raw_xml()?, base);
+        println!("{}", "XML dump only:".green());
+        println!("{}", String::from_utf8_lossy(&framed));
         Ok(())
     }
 
-    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+    /// Dispatch the request within the `session` of given name, or the active one.
+    /// New session opened by `<hello>` is named by `session`, or by the address of the server.
+    ///
+    /// On `dry_run`, the request is validated against the capabilities of the session's server, but not sent.
+    pub fn handle(
+        &self,
+        cli_api: &mut impl CliManagerCommandApi,
+        session: Option<&str>,
+        dry_run: bool,
+    ) -> Result<()> {
+        if let NetconfCommand::EditConfig { show_xml: true, .. } = self {
+            let session = match session {
+                Some(_) => cli_api.session(Some(&target_session_name(cli_api, session)?)),
+                None => cli_api.session(None),
+            };
+            if let EditConfigContent::Config(config) = self.edit_config_content(cli_api, session)? {
+                println!("{}", raw_to_pretty_xml(&config)?);
+            }
+            return Ok(());
+        }
+
         let request_dump_mode = cli_api.config().request_dump_mode();
-        let response_dump_mode = cli_api.config().response_dump_mode();
 
         if let NetconfCommand::Hello {
             address,
@@ -231,144 +536,366 @@ impl NetconfCommand {
             password,
         } = &self
         {
-            if cli_api.pending_session().is_some() {
-                bail!("There is a pending session opened already! Close it via <close-session> request first to initiate a new one...");
+            if dry_run {
+                bail!("There are no server capabilities to validate <hello> against, use \"dump hello\" instead!");
             }
-
-            let auth = SshAuthentication::UserPassword(user.to_owned(), password.to_owned());
-
+            let password = match password {
+                Some(password) => password.as_str().into(),
+                None => cli_api
+                    .credentials_mut()
+                    .password(user, &address.to_string())?,
+            };
+            let auth = SshAuthentication::UserPassword(user.to_owned(), password);
             let client_capabilities: Vec<Capability> =
                 cli_api.config().client_capabilities().to_vec();
+            let name = session.map_or_else(|| address.to_string(), str::to_string);
+            let session = NetconfSession::new(*address, *port, auth, client_capabilities);
+            return open_session(cli_api, name, session);
+        };
 
-            let mut session = NetconfSession::new(*address, *port, auth, client_capabilities);
+        let name = target_session_name(cli_api, session)?;
+        // the message-id is taken by the session only once the valid request is dispatched
+        let message_id = cli_api.session(Some(&name)).unwrap().next_message_id();
 
-            let _ = self.dump_command_xml(cli_api, request_dump_mode, "Request:", "0");
+        let response_dump = self.response_dump(
+            cli_api.config().response_dump_mode(),
+            cli_api.session(Some(&name)),
+        )?;
+        if self
+            .client_side_xpath(cli_api.session(Some(&name)))
+            .is_some()
+        {
+            println!(
+                "{}",
+                "Server lacks the :xpath capability, the filter is evaluated by client.".yellow()
+            );
+        }
+        let request = self.to_request(cli_api, cli_api.session(Some(&name)), message_id)?;
+        let encoder = json_encoder(cli_api, cli_api.session(Some(&name)));
+        dump_request(request_dump_mode, &encoder, request.as_ref())?;
 
-            // do NOT validate hello request -> no server capabilities yet to validate against!
-            let do_validate_backup = session.validate_capabilities();
-            session.set_validate_capabilities(false);
+        let pending_session = cli_api.session_mut(Some(&name)).unwrap();
+        if dry_run || pending_session.validate_capabilities() {
+            match pending_session.server_capabilities() {
+                Some(caps) => request.validate(caps.as_slice())?,
+                None => bail!("No server capabilities! Session not initiated yet?"),
+            };
+        }
+        if dry_run {
+            println!(
+                "Request valid for capabilities of session \"{}\", not sent.",
+                name
+            );
+            return Ok(());
+        }
 
-            session.connect()?;
-            println!("SSH connected to target NETCONF server.");
+        // commit the message-id the request was built with
+        pending_session.new_message_id();
+        request.dispatch(pending_session, &response_dump, &encoder)?;
+        if let NetconfCommand::CloseSession {} = self {
+            cli_api.remove_session(&name);
+        }
+        Ok(())
+    }
+}
 
-            let response = session.exchange_hello()?;
-            let _ = dump_response(response_dump_mode, &response);
+/// Format of the `<edit-config>` content.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ContentFormat {
+    Xml,
+    /// JSON of RFC 7951 style, see [`JsonDecoder`].
+    Json,
+    Yaml,
+}
 
-            session.set_validate_capabilities(do_validate_backup);
+impl FromStr for ContentFormat {
+    type Err = anyhow::Error;
 
-            if response.typed.succeeded() {
-                println!(
-                    "Hello capability exchange successful, base capability: {}",
-                    format!("{}", session.base_capability()).cyan()
-                );
-                cli_api.set_pending_session(Some(session));
-                return Ok(());
-            }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "xml" => Ok(Self::Xml),
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            s => bail!("Unsupported content format: \"{}\"", s),
+        }
+    }
+}
 
-            bail!("Failed to connect to NETCONF server!")
+impl Display for ContentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ContentFormat::Xml => "xml",
+            ContentFormat::Json => "json",
+            ContentFormat::Yaml => "yaml",
         };
+        write!(f, "{}", s)
+    }
+}
 
-        let message_id = match cli_api.pending_session_mut() {
-            Some(session) => session.new_message_id(),
-            None => bail!(NO_SESSION_ERROR_STR),
-        };
+impl ContentFormat {
+    pub fn values() -> Vec<&'static str> {
+        vec!["xml", "json", "yaml"]
+    }
 
-        let _ = self.dump_command_xml(cli_api, request_dump_mode, "Request:", &message_id);
+    /// Format of the file by its extension, XML by default.
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("json") => ContentFormat::Json,
+            Some("yaml" | "yml") => ContentFormat::Yaml,
+            _ => ContentFormat::Xml,
+        }
+    }
+}
 
-        let pending_session = cli_api.pending_session_mut().unwrap();
+/// Name of the session targeted by a command - the explicitly given one, or the active one.
+pub fn target_session_name(
+    cli_api: &impl CliManagerCommandApi,
+    name: Option<&str>,
+) -> Result<String> {
+    match name.or_else(|| cli_api.active_session()) {
+        Some(name) if cli_api.sessions().contains_key(name) => Ok(name.to_string()),
+        Some(name) => bail!("Unknown session \"{}\", see \"session list\"", name),
+        None => bail!(NO_SESSION_ERROR_STR),
+    }
+}
 
-        match &self {
-            NetconfCommand::Lock { target } => {
-                let request = LockRequest::new(message_id, target.clone());
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::Unlock { target } => {
-                let request = UnlockRequest::new(message_id, target.clone());
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::CopyConfig {
-                from_datastore,
-                from_url,
-                to_datastore,
-                to_url,
-            } => {
-                let source = args_to_config_waypoint(from_datastore, from_url)?;
-                let target = args_to_config_waypoint(to_datastore, to_url)?;
-                let request = CopyConfigRequest::new(message_id, target, source);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::DeleteConfig { datastore, url } => {
-                let target = args_to_config_waypoint(datastore, url)?;
-                let request = DeleteConfigRequest::new(message_id, target);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::DiscardChanges {} => {
-                let request = DiscardChangesRequest::new(message_id);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::CancelCommit { persist_id } => {
-                let request = CancelCommitRequest::new(message_id, *persist_id);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::CloseSession {} => {
-                let request = CloseSessionRequest::new(message_id);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-                if response.typed.succeeded() {
-                    cli_api.set_pending_session(None);
-                }
-            }
-            NetconfCommand::Get { filter } => {
-                let filter = filter.as_ref().map(|f| f.into());
-                let request = GetRequest::new(message_id, filter);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::GetConfig { source, filter } => {
-                let filter = filter.as_ref().map(|f| f.into());
-                let request = GetConfigRequest::new(message_id, source.to_owned(), filter);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            NetconfCommand::Validate { source } => {
-                let source = source.to_validate_source();
-                let request = ValidateRequest::new(message_id, source);
-                let response = pending_session.dispatch_request(request)?;
-                let _ = dump_response(response_dump_mode, &response);
-            }
-            _ => {}
-        }
+/// Connect the (new) session & exchange `<hello>` messages, making it the active session named `name` on success.
+pub fn open_session(
+    cli_api: &mut impl CliManagerCommandApi,
+    name: String,
+    mut session: NetconfSession,
+) -> Result<()> {
+    if cli_api.sessions().contains_key(&name) {
+        bail!("Session \"{}\" is opened already! Close it via \"session close {}\" first, or choose another name via --session...", name, name);
+    }
 
-        Ok(())
+    let request_dump_mode = cli_api.config().request_dump_mode();
+    let response_dump_mode = cli_api.config().response_dump_mode();
+
+    let request = HelloRequest::new(session.client_capabilities().to_vec());
+    dump_request(request_dump_mode, &json_encoder(cli_api, None), &request)?;
+
+    session.connect()?;
+    println!("SSH connected to target NETCONF server.");
+
+    let response = session.exchange_hello()?;
+    let encoder = json_encoder(cli_api, Some(&session));
+    let dumped = dump_response(&ResponseDump::new(response_dump_mode), &encoder, &response);
+
+    if response.typed.succeeded() {
+        println!(
+            "Hello capability exchange successful, base capability: {}",
+            format!("{}", session.base_capability()).cyan()
+        );
+        // the session is usable even if its <hello> could not be dumped
+        cli_api.add_session(name, session);
+        return dumped;
     }
+
+    dumped?;
+    bail!("Failed to connect to NETCONF server!")
 }
 
-pub fn dump_response<R: NetconfResponse>(
+/// Encoder of the JSON & YAML dumps - by the schema hints & YANG modules advertised by server of the `session`.
+pub fn json_encoder(
+    cli_api: &impl CliManagerCommandApi,
+    session: Option<&NetconfSession>,
+) -> JsonEncoder {
+    let encoder = JsonEncoder::new(cli_api.config().schema_hints().clone());
+    match session.and_then(|x| x.server_capabilities()) {
+        Some(caps) => encoder.with_capabilities(caps),
+        None => encoder,
+    }
+}
+
+/// The NETCONF message `xml` in the dump format, `None` if not dumped at all.
+fn format_dump(
     dump_mode: DumpXmlFormat,
-    response: &FullResponse<R>,
+    encoder: &JsonEncoder,
+    xml: &str,
+) -> Result<Option<String>> {
+    Ok(match dump_mode {
+        DumpXmlFormat::None => None,
+        DumpXmlFormat::Raw => Some(xml.to_string()),
+        DumpXmlFormat::Pretty => Some(raw_to_pretty_xml(xml)?),
+        DumpXmlFormat::Json => Some(format!("{:#}", encoder.encode_message(xml)?)),
+        DumpXmlFormat::Yaml => Some(
+            to_yaml(&encoder.encode_message(xml)?)?
+                .trim_end()
+                .to_string(),
+        ),
+    })
+}
+
+fn dump_request(
+    dump_mode: DumpXmlFormat,
+    encoder: &JsonEncoder,
+    request: &dyn DynRequest,
 ) -> Result<()> {
-    // TODO - add Cow for lighter handling?
     let dump_string = match dump_mode {
         DumpXmlFormat::None => None,
-        DumpXmlFormat::Raw => Some(response.dump.clone()),
-        DumpXmlFormat::Pretty => Some(raw_to_pretty_xml(&response.dump)?),
+        DumpXmlFormat::Pretty => Some(XmlView::default().render(&request.raw_xml()?, encoder)?),
+        _ => format_dump(dump_mode, encoder, &request.raw_xml()?)?,
+    };
+    if let Some(s) = dump_string {
+        println!("{}", "Request:".green());
+        print_paged(&s);
+    }
+    Ok(())
+}
+
+pub fn dump_response<R: NetconfResponse>(
+    dump: &ResponseDump,
+    encoder: &JsonEncoder,
+    response: &FullResponse<R>,
+) -> Result<()> {
+    // TODO - add Cow for lighter handling?
+    let dump_string = match dump.format {
+        DumpXmlFormat::Pretty => Some(dump.view.render(&response.dump, encoder)?),
+        format => format_dump(format, encoder, &response.dump)?,
     };
     if let Some(s) = dump_string {
         let header_color = match response.typed.succeeded() {
             true => Color::Green,
             false => Color::BrightRed,
         };
-        println!("{}", "Response:".color(header_color));
-        match response.typed.succeeded() {
-            true => println!("{}", &s),
-            false => println!("{}", &s.yellow()),
+        let mut output = vec!["Response:".color(header_color).to_string()];
+        match response.typed.succeeded() || dump.format == DumpXmlFormat::Pretty {
+            true => output.push(s),
+            false => output.push(s.yellow().to_string()),
         };
+        if !response.attributes.is_empty() {
+            output.push("Reply attributes:".color(header_color).to_string());
+            for attribute in response.attributes.iter() {
+                output.push(format!("\t{}", attribute));
+            }
+        }
+        print_paged(&output.join("\n"));
+    }
+    Ok(())
+}
+
+/// Save the response into the `path` - in the `format`, or as pretty XML.
+fn save_response<R: NetconfResponse>(
+    path: &Path,
+    format: DumpXmlFormat,
+    encoder: &JsonEncoder,
+    response: &FullResponse<R>,
+) -> Result<()> {
+    let format = match format {
+        DumpXmlFormat::None => DumpXmlFormat::Pretty,
+        format => format,
+    };
+    let content = format_dump(format, encoder, &response.dump)?.unwrap_or_default();
+    std::fs::write(path, format!("{}\n", content))
+        .with_context(|| format!("Failed to write file {}", path.display()))?;
+    println!("Response saved to {}", path.display().to_string().bold());
+    Ok(())
+}
+
+/// Print the nodes of the response data selected by the `xpath` - in the dump format, pretty XML by default -
+/// or save them into the file of the dump.
+fn select_response<R: NetconfResponse>(
+    dump: &ResponseDump,
+    xpath: &str,
+    encoder: &JsonEncoder,
+    response: &FullResponse<R>,
+) -> Result<()> {
+    let reply = XmlNode::parse(&response.dump)?;
+    let data = reply.child("data");
+    let mut xpath = XPath::parse(xpath)?;
+    for node in std::iter::once(&reply).chain(data) {
+        for (prefix, namespace) in node.namespace_declarations.iter() {
+            if let Some(prefix) = prefix {
+                xpath = xpath.with_namespace(prefix, namespace);
+            }
+        }
+    }
+    let roots = data.map(|x| x.children.as_slice()).unwrap_or_default();
+    let format = match dump.format {
+        DumpXmlFormat::None => DumpXmlFormat::Pretty,
+        format => format,
+    };
+
+    let format_elements = |elements: &[XmlNode]| -> Result<String> {
+        Ok(match format {
+            DumpXmlFormat::Json => format!("{:#}", encoder.encode(elements)),
+            DumpXmlFormat::Yaml => to_yaml(&encoder.encode(elements))?.trim_end().to_string(),
+            DumpXmlFormat::Raw => XmlNode::fragment_to_xml(elements)?,
+            _ => {
+                let mut rendered = vec![];
+                for element in elements.iter() {
+                    rendered.push(match dump.out {
+                        Some(_) => raw_to_pretty_xml(&element.to_xml()?)?,
+                        None => dump.view.render(&element.to_xml()?, encoder)?,
+                    });
+                }
+                rendered.join("\n")
+            }
+        })
+    };
+    let selected = match xpath.select(roots)? {
+        Selection::Nodes(nodes) if nodes.is_empty() => {
+            println!("{}", "No nodes selected.".yellow());
+            return Ok(());
+        }
+        Selection::Nodes(nodes) => {
+            let elements: Vec<XmlNode> = nodes
+                .iter()
+                .flat_map(|x| match x {
+                    SelectedNode::Root => roots.to_vec(),
+                    SelectedNode::Element(element) => vec![(*element).clone()],
+                    _ => vec![],
+                })
+                .collect();
+            let elements_only = nodes
+                .iter()
+                .all(|x| matches!(x, SelectedNode::Root | SelectedNode::Element(_)));
+            match elements_only {
+                true => format_elements(&elements)?,
+                // attributes & text by their values, interleaved with the elements
+                false => nodes
+                    .iter()
+                    .map(|x| match x {
+                        SelectedNode::Root => format_elements(roots),
+                        SelectedNode::Element(element) => {
+                            format_elements(std::slice::from_ref(*element))
+                        }
+                        node => Ok(node.string_value()),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .join("\n"),
+            }
+        }
+        value => value.string_value(),
+    };
+
+    match &dump.out {
+        Some(path) => {
+            std::fs::write(path, format!("{}\n", selected))
+                .with_context(|| format!("Failed to write file {}", path.display()))?;
+            println!("Selection saved to {}", path.display().to_string().bold());
+        }
+        None => print_paged(&format!("{}\n{}", "Selected:".green(), selected)),
+    }
+    Ok(())
+}
+
+/// Dump the response (or save the successful one into file), failing on `<rpc-error>` reply.
+fn check_response<R: NetconfResponse>(
+    dump: &ResponseDump,
+    encoder: &JsonEncoder,
+    response: &FullResponse<R>,
+) -> Result<()> {
+    if let (Some(xpath), true) = (&dump.select, response.typed.succeeded()) {
+        return select_response(dump, xpath, encoder, response);
+    }
+    if let (Some(path), true) = (&dump.out, response.typed.succeeded()) {
+        return save_response(path, dump.format, encoder, response);
+    }
+    dump_response(dump, encoder, response)?;
+    if !response.typed.succeeded() {
+        bail!("NETCONF server replied with <rpc-error>!");
     }
     Ok(())
 }
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use netconf_client::transcript::TranscriptRecorder;
+
+use crate::{
+    cli_manager::CliManagerCommandApi, command_handler::netconf_commands::target_session_name,
+};
+
+#[derive(Subcommand, Debug)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+pub enum RecordCommand {
+    /// Record transcript of the session into the file - all the messages with timestamps, starting by the <hello> exchange.
+    /// The file is replaced, one JSON object per message. Passwords, secrets & keys are replaced by `***`.
+    Start {
+        /// Transcript file.
+        file: PathBuf,
+        /// Keep passwords, secrets & keys in the transcript, e.g. for offline regression fixtures.
+        #[clap(long)]
+        unredacted: bool,
+        /// Name of the targeted session, the active one by default.
+        #[clap(long)]
+        session: Option<String>,
+    },
+    /// Stop recording transcript of the session.
+    Stop {
+        /// Name of the targeted session, the active one by default.
+        #[clap(long)]
+        session: Option<String>,
+    },
+}
+
+impl RecordCommand {
+    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+        match self {
+            RecordCommand::Start {
+                file,
+                unredacted,
+                session,
+            } => {
+                let name = target_session_name(cli_api, session.as_deref())?;
+                let recorder = match unredacted {
+                    true => TranscriptRecorder::to_file(file)?.unredacted(),
+                    false => TranscriptRecorder::to_file(file)?,
+                };
+                cli_api
+                    .session_mut(Some(&name))
+                    .unwrap()
+                    .set_recorder(Some(recorder))?;
+                println!(
+                    "Recording transcript of session \"{}\" into {}",
+                    name,
+                    file.display().to_string().bold()
+                );
+            }
+            RecordCommand::Stop { session } => {
+                let name = target_session_name(cli_api, session.as_deref())?;
+                cli_api
+                    .session_mut(Some(&name))
+                    .unwrap()
+                    .set_recorder(None)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
-use clap::{Parser, Subcommand};
+use clap::{ErrorKind, Parser, Subcommand};
 
 use super::{
-    cli_config_commands::CliConfigCommand, netconf_commands::NetconfCommand,
+    backup_commands::{BackupCommand, RestoreCommand},
+    cli_config_commands::CliConfigCommand,
+    diff_commands::DiffCommand,
+    netconf_commands::{open_session, NetconfCommand},
+    record_commands::RecordCommand,
+    session_commands::SessionCommand,
     show_commands::ShowCommand,
 };
-use crate::cli_manager::{CliManagerCommandApi, DumpXmlFormat};
+use crate::cli_manager::CliManagerCommandApi;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 #[derive(Parser, Debug)]
 #[clap(multicall(true), disable_help_flag(true))]
@@ -18,18 +23,80 @@ pub struct RootArgs {
 
 #[derive(Subcommand, Debug)]
 pub enum RootCommand {
+    /// Save configuration of the datastores into timestamped files, with the metadata of server.
+    Backup(BackupCommand),
     /// Modify the CLI behavior by applying various settings.
     #[clap(subcommand)]
     Config(CliConfigCommand),
+    /// Open NETCONF session to the server of named profile from the configuration file.
+    Connect {
+        /// Name of the profile, see "show profiles".
+        profile: String,
+        /// Name of the new session, the profile name by default.
+        #[clap(long)]
+        session: Option<String>,
+    },
+    /// Show changes of the new configuration against the old one - datastores of the sessions, or XML files.
+    Diff(DiffCommand),
     /// Dump the NETCONF request only, do not send to server
+    Dump {
+        /// Name of the session providing the message-id & framing, the active one by default.
+        #[clap(long, global = true)]
+        session: Option<String>,
+        /// Show the chunk headers of `:base:1.1` framing, regardless of the session.
+        #[clap(long, global = true)]
+        chunked: bool,
+        #[clap(subcommand)]
+        command: NetconfCommand,
+    },
+    /// Record transcript of the session into a file, e.g. for bug reports or replay in regression tests.
     #[clap(subcommand)]
-    Dump(NetconfCommand),
+    Record(RecordCommand),
+    /// Restore the backup file via candidate datastore - load, validate & commit.
+    Restore(RestoreCommand),
     /// Raw NETCONF request dispatched to target server with subsequent response dump.
+    Send {
+        /// Name of the targeted session, the active one by default.
+        /// Names the new session for <hello>, the server address by default.
+        #[clap(long, global = true)]
+        session: Option<String>,
+        /// Validate the request against capabilities of the session's server, without sending it.
+        #[clap(long, global = true)]
+        dry_run: bool,
+        #[clap(subcommand)]
+        command: NetconfCommand,
+    },
+    /// Manage multiple opened NETCONF sessions.
     #[clap(subcommand)]
-    Send(NetconfCommand),
+    Session(SessionCommand),
     /// Display various aspects of running CLI/NETCONF session, etc.
-    #[clap(subcommand)]
-    Show(ShowCommand),
+    Show {
+        /// Name of the targeted session, the active one by default.
+        #[clap(long, global = true)]
+        session: Option<String>,
+        #[clap(subcommand)]
+        command: ShowCommand,
+    },
+}
+
+/// Rewrite the trailing `| select <xpath>` pipe of the command into its `--select <xpath>` argument.
+fn pipe_to_args(mut tokens: Vec<String>) -> Result<Vec<String>> {
+    let position = match tokens.iter().position(|x| x == "|") {
+        Some(position) => position,
+        None => return Ok(tokens),
+    };
+    let pipe = tokens.split_off(position);
+    match &pipe[1..] {
+        [command, xpath] if command == "select" => {
+            tokens.push("--select".to_string());
+            tokens.push(xpath.to_string());
+            Ok(tokens)
+        }
+        _ => bail!(
+            "Unsupported pipe \"{}\", expected \"| select <XPATH>\"",
+            pipe.join(" ")
+        ),
+    }
 }
 
 pub struct CommandHandler {}
@@ -39,24 +106,69 @@ impl CommandHandler {
         Self {}
     }
 
+    /// Parse & execute a single command line, as entered in REPL or read from script.
+    pub fn handle_line(
+        &mut self,
+        cli_api: &mut impl CliManagerCommandApi,
+        line: &str,
+    ) -> Result<()> {
+        match shlex::split(line) {
+            Some(tokens) => self.handle_tokens(cli_api, tokens),
+            None => bail!("Failed to parse command - probably mismatched quotes?"),
+        }
+    }
+
+    /// Parse & execute a single command already split into tokens.
+    ///
+    /// Requested help is printed out, any other parsing failure results in an error.
+    pub fn handle_tokens(
+        &mut self,
+        cli_api: &mut impl CliManagerCommandApi,
+        tokens: Vec<String>,
+    ) -> Result<()> {
+        match RootArgs::try_parse_from(pipe_to_args(tokens)?) {
+            Ok(matches) => self.handle_command(cli_api, &matches.command),
+            Err(err) if err.kind() == ErrorKind::DisplayHelp => {
+                println!("{}", err.to_string().trim());
+                Ok(())
+            }
+            Err(err) => bail!(err.to_string()),
+        }
+    }
+
     pub fn handle_command(
         &mut self,
         cli_api: &mut impl CliManagerCommandApi,
         command: &RootCommand,
     ) -> Result<()> {
         match command {
+            RootCommand::Backup(command) => command.handle(cli_api),
             RootCommand::Config(command) => command.handle(cli_api),
-            RootCommand::Dump(command) => {
-                let _ = command.dump_command_xml(
-                    cli_api,
-                    DumpXmlFormat::Pretty,
-                    "XML dump only:",
-                    "dump",
-                );
-                Ok(())
+            RootCommand::Connect { profile, session } => {
+                let name = session.as_ref().unwrap_or(profile).clone();
+                let profile = match cli_api.profiles().get(profile) {
+                    Some(profile) => profile.clone(),
+                    None => bail!("Unknown profile \"{}\", see \"show profiles\"", profile),
+                };
+                let capabilities = cli_api.config().client_capabilities().to_vec();
+                let session = profile.to_session(&capabilities, cli_api.credentials_mut())?;
+                open_session(cli_api, name, session)
             }
-            RootCommand::Send(command) => command.handle(cli_api),
-            RootCommand::Show(command) => command.handle(cli_api),
+            RootCommand::Diff(command) => command.handle(cli_api),
+            RootCommand::Dump {
+                session,
+                chunked,
+                command,
+            } => command.dump(cli_api, session.as_deref(), *chunked),
+            RootCommand::Record(command) => command.handle(cli_api),
+            RootCommand::Restore(command) => command.handle(cli_api),
+            RootCommand::Send {
+                session,
+                dry_run,
+                command,
+            } => command.handle(cli_api, session.as_deref(), *dry_run),
+            RootCommand::Session(command) => command.handle(cli_api),
+            RootCommand::Show { session, command } => command.handle(cli_api, session.as_deref()),
         }
     }
 
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use netconf_client::trace::WireTrace;
+
+use crate::{
+    cli_manager::CliManagerCommandApi, command_handler::netconf_commands::target_session_name,
+};
+
+#[derive(Subcommand, Debug)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+pub enum SessionCommand {
+    /// List opened NETCONF sessions, the active one is marked by "*".
+    List,
+    /// Make the session active - target of the commands without explicit --session.
+    Use {
+        /// Name of the session.
+        name: String,
+    },
+    /// Close the session via <close-session> request, the active one if no name is given.
+    ///
+    /// The session is forgotten even if the request fails, e.g. for an unresponsive server.
+    Close {
+        /// Name of the session.
+        name: Option<String>,
+    },
+    /// Append the raw framed bytes exchanged with server to the file, with passwords & keys redacted.
+    /// Tracing is stopped if no file is given.
+    Trace {
+        /// Wire trace file.
+        file: Option<PathBuf>,
+        /// Name of the targeted session, the active one by default.
+        #[clap(long)]
+        session: Option<String>,
+    },
+}
+
+impl SessionCommand {
+    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+        match self {
+            SessionCommand::List => {
+                if cli_api.sessions().is_empty() {
+                    println!("{}", "No opened sessions!".yellow());
+                }
+                for (name, session) in cli_api.sessions() {
+                    let marker = match cli_api.active_session() == Some(name.as_str()) {
+                        true => "*",
+                        false => " ",
+                    };
+                    println!(
+                        "{} {}: session-id:{}@{}",
+                        marker,
+                        name.cyan(),
+                        session.session_id().unwrap_or(0),
+                        session.target_string()
+                    );
+                }
+            }
+            SessionCommand::Use { name } => {
+                if !cli_api.sessions().contains_key(name) {
+                    bail!("Unknown session \"{}\", see \"session list\"", name);
+                }
+                cli_api.set_active_session(Some(name.clone()));
+            }
+            SessionCommand::Close { name } => {
+                let name = target_session_name(cli_api, name.as_deref())?;
+                let mut session = cli_api.remove_session(&name).unwrap();
+                if let Err(err) = session.request_close_session() {
+                    println!(
+                        "{}",
+                        format!("Failed to close session \"{}\": {}", name, err).yellow()
+                    );
+                }
+            }
+            SessionCommand::Trace { file, session } => {
+                let name = target_session_name(cli_api, session.as_deref())?;
+                let wire_trace = match file {
+                    Some(path) => Some(WireTrace::to_file(path)?),
+                    None => None,
+                };
+                cli_api
+                    .session_mut(Some(&name))
+                    .unwrap()
+                    .set_wire_trace(wire_trace);
+            }
+        }
+        Ok(())
+    }
+}
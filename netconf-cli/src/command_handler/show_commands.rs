@@ -3,9 +3,10 @@ use std::str::FromStr;
 use anyhow::{bail, Result};
 use clap::Subcommand;
 use colored::Colorize;
+use netconf_client::types::{Conformance, Module};
 
 use crate::{
-    cli_manager::CliManagerCommandApi, command_handler::netconf_commands::NO_SESSION_ERROR_STR,
+    cli_manager::CliManagerCommandApi, command_handler::netconf_commands::target_session_name,
 };
 
 #[derive(Subcommand, Debug)]
@@ -17,6 +18,16 @@ pub enum ShowCommand {
         #[clap(possible_values = CapabilityOwner::values())]
         owner: CapabilityOwner,
     },
+    /// Show YANG modules implemented by NETCONF server, as retrieved from its YANG library.
+    ///
+    /// Falls back to modules advertised in server <hello>, if YANG library is not available.
+    Modules {
+        /// Show only modules with name containing the specified text.
+        #[clap(long)]
+        name: Option<String>,
+    },
+    /// Show connection profiles of the configuration file, to be used by "connect <profile>".
+    Profiles,
 }
 
 #[derive(Debug, Clone, clap::ArgEnum)]
@@ -44,7 +55,12 @@ impl FromStr for CapabilityOwner {
 }
 
 impl ShowCommand {
-    pub fn handle(&self, cli_api: &mut impl CliManagerCommandApi) -> Result<()> {
+    /// Show the aspects of the `session` of given name, or of the active one.
+    pub fn handle(
+        &self,
+        cli_api: &mut impl CliManagerCommandApi,
+        session: Option<&str>,
+    ) -> Result<()> {
         match self {
             ShowCommand::Capabilities { owner } => match owner {
                 CapabilityOwner::Client => {
@@ -53,8 +69,15 @@ impl ShowCommand {
                         println!("\t{}", cap)
                     }
                 }
-                CapabilityOwner::Server => match cli_api.pending_session() {
-                    Some(session) => match session.server_capabilities() {
+                CapabilityOwner::Server => {
+                    let name = match target_session_name(cli_api, session) {
+                        Ok(name) => name,
+                        Err(err) => {
+                            println!("{}", err.to_string().yellow());
+                            return Ok(());
+                        }
+                    };
+                    match cli_api.session(Some(&name)).unwrap().server_capabilities() {
                         Some(caps) => {
                             println!("Advertised {} session capabilities:", "server".cyan());
                             for cap in caps {
@@ -62,13 +85,75 @@ impl ShowCommand {
                             }
                         }
                         None => println!("{}", "No server capabilities advertised!".yellow()),
-                    },
-                    None => {
-                        println!("{}", NO_SESSION_ERROR_STR.yellow());
                     }
-                },
+                }
             },
+            ShowCommand::Profiles => {
+                if cli_api.profiles().is_empty() {
+                    println!("{}", "No profiles configured!".yellow());
+                }
+                for (name, profile) in cli_api.profiles() {
+                    println!("\t{}: {}", name.cyan(), profile);
+                }
+            }
+            ShowCommand::Modules { name } => {
+                let session_name = target_session_name(cli_api, session)?;
+                let session = cli_api.session_mut(Some(&session_name)).unwrap();
+                let name_matches = |module: &str| match name {
+                    Some(name) => module.contains(name.as_str()),
+                    None => true,
+                };
+                match session.get_yang_library() {
+                    Ok(library) => {
+                        println!("YANG library content-id: {}", library.content_id.cyan());
+                        for set in library.module_sets.iter() {
+                            println!("Module set {}:", set.name.cyan());
+                            for module in set.modules.iter().filter(|x| name_matches(&x.name)) {
+                                dump_module(module);
+                            }
+                        }
+                        for datastore in library.datastores.iter() {
+                            println!(
+                                "Datastore {} uses schema {}",
+                                datastore.datastore.to_string().cyan(),
+                                datastore.schema
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        println!("{}", err.to_string().yellow());
+                        let caps = match session.server_capabilities() {
+                            Some(caps) => caps,
+                            None => bail!("No server capabilities advertised!"),
+                        };
+                        println!("Modules advertised in server {}:", "<hello>".cyan());
+                        for module in caps.modules().filter(|x| name_matches(&x.module)) {
+                            println!("\t{}", module);
+                        }
+                    }
+                }
+            }
         };
         Ok(())
     }
 }
+
+fn dump_module(module: &Module) {
+    let conformance = match module.conformance {
+        Conformance::Implement => "",
+        Conformance::Import => " (import-only)",
+    };
+    println!("\t{}{}", module.to_string().bright_green(), conformance);
+    if !module.features.is_empty() {
+        println!("\t\tfeatures: {}", module.features.join(", "));
+    }
+    if !module.deviations.is_empty() {
+        println!("\t\tdeviations: {}", module.deviations.join(", "));
+    }
+    for submodule in module.submodules.iter() {
+        match &submodule.revision {
+            Some(revision) => println!("\t\tsubmodule: {}@{}", submodule.name, revision),
+            None => println!("\t\tsubmodule: {}", submodule.name),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{io::IsTerminal, sync::Mutex};
+
+use tracing_subscriber::{
+    filter::{self, LevelFilter},
+    fmt,
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::cli_manager::LogLevel;
+
+/// Level of the logged events, changed by the `config log-level` command.
+static LEVEL: Mutex<LevelFilter> = Mutex::new(LevelFilter::WARN);
+
+/// Install the global subscriber, logging to stderr at the `warn` level until set otherwise.
+///
+/// Spans are enabled regardless of the level - the long-lived span of a session opened at lower verbosity
+/// still provides its context to the events, once the level is raised.
+pub fn init() {
+    let filter = filter::dynamic_filter_fn(|metadata, _| {
+        metadata.is_span() || *metadata.level() <= *LEVEL.lock().unwrap()
+    });
+    let layer = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_filter(filter);
+    let _ = tracing_subscriber::registry().with(layer).try_init();
+}
+
+/// Change level of the logged events.
+pub fn set_level(level: LogLevel) {
+    *LEVEL.lock().unwrap() = match level {
+        LogLevel::Off => LevelFilter::OFF,
+        LogLevel::Error => LevelFilter::ERROR,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Info => LevelFilter::INFO,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Trace => LevelFilter::TRACE,
+    };
+}
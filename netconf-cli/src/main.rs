@@ -2,19 +2,114 @@
 // #![warn(missing_docs)]
 // #![deny(missing_doc_code_examples)]
 
+mod batch_mode;
 mod cli_manager;
 mod command_handler;
+mod logging;
+mod output;
+
+use std::{
+    fs::File,
+    io::{BufReader, IsTerminal},
+    net::IpAddr,
+    path::PathBuf,
+};
 
 use anyhow::Result;
-use clap::StructOpt;
-use cli_manager::CliManager;
-use command_handler::{CommandHandler, RootArgs};
+use batch_mode::BatchRunner;
+use clap::Parser;
+use cli_manager::{default_config_path, CliManager};
+use command_handler::CommandHandler;
 
 use colored::Colorize;
 
+/// Interactive REPL for NETCONF sessions.
+///
+/// Without any arguments (and with terminal input), the interactive REPL is started.
+/// Otherwise the commands are executed non-interactively - from the arguments, script file or piped stdin,
+/// with non-zero exit code if any of them fails.
+#[derive(Parser, Debug)]
+#[clap(name = "netconf-cli", version)]
+#[clap(setting = clap::AppSettings::DeriveDisplayOrder)]
+struct CliArgs {
+    /// Configuration file with settings & connection profiles [default: netconf-cli/config.toml in user's config directory]
+    #[clap(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Execute commands from the script file ("-" for stdin), one command per line.
+    /// Empty lines & lines starting with "#" are skipped.
+    #[clap(long, value_name = "FILE", conflicts_with = "command")]
+    script: Option<String>,
+    /// Continue with the following commands after a failed one.
+    #[clap(long)]
+    keep_going: bool,
+    /// Open NETCONF session to the host before executing the commands.
+    #[clap(long, requires = "user", conflicts_with = "profile")]
+    host: Option<IpAddr>,
+    /// Port of the NETCONF server.
+    #[clap(long, default_value_t = 830)]
+    port: u16,
+    /// User name for the NETCONF session.
+    #[clap(long)]
+    user: Option<String>,
+    /// Password for the NETCONF session.
+    /// Looked up in the environment & credentials store, or prompted for, if left out.
+    #[clap(long)]
+    password: Option<String>,
+    /// Open NETCONF session to the server of named profile before executing the commands.
+    #[clap(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Single command to be executed, e.g. "send get-config running".
+    /// Put it after "--" if it has any options of its own.
+    command: Vec<String>,
+}
+
 fn main() -> Result<()> {
-    let mut cli_manager = CliManager::new()?;
+    let args = CliArgs::parse();
+    logging::init();
+
+    let interactive = args.script.is_none()
+        && args.command.is_empty()
+        && args.host.is_none()
+        && args.profile.is_none()
+        && std::io::stdin().is_terminal();
+    let config_path = args.config.clone().or_else(default_config_path);
+    if interactive {
+        return run_interactive(config_path);
+    }
+
+    if !run_batch(args, config_path)? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Execute the commands non-interactively, returning `true` if all of them succeeded.
+fn run_batch(args: CliArgs, config_path: Option<PathBuf>) -> Result<bool> {
+    let mut runner = BatchRunner::new(args.keep_going, config_path)?;
+
+    if let (Some(host), Some(user)) = (args.host, &args.user) {
+        runner.connect(host, args.port, user, args.password.as_deref());
+    }
+    if let Some(profile) = &args.profile {
+        runner.connect_profile(profile);
+    }
+
+    if !args.command.is_empty() {
+        runner.run_tokens("command", args.command);
+    } else {
+        match args.script.as_deref() {
+            None | Some("-") => runner.run_script("stdin", std::io::stdin().lock())?,
+            Some(path) => runner.run_script(path, BufReader::new(File::open(path)?))?,
+        }
+    }
+
+    Ok(runner.finish())
+}
+
+fn run_interactive(config_path: Option<PathBuf>) -> Result<()> {
+    let mut cli_manager = CliManager::new(config_path)?;
     let mut command_handler = CommandHandler::new();
+    output::enable_paging();
 
     dump_welcome_banner();
 
@@ -24,27 +119,8 @@ fn main() -> Result<()> {
                 if line.is_empty() {
                     continue;
                 }
-                let tokens = match shlex::split(&line) {
-                    Some(split) => split,
-                    None => {
-                        println!(
-                            "{}",
-                            "Failed to parse command - probably mismatched quotes?".yellow()
-                        );
-                        continue;
-                    }
-                };
-                // move this into command handler?
-                match RootArgs::try_parse_from(tokens) {
-                    Ok(matches) => {
-                        match command_handler.handle_command(&mut cli_manager, &matches.command) {
-                            Ok(_) => {}
-                            Err(err) => println!("{}", err.to_string().trim().yellow()),
-                        }
-                    }
-                    Err(err) => {
-                        println!("{}", err.to_string().trim());
-                    }
+                if let Err(err) = command_handler.handle_line(&mut cli_manager, &line) {
+                    println!("{}", err.to_string().trim().yellow());
                 }
             }
             reedline::Signal::CtrlC => {
@@ -0,0 +1,165 @@
+use std::{
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+use colored::Colorize;
+use netconf_client::{json::JsonEncoder, types::XmlNode};
+
+/// Pager used unless set otherwise by the `PAGER` environment variable.
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Whether long output is shown by the pager - in the interactive mode only.
+static PAGING: AtomicBool = AtomicBool::new(false);
+
+/// Show long output by the pager from now on, see [`print_paged()`].
+pub fn enable_paging() {
+    PAGING.store(true, Ordering::Relaxed);
+}
+
+/// Terminal view of the XML - indented & colored tags, attributes, namespaces and text.
+#[derive(Debug, Clone, Default)]
+pub struct XmlView {
+    /// Elements nested deeper than `depth` (root element being the 1st level) are collapsed into their parent.
+    pub depth: Option<usize>,
+    /// Namespaces are shown by name of their YANG module, wherever known.
+    pub short_namespaces: bool,
+}
+
+impl XmlView {
+    /// Colored & indented view of the XML message, with module names of namespaces known to the `encoder`.
+    pub fn render(&self, xml: &str, encoder: &JsonEncoder) -> Result<String> {
+        let root = XmlNode::parse(xml)?;
+        let mut lines = vec![];
+        self.render_node(&root, 1, encoder, &mut lines);
+        Ok(lines.join("\n"))
+    }
+
+    fn render_node(
+        &self,
+        node: &XmlNode,
+        level: usize,
+        encoder: &JsonEncoder,
+        lines: &mut Vec<String>,
+    ) {
+        let indent = "  ".repeat(level - 1);
+        let name = node.qualified_name();
+        let mut start = format!("<{}", name).bright_blue().to_string();
+        for (prefix, namespace) in node.namespace_declarations.iter() {
+            let key = match prefix {
+                Some(prefix) => format!("xmlns:{}", prefix),
+                None => "xmlns".to_string(),
+            };
+            let value = match self.short_namespaces {
+                true => encoder.module_name(namespace).unwrap_or(namespace),
+                false => namespace,
+            };
+            let declaration = format!(" {}=\"{}\"", key, escape(value, true));
+            start.push_str(&declaration.magenta().to_string());
+        }
+        for attribute in node.attributes.iter() {
+            start.push_str(&format!(
+                " {}={}",
+                attribute.qualified_name().cyan(),
+                format!("\"{}\"", escape(&attribute.value, true)).green()
+            ));
+        }
+        let end = format!("</{}>", name).bright_blue();
+        let text = node.text.as_deref().map(|x| escape(x, false));
+
+        if node.children.is_empty() {
+            match text {
+                Some(text) => lines.push(format!(
+                    "{}{}{}{}{}",
+                    indent,
+                    start,
+                    ">".bright_blue(),
+                    text,
+                    end
+                )),
+                None => lines.push(format!("{}{}{}", indent, start, "/>".bright_blue())),
+            }
+            return;
+        }
+        if self.depth.is_some_and(|depth| level >= depth) {
+            let collapsed = match count(node) - 1 {
+                1 => "<!-- 1 element collapsed -->".to_string(),
+                n => format!("<!-- {} elements collapsed -->", n),
+            };
+            lines.push(format!(
+                "{}{}{}{}{}",
+                indent,
+                start,
+                ">".bright_blue(),
+                collapsed.dimmed(),
+                end
+            ));
+            return;
+        }
+
+        lines.push(format!("{}{}{}", indent, start, ">".bright_blue()));
+        if let Some(text) = text {
+            lines.push(format!("{}  {}", indent, text));
+        }
+        for child in node.children.iter() {
+            self.render_node(child, level + 1, encoder, lines);
+        }
+        lines.push(format!("{}{}", indent, end));
+    }
+}
+
+/// Number of elements of the sub-tree, incl. the `node` itself.
+fn count(node: &XmlNode) -> usize {
+    1 + node.children.iter().map(count).sum::<usize>()
+}
+
+fn escape(text: &str, attribute: bool) -> String {
+    let escaped = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    match attribute {
+        true => escaped.replace('"', "&quot;"),
+        false => escaped,
+    }
+}
+
+/// Print the `text`, through the pager if it does not fit the terminal - `$PAGER`, or `less -R` by default.
+///
+/// Printed directly unless the paging is enabled (see [`enable_paging()`]), if the output is not a terminal,
+/// or the pager fails to start.
+pub fn print_paged(text: &str) {
+    let rows = match PAGING.load(Ordering::Relaxed) && std::io::stdout().is_terminal() {
+        true => crossterm::terminal::size().map_or(0, |(_, rows)| rows as usize),
+        false => 0,
+    };
+    if rows == 0 || text.lines().count() < rows {
+        println!("{}", text);
+        return;
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let (program, args) = match shlex::split(&pager).as_deref() {
+        Some([program, args @ ..]) => (program.clone(), args.to_vec()),
+        _ => {
+            println!("{}", text);
+            return;
+        }
+    };
+    match Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the pager may quit before reading all the text
+                let _ = writeln!(stdin, "{}", text);
+            }
+            let _ = child.wait();
+        }
+        Err(_) => println!("{}", text),
+    }
+}
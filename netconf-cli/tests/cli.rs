@@ -0,0 +1,228 @@
+//! Tests of the non-interactive `netconf-cli` runs against [`MockServer`] listening on plain TCP,
+//! connected by the `tcp` transport of the configuration file profile.
+
+use std::{
+    io::Write,
+    net::SocketAddr,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use netconf_client::types::Capability;
+use netconf_mock_server::{MockServer, MockServerHandle, Reply};
+
+const DATA: &str = r#"<system xmlns="urn:example:sys"><hostname>r1</hostname></system>"#;
+
+/// Directory of the test files, unique per test.
+fn test_dir() -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "netconf-cli-test-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Mock server of the writable "running" datastore, replying by the `DATA` to `<get-config>`,
+/// and by `<rpc-error>` to the other operations (except for `<close-session>`).
+fn data_server() -> MockServer {
+    MockServer::new()
+        .capabilities(vec![
+            Capability::Base,
+            Capability::Base11,
+            Capability::WritableRunning,
+        ])
+        .reply("get-config", Reply::data(DATA))
+}
+
+/// Mock server listening on a free local port, with the configuration file of its "mock" profile.
+fn mock_server(server: MockServer) -> (MockServerHandle, PathBuf) {
+    let handle = server
+        .listen_tcp(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .unwrap();
+    let address = handle.local_addr().unwrap();
+    let config = test_dir().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[profiles.mock]\nhost = \"{}\"\nport = {}\ntransport = \"tcp\"\nuser = \"test\"\n",
+            address.ip(),
+            address.port()
+        ),
+    )
+    .unwrap();
+    (handle, config)
+}
+
+/// Run the CLI with the configuration file & arguments, feeding the `stdin`.
+fn run(config: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_netconf-cli"))
+        .arg("--config")
+        .arg(config)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Operations received by server, in order.
+fn operations(handle: &MockServerHandle) -> Vec<String> {
+    handle.requests().into_iter().map(|x| x.operation).collect()
+}
+
+#[test]
+fn one_shot_command() {
+    let (handle, config) = mock_server(data_server());
+    let output = run(
+        &config,
+        &["--profile", "mock", "--", "send", "get-config", "running"],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.contains("<hostname>r1</hostname>"), "{}", stdout);
+    assert_eq!(operations(&handle), ["get-config", "close-session"]);
+    handle.join().unwrap();
+}
+
+/// Script of commands, the second one failing on the mock server not supporting `<lock>`.
+const SCRIPT: &str =
+    "# comment\nsend get-config running\n\nsend lock running\nsend get-config running\n";
+
+#[test]
+fn script_stops_on_first_failure() {
+    let (handle, config) = mock_server(data_server());
+    let script = config.with_file_name("script.txt");
+    std::fs::write(&script, SCRIPT).unwrap();
+    let output = run(
+        &config,
+        &["--profile", "mock", "--script", script.to_str().unwrap()],
+        "",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(
+        stderr.contains("Executed 3 command(s), 1 failed"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("script.txt:4: send lock running"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("use --keep-going"), "{}", stderr);
+    assert_eq!(operations(&handle), ["get-config", "lock", "close-session"]);
+    handle.join().unwrap();
+}
+
+#[test]
+fn stdin_script_keep_going() {
+    let (handle, config) = mock_server(data_server());
+    let output = run(
+        &config,
+        &["--profile", "mock", "--keep-going", "--script", "-"],
+        SCRIPT,
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(
+        stderr.contains("Executed 4 command(s), 1 failed"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("stdin:4: send lock running"), "{}", stderr);
+    assert!(!stderr.contains("use --keep-going"), "{}", stderr);
+    assert_eq!(
+        operations(&handle),
+        ["get-config", "lock", "get-config", "close-session"]
+    );
+    handle.join().unwrap();
+}
+
+#[test]
+fn piped_stdin_succeeds() {
+    let (handle, config) = mock_server(data_server());
+    let output = run(&config, &["--profile", "mock"], "send get-config running\n");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(operations(&handle), ["get-config", "close-session"]);
+    handle.join().unwrap();
+}
+
+#[test]
+fn failures_exit_code() {
+    let (handle, config) = mock_server(data_server());
+
+    // <rpc-error> reply of the one-shot command
+    let output = run(
+        &config,
+        &["--profile", "mock", "--", "send", "lock", "running"],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    // invalid command, not sent at all
+    let output = run(&config, &["--profile", "mock", "--", "send", "bogus"], "");
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    // unknown profile stops the batch, regardless of --keep-going
+    let output = run(
+        &config,
+        &["--profile", "unknown", "--keep-going", "--script", "-"],
+        SCRIPT,
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(
+        stderr.contains("Executed 1 command(s), 1 failed"),
+        "{}",
+        stderr
+    );
+
+    assert_eq!(
+        operations(&handle),
+        ["lock", "close-session", "close-session"]
+    );
+    handle.join().unwrap();
+}
+
+#[test]
+fn message_ids_of_sent_requests_only() {
+    let (handle, config) = mock_server(data_server());
+    // commit fails the validation against capabilities of server lacking :candidate
+    let script = "send get-config running\nsend commit\nsend --dry-run get-config running\nsend get-config running\n";
+    let output = run(
+        &config,
+        &["--profile", "mock", "--keep-going", "--script", "-"],
+        script,
+    );
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    let requests: Vec<_> = handle
+        .requests()
+        .into_iter()
+        .map(|x| (x.operation, x.message_id.unwrap_or_default()))
+        .collect();
+    let expected = [
+        ("get-config", "1"),
+        ("get-config", "2"),
+        ("close-session", "3"),
+    ];
+    assert_eq!(
+        requests,
+        expected.map(|(operation, id)| (operation.to_string(), id.to_string()))
+    );
+    handle.join().unwrap();
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::types::Capability;
+
+fuzz_target!(|urn: &str| {
+    if let Ok(capability) = Capability::from_urn(urn) {
+        // parsed capabilities round-trip via their URN
+        let reparsed = Capability::from_urn(&capability.get_urn()).unwrap();
+        assert_eq!(reparsed, capability);
+    }
+});
@@ -0,0 +1,46 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::{framing::FrameDecoder, BaseCapability};
+
+/// Decode all the messages from the stream fed at once, and in pieces of the size given by the first byte.
+fn decode(base: BaseCapability, data: &[u8]) {
+    let (piece_size, stream) = match data.split_first() {
+        Some((size, stream)) => (*size as usize + 1, stream),
+        None => return,
+    };
+
+    let mut whole = FrameDecoder::new(base);
+    whole.feed(stream);
+    let mut expected = vec![];
+    while let Ok(Some(message)) = whole.next_message() {
+        expected.push(message);
+    }
+
+    let mut pieces = FrameDecoder::new(base);
+    let mut actual = vec![];
+    let mut failed = false;
+    for piece in stream.chunks(piece_size) {
+        pieces.feed(piece);
+        loop {
+            match pieces.next_message() {
+                Ok(Some(message)) => actual.push(message),
+                Ok(None) => break,
+                Err(_) => {
+                    failed = true;
+                    break;
+                }
+            }
+        }
+        if failed {
+            break;
+        }
+    }
+    if !failed {
+        assert_eq!(expected, actual);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode(BaseCapability::Base11, data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::messages::{get_config::GetConfigResponse, NetconfResponse};
+
+fuzz_target!(|reply: &str| {
+    let _ = GetConfigResponse::from_netconf_rpc(reply);
+});
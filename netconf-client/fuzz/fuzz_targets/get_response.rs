@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::messages::{get::GetResponse, NetconfResponse};
+
+fuzz_target!(|reply: &str| {
+    let _ = GetResponse::from_netconf_rpc(reply);
+});
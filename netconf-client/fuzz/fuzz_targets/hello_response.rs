@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::messages::{hello::HelloResponse, NetconfResponse};
+
+fuzz_target!(|reply: &str| {
+    let _ = HelloResponse::from_netconf_rpc(reply);
+});
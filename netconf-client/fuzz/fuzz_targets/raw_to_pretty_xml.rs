@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::messages::raw_to_pretty_xml;
+
+fuzz_target!(|xml: &str| {
+    let _ = raw_to_pretty_xml(xml);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::{messages::NetconfResponse, types::SimpleResponse};
+
+fuzz_target!(|reply: &str| {
+    let _ = SimpleResponse::from_netconf_rpc(reply);
+});
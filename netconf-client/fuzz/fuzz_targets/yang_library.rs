@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use netconf_client::types::{XmlNode, YangLibrary};
+
+fuzz_target!(|data: &str| {
+    if let Ok(node) = XmlNode::parse(data) {
+        let _ = node.to_xml();
+        let _ = YangLibrary::from_data(&node);
+    }
+});
@@ -0,0 +1,239 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    common::XMLNS,
+    diff::ListHints,
+    types::{Datastore, XmlNode},
+};
+
+/// XML namespace of the configuration backup file.
+pub const BACKUP_XMLNS: &str = "urn:netconf-client:config-backup";
+
+/// YANG module implemented by server, in the given revision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleRevision {
+    pub name: String,
+    pub revision: Option<String>,
+}
+
+/// Configuration of a datastore, with the metadata of the server it was retrieved from.
+///
+/// Stored as XML file of the `<config-backup>` root, holding the configuration in the `<data>` element
+/// the same way as the `<get-config>` reply:
+///
+/// ```xml
+/// <config-backup xmlns="urn:netconf-client:config-backup">
+///   <device>192.0.2.1:830</device>
+///   <datastore>running</datastore>
+///   <timestamp>2022-05-01T10:00:00Z</timestamp>
+///   <capabilities><capability>urn:ietf:params:netconf:base:1.1</capability></capabilities>
+///   <modules><module><name>ietf-interfaces</name><revision>2018-02-20</revision></module></modules>
+///   <data xmlns="urn:ietf:params:xml:ns:netconf:base:1.0">...</data>
+/// </config-backup>
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigBackup {
+    /// Target NETCONF server, in the form of "host:port".
+    pub device: String,
+    pub datastore: Datastore,
+    /// Time of the backup, e.g. in the RFC 3339 format.
+    pub timestamp: String,
+    /// URNs of the capabilities advertised by server.
+    pub capabilities: Vec<String>,
+    /// YANG modules implemented by server.
+    pub modules: Vec<ModuleRevision>,
+    /// Top-level elements of the configuration.
+    pub config: Vec<XmlNode>,
+}
+
+impl ConfigBackup {
+    /// Serialize the backup into (pretty printed) XML document, keeping the text of configuration as is.
+    pub fn to_xml(&self) -> Result<String> {
+        let leaf = |name: &str, text: &str| {
+            XmlNode::new(name)
+                .with_namespace(BACKUP_XMLNS)
+                .with_text(text)
+        };
+
+        let mut capabilities = XmlNode::new("capabilities").with_namespace(BACKUP_XMLNS);
+        for capability in self.capabilities.iter() {
+            capabilities = capabilities.with_child(leaf("capability", capability));
+        }
+        let mut modules = XmlNode::new("modules").with_namespace(BACKUP_XMLNS);
+        for module in self.modules.iter() {
+            let mut node = XmlNode::new("module")
+                .with_namespace(BACKUP_XMLNS)
+                .with_child(leaf("name", &module.name));
+            if let Some(revision) = &module.revision {
+                node = node.with_child(leaf("revision", revision));
+            }
+            modules = modules.with_child(node);
+        }
+        let mut data = XmlNode::new("data").with_namespace(XMLNS);
+        data.children = self.config.clone();
+
+        let root = XmlNode::new("config-backup")
+            .with_namespace(BACKUP_XMLNS)
+            .with_child(leaf("device", &self.device))
+            .with_child(leaf("datastore", &self.datastore.to_string()))
+            .with_child(leaf("timestamp", &self.timestamp))
+            .with_child(capabilities)
+            .with_child(modules)
+            .with_child(data);
+        root.to_pretty_xml()
+    }
+
+    /// Parse the backup from XML document.
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let root = XmlNode::parse(xml)?;
+        if root.name != "config-backup" {
+            bail!(
+                "Expected <config-backup> root element, found <{}>",
+                root.name
+            );
+        }
+        let text = |name: &str| {
+            root.child_text(name)
+                .map(str::to_string)
+                .with_context(|| format!("Missing <{}> of the backup", name))
+        };
+
+        let capabilities = root
+            .child("capabilities")
+            .map(|x| {
+                x.children_named("capability")
+                    .map(|x| x.text().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let modules = root
+            .child("modules")
+            .map(|x| {
+                x.children_named("module")
+                    .map(|x| ModuleRevision {
+                        name: x.child_text("name").unwrap_or_default().to_string(),
+                        revision: x.child_text("revision").map(str::to_string),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let config = match root.child("data") {
+            Some(data) => data.children.clone(),
+            None => bail!("Missing <data> of the backup"),
+        };
+
+        Ok(Self {
+            device: text("device")?,
+            datastore: text("datastore")?.parse()?,
+            timestamp: text("timestamp")?,
+            capabilities,
+            modules,
+            config,
+        })
+    }
+
+    /// Problems of restoring the backup onto server implementing the `modules` - missing modules,
+    /// or modules of older revisions than in the backup.
+    ///
+    /// Newer revisions are backward compatible, as required by [RFC 7950 - section 11](https://datatracker.ietf.org/doc/html/rfc7950#section-11).
+    /// Nothing can be checked if the server modules are unknown, i.e. `modules` is empty.
+    pub fn incompatible_modules(&self, modules: &[ModuleRevision]) -> Vec<String> {
+        if modules.is_empty() {
+            return vec![];
+        }
+        self.modules
+            .iter()
+            .filter_map(|backup| {
+                let current = match modules.iter().find(|x| x.name == backup.name) {
+                    Some(current) => current,
+                    None => return Some(format!("module \"{}\" is missing", backup.name)),
+                };
+                // revisions are dates of "YYYY-MM-DD" format
+                match (&backup.revision, &current.revision) {
+                    (Some(backup_revision), Some(revision)) if revision < backup_revision => {
+                        Some(format!(
+                            "module \"{}\" is of revision {}, older than {} of the backup",
+                            backup.name, revision, backup_revision
+                        ))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The way the backup is loaded into the candidate datastore.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RestoreMethod {
+    /// `<edit-config>` with `replace` default operation.
+    Replace,
+    /// `<copy-config>` of the inline configuration.
+    Copy,
+}
+
+impl FromStr for RestoreMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "replace" => RestoreMethod::Replace,
+            "copy" => RestoreMethod::Copy,
+            s => bail!("Unknown restore method \"{}\"!", s),
+        })
+    }
+}
+
+impl std::fmt::Display for RestoreMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RestoreMethod::Replace => "replace",
+            RestoreMethod::Copy => "copy",
+        };
+        f.write_str(s)
+    }
+}
+
+impl RestoreMethod {
+    /// Provides all the values of the method.
+    pub fn values() -> Vec<&'static str> {
+        vec!["replace", "copy"]
+    }
+}
+
+/// Options of the [`crate::NetconfSession::restore()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoreOptions {
+    pub method: RestoreMethod,
+    /// Timeout of the confirmed commit in seconds, for servers supporting the `:confirmed-commit` capability.
+    pub confirm_timeout: u32,
+    /// Restore the backup despite the incompatible modules of server.
+    pub force: bool,
+    /// Lists of the configuration, for the check of the committed configuration against the backup.
+    pub list_hints: ListHints,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            method: RestoreMethod::Replace,
+            confirm_timeout: 600,
+            force: false,
+            list_hints: ListHints::new(),
+        }
+    }
+}
+
+/// Outcome of the successful [`crate::NetconfSession::restore()`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RestoreReport {
+    /// Problems of the modules, ignored by the forced restore.
+    pub incompatible_modules: Vec<String>,
+    /// Whether the candidate datastore has been validated prior to commit (`:validate` capability).
+    pub validated: bool,
+    /// Whether the configuration has been committed by confirmed commit (`:confirmed-commit` capability),
+    /// and confirmed only after the "running" datastore was checked to match the backup.
+    pub confirmed_commit: bool,
+}
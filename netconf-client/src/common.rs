@@ -3,10 +3,12 @@ pub const XMLNS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
 use anyhow::{bail, Result};
 use quick_xml::{
     events::{BytesEnd, BytesStart, Event},
-    Writer,
+    Reader, Writer,
 };
 use std::io::Cursor;
 
+use crate::types::RpcAttribute;
+
 /// Find the outermost tag with specified name,
 /// and return the string slice covering it and all of its contents.
 pub(crate) fn get_tag_slice<'i>(input: &'i str, tag: &str) -> Result<&'i str> {
@@ -55,3 +57,182 @@ pub(crate) fn xml_events_to_string(events: &[Event], wrap: RpcWrapMode) -> Resul
     let rpc_bytes = writer.into_inner().into_inner();
     Ok(String::from_utf8(rpc_bytes)?)
 }
+
+/// Extend the root element of `raw_xml` with custom `attributes`,
+/// including the declarations of their namespaces.
+///
+/// Fails for attributes clashing with those of the root element - `message-id`, namespace declarations
+/// & their prefixes, or with each other.
+pub(crate) fn push_root_attributes(raw_xml: &str, attributes: &[RpcAttribute]) -> Result<String> {
+    let mut reader = Reader::from_str(raw_xml);
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    let mut read_buf = Vec::new();
+    let mut root_found = false;
+
+    loop {
+        let event = reader.read_event(&mut read_buf)?;
+        match event {
+            Event::Eof => break,
+            Event::Start(elem) if !root_found => {
+                root_found = true;
+                let elem = extend_with_attributes(elem.into_owned(), attributes)?;
+                writer.write_event(Event::Start(elem))?;
+            }
+            Event::Empty(elem) if !root_found => {
+                root_found = true;
+                let elem = extend_with_attributes(elem.into_owned(), attributes)?;
+                writer.write_event(Event::Empty(elem))?;
+            }
+            event => writer.write_event(event)?,
+        }
+        read_buf.clear();
+    }
+
+    if !root_found {
+        bail!("Missing root element to attach attributes to");
+    }
+
+    Ok(String::from_utf8(writer.into_inner().into_inner())?)
+}
+
+fn extend_with_attributes(
+    mut elem: BytesStart<'static>,
+    attributes: &[RpcAttribute],
+) -> Result<BytesStart<'static>> {
+    // qualified names of the attributes present, & the namespace prefixes declared
+    let mut names = vec![];
+    let mut declared: Vec<(String, String)> = vec![];
+    for attribute in elem.attributes() {
+        let attribute = attribute?;
+        let key = String::from_utf8(attribute.key.to_vec())?;
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            let value = String::from_utf8(attribute.value.to_vec())?;
+            declared.push((prefix.to_string(), value));
+        }
+        names.push(key);
+    }
+
+    let mut extra = vec![];
+    for attribute in attributes.iter() {
+        let name = attribute.qualified_name();
+        if name == "xmlns" || name.starts_with("xmlns:") || name.starts_with("xml:") {
+            bail!("Custom attribute {} is reserved by XML", name);
+        }
+        if attribute.namespace.is_none() && name.contains(':') {
+            bail!(
+                "Custom attribute {} is prefixed, but without namespace",
+                name
+            );
+        }
+        if names.contains(&name) {
+            bail!("Custom attribute {} clashes with attribute of <rpc>", name);
+        }
+        if let Some((prefix, namespace)) = &attribute.namespace {
+            if prefix == "xmlns" || prefix == "xml" {
+                bail!(
+                    "Prefix \"{}\" of custom attribute {} is reserved by XML",
+                    prefix,
+                    name
+                );
+            }
+            match declared.iter().find(|(x, _)| x == prefix) {
+                Some((_, value)) if value != namespace => bail!(
+                    "Prefix \"{}\" of custom attribute {} is declared for namespace \"{}\" already",
+                    prefix,
+                    name,
+                    value
+                ),
+                Some(_) => {}
+                None => {
+                    extra.push((format!("xmlns:{}", prefix), namespace.clone()));
+                    declared.push((prefix.clone(), namespace.clone()));
+                }
+            }
+        }
+        extra.push((name.clone(), attribute.value.clone()));
+        names.push(name);
+    }
+    for (key, value) in extra.iter() {
+        elem.push_attribute((key.as_str(), value.as_str()));
+    }
+    Ok(elem)
+}
+
+/// Collect the custom attributes of the root element of `raw_xml`.
+///
+/// Standard attributes - `message-id`, default `xmlns` and namespace declarations - are not included.
+pub(crate) fn parse_root_attributes(raw_xml: &str) -> Result<Vec<RpcAttribute>> {
+    let mut reader = Reader::from_str(raw_xml);
+    let mut read_buf = Vec::new();
+
+    loop {
+        match reader.read_event(&mut read_buf)? {
+            Event::Eof => bail!("Missing root element"),
+            Event::Start(elem) | Event::Empty(elem) => {
+                let mut declarations = vec![];
+                let mut plain = vec![];
+                for attribute in elem.attributes() {
+                    let attribute = attribute?;
+                    let key = String::from_utf8(attribute.key.to_vec())?;
+                    let value = attribute.unescape_and_decode_value(&reader)?;
+                    match key.strip_prefix("xmlns:") {
+                        Some(prefix) => declarations.push((prefix.to_string(), value)),
+                        None => plain.push((key, value)),
+                    }
+                }
+
+                let attributes = plain
+                    .into_iter()
+                    .filter(|(key, _)| key != "message-id" && key != "xmlns")
+                    .map(|(key, value)| {
+                        let namespace = key.split_once(':').and_then(|(prefix, name)| {
+                            declarations
+                                .iter()
+                                .find(|(declared, _)| declared == prefix)
+                                .map(|(_, namespace)| (prefix, namespace, name))
+                        });
+                        match namespace {
+                            Some((prefix, namespace, name)) => {
+                                RpcAttribute::namespaced(prefix, namespace, name, &value)
+                            }
+                            None => RpcAttribute::new(&key, &value),
+                        }
+                    })
+                    .collect();
+                return Ok(attributes);
+            }
+            _ => {}
+        }
+        read_buf.clear();
+    }
+}
+
+/// Message-id & operation (name of the first child element, or `hello`) of the request `raw_xml`,
+/// empty strings for the missing ones.
+pub(crate) fn rpc_operation(raw_xml: &str) -> (String, String) {
+    let mut reader = Reader::from_str(raw_xml);
+    let mut read_buf = Vec::new();
+    let mut message_id = String::new();
+    let mut in_root = false;
+
+    loop {
+        match reader.read_event(&mut read_buf) {
+            Ok(Event::Start(elem)) | Ok(Event::Empty(elem)) => {
+                let name = String::from_utf8_lossy(elem.local_name()).to_string();
+                if in_root || name == "hello" {
+                    return (message_id, name);
+                }
+                message_id = elem
+                    .attributes()
+                    .flatten()
+                    .find(|x| x.key == b"message-id")
+                    .map(|x| String::from_utf8_lossy(&x.value).to_string())
+                    .unwrap_or_default();
+                in_root = true;
+            }
+            Ok(Event::Eof) | Err(_) => return (message_id, String::new()),
+            _ => {}
+        }
+        read_buf.clear();
+    }
+}
@@ -0,0 +1,606 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use quick_xml::escape::escape;
+
+use crate::{
+    common::XMLNS,
+    types::{XmlAttribute, XmlNode},
+};
+
+/// Kind of the YANG list-like node, identifying its entries among siblings of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListKind {
+    /// List identified by values of the key leaves.
+    List(Vec<String>),
+    /// Leaf-list identified by the value.
+    LeafList,
+}
+
+/// Schema-less hints of the diff engine, identifying entries of lists & leaf-lists by element name.
+///
+/// Repeated leaves without any hint are treated as leaf-list entries, other repeated elements
+/// without any hint are lined up by their order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListHints {
+    hints: HashMap<String, ListKind>,
+}
+
+impl ListHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare elements of the `name` as list entries identified by the `keys` leaves.
+    pub fn list(mut self, name: &str, keys: &[&str]) -> Self {
+        let keys = keys.iter().map(|x| x.to_string()).collect();
+        self.hints.insert(name.to_string(), ListKind::List(keys));
+        self
+    }
+
+    /// Declare elements of the `name` as leaf-list entries identified by their values.
+    pub fn leaf_list(mut self, name: &str) -> Self {
+        self.hints.insert(name.to_string(), ListKind::LeafList);
+        self
+    }
+
+    /// Hint of the elements with the `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&ListKind> {
+        self.hints.get(name)
+    }
+
+    /// Add the hints of `other`, keeping the already present ones.
+    pub fn merge(mut self, other: &ListHints) -> Self {
+        for (name, kind) in other.hints.iter() {
+            self.hints
+                .entry(name.clone())
+                .or_insert_with(|| kind.clone());
+        }
+        self
+    }
+}
+
+/// Identification of the element among its siblings of the same name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Entry {
+    /// The only element of the name.
+    Single,
+    /// List entry with the (key leaf name, value) pairs.
+    List(Vec<(String, String)>),
+    /// Leaf-list entry, identified by the text of the element.
+    LeafList,
+    /// N-th (1-based) of the repeated elements without any known identity.
+    Repeated(usize),
+}
+
+/// Kind of the difference of the element.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DiffKind {
+    Unchanged,
+    /// Element present on the new side only.
+    Added,
+    /// Element present on the old side only.
+    Removed,
+    /// Element present on both sides, with different text or attributes.
+    /// Its descendants may be changed as well.
+    Modified,
+    /// Element present on both sides with the same text & attributes, with changed descendants.
+    Nested,
+}
+
+/// Node of the configuration diff tree, lining up the elements of the old & new configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffNode {
+    pub kind: DiffKind,
+    pub entry: Entry,
+    /// The element without its children - of the new side, or of the old side if removed.
+    pub element: XmlNode,
+    /// The old element without its children, if modified.
+    pub old_element: Option<XmlNode>,
+    /// Differences of the child elements. All the children of added/removed element are added/removed as well.
+    pub children: Vec<DiffNode>,
+}
+
+/// Difference of two configurations, e.g. the `<data>` of `<get-config>` replies of two datastores.
+///
+/// List entries are lined up by their keys, if known via [`ListHints`], or by their name & namespace otherwise.
+/// The diff is rendered as unified text via [`Self::to_unified()`], as annotated tree via [`Self::to_tree()`],
+/// or turned into the `<edit-config>` patch via [`Self::to_edit_config()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    pub nodes: Vec<DiffNode>,
+}
+
+impl ConfigDiff {
+    /// Compare the top-level elements of the `old` & `new` configuration.
+    pub fn new(old: &[XmlNode], new: &[XmlNode], hints: &ListHints) -> Self {
+        Self {
+            nodes: diff_siblings(old, new, hints),
+        }
+    }
+
+    /// Compare two XML fragments of the configuration.
+    pub fn from_xml(old: &str, new: &str, hints: &ListHints) -> Result<Self> {
+        let old = XmlNode::parse_fragment(old)?;
+        let new = XmlNode::parse_fragment(new)?;
+        Ok(Self::new(&old, &new, hints))
+    }
+
+    /// Check whether the configurations are the same.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.iter().all(|x| x.kind == DiffKind::Unchanged)
+    }
+
+    /// Unified diff of the configurations, pretty printed as XML & lined up by the entries.
+    ///
+    /// Hunks of changed lines are surrounded by `context` unchanged lines, and headed by the path of the first change.
+    /// Returns empty string if there are no changes.
+    pub fn to_unified(&self, old_label: &str, new_label: &str, context: usize) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut lines = vec![];
+        for node in self.nodes.iter() {
+            node.unified_lines(0, None, "", &mut lines);
+        }
+
+        let changed: Vec<usize> = (0..lines.len())
+            .filter(|x| lines[*x].marker != ' ')
+            .collect();
+        let mut result = format!("--- {}\n+++ {}\n", old_label, new_label);
+        let mut index = 0;
+        while index < changed.len() {
+            let start = changed[index].saturating_sub(context);
+            let mut end = changed[index] + context;
+            while index + 1 < changed.len() && changed[index + 1] <= end + context + 1 {
+                index += 1;
+                end = changed[index] + context;
+            }
+            let end = end.min(lines.len() - 1);
+
+            let first_change = lines[start..=end]
+                .iter()
+                .find(|x| x.marker != ' ')
+                .map(|x| x.path.as_str())
+                .unwrap_or_default();
+            result.push_str(&format!("@@ {} @@\n", first_change));
+            for line in lines[start..=end].iter() {
+                result.push_str(&format!("{}{}\n", line.marker, line.text));
+            }
+            index += 1;
+        }
+        result
+    }
+
+    /// Tree of the changed elements & their ancestors, each one prefixed by the marker of the change:
+    /// `+` added, `-` removed, `~` modified (with `old -> new` value of leaves).
+    pub fn to_tree(&self) -> String {
+        let mut result = String::new();
+        for node in self.nodes.iter() {
+            node.tree_lines(0, &mut result);
+        }
+        result
+    }
+
+    /// Elements of the `<config>` for the `<edit-config>` request, changing the old configuration into the new one
+    /// with the default `merge` operation.
+    ///
+    /// Removed elements are deleted via the `operation="delete"` attribute, elements of changed attributes are replaced.
+    /// Entries of the repeated elements without any known identity cannot be addressed exactly.
+    pub fn to_edit_config(&self) -> Vec<XmlNode> {
+        self.nodes
+            .iter()
+            .filter_map(DiffNode::edit_config)
+            .collect()
+    }
+
+    /// Elements of the old configuration, as compared.
+    pub fn old_side(&self) -> Vec<XmlNode> {
+        self.nodes.iter().filter_map(|x| x.side(false)).collect()
+    }
+
+    /// Elements of the new configuration, as compared.
+    pub fn new_side(&self) -> Vec<XmlNode> {
+        self.nodes.iter().filter_map(|x| x.side(true)).collect()
+    }
+}
+
+struct Line {
+    marker: char,
+    text: String,
+    path: String,
+}
+
+impl DiffNode {
+    fn added_or_removed(node: &XmlNode, entry: Entry, kind: DiffKind, hints: &ListHints) -> Self {
+        let entries = entries(&node.children, &[], hints);
+        Self {
+            kind,
+            entry,
+            element: shallow(node),
+            old_element: None,
+            children: node
+                .children
+                .iter()
+                .zip(entries)
+                .map(|(child, entry)| Self::added_or_removed(child, entry, kind, hints))
+                .collect(),
+        }
+    }
+
+    fn compare(old: &XmlNode, new: &XmlNode, entry: Entry, hints: &ListHints) -> Self {
+        let children = diff_siblings(&old.children, &new.children, hints);
+        let old_element = shallow(old);
+        let element = shallow(new);
+        let (kind, old_element) = if !same_element(&old_element, &element) {
+            (DiffKind::Modified, Some(old_element))
+        } else if children.iter().any(|x| x.kind != DiffKind::Unchanged) {
+            (DiffKind::Nested, None)
+        } else {
+            (DiffKind::Unchanged, None)
+        };
+        Self {
+            kind,
+            entry,
+            element,
+            old_element,
+            children,
+        }
+    }
+
+    /// Path segment of the element, with predicate identifying the entry.
+    fn path_segment(&self) -> String {
+        match &self.entry {
+            Entry::Single => self.element.name.clone(),
+            Entry::List(keys) => {
+                let predicates: Vec<String> = keys
+                    .iter()
+                    .map(|(key, value)| format!("[{}='{}']", key, value))
+                    .collect();
+                format!("{}{}", self.element.name, predicates.join(""))
+            }
+            Entry::LeafList => format!("{}[.='{}']", self.element.name, self.element.text()),
+            Entry::Repeated(n) => format!("{}[{}]", self.element.name, n),
+        }
+    }
+
+    fn unified_lines(
+        &self,
+        depth: usize,
+        parent_namespace: Option<&str>,
+        parent_path: &str,
+        lines: &mut Vec<Line>,
+    ) {
+        let path = format!("{}/{}", parent_path, self.path_segment());
+        let indent = "  ".repeat(depth);
+        let marker = match self.kind {
+            DiffKind::Added => '+',
+            DiffKind::Removed => '-',
+            _ => ' ',
+        };
+        let mut push = |marker: char, text: String| {
+            lines.push(Line {
+                marker,
+                text: format!("{}{}", indent, text),
+                path: path.clone(),
+            })
+        };
+
+        if self.children.is_empty() {
+            if let Some(old) = &self.old_element {
+                push('-', leaf_tag(old, parent_namespace));
+                push('+', leaf_tag(&self.element, parent_namespace));
+            } else {
+                push(marker, leaf_tag(&self.element, parent_namespace));
+            }
+            return;
+        }
+
+        if let Some(old) = &self.old_element {
+            push('-', open_tag(old, parent_namespace));
+            if let Some(text) = &old.text {
+                push('-', format!("  {}", escape_str(text)));
+            }
+            push('+', open_tag(&self.element, parent_namespace));
+            if let Some(text) = &self.element.text {
+                push('+', format!("  {}", escape_str(text)));
+            }
+        } else {
+            push(marker, open_tag(&self.element, parent_namespace));
+            if let Some(text) = &self.element.text {
+                push(marker, format!("  {}", escape_str(text)));
+            }
+        }
+        for child in self.children.iter() {
+            child.unified_lines(depth + 1, self.element.namespace.as_deref(), &path, lines);
+        }
+        lines.push(Line {
+            marker,
+            text: format!("{}</{}>", indent, self.element.name),
+            path,
+        });
+    }
+
+    fn tree_lines(&self, depth: usize, result: &mut String) {
+        let marker = match self.kind {
+            DiffKind::Unchanged => return,
+            DiffKind::Added => '+',
+            DiffKind::Removed => '-',
+            DiffKind::Modified => '~',
+            DiffKind::Nested => ' ',
+        };
+        self.tree_line(marker, depth, result);
+        for child in self.children.iter() {
+            match self.kind {
+                // show the whole added/removed sub-tree
+                DiffKind::Added | DiffKind::Removed => child.tree_all(marker, depth + 1, result),
+                _ => child.tree_lines(depth + 1, result),
+            }
+        }
+    }
+
+    fn tree_all(&self, marker: char, depth: usize, result: &mut String) {
+        self.tree_line(marker, depth, result);
+        for child in self.children.iter() {
+            child.tree_all(marker, depth + 1, result);
+        }
+    }
+
+    fn tree_line(&self, marker: char, depth: usize, result: &mut String) {
+        let indent = "  ".repeat(depth);
+        let label = match (&self.entry, self.children.is_empty()) {
+            (Entry::Single | Entry::Repeated(_), true) => {
+                let value = match &self.old_element {
+                    Some(old) => format!("{} -> {}", old.text(), self.element.text()),
+                    None => self.element.text().to_string(),
+                };
+                format!("{}: {}", self.path_segment(), value)
+            }
+            _ => self.path_segment(),
+        };
+        result.push_str(&format!("{} {}{}\n", marker, indent, label));
+    }
+
+    /// Element of the old/new side, with all its descendants.
+    fn side(&self, new: bool) -> Option<XmlNode> {
+        let mut node = match (self.kind, new) {
+            (DiffKind::Added, false) | (DiffKind::Removed, true) => return None,
+            (DiffKind::Modified, false) => self.old_element.clone().unwrap_or_default(),
+            _ => self.element.clone(),
+        };
+        node.children = self.children.iter().filter_map(|x| x.side(new)).collect();
+        Some(node)
+    }
+
+    fn edit_config(&self) -> Option<XmlNode> {
+        match self.kind {
+            DiffKind::Unchanged => None,
+            DiffKind::Added => self.side(true),
+            DiffKind::Removed => {
+                let mut node = self.addressing_element();
+                if self.entry != Entry::LeafList {
+                    node.text = None;
+                }
+                node.set_attribute(operation_attribute("delete"));
+                Some(node)
+            }
+            DiffKind::Modified if self.attributes_modified() => {
+                let mut node = self.side(true)?;
+                node.set_attribute(operation_attribute("replace"));
+                Some(node)
+            }
+            DiffKind::Modified if self.children.is_empty() => Some(self.element.clone()),
+            DiffKind::Modified | DiffKind::Nested => {
+                let mut node = self.addressing_element();
+                node.children
+                    .extend(self.children.iter().filter_map(DiffNode::edit_config));
+                Some(node)
+            }
+        }
+    }
+
+    /// The element with its list keys only (if any), addressing the entry in the `<edit-config>`.
+    fn addressing_element(&self) -> XmlNode {
+        let mut node = self.element.clone();
+        if let Entry::List(keys) = &self.entry {
+            for (key, value) in keys {
+                let namespace = self
+                    .children
+                    .iter()
+                    .find(|x| &x.element.name == key)
+                    .and_then(|x| x.element.namespace.clone());
+                node.children.push(XmlNode {
+                    name: key.clone(),
+                    namespace,
+                    text: Some(value.clone()),
+                    ..Default::default()
+                });
+            }
+        }
+        node
+    }
+
+    fn attributes_modified(&self) -> bool {
+        match &self.old_element {
+            Some(old) => !same_attributes(&old.attributes, &self.element.attributes),
+            None => false,
+        }
+    }
+}
+
+/// Line up the `old` & `new` sibling elements, keeping the order of the new ones.
+/// Removed elements precede the new elements following them, whether matched or added.
+fn diff_siblings(old: &[XmlNode], new: &[XmlNode], hints: &ListHints) -> Vec<DiffNode> {
+    let old_entries = entries(old, new, hints);
+    let new_entries = entries(new, old, hints);
+
+    let identity = |node: &XmlNode, entry: &Entry| {
+        let value = match entry {
+            Entry::LeafList => node.text.clone(),
+            _ => None,
+        };
+        (
+            node.namespace.clone(),
+            node.name.clone(),
+            entry.clone(),
+            value,
+        )
+    };
+    let mut old_by_identity: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, (node, entry)) in old.iter().zip(old_entries.iter()).enumerate().rev() {
+        old_by_identity
+            .entry(identity(node, entry))
+            .or_default()
+            .push(index);
+    }
+    let matches: Vec<Option<usize>> = new
+        .iter()
+        .zip(new_entries.iter())
+        .map(|(node, entry)| {
+            old_by_identity
+                .get_mut(&identity(node, entry))
+                .and_then(|x| x.pop())
+        })
+        .collect();
+    let mut removed = vec![true; old.len()];
+    for index in matches.iter().flatten() {
+        removed[*index] = false;
+    }
+
+    let mut result = vec![];
+    let mut next_old = 0;
+    // removed elements up to the old index, or up to the next matched old element
+    let mut push_removed = |until: Option<usize>, result: &mut Vec<DiffNode>| {
+        let until = until.unwrap_or_else(|| {
+            (next_old..old.len())
+                .find(|x| !removed[*x])
+                .unwrap_or(old.len())
+        });
+        while next_old < until {
+            if removed[next_old] {
+                let entry = old_entries[next_old].clone();
+                let node =
+                    DiffNode::added_or_removed(&old[next_old], entry, DiffKind::Removed, hints);
+                result.push(node);
+            }
+            next_old += 1;
+        }
+    };
+    for ((node, entry), old_index) in new.iter().zip(new_entries).zip(matches) {
+        match old_index {
+            Some(old_index) => {
+                push_removed(Some(old_index + 1), &mut result);
+                result.push(DiffNode::compare(&old[old_index], node, entry, hints));
+            }
+            None => {
+                push_removed(None, &mut result);
+                let node = DiffNode::added_or_removed(node, entry, DiffKind::Added, hints);
+                result.push(node);
+            }
+        }
+    }
+    push_removed(Some(old.len()), &mut result);
+    result
+}
+
+/// Identification of the `nodes` among their siblings, incl. the `other` side siblings for detection of the repeated ones.
+fn entries(nodes: &[XmlNode], other: &[XmlNode], hints: &ListHints) -> Vec<Entry> {
+    let mut seen = HashSet::new();
+    let mut repeated = HashSet::new();
+    for side in [nodes, other] {
+        seen.clear();
+        for node in side {
+            if !seen.insert((&node.namespace, &node.name)) {
+                repeated.insert((&node.namespace, &node.name));
+            }
+        }
+    }
+
+    let mut counters: HashMap<(&Option<String>, &String), usize> = HashMap::new();
+    nodes
+        .iter()
+        .map(|node| match hints.get(&node.name) {
+            Some(ListKind::List(keys)) => Entry::List(
+                keys.iter()
+                    .map(|key| (key.clone(), node.child_text(key).unwrap_or("").to_string()))
+                    .collect(),
+            ),
+            Some(ListKind::LeafList) => Entry::LeafList,
+            None if !repeated.contains(&(&node.namespace, &node.name)) => Entry::Single,
+            None if node.is_leaf() => Entry::LeafList,
+            None => {
+                let counter = counters.entry((&node.namespace, &node.name)).or_insert(0);
+                *counter += 1;
+                Entry::Repeated(*counter)
+            }
+        })
+        .collect()
+}
+
+/// Copy of the element without its children & source document namespace prefixes/declarations.
+fn shallow(node: &XmlNode) -> XmlNode {
+    XmlNode {
+        name: node.name.clone(),
+        prefix: None,
+        namespace: node.namespace.clone(),
+        namespace_declarations: vec![],
+        attributes: node.attributes.clone(),
+        children: vec![],
+        text: node.text.clone(),
+    }
+}
+
+fn same_element(a: &XmlNode, b: &XmlNode) -> bool {
+    a.text == b.text && same_attributes(&a.attributes, &b.attributes)
+}
+
+/// Attributes are compared irrespective of their order & namespace prefixes.
+fn same_attributes(a: &[XmlAttribute], b: &[XmlAttribute]) -> bool {
+    let contains = |attributes: &[XmlAttribute], x: &XmlAttribute| {
+        attributes
+            .iter()
+            .any(|y| y.name == x.name && y.namespace == x.namespace && y.value == x.value)
+    };
+    a.len() == b.len() && a.iter().all(|x| contains(b, x))
+}
+
+fn operation_attribute(operation: &str) -> XmlAttribute {
+    XmlAttribute {
+        name: "operation".to_string(),
+        prefix: Some("nc".to_string()),
+        namespace: Some(XMLNS.to_string()),
+        value: operation.to_string(),
+    }
+}
+
+fn escape_str(s: &str) -> String {
+    String::from_utf8_lossy(&escape(s.as_bytes())).to_string()
+}
+
+/// Opening tag of the element, with its namespace declared if different from the parent one.
+fn open_tag(node: &XmlNode, parent_namespace: Option<&str>) -> String {
+    let mut tag = format!("<{}", node.name);
+    if node.namespace.as_deref() != parent_namespace {
+        tag.push_str(&format!(
+            " xmlns=\"{}\"",
+            escape_str(node.namespace.as_deref().unwrap_or_default())
+        ));
+    }
+    for attribute in node.attributes.iter() {
+        tag.push_str(&format!(
+            " {}=\"{}\"",
+            attribute.qualified_name(),
+            escape_str(&attribute.value)
+        ));
+    }
+    tag.push('>');
+    tag
+}
+
+fn leaf_tag(node: &XmlNode, parent_namespace: Option<&str>) -> String {
+    let open = open_tag(node, parent_namespace);
+    match &node.text {
+        Some(text) => format!("{}{}</{}>", open, escape_str(text), node.name),
+        None => format!("{}/>", open.trim_end_matches('>')),
+    }
+}
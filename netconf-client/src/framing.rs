@@ -0,0 +1,203 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+
+/// Type of a `:base` capability defining the framing of messages exchanged between the endpoints.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BaseCapability {
+    /// NETCONF 1.0 end-of-message delimited framing.
+    Base,
+    /// NETCONF 1.1 chunked framing.
+    Base11,
+}
+
+impl BaseCapability {
+    /// [RFC 6242](https://datatracker.ietf.org/doc/html/rfc6242) specified end-of-message separator.
+    pub fn eom_separator(&self) -> &'static str {
+        match self {
+            BaseCapability::Base => "]]>]]>",
+            BaseCapability::Base11 => "\n##\n",
+        }
+    }
+}
+
+impl Display for BaseCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            BaseCapability::Base => "plain :base:1.0",
+            BaseCapability::Base11 => "chunked :base:1.1",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Maximum chunk size allowed by [RFC 6242 - section 4.2](https://datatracker.ietf.org/doc/html/rfc6242#section-4.2).
+const MAX_CHUNK_SIZE: u64 = 4294967295;
+
+/// Encode the whole `message` into framed bytes ready to be written into the transport stream.
+///
+/// Chunked framing puts the whole message into a single chunk.
+pub fn encode_message(message: &str, base: BaseCapability) -> Vec<u8> {
+    let mut result = Vec::with_capacity(message.len() + 16);
+    if base == BaseCapability::Base11 && !message.is_empty() {
+        result.extend_from_slice(format!("\n#{}\n", message.len()).as_bytes());
+    }
+    result.extend_from_slice(message.as_bytes());
+    result.extend_from_slice(base.eom_separator().as_bytes());
+    result
+}
+
+/// Incremental decoder of framed messages.
+///
+/// Raw bytes read from the transport are fed via [`Self::feed()`] in arbitrarily sized pieces,
+/// complete messages are then taken via [`Self::next_message()`].
+/// Any bytes following the decoded message are kept for the subsequent messages.
+///
+/// Decoding resumes where the previous attempt stopped - the already searched bytes (end-of-message framing),
+/// or the already parsed chunks (chunked framing) are not scanned again, keeping the decoding linear in the message size.
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    base: BaseCapability,
+    buffer: Vec<u8>,
+    /// Start of the next message in the buffer, preceded by the already decoded ones.
+    start: usize,
+    /// Offset in the buffer to resume decoding from.
+    scan: usize,
+    /// Content of the chunks parsed so far, of the incomplete message.
+    chunks: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Create new decoder with empty buffer, using the specified framing.
+    pub fn new(base: BaseCapability) -> Self {
+        Self {
+            base,
+            buffer: vec![],
+            start: 0,
+            scan: 0,
+            chunks: vec![],
+        }
+    }
+
+    /// Framing currently used by the decoder.
+    pub fn base(&self) -> BaseCapability {
+        self.base
+    }
+
+    /// Switch the framing, e.g. after the `<hello>` exchange. Already buffered data are kept,
+    /// and decoded again by the new framing.
+    pub fn set_base(&mut self, base: BaseCapability) {
+        self.base = base;
+        self.scan = self.start;
+        self.chunks.clear();
+    }
+
+    /// Append bytes read from the transport.
+    pub fn feed(&mut self, data: &[u8]) {
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.scan -= self.start;
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Data buffered so far, not yet decoded into a complete message.
+    pub fn pending(&self) -> &[u8] {
+        &self.buffer[self.start..]
+    }
+
+    /// Decode the next complete message from the buffered data.
+    ///
+    /// Returns `Ok(None)` if more data are needed, or error in case of framing violation.
+    /// Invalid UTF-8 sequences are replaced in the message.
+    pub fn next_message(&mut self) -> Result<Option<String>> {
+        let decoded = match self.base {
+            BaseCapability::Base => self.decode_eom(),
+            BaseCapability::Base11 => self.decode_chunked()?,
+        };
+        Ok(decoded.map(|message| String::from_utf8_lossy(&message).into_owned()))
+    }
+
+    /// End-of-message framing - [RFC 6242 - section 4.3](https://datatracker.ietf.org/doc/html/rfc6242#section-4.3).
+    fn decode_eom(&mut self) -> Option<Vec<u8>> {
+        let separator = BaseCapability::Base.eom_separator().as_bytes();
+        let position = self.buffer[self.scan..]
+            .windows(separator.len())
+            .position(|window| window == separator);
+        match position {
+            Some(index) => {
+                let end = self.scan + index;
+                let message = self.buffer[self.start..end].to_vec();
+                self.start = end + separator.len();
+                self.scan = self.start;
+                Some(message)
+            }
+            None => {
+                // separator may be split by the next data
+                let searched = self.buffer.len().saturating_sub(separator.len() - 1);
+                self.scan = self.scan.max(searched);
+                None
+            }
+        }
+    }
+
+    /// Chunked framing - [RFC 6242 - section 4.2](https://datatracker.ietf.org/doc/html/rfc6242#section-4.2).
+    fn decode_chunked(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            let index = self.scan;
+            let rest = &self.buffer[index..];
+            if rest.len() < 3 {
+                return Ok(None);
+            }
+            if rest[0] != b'\n' || rest[1] != b'#' {
+                bail!(
+                    "Invalid chunk header start at offset {}: {:?}",
+                    index - self.start,
+                    String::from_utf8_lossy(&rest[..2])
+                );
+            }
+            if rest[2] == b'#' {
+                // end-of-chunks
+                match rest.get(3) {
+                    None => return Ok(None),
+                    Some(b'\n') if self.chunks.is_empty() => {
+                        bail!("End of chunks without any chunk")
+                    }
+                    Some(b'\n') => {
+                        self.start = index + 4;
+                        self.scan = self.start;
+                        return Ok(Some(std::mem::take(&mut self.chunks)));
+                    }
+                    Some(other) => bail!("Invalid end of chunks marker, found byte {:#04x}", other),
+                }
+            }
+
+            let digits = rest[2..].iter().take_while(|x| x.is_ascii_digit()).count();
+            let header_end = 2 + digits;
+            if digits > 10 {
+                bail!("Chunk size too long");
+            }
+            match rest.get(header_end) {
+                None => return Ok(None),
+                Some(b'\n') if digits > 0 => {}
+                Some(other) => bail!("Invalid chunk size, found byte {:#04x}", other),
+            }
+            if rest[2] == b'0' {
+                bail!("Chunk size must not start with zero");
+            }
+            let size: u64 = std::str::from_utf8(&rest[2..header_end])?.parse()?;
+            if size > MAX_CHUNK_SIZE {
+                bail!("Chunk size {} exceeds maximum {}", size, MAX_CHUNK_SIZE);
+            }
+
+            let data_start = header_end + 1;
+            let data_end = match data_start.checked_add(size as usize) {
+                Some(end) if end <= rest.len() => end,
+                _ => return Ok(None),
+            };
+            self.chunks.extend_from_slice(&rest[data_start..data_end]);
+            self.scan = index + data_end;
+        }
+    }
+}
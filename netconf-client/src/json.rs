@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+
+use crate::{
+    common::XMLNS,
+    diff::ListKind,
+    schema::{LeafType, SchemaHints},
+    types::{Capability, XmlAttribute, XmlNode},
+};
+
+/// Name of the YANG module defining the NETCONF `operation` annotation.
+const NETCONF_MODULE: &str = "ietf-netconf";
+
+/// Encoder of the XML data tree into JSON - [RFC 7951](https://datatracker.ietf.org/doc/html/rfc7951) style.
+///
+/// Member names are qualified by the YANG module name, wherever the namespace differs from the parent one,
+/// lists & leaf-lists are encoded as arrays. Leaves are typed by the [`SchemaHints`], strings otherwise -
+/// except for the leaves without any value, encoded as the `empty` type.
+///
+/// Elements of namespaces of unknown modules are left unqualified.
+#[derive(Debug, Clone, Default)]
+pub struct JsonEncoder {
+    hints: SchemaHints,
+    modules: HashMap<String, String>,
+}
+
+impl JsonEncoder {
+    pub fn new(hints: SchemaHints) -> Self {
+        Self {
+            hints,
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Add the YANG modules advertised by server, for qualifying the member names.
+    pub fn with_capabilities(mut self, capabilities: &[Capability]) -> Self {
+        for capability in capabilities {
+            if let Capability::YangModule(module) = capability {
+                self.modules
+                    .insert(module.namespace.clone(), module.module.clone());
+            }
+        }
+        self
+    }
+
+    /// Add the YANG module of given `namespace`.
+    pub fn with_module(mut self, namespace: &str, name: &str) -> Self {
+        self.modules.insert(namespace.to_string(), name.to_string());
+        self
+    }
+
+    /// JSON object of the top-level data `nodes`, e.g. children of the `<data>` element.
+    pub fn encode(&self, nodes: &[XmlNode]) -> Value {
+        Value::Object(self.encode_siblings(nodes, None, &[]))
+    }
+
+    /// JSON of the whole NETCONF message - the content of `<data>` for replies carrying it, the root element otherwise.
+    pub fn encode_message(&self, xml: &str) -> Result<Value> {
+        let root = XmlNode::parse(xml)?;
+        match root.child("data") {
+            Some(data) if root.name == "rpc-reply" => {
+                let scope = declarations(&[], &root);
+                Ok(Value::Object(self.encode_siblings(
+                    &data.children,
+                    None,
+                    &declarations(&scope, data),
+                )))
+            }
+            _ => Ok(self.encode(std::slice::from_ref(&root))),
+        }
+    }
+
+    /// Name of the YANG module of given `namespace` - advertised by server, or known to the schema hints.
+    pub fn module_name(&self, namespace: &str) -> Option<&str> {
+        self.modules
+            .get(namespace)
+            .map(String::as_str)
+            .or_else(|| self.hints.module_name(namespace))
+    }
+
+    /// Members of the sibling elements, grouped by qualified name in order of the first appearance.
+    fn encode_siblings(
+        &self,
+        nodes: &[XmlNode],
+        parent_namespace: Option<&str>,
+        scope: &[(Option<String>, String)],
+    ) -> Map<String, Value> {
+        let mut groups: Vec<(String, Vec<&XmlNode>)> = vec![];
+        for node in nodes {
+            let name = match (node.namespace.as_deref(), parent_namespace) {
+                (Some(namespace), parent) if Some(namespace) != parent => {
+                    match self.module_name(namespace) {
+                        Some(module) => format!("{}:{}", module, node.name),
+                        None => node.name.clone(),
+                    }
+                }
+                _ => node.name.clone(),
+            };
+            match groups.iter_mut().find(|(x, _)| *x == name) {
+                Some((_, group)) => group.push(node),
+                None => groups.push((name, vec![node])),
+            }
+        }
+
+        let mut members = Map::new();
+        for (name, group) in groups {
+            let is_list = group.len() > 1 || self.hints.list_hints().get(&group[0].name).is_some();
+            let value = match is_list {
+                true => Value::Array(group.iter().map(|x| self.encode_node(x, scope)).collect()),
+                false => self.encode_node(group[0], scope),
+            };
+            members.insert(name, value);
+        }
+        members
+    }
+
+    fn encode_node(&self, node: &XmlNode, scope: &[(Option<String>, String)]) -> Value {
+        let scope = declarations(scope, node);
+        let leaf_type = self.hints.leaf_type(&node.name);
+        if !node.children.is_empty() {
+            return Value::Object(self.encode_siblings(
+                &node.children,
+                node.namespace.as_deref(),
+                &scope,
+            ));
+        }
+        let is_leaf_list = matches!(
+            self.hints.list_hints().get(&node.name),
+            Some(ListKind::LeafList)
+        );
+
+        let text = node.text.as_deref().unwrap_or("");
+        match leaf_type {
+            Some(LeafType::Number) => match text.trim().parse::<i64>() {
+                Ok(number) => Value::from(number),
+                Err(_) => Value::from(text),
+            },
+            Some(LeafType::Boolean) => match text.trim() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::from(text),
+            },
+            Some(LeafType::Empty) => Value::Array(vec![Value::Null]),
+            Some(LeafType::IdentityRef) => Value::from(self.qualify_identity(text.trim(), &scope)),
+            Some(LeafType::String) => Value::from(text),
+            None if text.is_empty() && !is_leaf_list => Value::Array(vec![Value::Null]),
+            None => Value::from(text),
+        }
+    }
+
+    /// Identity of the "prefix:name" XML value, qualified by the module name instead of the XML prefix.
+    fn qualify_identity(&self, value: &str, scope: &[(Option<String>, String)]) -> String {
+        let (prefix, identity) = match value.split_once(':') {
+            Some((prefix, identity)) => (prefix, identity),
+            None => return value.to_string(),
+        };
+        let namespace = scope
+            .iter()
+            .rev()
+            .find(|(x, _)| x.as_deref() == Some(prefix))
+            .map(|(_, namespace)| namespace.as_str());
+        match namespace.and_then(|x| self.module_name(x)) {
+            Some(module) => format!("{}:{}", module, identity),
+            None => value.to_string(),
+        }
+    }
+}
+
+/// Namespace declarations in scope of the `node` - the inherited ones followed by its own.
+fn declarations(
+    scope: &[(Option<String>, String)],
+    node: &XmlNode,
+) -> Vec<(Option<String>, String)> {
+    let mut declarations = scope.to_vec();
+    declarations.extend(node.namespace_declarations.iter().cloned());
+    declarations
+}
+
+/// Decoder of the JSON of [RFC 7951](https://datatracker.ietf.org/doc/html/rfc7951) style into the XML data tree - reverse of [`JsonEncoder`].
+///
+/// Members qualified by the YANG module name get namespace of the module, the unqualified ones inherit the parent namespace.
+/// Arrays are decoded as entries of lists & leaf-lists, `[null]` as the `empty` leaf.
+///
+/// Annotations of [RFC 7952](https://datatracker.ietf.org/doc/html/rfc7952#section-5.2) are decoded as attributes - `"@": {...}` of the object itself,
+/// and `"@name": {...}` of its sibling member `name`. The `"@operation": "delete"` member is a shorthand of `"@": {"ietf-netconf:operation": "delete"}`.
+#[derive(Debug, Clone, Default)]
+pub struct JsonDecoder {
+    hints: SchemaHints,
+    namespaces: HashMap<String, String>,
+}
+
+impl JsonDecoder {
+    pub fn new(hints: SchemaHints) -> Self {
+        Self {
+            hints,
+            namespaces: HashMap::new(),
+        }
+    }
+
+    /// Add the YANG modules advertised by server, for resolving namespaces of the member names.
+    pub fn with_capabilities(mut self, capabilities: &[Capability]) -> Self {
+        for capability in capabilities {
+            if let Capability::YangModule(module) = capability {
+                self.namespaces
+                    .insert(module.module.clone(), module.namespace.clone());
+            }
+        }
+        self
+    }
+
+    /// Add the YANG module of given `namespace`.
+    pub fn with_module(mut self, namespace: &str, name: &str) -> Self {
+        self.namespaces
+            .insert(name.to_string(), namespace.to_string());
+        self
+    }
+
+    /// Top-level elements of the data, from the JSON object of module-qualified members.
+    pub fn decode(&self, value: &Value) -> Result<Vec<XmlNode>> {
+        match value {
+            Value::Object(members) => Ok(self.decode_object(members, None)?.0),
+            _ => bail!("Expected JSON object of the data"),
+        }
+    }
+
+    /// Top-level elements of the data, from the JSON text.
+    pub fn decode_json(&self, json: &str) -> Result<Vec<XmlNode>> {
+        self.decode(&serde_json::from_str(json)?)
+    }
+
+    /// Top-level elements of the data, from the YAML document of the same structure as JSON.
+    ///
+    /// Keys of the annotations must be quoted, e.g. `"@operation": delete`.
+    pub fn decode_yaml(&self, yaml: &str) -> Result<Vec<XmlNode>> {
+        self.decode(&from_yaml(yaml)?)
+    }
+
+    fn namespace(&self, module: &str) -> Result<&str> {
+        if module == NETCONF_MODULE {
+            return Ok(XMLNS);
+        }
+        match self.namespaces.get(module) {
+            Some(namespace) => Ok(namespace),
+            None => match self.hints.module_namespace(module) {
+                Some(namespace) => Ok(namespace),
+                None => bail!("Unknown namespace of the YANG module \"{}\"", module),
+            },
+        }
+    }
+
+    /// Child elements & attributes (own annotations) of the object.
+    fn decode_object(
+        &self,
+        members: &Map<String, Value>,
+        namespace: Option<&str>,
+    ) -> Result<(Vec<XmlNode>, Vec<XmlAttribute>)> {
+        let mut groups: Vec<(&str, Vec<XmlNode>)> = vec![];
+        let mut annotations = vec![];
+        let mut sibling_annotations = vec![];
+        for (name, value) in members.iter() {
+            match (name.strip_prefix('@'), value) {
+                (Some(""), Value::Object(own)) => {
+                    for (name, value) in own.iter() {
+                        annotations.push(self.annotation(name, value)?);
+                    }
+                }
+                (Some(""), _) => bail!("Annotations \"@\" must be JSON object"),
+                (Some(name), Value::Object(_) | Value::Array(_)) => {
+                    sibling_annotations.push((name, value))
+                }
+                (Some(name), value) => annotations.push(self.annotation(name, value)?),
+                (None, value) => {
+                    let (child_namespace, local_name) = match name.split_once(':') {
+                        Some((module, local_name)) => (Some(self.namespace(module)?), local_name),
+                        None => (namespace, name.as_str()),
+                    };
+                    let child_namespace = match child_namespace {
+                        Some(namespace) => namespace,
+                        None => bail!("Missing module of the top-level member \"{}\"", name),
+                    };
+                    let nodes = match value {
+                        Value::Array(entries) if !is_empty_value(value) => entries
+                            .iter()
+                            .map(|x| self.decode_value(local_name, child_namespace, x))
+                            .collect::<Result<_>>()?,
+                        value => vec![self.decode_value(local_name, child_namespace, value)?],
+                    };
+                    groups.push((name, nodes));
+                }
+            }
+        }
+
+        for (name, value) in sibling_annotations {
+            let nodes = match groups.iter_mut().find(|(x, _)| *x == name) {
+                Some((_, nodes)) => nodes,
+                None => bail!("Annotated member \"{}\" not found", name),
+            };
+            for (index, node) in nodes.iter_mut().enumerate() {
+                // leaf-list entries are annotated one by one
+                let entry = match value {
+                    Value::Array(entries) => entries.get(index).unwrap_or(&Value::Null),
+                    value => value,
+                };
+                if let Value::Object(entry) = entry {
+                    for (name, value) in entry.iter() {
+                        node.set_attribute(self.annotation(name, value)?);
+                    }
+                }
+            }
+        }
+        let children = groups.into_iter().flat_map(|(_, nodes)| nodes).collect();
+        Ok((children, annotations))
+    }
+
+    fn decode_value(&self, name: &str, namespace: &str, value: &Value) -> Result<XmlNode> {
+        let mut node = XmlNode::new(name).with_namespace(namespace);
+        match value {
+            Value::Object(members) => {
+                let (children, attributes) = self.decode_object(members, Some(namespace))?;
+                node.children = children;
+                for attribute in attributes {
+                    node.set_attribute(attribute);
+                }
+            }
+            value if is_empty_value(value) || value.is_null() => {}
+            Value::Array(_) => bail!("Unexpected nested array of \"{}\"", name),
+            Value::String(text) => {
+                if let (Some(LeafType::IdentityRef), Some((module, _))) =
+                    (self.hints.leaf_type(name), text.split_once(':'))
+                {
+                    // module name serves as the XML prefix of the identity
+                    let declaration = (
+                        Some(module.to_string()),
+                        self.namespace(module)?.to_string(),
+                    );
+                    node.namespace_declarations.push(declaration);
+                }
+                node.text = Some(text.clone());
+            }
+            value => node.text = Some(value.to_string()),
+        }
+        Ok(node)
+    }
+
+    /// Attribute of the annotation - `module:name`, or the NETCONF `operation`.
+    fn annotation(&self, name: &str, value: &Value) -> Result<XmlAttribute> {
+        let value = match value {
+            Value::String(text) => text.clone(),
+            Value::Number(_) | Value::Bool(_) => value.to_string(),
+            _ => bail!("Unsupported value of the annotation \"{}\"", name),
+        };
+        let (module, name) = match name.split_once(':') {
+            Some((module, name)) => (module, name),
+            None if name == "operation" => (NETCONF_MODULE, name),
+            None => bail!("Missing module of the annotation \"{}\"", name),
+        };
+        let prefix = match module {
+            NETCONF_MODULE => "nc",
+            module => module,
+        };
+        Ok(XmlAttribute {
+            name: name.to_string(),
+            prefix: Some(prefix.to_string()),
+            namespace: Some(self.namespace(module)?.to_string()),
+            value,
+        })
+    }
+}
+
+/// The `[null]` value of the `empty` leaf.
+fn is_empty_value(value: &Value) -> bool {
+    matches!(value, Value::Array(entries) if entries.len() == 1 && entries[0].is_null())
+}
+
+/// YAML document of the (JSON) `value`.
+pub fn to_yaml(value: &Value) -> Result<String> {
+    Ok(serde_yaml::to_string(value)?)
+}
+
+/// JSON value of the YAML document.
+///
+/// Keys of the annotations must be quoted, e.g. `"@operation": delete` - `@` is reserved by YAML.
+pub fn from_yaml(yaml: &str) -> Result<Value> {
+    serde_yaml::from_str(yaml).with_context(|| {
+        "Invalid YAML document (keys of the annotations must be quoted, e.g. \"@operation\": delete)"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Excerpts of the `ietf-interfaces` & `iana-if-type` YANG modules.
+    const INTERFACES_YANG: &str = r#"
+module ietf-interfaces {
+  namespace "urn:ietf:params:xml:ns:yang:ietf-interfaces";
+  prefix if;
+
+  typedef interface-state-ref {
+    type leafref { path "/if:interfaces-state/if:interface/if:name"; }
+  }
+  /* state data */
+  container interfaces-state {
+    config false;
+    list interface {
+      key "name";
+      leaf name { type string; }
+      leaf type { type identityref { base interface-type; } }
+      leaf admin-status { type enumeration { enum up { value 1; } enum down; } }
+      leaf if-index { type int32 { range "1.." + '.2147483647'; } }
+      leaf-list higher-layer-if { type interface-state-ref; }
+    }
+  }
+}
+"#;
+    const IANA_IF_TYPE_YANG: &str = r#"
+module iana-if-type {
+  namespace "urn:ietf:params:xml:ns:yang:iana-if-type";
+  prefix ianaift; // identities only
+}
+"#;
+
+    fn interfaces_hints() -> SchemaHints {
+        SchemaHints::from_yang(&[INTERFACES_YANG, IANA_IF_TYPE_YANG]).unwrap()
+    }
+
+    fn encode(encoder: &JsonEncoder, xml: &str) -> String {
+        encoder
+            .encode(&XmlNode::parse_fragment(xml).unwrap())
+            .to_string()
+    }
+
+    #[test]
+    fn encode_reply() {
+        let hints = interfaces_hints();
+        assert_eq!(hints.leaf_type("if-index"), Some(LeafType::Number));
+        assert_eq!(hints.leaf_type("higher-layer-if"), Some(LeafType::String));
+        let reply = concat!(
+            r#"<rpc-reply xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" message-id="201"><data>"#,
+            r#"<interfaces-state xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces"><interface><name>GigabitEthernet1</name>"#,
+            r#"<type xmlns:ianaift="urn:ietf:params:xml:ns:yang:iana-if-type">ianaift:ethernetCsmacd</type>"#,
+            r#"<admin-status>up</admin-status><if-index>1</if-index></interface></interfaces-state></data></rpc-reply>"#,
+        );
+        assert_eq!(
+            JsonEncoder::new(hints.clone())
+                .encode_message(reply)
+                .unwrap()
+                .to_string(),
+            r#"{"ietf-interfaces:interfaces-state":{"interface":[{"name":"GigabitEthernet1","type":"iana-if-type:ethernetCsmacd","admin-status":"up","if-index":1}]}}"#
+        );
+        // any other message is encoded as a whole
+        assert_eq!(
+            JsonEncoder::new(hints)
+                .encode_message(r#"<rpc-reply xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" message-id="1"><ok/></rpc-reply>"#)
+                .unwrap()
+                .to_string(),
+            r#"{"rpc-reply":{"ok":[null]}}"#
+        );
+    }
+
+    #[test]
+    fn typed_leaves() {
+        let hints = SchemaHints::new()
+            .module("urn:example:sys", "example-sys")
+            .leaf("mtu", LeafType::Number)
+            .leaf("enabled", LeafType::Boolean)
+            .leaf("speed", LeafType::String)
+            .leaf("shutdown", LeafType::Empty)
+            .leaf_list("dns", LeafType::String);
+        let encoder = JsonEncoder::new(hints);
+        let json = encoder.encode(
+            &XmlNode::parse_fragment(
+                r#"<system xmlns="urn:example:sys"><mtu> 1500 </mtu><speed>100000000000</speed>
+                <enabled>true</enabled><shutdown/><dns>1.1.1.1</dns><ext xmlns="urn:example:ext">x</ext></system>"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            json.to_string(),
+            r#"{"example-sys:system":{"mtu":1500,"speed":"100000000000","enabled":true,"shutdown":[null],"dns":["1.1.1.1"],"ext":"x"}}"#
+        );
+        assert_eq!(
+            to_yaml(&json).unwrap(),
+            "example-sys:system:\n  mtu: 1500\n  speed: '100000000000'\n  enabled: true\n  shutdown:\n  - null\n  dns:\n  - 1.1.1.1\n  ext: x\n"
+        );
+
+        // values not matching the hinted type are kept as strings
+        assert_eq!(
+            encode(
+                &encoder,
+                "<a><mtu>auto</mtu><enabled>yes</enabled><shutdown>x</shutdown></a>"
+            ),
+            r#"{"a":{"mtu":"auto","enabled":"yes","shutdown":[null]}}"#
+        );
+    }
+
+    #[test]
+    fn leaves_without_hints() {
+        // strings as is, except for the leaves without value - the `empty` type
+        assert_eq!(
+            encode(
+                &JsonEncoder::default(),
+                "<a><mtu>1500</mtu><enabled>true</enabled><name> eth0 </name><empty/><blank></blank></a>"
+            ),
+            r#"{"a":{"mtu":"1500","enabled":"true","name":" eth0 ","empty":[null],"blank":[null]}}"#
+        );
+        assert_eq!(
+            encode(
+                &JsonEncoder::default(),
+                "<type>ianaift:ethernetCsmacd</type>"
+            ),
+            r#"{"type":"ianaift:ethernetCsmacd"}"#
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let hints = SchemaHints::new()
+            .list("interface", &["name"])
+            .leaf_list("dns", LeafType::String)
+            .leaf_list("port", LeafType::Number);
+        let encoder = JsonEncoder::new(hints);
+        // single entries of lists & leaf-lists are arrays as well, empty leaf-list entry is a string
+        assert_eq!(
+            encode(
+                &encoder,
+                "<a><interface><name>eth0</name></interface><dns/><port>22</port></a>"
+            ),
+            r#"{"a":{"interface":[{"name":"eth0"}],"dns":[""],"port":[22]}}"#
+        );
+        // repeated elements are arrays without hints, grouped in order of the first appearance
+        assert_eq!(
+            encode(
+                &JsonEncoder::default(),
+                "<a><x>1</x><y>2</y><x>3</x><z><w/></z><z><w>4</w></z></a>"
+            ),
+            r#"{"a":{"x":["1","3"],"y":"2","z":[{"w":[null]},{"w":"4"}]}}"#
+        );
+        assert_eq!(
+            encode(&JsonEncoder::default(), "<a>1</a><a>2</a>"),
+            r#"{"a":["1","2"]}"#
+        );
+    }
+
+    #[test]
+    fn namespaces() {
+        let encoder = JsonEncoder::default()
+            .with_module("urn:example:sys", "example-sys")
+            .with_module("urn:example:ext", "example-ext")
+            .with_module("urn:example:types", "example-types");
+        // qualified wherever the namespace changes, incl. switching back to the outer one
+        assert_eq!(
+            encode(
+                &encoder,
+                r#"<system xmlns="urn:example:sys"><ext xmlns="urn:example:ext"><inner xmlns="urn:example:sys"><leaf>x</leaf></inner><own>y</own></ext><name>z</name></system>"#
+            ),
+            r#"{"example-sys:system":{"example-ext:ext":{"example-sys:inner":{"leaf":"x"},"own":"y"},"name":"z"}}"#
+        );
+        // same names of different modules are distinct members, unless the module is unknown
+        assert_eq!(
+            encode(
+                &encoder,
+                r#"<system xmlns="urn:example:sys"><name>a</name><name xmlns="urn:example:ext">b</name><name xmlns="urn:example:unknown">c</name></system>"#
+            ),
+            r#"{"example-sys:system":{"name":["a","c"],"example-ext:name":"b"}}"#
+        );
+
+        // identities qualified by the module of the prefix in scope
+        let encoder = JsonEncoder::new(SchemaHints::new().leaf("type", LeafType::IdentityRef))
+            .with_module("urn:example:types", "example-types");
+        assert_eq!(
+            encode(
+                &encoder,
+                r#"<a xmlns:t="urn:example:types"><b xmlns:t="urn:example:other"><type>t:x</type></b><type>t:y</type><type>u:z</type><type>plain</type></a>"#
+            ),
+            r#"{"a":{"b":{"type":"t:x"},"type":["example-types:y","u:z","plain"]}}"#
+        );
+    }
+
+    fn decode_yaml(decoder: &JsonDecoder, yaml: &str) -> String {
+        XmlNode::fragment_to_xml(&decoder.decode_yaml(yaml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn decode_annotations() {
+        let decoder = JsonDecoder::new(interfaces_hints());
+        let yaml = r#"
+ietf-interfaces:interfaces-state:
+  interface:
+    - name: eth0
+      type: iana-if-type:ethernetCsmacd
+      if-index: 3
+      higher-layer-if: [vlan10, vlan20]
+      "@higher-layer-if": [null, {ietf-netconf:operation: delete}]
+    - name: eth1
+      "@operation": remove
+"#;
+        assert_eq!(
+            decode_yaml(&decoder, yaml),
+            concat!(
+                r#"<interfaces-state xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces"><interface><name>eth0</name>"#,
+                r#"<type xmlns:iana-if-type="urn:ietf:params:xml:ns:yang:iana-if-type">iana-if-type:ethernetCsmacd</type>"#,
+                r#"<if-index>3</if-index><higher-layer-if>vlan10</higher-layer-if>"#,
+                r#"<higher-layer-if xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0" nc:operation="delete">vlan20</higher-layer-if></interface>"#,
+                r#"<interface xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0" nc:operation="remove"><name>eth1</name></interface></interfaces-state>"#,
+            )
+        );
+
+        let decoder = JsonDecoder::default().with_module("urn:example:sys", "example-sys");
+        let yaml = r#"
+example-sys:system:
+  "@": {ietf-netconf:operation: merge}
+  hostname: router
+  "@hostname": {ietf-netconf:operation: replace, example-sys:origin: intent}
+"#;
+        assert_eq!(
+            decode_yaml(&decoder, yaml),
+            concat!(
+                r#"<system xmlns="urn:example:sys" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0" nc:operation="merge">"#,
+                r#"<hostname xmlns:example-sys="urn:example:sys" nc:operation="replace" example-sys:origin="intent">router</hostname></system>"#,
+            )
+        );
+
+        for (yaml, error) in [
+            ("example-sys:a:\n  \"@\": delete\n", "must be JSON object"),
+            (
+                "example-sys:a:\n  \"@b\": {operation: delete}\n",
+                "\"b\" not found",
+            ),
+            ("example-sys:a:\n  \"@\": {origin: x}\n", "Missing module"),
+            (
+                "example-sys:a:\n  \"@\": {unknown:origin: x}\n",
+                "\"unknown\"",
+            ),
+            (
+                "example-sys:a:\n  \"@operation\": [delete]\n",
+                "\"operation\" not found",
+            ),
+        ] {
+            let err = decoder.decode_yaml(yaml).unwrap_err();
+            assert!(err.to_string().contains(error), "{}: {}", yaml, err);
+        }
+    }
+
+    #[test]
+    fn decode_types_and_lists() {
+        let decoder = JsonDecoder::default()
+            .with_module("urn:example:sys", "example-sys")
+            .with_module("urn:example:ext", "example-ext");
+        assert_eq!(
+            decoder
+                .decode_json(r#"{"example-sys:a": {"mtu": 1500, "enabled": false, "empty": [null], "none": null, "dns": ["1.1.1.1", "8.8.8.8"], "entry": [{"k": 1}, {"k": 2}], "example-ext:b": {"c": "x", "example-sys:d": ""}}}"#)
+                .map(|x| XmlNode::fragment_to_xml(&x).unwrap())
+                .unwrap(),
+            concat!(
+                r#"<a xmlns="urn:example:sys"><mtu>1500</mtu><enabled>false</enabled><empty/><none/>"#,
+                r#"<dns>1.1.1.1</dns><dns>8.8.8.8</dns><entry><k>1</k></entry><entry><k>2</k></entry>"#,
+                r#"<b xmlns="urn:example:ext"><c>x</c><d xmlns="urn:example:sys"></d></b></a>"#,
+            )
+        );
+
+        assert!(decoder.decode_json(r#"{"interfaces": {}}"#).is_err());
+        assert!(decoder
+            .decode_json(r#"{"unknown:interfaces": {}}"#)
+            .is_err());
+        assert!(decoder.decode_json(r#"["example-sys:a"]"#).is_err());
+        assert!(decoder.decode_json(r#"{"example-sys:a": [[1]]}"#).is_err());
+    }
+
+    #[test]
+    fn decode_encoded() {
+        let hints = interfaces_hints().list("interface", &["name"]);
+        let data = XmlNode::parse_fragment(concat!(
+            r#"<interfaces-state xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces"><interface><name>eth0</name>"#,
+            r#"<type xmlns:ianaift="urn:ietf:params:xml:ns:yang:iana-if-type">ianaift:ethernetCsmacd</type>"#,
+            r#"<if-index>1</if-index><higher-layer-if>vlan10</higher-layer-if></interface></interfaces-state>"#,
+        ))
+        .unwrap();
+        let encoder = JsonEncoder::new(hints.clone());
+        let json = encoder.encode(&data);
+        let decoded = JsonDecoder::new(hints).decode(&json).unwrap();
+        assert_eq!(encoder.encode(&decoded), json);
+        assert_eq!(from_yaml(&to_yaml(&json).unwrap()).unwrap(), json);
+    }
+
+    #[test]
+    fn yaml_annotations() {
+        let yaml = r#"
+system:
+  "@": {ietf-netconf:operation: merge}
+  "@hostname":
+    "@ietf-netconf:operation": replace
+  interface:
+    - name: eth0
+      '@operation': delete
+      description: |
+        @operation: not an annotation
+          @: nor this
+    - "@literal: item"
+"#;
+        let json = from_yaml(yaml).unwrap();
+        assert_eq!(
+            json["system"]["@hostname"]["@ietf-netconf:operation"],
+            "replace"
+        );
+        assert_eq!(json["system"]["@"]["ietf-netconf:operation"], "merge");
+        let interfaces = json["system"]["interface"].as_array().unwrap();
+        assert_eq!(interfaces[0]["@operation"], "delete");
+        assert_eq!(
+            interfaces[0]["description"],
+            "@operation: not an annotation\n  @: nor this\n"
+        );
+        assert_eq!(interfaces[1], "@literal: item");
+
+        // unquoted keys of the annotations are rejected by YAML
+        let err = from_yaml("system:\n  @operation: delete\n").unwrap_err();
+        assert!(err.to_string().contains("must be quoted"), "{}", err);
+    }
+}
@@ -15,8 +15,47 @@ mod message_validation;
 /// unless (de)serializing only?
 pub mod messages;
 
+/// NETCONF message framing - end-of-message & chunked encoding/decoding, as defined by [RFC 6242 - section 4](https://datatracker.ietf.org/doc/html/rfc6242#section-4).
+/// Used by [`NetconfSession`] on the client side, and suitable also for the server side of the session.
+pub mod framing;
+pub use framing::BaseCapability;
+
+/// Transport abstraction of the byte stream carrying NETCONF messages, allowing [`NetconfSession`]
+/// to run over transports other than SSH - e.g. in-memory streams for testing.
+pub mod transport;
+pub use transport::Transport;
+
+/// Difference of configurations - e.g. of two datastores, lined up by the list entries,
+/// rendered as unified text or annotated tree, or turned into the `<edit-config>` patch.
+pub mod diff;
+
+/// Schema-less hints of the data encoding - namespaces of YANG modules, lists & types of leaves,
+/// gathered from YANG modules or given by hand.
+pub mod schema;
+
+/// Encoding of the XML data tree into JSON of [RFC 7951](https://datatracker.ietf.org/doc/html/rfc7951) style or YAML, and decoding back.
+pub mod json;
+
+/// Configuration backup files with the metadata of the server, restored via the candidate datastore.
+pub mod backup;
+
+/// Transactional edits of the configuration - locked, validated & committed as a whole, or rolled back.
+pub mod transaction;
+
+/// Wire trace of the raw framed bytes exchanged with server, with secrets redacted.
+pub mod trace;
+
+/// Session transcripts - recorded messages of the session, replayed by the transport feeding them back.
+pub mod transcript;
+
+/// Client-side XPath 1.0 evaluation over the retrieved data, e.g. for servers lacking the `:xpath` capability.
+pub mod xpath;
+
+/// Subtree filters built from the typed nodes, and evaluated over the data by client.
+pub mod subtree;
+
 mod ssh_client;
-pub use ssh_client::SshAuthentication;
+pub use ssh_client::{Password, SshAuthentication, SshClient, SSH_TIMEOUT};
 
 mod netconf_session;
 pub use netconf_session::NetconfSession;
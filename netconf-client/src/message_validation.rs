@@ -1,49 +1,299 @@
-use crate::types::{Capability, Datastore};
+use crate::types::{Capability, Datastore, Filter, FilterPayload};
 use anyhow::{bail, Result};
 use url::Url;
 
-/// Check whether `server_capabilities` include URL capability with the same scheme value,
-/// as the input `url` parameter.
-pub fn validate_url(url: &str, server_capabilities: &[Capability]) -> Result<()> {
-    for cap in server_capabilities.iter() {
-        if let Capability::Url(schemes) = cap {
-            let wp_url = Url::parse(url)?;
-            if schemes.iter().any(|x| x == wp_url.scheme()) {
-                return Ok(());
-            } else {
-                bail!(
-                    "Server URL capability schemes: [{}] do not include waypoint scheme \"{}\".",
-                    schemes.join(", "),
-                    wp_url.scheme()
-                );
+/// NETCONF operation of the validated request.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Operation {
+    Get,
+    GetConfig,
+    EditConfig,
+    CopyConfig,
+    DeleteConfig,
+    Lock,
+    Unlock,
+    Commit,
+    CancelCommit,
+    DiscardChanges,
+    Validate,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Operation::Get => "<get>",
+            Operation::GetConfig => "<get-config>",
+            Operation::EditConfig => "<edit-config>",
+            Operation::CopyConfig => "<copy-config>",
+            Operation::DeleteConfig => "<delete-config>",
+            Operation::Lock => "<lock>",
+            Operation::Unlock => "<unlock>",
+            Operation::Commit => "<commit>",
+            Operation::CancelCommit => "<cancel-commit>",
+            Operation::DiscardChanges => "<discard-changes>",
+            Operation::Validate => "<validate>",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Single aspect of the request payload, that may be conditioned by server capabilities.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Usage<'a> {
+    /// Datastore used as a value of the named parameter, e.g. `target`.
+    Datastore(&'static str, &'a Datastore),
+    /// URL used as a value of the named parameter.
+    Url(&'static str, &'a str),
+    /// Presence of the named parameter, or its specific value (e.g. `rollback-on-error`).
+    Parameter(&'static str),
+}
+
+impl<'a> Usage<'a> {
+    /// Usage of the `filter`, if it has a capability conditioned type.
+    pub fn of_filter(filter: &Option<FilterPayload>) -> Option<Self> {
+        match filter {
+            Some(FilterPayload {
+                filter: Filter::Xpath(_),
+                ..
+            }) => Some(Usage::Parameter("xpath-filter")),
+            _ => None,
+        }
+    }
+}
+
+/// Part of the request checked by the [`Rule`].
+enum Subject {
+    /// The operation itself.
+    Operation,
+    /// Datastore of the specified name, used as any parameter (`None`) or the specified one.
+    Datastore(Option<&'static str>, &'static str),
+    /// Named parameter, see [`Usage::Parameter`].
+    Parameter(&'static str),
+}
+
+/// Condition to be fulfilled by server for the [`Rule`] subject.
+enum Requirement {
+    /// At least one of the capabilities must be advertised by server.
+    AnyOf(&'static [Capability]),
+    /// Subject is never allowed, with the reason description.
+    Forbidden(&'static str),
+}
+
+/// Capability rule for the subject used in one of the operations.
+struct Rule {
+    /// Operations the rule applies to. Empty slice applies to all the operations.
+    operations: &'static [Operation],
+    subject: Subject,
+    requirement: Requirement,
+}
+
+/// Table of all the capability rules applied to requests, as defined by [RFC 6241](https://datatracker.ietf.org/doc/html/rfc6241#section-8)
+/// and [RFC 8342](https://datatracker.ietf.org/doc/html/rfc8342).
+///
+/// Note that `<lock>` of the "running" datastore is allowed by `:candidate` as well as `:writable-running`,
+/// locking it is recommended also for servers writing it only via `<commit>` of "candidate"
+/// ([RFC 6241 - section 8.3.5.2](https://datatracker.ietf.org/doc/html/rfc6241#section-8.3.5.2)).
+const RULES: &[Rule] = &[
+    // datastores
+    Rule {
+        operations: &[],
+        subject: Subject::Datastore(None, "candidate"),
+        requirement: Requirement::AnyOf(&[Capability::Candidate]),
+    },
+    Rule {
+        operations: &[],
+        subject: Subject::Datastore(None, "startup"),
+        requirement: Requirement::AnyOf(&[Capability::Startup]),
+    },
+    Rule {
+        operations: &[Operation::EditConfig, Operation::CopyConfig],
+        subject: Subject::Datastore(Some("target"), "running"),
+        requirement: Requirement::AnyOf(&[Capability::WritableRunning]),
+    },
+    Rule {
+        operations: &[Operation::Lock, Operation::Unlock],
+        subject: Subject::Datastore(Some("target"), "running"),
+        requirement: Requirement::AnyOf(&[Capability::WritableRunning, Capability::Candidate]),
+    },
+    Rule {
+        operations: &[Operation::DeleteConfig],
+        subject: Subject::Datastore(Some("target"), "running"),
+        requirement: Requirement::Forbidden(
+            "the running configuration datastore cannot be deleted",
+        ),
+    },
+    Rule {
+        operations: &[],
+        subject: Subject::Datastore(None, "operational"),
+        requirement: Requirement::Forbidden(
+            "the operational state datastore is accessible only via NMDA operations",
+        ),
+    },
+    Rule {
+        operations: &[
+            Operation::EditConfig,
+            Operation::CopyConfig,
+            Operation::DeleteConfig,
+            Operation::Lock,
+            Operation::Unlock,
+        ],
+        subject: Subject::Datastore(Some("target"), "intended"),
+        requirement: Requirement::Forbidden("the intended configuration datastore is read-only"),
+    },
+    Rule {
+        operations: &[Operation::CopyConfig, Operation::DeleteConfig],
+        subject: Subject::Parameter("target"),
+        requirement: Requirement::Forbidden("inline <config> is valid only as the <source>"),
+    },
+    // parameters
+    Rule {
+        operations: &[Operation::Get, Operation::GetConfig],
+        subject: Subject::Parameter("xpath-filter"),
+        requirement: Requirement::AnyOf(&[Capability::XPath]),
+    },
+    Rule {
+        operations: &[Operation::EditConfig],
+        subject: Subject::Parameter("rollback-on-error"),
+        requirement: Requirement::AnyOf(&[Capability::RollbackOnError]),
+    },
+    Rule {
+        operations: &[Operation::EditConfig],
+        subject: Subject::Parameter("test-option"),
+        requirement: Requirement::AnyOf(&[Capability::Validate, Capability::Validate11]),
+    },
+    Rule {
+        operations: &[Operation::EditConfig],
+        subject: Subject::Parameter("test-only"),
+        requirement: Requirement::AnyOf(&[Capability::Validate11]),
+    },
+    Rule {
+        operations: &[Operation::Commit],
+        subject: Subject::Parameter("confirmed"),
+        requirement: Requirement::AnyOf(&[
+            Capability::ConfirmedCommit,
+            Capability::ConfirmedCommit10,
+        ]),
+    },
+    Rule {
+        operations: &[Operation::Commit],
+        subject: Subject::Parameter("persist"),
+        requirement: Requirement::AnyOf(&[Capability::ConfirmedCommit]),
+    },
+    Rule {
+        operations: &[Operation::Commit],
+        subject: Subject::Parameter("persist-id"),
+        requirement: Requirement::AnyOf(&[Capability::ConfirmedCommit]),
+    },
+    // operations
+    Rule {
+        operations: &[Operation::Commit, Operation::DiscardChanges],
+        subject: Subject::Operation,
+        requirement: Requirement::AnyOf(&[Capability::Candidate]),
+    },
+    Rule {
+        operations: &[Operation::CancelCommit],
+        subject: Subject::Operation,
+        requirement: Requirement::AnyOf(&[Capability::ConfirmedCommit]),
+    },
+    Rule {
+        operations: &[Operation::Validate],
+        subject: Subject::Operation,
+        requirement: Requirement::AnyOf(&[Capability::Validate, Capability::Validate11]),
+    },
+];
+
+impl Subject {
+    fn matches(&self, usage: Option<&Usage>) -> bool {
+        match (self, usage) {
+            (Subject::Operation, None) => true,
+            (Subject::Datastore(parameter, name), Some(Usage::Datastore(used_as, datastore))) => {
+                parameter.is_none_or(|x| x == *used_as) && datastore.to_string() == *name
             }
+            (Subject::Parameter(name), Some(Usage::Parameter(used))) => name == used,
+            _ => false,
         }
     }
-    bail!("Missing capability \":url\"");
 }
 
-/// Validate that if and only if the specified `datastore` is equal to `trigger`,
-/// the `needed_capability` is present among the `server_capabilities`.
-pub fn validate_datastore_capability(
-    datastore: &Datastore,
-    trigger: &Datastore,
-    needed_capability: &Capability,
-    server_capabilities: &[Capability],
-) -> Result<()> {
-    if datastore == trigger && !server_capabilities.contains(needed_capability) {
-        bail!("Cannot use datastore \"{trigger}\", missing capability {needed_capability}");
+fn describe(usage: Option<&Usage>) -> String {
+    match usage {
+        None => "operation".to_string(),
+        Some(Usage::Datastore(parameter, datastore)) => {
+            format!("datastore \"{datastore}\" as <{parameter}>")
+        }
+        Some(Usage::Url(parameter, url)) => format!("URL \"{url}\" as <{parameter}>"),
+        Some(Usage::Parameter(parameter)) => format!("parameter \"{parameter}\""),
     }
-    Ok(())
 }
 
-/// Validate that the specified `needed_capability` is present among the `server_capabilities`.
-pub fn validate_capability_presence(
-    needed_capability: &Capability,
+/// Check the URL scheme against the `:url` capability schemes of server.
+fn check_url(url: &str, server_capabilities: &[Capability]) -> Option<String> {
+    let schemes = server_capabilities.iter().find_map(|cap| match cap {
+        Capability::Url(url) => Some(&url.schemes),
+        _ => None,
+    });
+    let schemes = match schemes {
+        Some(schemes) => schemes,
+        None => return Some("requires capability :url:1.0".to_string()),
+    };
+    match Url::parse(url) {
+        Ok(parsed) if schemes.iter().any(|x| x == parsed.scheme()) => None,
+        Ok(parsed) => Some(format!(
+            "has scheme \"{}\" not included in server URL capability schemes: [{}]",
+            parsed.scheme(),
+            schemes.join(", ")
+        )),
+        Err(err) => Some(format!("is not a valid URL: {err}")),
+    }
+}
+
+/// Validate the request `usages` for the `operation` against all the applicable [`RULES`],
+/// and `:url` capability schemes.
+///
+/// All the violations are reported together in the returned error.
+pub fn validate_usages(
+    operation: Operation,
+    usages: &[Usage],
     server_capabilities: &[Capability],
-    extra_bail_info: &str,
-) -> anyhow::Result<()> {
-    if !server_capabilities.contains(needed_capability) {
-        bail!("Missing required capability \"{needed_capability}\".{extra_bail_info}");
+) -> Result<()> {
+    let mut violations = vec![];
+
+    let subjects = std::iter::once(None).chain(usages.iter().map(Some));
+    for usage in subjects {
+        if let Some(Usage::Url(_, url)) = usage {
+            if let Some(violation) = check_url(url, server_capabilities) {
+                violations.push(format!("{} {}", describe(usage), violation));
+            }
+        }
+
+        let rules = RULES.iter().filter(|rule| {
+            (rule.operations.is_empty() || rule.operations.contains(&operation))
+                && rule.subject.matches(usage)
+        });
+        for rule in rules {
+            match &rule.requirement {
+                Requirement::AnyOf(needed) => {
+                    if !needed.iter().any(|x| server_capabilities.contains(x)) {
+                        let needed: Vec<String> = needed.iter().map(|x| x.to_string()).collect();
+                        violations.push(format!(
+                            "{} requires capability {}",
+                            describe(usage),
+                            needed.join(" or ")
+                        ));
+                    }
+                }
+                Requirement::Forbidden(reason) => {
+                    violations.push(format!("{} is not allowed - {}", describe(usage), reason));
+                }
+            }
+        }
+    }
+
+    match violations.is_empty() {
+        true => Ok(()),
+        false => bail!(
+            "Request {operation} violates server capabilities:\n - {}",
+            violations.join("\n - ")
+        ),
     }
-    Ok(())
 }
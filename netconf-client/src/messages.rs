@@ -16,7 +16,10 @@ pub mod validate;
 use anyhow::Result;
 use std::fmt::Debug;
 
-use crate::types::Capability;
+use crate::{
+    common::push_root_attributes,
+    types::{Capability, RpcAttribute},
+};
 
 pub trait ToRawXml {
     fn to_raw_xml(&self) -> Result<String>;
@@ -50,12 +53,70 @@ pub trait NetconfRequest: ToPrettyXml + Debug {
     fn validate_request(&self, _server_capabilities: &[Capability]) -> Result<()> {
         Ok(())
     }
+
+    /// Wrap the request to include custom `attributes` in its `<rpc>` element.
+    ///
+    /// NETCONF server echoes these back in the `<rpc-reply>`, see [`FullResponse::attributes`].
+    /// Serialization of the request fails for attributes clashing with `message-id` or the namespace declarations.
+    fn with_attributes(self, attributes: Vec<RpcAttribute>) -> AttributedRequest<Self>
+    where
+        Self: Sized,
+    {
+        AttributedRequest::new(self, attributes)
+    }
+}
+
+/// Any [`NetconfRequest`] extended with custom attributes of its `<rpc>` element,
+/// as defined in [RFC 6241 - section 4.1](https://datatracker.ietf.org/doc/html/rfc6241#section-4.1).
+#[derive(Debug, Clone)]
+pub struct AttributedRequest<R: NetconfRequest> {
+    request: R,
+    attributes: Vec<RpcAttribute>,
+}
+
+impl<R: NetconfRequest> AttributedRequest<R> {
+    /// Wrap the `request` with custom `attributes`.
+    pub fn new(request: R, attributes: Vec<RpcAttribute>) -> Self {
+        Self {
+            request,
+            attributes,
+        }
+    }
+
+    /// The wrapped request.
+    pub fn request(&self) -> &R {
+        &self.request
+    }
+
+    /// Custom attributes added to the `<rpc>` element.
+    pub fn attributes(&self) -> &[RpcAttribute] {
+        &self.attributes
+    }
+}
+
+impl<R: NetconfRequest> ToRawXml for AttributedRequest<R> {
+    fn to_raw_xml(&self) -> Result<String> {
+        let raw_xml = self.request.to_raw_xml()?;
+        push_root_attributes(&raw_xml, &self.attributes)
+    }
+}
+
+impl<R: NetconfRequest> ToPrettyXml for AttributedRequest<R> {}
+
+impl<R: NetconfRequest> NetconfRequest for AttributedRequest<R> {
+    type Response = R::Response;
+
+    fn validate_request(&self, server_capabilities: &[Capability]) -> Result<()> {
+        self.request.validate_request(server_capabilities)
+    }
 }
 
 #[derive(Debug)]
 pub struct FullResponse<R: NetconfResponse> {
     pub typed: R,
     pub dump: String,
+    /// Custom attributes echoed by server in the `<rpc-reply>` element.
+    pub attributes: Vec<RpcAttribute>,
 }
 
 impl<R: NetconfResponse> ToRawXml for FullResponse<R> {
@@ -99,6 +160,6 @@ pub fn raw_to_pretty_xml(rpc_str: &str) -> Result<String> {
         read_buf.clear();
     }
 
-    let result = String::from_utf8_lossy(&*writer.into_inner()).to_string();
+    let result = String::from_utf8_lossy(&writer.into_inner()).to_string();
     Ok(result)
 }
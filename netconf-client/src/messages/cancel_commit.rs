@@ -4,7 +4,7 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::validate_capability_presence,
+    message_validation::{validate_usages, Operation},
     types::{tag_wrapper::TagWrapper, Capability, SimpleResponse},
 };
 
@@ -22,15 +22,8 @@ pub struct CancelCommitRequest {
 impl NetconfRequest for CancelCommitRequest {
     type Response = CancelCommitResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        validate_capability_presence(
-            &Capability::ConfirmedCommit,
-            server_capabilities,
-            " Cannot perform <cancel-commit> operation.",
-        )
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        validate_usages(Operation::CancelCommit, &[], server_capabilities)
     }
 }
 
@@ -59,6 +52,7 @@ impl CancelCommitRequest {
 
 #[derive(Debug, Serialize)]
 struct CancelCommitRpc {
+    #[serde(rename = "persist-id")]
     persist_id: Option<TagWrapper<u32>>,
 }
 
@@ -4,7 +4,7 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::validate_capability_presence,
+    message_validation::{validate_usages, Operation, Usage},
     types::{tag_wrapper::TagWrapper, Capability, EmptyStruct, SimpleResponse},
 };
 
@@ -48,22 +48,18 @@ impl ToPrettyXml for CommitRequest {}
 impl NetconfRequest for CommitRequest {
     type Response = CommitResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        match self.commit_type {
-            CommitType::Plain => validate_capability_presence(
-                &Capability::Candidate,
-                server_capabilities,
-                " Cannot perform <commit> operation.",
-            ),
-            CommitType::Confirmed(_) => validate_capability_presence(
-                &Capability::ConfirmedCommit,
-                server_capabilities,
-                " Cannot perform confirmed <commit> operation.",
-            ),
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        let mut usages = vec![];
+        if let CommitType::Confirmed(params) = &self.commit_type {
+            usages.push(Usage::Parameter("confirmed"));
+            if params.persist.is_some() {
+                usages.push(Usage::Parameter("persist"));
+            }
+            if params.persist_id.is_some() {
+                usages.push(Usage::Parameter("persist-id"));
+            }
         }
+        validate_usages(Operation::Commit, &usages, server_capabilities)
     }
 }
 
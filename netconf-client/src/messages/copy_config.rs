@@ -4,16 +4,15 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::{validate_datastore_capability, validate_url},
-    types::{Capability, ConfigWaypoint, ConfigWaypointRpc, Datastore, SimpleResponse},
+    message_validation::{validate_usages, Operation},
+    types::{Capability, ConfigWaypoint, ConfigWaypointRpc, SimpleResponse},
 };
 
-use super::NetconfRequest;
+use super::{NetconfRequest, ToPrettyXml, ToRawXml};
 
 /// Representation of NETCONF `<copy-config>` operation request.
 /// Create or replace an entire configuration datastore with the contents of another complete configuration datastore.
-#[derive(Debug, Serialize, Clone)]
-#[serde(into = "CopyConfigRequestRpc")]
+#[derive(Debug, Clone)]
 pub struct CopyConfigRequest {
     message_id: String,
     xmlns: String,
@@ -24,45 +23,12 @@ pub struct CopyConfigRequest {
 impl NetconfRequest for CopyConfigRequest {
     type Response = CopyConfigResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        match &self.target {
-            ConfigWaypoint::Datastore(datastore) => {
-                validate_datastore_capability(
-                    datastore,
-                    &Datastore::Running,
-                    &Capability::WritableRunning,
-                    server_capabilities,
-                )?;
-                validate_datastore_capability(
-                    datastore,
-                    &Datastore::Candidate,
-                    &Capability::Candidate,
-                    server_capabilities,
-                )?;
-            }
-            ConfigWaypoint::Url(url) => {
-                validate_url(url, server_capabilities)?;
-            }
-        };
-
-        match &self.source {
-            ConfigWaypoint::Datastore(datastore) => {
-                validate_datastore_capability(
-                    datastore,
-                    &Datastore::Candidate,
-                    &Capability::Candidate,
-                    server_capabilities,
-                )?;
-            }
-            ConfigWaypoint::Url(url) => {
-                validate_url(url, server_capabilities)?;
-            }
-        };
-
-        Ok(())
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        let usages = [
+            self.target.to_usage("target"),
+            self.source.to_usage("source"),
+        ];
+        validate_usages(Operation::CopyConfig, &usages, server_capabilities)
     }
 }
 
@@ -91,6 +57,30 @@ impl CopyConfigRequest {
     }
 }
 
+impl ToRawXml for CopyConfigRequest {
+    fn to_raw_xml(&self) -> anyhow::Result<String> {
+        const TOKEN: &str = "MAGIC_TOKEN";
+
+        // serialize RPC with a TOKEN in place of inline <config> contents, to be replaced later
+        // (auto would have escaped tags to html &lt; / &gt;)
+        let mut request = self.clone();
+        let mut configs = vec![];
+        for waypoint in [&mut request.target, &mut request.source] {
+            if let ConfigWaypoint::Config(config) = waypoint {
+                configs.push(std::mem::replace(config, TOKEN.to_string()));
+            }
+        }
+        let rpc: CopyConfigRequestRpc = request.into();
+        let mut rpc_str = quick_xml::se::to_string(&rpc)?;
+        for config in configs {
+            rpc_str = rpc_str.replacen(TOKEN, &config, 1);
+        }
+        Ok(rpc_str)
+    }
+}
+
+impl ToPrettyXml for CopyConfigRequest {}
+
 #[derive(Debug, Serialize)]
 #[serde(rename = "rpc")]
 struct CopyConfigRequestRpc {
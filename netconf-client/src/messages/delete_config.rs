@@ -1,12 +1,11 @@
 use std::fmt::Debug;
 
-use anyhow::bail;
 use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::{validate_datastore_capability, validate_url},
-    types::{Capability, ConfigWaypoint, ConfigWaypointRpc, Datastore, SimpleResponse},
+    message_validation::{validate_usages, Operation},
+    types::{Capability, ConfigWaypoint, ConfigWaypointRpc, SimpleResponse},
 };
 
 use super::NetconfRequest;
@@ -24,27 +23,9 @@ pub struct DeleteConfigRequest {
 impl NetconfRequest for DeleteConfigRequest {
     type Response = DeleteConfigResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        match &self.target {
-            ConfigWaypoint::Datastore(datastore) => {
-                if datastore == &Datastore::Running {
-                    bail!("Cannot delete \"running\" datastore");
-                }
-                validate_datastore_capability(
-                    datastore,
-                    &Datastore::Candidate,
-                    &Capability::Candidate,
-                    server_capabilities,
-                )?;
-            }
-            ConfigWaypoint::Url(url) => {
-                validate_url(url, server_capabilities)?;
-            }
-        };
-        Ok(())
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        let usages = [self.target.to_usage("target")];
+        validate_usages(Operation::DeleteConfig, &usages, server_capabilities)
     }
 }
 
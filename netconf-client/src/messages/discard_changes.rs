@@ -4,7 +4,7 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::validate_capability_presence,
+    message_validation::{validate_usages, Operation},
     types::{Capability, SimpleResponse},
 };
 
@@ -22,11 +22,8 @@ pub struct DiscardChangesRequest {
 impl NetconfRequest for DiscardChangesRequest {
     type Response = DiscardChangesResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        validate_capability_presence(&Capability::Candidate, server_capabilities, "")
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        validate_usages(Operation::DiscardChanges, &[], server_capabilities)
     }
 }
 
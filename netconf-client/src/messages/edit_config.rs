@@ -6,10 +6,9 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::{
-        validate_capability_presence, validate_datastore_capability, validate_url,
-    },
-    types::{tag_wrapper::TagWrapper, Capability, Datastore, SimpleResponse},
+    json::JsonDecoder,
+    message_validation::{self, validate_usages, Usage},
+    types::{tag_wrapper::TagWrapper, Capability, Datastore, SimpleResponse, XmlNode},
 };
 
 use super::{NetconfRequest, ToPrettyXml, ToRawXml};
@@ -66,6 +65,99 @@ pub enum ErrorOption {
     RollbackOnError,
 }
 
+impl std::fmt::Display for DefaultOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DefaultOperation::Merge => "merge",
+            DefaultOperation::Replace => "replace",
+            DefaultOperation::None => "none",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::fmt::Display for TestOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TestOption::TestThenSet => "test-then-set",
+            TestOption::Set => "set",
+            TestOption::TestOnly => "test-only",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::fmt::Display for ErrorOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ErrorOption::StopOnError => "stop-on-error",
+            ErrorOption::ContinueOnError => "continue-on-error",
+            ErrorOption::RollbackOnError => "rollback-on-error",
+        };
+        f.write_str(s)
+    }
+}
+
+impl std::str::FromStr for DefaultOperation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "merge" => DefaultOperation::Merge,
+            "replace" => DefaultOperation::Replace,
+            "none" => DefaultOperation::None,
+            s => anyhow::bail!("Unknown default-operation \"{}\"!", s),
+        })
+    }
+}
+
+impl std::str::FromStr for TestOption {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "test-then-set" => TestOption::TestThenSet,
+            "set" => TestOption::Set,
+            "test-only" => TestOption::TestOnly,
+            s => anyhow::bail!("Unknown test-option \"{}\"!", s),
+        })
+    }
+}
+
+impl std::str::FromStr for ErrorOption {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "stop-on-error" => ErrorOption::StopOnError,
+            "continue-on-error" => ErrorOption::ContinueOnError,
+            "rollback-on-error" => ErrorOption::RollbackOnError,
+            s => anyhow::bail!("Unknown error-option \"{}\"!", s),
+        })
+    }
+}
+
+impl DefaultOperation {
+    /// Provides all the values of the parameter.
+    pub fn values() -> Vec<&'static str> {
+        vec!["merge", "replace", "none"]
+    }
+}
+
+impl TestOption {
+    /// Provides all the values of the parameter.
+    pub fn values() -> Vec<&'static str> {
+        vec!["test-then-set", "set", "test-only"]
+    }
+}
+
+impl ErrorOption {
+    /// Provides all the values of the parameter.
+    pub fn values() -> Vec<&'static str> {
+        vec!["stop-on-error", "continue-on-error", "rollback-on-error"]
+    }
+}
+
 /// Representation of NETCONF `<edit-config>` operation request -
 #[derive(Debug, Clone)]
 pub struct EditConfigRequest {
@@ -83,6 +175,24 @@ pub enum EditConfigContent {
     Url(String),
 }
 
+impl EditConfigContent {
+    /// Configuration changes of the JSON of RFC 7951 style, incl. the `operation` annotations - see [`JsonDecoder`].
+    pub fn from_json(json: &str, decoder: &JsonDecoder) -> Result<Self> {
+        let config = decoder.decode_json(json)?;
+        Ok(EditConfigContent::Config(XmlNode::fragment_to_xml(
+            &config,
+        )?))
+    }
+
+    /// Configuration changes of the YAML document of the same structure as JSON, see [`Self::from_json()`].
+    pub fn from_yaml(yaml: &str, decoder: &JsonDecoder) -> Result<Self> {
+        let config = decoder.decode_yaml(yaml)?;
+        Ok(EditConfigContent::Config(XmlNode::fragment_to_xml(
+            &config,
+        )?))
+    }
+}
+
 /// Input parameters of the `<edit-config>` operation request.
 /// See [RFC 6421 - section 7.2](https://datatracker.ietf.org/doc/html/rfc6241#section-7.2)
 #[derive(Debug, Clone)]
@@ -117,9 +227,11 @@ impl From<EditConfigParams> for EditConfigRpc {
         };
         Self {
             target: TagWrapper::new(params.target),
-            default_operation: params.default_operation.map(TagWrapper::new),
-            test_option: params.test_option.map(TagWrapper::new),
-            error_option: params.error_option.map(TagWrapper::new),
+            default_operation: params
+                .default_operation
+                .map(|x| TagWrapper::new(x.to_string())),
+            test_option: params.test_option.map(|x| TagWrapper::new(x.to_string())),
+            error_option: params.error_option.map(|x| TagWrapper::new(x.to_string())),
             config,
             url,
         }
@@ -142,24 +254,24 @@ impl ToRawXml for EditConfigRequest {
         const TOKEN: &str = "MAGIC_TOKEN";
         let mut params = self.params.clone();
 
-        // reset <config> / <url> contents for automatic serialization to a TOKEN to be replaced later
-        let config_backup = params.config;
-        let s = TOKEN.to_string();
-        params.config = match &config_backup {
-            EditConfigContent::Config(_) => EditConfigContent::Config(s),
-            EditConfigContent::Url(_) => EditConfigContent::Url(s),
+        // reset <config> contents for automatic serialization to a TOKEN to be replaced later
+        // (<url> is plain text, escaped by the serializer as usual)
+        let config_backup = match &params.config {
+            EditConfigContent::Config(config) => Some(config.clone()),
+            EditConfigContent::Url(_) => None,
         };
+        if config_backup.is_some() {
+            params.config = EditConfigContent::Config(TOKEN.to_string());
+        }
 
-        // serialize RPC without <config> / <url> data
+        // serialize RPC without <config> data
         let instance = Self::new(self.message_id.clone(), params);
         let instance: EditConfigRequestRpc = instance.into();
         let mut instance_str = to_string(&instance)?;
-        // replace back the original filter data (auto would have escaped tags to html &lt; / &gt;)
-        let s = match &config_backup {
-            EditConfigContent::Config(config) => config,
-            EditConfigContent::Url(url) => url,
-        };
-        instance_str = instance_str.replace(TOKEN, s);
+        // replace back the original config data (auto would have escaped tags to html &lt; / &gt;)
+        if let Some(config) = config_backup {
+            instance_str = instance_str.replace(TOKEN, &config);
+        }
         Ok(instance_str)
     }
 }
@@ -169,31 +281,25 @@ impl ToPrettyXml for EditConfigRequest {}
 impl NetconfRequest for EditConfigRequest {
     type Response = EditConfigResponse;
 
-    fn validate_request(&self, server_capabilities: &[crate::types::Capability]) -> Result<()> {
-        validate_datastore_capability(
-            &self.params.target,
-            &Datastore::Candidate,
-            &Capability::Candidate,
-            server_capabilities,
-        )?;
+    fn validate_request(&self, server_capabilities: &[Capability]) -> Result<()> {
+        let mut usages = vec![Usage::Datastore("target", &self.params.target)];
         if let Some(ErrorOption::RollbackOnError) = &self.params.error_option {
-            validate_capability_presence(
-                &Capability::RollbackOnError,
-                server_capabilities,
-                " Cannot use \"rollback-on-error\" <error-option> parameter.",
-            )?;
+            usages.push(Usage::Parameter("rollback-on-error"));
         }
-        if self.params.test_option.is_some() {
-            validate_capability_presence(
-                &Capability::Validate11,
-                server_capabilities,
-                " Cannot use <test-option> parameter.",
-            )?;
+        if let Some(test_option) = &self.params.test_option {
+            usages.push(Usage::Parameter("test-option"));
+            if test_option == &TestOption::TestOnly {
+                usages.push(Usage::Parameter("test-only"));
+            }
         }
         if let EditConfigContent::Url(url) = &self.params.config {
-            validate_url(url, server_capabilities)?;
+            usages.push(Usage::Url("url", url));
         }
-        Ok(())
+        validate_usages(
+            message_validation::Operation::EditConfig,
+            &usages,
+            server_capabilities,
+        )
     }
 }
 
@@ -212,20 +318,14 @@ struct EditConfigRequestRpc {
 struct EditConfigRpc {
     target: TagWrapper<Datastore>,
     #[serde(rename = "default-operation")]
-    default_operation: Option<TagWrapper<DefaultOperation>>,
+    default_operation: Option<TagWrapper<String>>,
     #[serde(rename = "test-option")]
-    test_option: Option<TagWrapper<TestOption>>,
+    test_option: Option<TagWrapper<String>>,
     #[serde(rename = "error-option")]
-    error_option: Option<TagWrapper<ErrorOption>>,
+    error_option: Option<TagWrapper<String>>,
     config: Option<TagWrapper<String>>,
     url: Option<TagWrapper<String>>,
 }
 
-#[derive(Debug, Serialize)]
-struct ConfigRpc {
-    #[serde(rename = "$value")]
-    item: String,
-}
-
 /// Representation of a server response to [`EditConfigRequest`].
 pub type EditConfigResponse = SimpleResponse;
@@ -9,7 +9,8 @@ use serde::Deserialize;
 
 use crate::{
     common::{get_tag_slice, xml_events_to_string, RpcWrapMode, XMLNS},
-    types::{FilterPayload, RpcErrorRpc, RpcReply},
+    message_validation::{validate_usages, Operation, Usage},
+    types::{Capability, FilterPayload, RpcErrorRpc, RpcReply},
 };
 
 use super::{FullResponse, NetconfRequest, NetconfResponse, ToPrettyXml, ToRawXml};
@@ -50,6 +51,11 @@ impl ToPrettyXml for GetRequest {}
 
 impl NetconfRequest for GetRequest {
     type Response = GetResponse;
+
+    fn validate_request(&self, server_capabilities: &[Capability]) -> Result<()> {
+        let usages: Vec<Usage> = Usage::of_filter(&self.filter).into_iter().collect();
+        validate_usages(Operation::Get, &usages, server_capabilities)
+    }
 }
 
 #[derive(Debug)]
@@ -64,8 +70,8 @@ struct GetResponseRpc {
     #[serde(rename = "message-id")]
     message_id: String,
     xmlns: String,
-    #[serde(rename = "rpc-error")]
-    rpc_error: Option<RpcErrorRpc>,
+    #[serde(rename = "rpc-error", default)]
+    rpc_errors: Vec<RpcErrorRpc>,
 }
 
 impl FullResponse<GetResponse> {
@@ -85,9 +91,9 @@ impl NetconfResponse for GetResponse {
         let rpc: GetResponseRpc = from_str(s)?;
         let message_id = rpc.message_id;
         let xmlns = rpc.xmlns;
-        let reply = match rpc.rpc_error {
-            None => RpcReply::Ok,
-            Some(err) => RpcReply::Error(err.into()),
+        let reply = match rpc.rpc_errors.is_empty() {
+            true => RpcReply::Ok,
+            false => RpcReply::Error(rpc.rpc_errors.into_iter().map(Into::into).collect()),
         };
         Ok(Self {
             message_id,
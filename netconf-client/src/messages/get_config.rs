@@ -9,7 +9,7 @@ use serde::Deserialize;
 
 use crate::{
     common::{get_tag_slice, xml_events_to_string, RpcWrapMode, XMLNS},
-    message_validation::validate_datastore_capability,
+    message_validation::{validate_usages, Operation, Usage},
     types::{Capability, Datastore, FilterPayload, RpcErrorRpc, RpcReply},
 };
 
@@ -63,13 +63,10 @@ impl ToPrettyXml for GetConfigRequest {}
 impl NetconfRequest for GetConfigRequest {
     type Response = GetConfigResponse;
 
-    fn validate_request(&self, server_capabilities: &[crate::types::Capability]) -> Result<()> {
-        validate_datastore_capability(
-            &self.source,
-            &Datastore::Candidate,
-            &Capability::Candidate,
-            server_capabilities,
-        )
+    fn validate_request(&self, server_capabilities: &[Capability]) -> Result<()> {
+        let mut usages = vec![Usage::Datastore("source", &self.source)];
+        usages.extend(Usage::of_filter(&self.filter));
+        validate_usages(Operation::GetConfig, &usages, server_capabilities)
     }
 }
 
@@ -85,8 +82,8 @@ struct GetConfigResponseRpc {
     #[serde(rename = "message-id")]
     message_id: String,
     xmlns: String,
-    #[serde(rename = "rpc-error")]
-    rpc_error: Option<RpcErrorRpc>,
+    #[serde(rename = "rpc-error", default)]
+    rpc_errors: Vec<RpcErrorRpc>,
 }
 
 impl NetconfResponse for GetConfigResponse {
@@ -97,9 +94,9 @@ impl NetconfResponse for GetConfigResponse {
         let rpc: GetConfigResponseRpc = from_str(s)?;
         let message_id = rpc.message_id;
         let xmlns = rpc.xmlns;
-        let reply = match rpc.rpc_error {
-            None => RpcReply::Ok,
-            Some(err) => RpcReply::Error(err.into()),
+        let reply = match rpc.rpc_errors.is_empty() {
+            true => RpcReply::Ok,
+            false => RpcReply::Error(rpc.rpc_errors.into_iter().map(Into::into).collect()),
         };
         Ok(Self {
             message_id,
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::{
     common::XMLNS,
@@ -62,7 +63,7 @@ impl From<HelloRequest> for HelloRequestRpc {
 
 /// Initial `<hello>` response sent by NETCONF server.
 #[derive(Debug, Deserialize)]
-#[serde(from = "HelloResponseRpc")]
+#[serde(try_from = "HelloResponseRpc")]
 pub struct HelloResponse {
     #[serde(rename = "session-id")]
     pub session_id: u32,
@@ -79,18 +80,28 @@ struct HelloResponseRpc {
     capabilities: CapabilitiesRpc,
 }
 
-impl From<HelloResponseRpc> for HelloResponse {
+impl TryFrom<HelloResponseRpc> for HelloResponse {
+    type Error = anyhow::Error;
+
     /// Automatically convert `HelloResponse` info/from its RPC representation struct used in serialization.
-    fn from(rpc: HelloResponseRpc) -> Self {
-        HelloResponse {
+    fn try_from(rpc: HelloResponseRpc) -> anyhow::Result<Self> {
+        // malformed capability of server must not fail the whole session
+        let capabilities = rpc
+            .capabilities
+            .items
+            .iter()
+            .map(|cap| {
+                let urn = cap.item.trim();
+                Capability::from_urn(urn).unwrap_or_else(|err| {
+                    warn!(error = %err, "malformed capability kept as is");
+                    Capability::Other(urn.to_string())
+                })
+            })
+            .collect();
+        Ok(HelloResponse {
             session_id: rpc.session_id,
-            capabilities: rpc
-                .capabilities
-                .items
-                .iter()
-                .map(|cap| Capability::from_urn(&cap.item).unwrap())
-                .collect(),
-        }
+            capabilities,
+        })
     }
 }
 
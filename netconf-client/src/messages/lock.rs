@@ -2,7 +2,7 @@ use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::validate_datastore_capability,
+    message_validation::{validate_usages, Operation, Usage},
     types::{tag_wrapper::TagWrapper, Capability, Datastore, SimpleResponse},
 };
 
@@ -26,16 +26,9 @@ impl LockRequest {
 impl NetconfRequest for LockRequest {
     type Response = LockResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        validate_datastore_capability(
-            &self.target,
-            &Datastore::Candidate,
-            &Capability::Candidate,
-            server_capabilities,
-        )
+    fn validate_request(&self, server_capabilities: &[Capability]) -> anyhow::Result<()> {
+        let usages = [Usage::Datastore("target", &self.target)];
+        validate_usages(Operation::Lock, &usages, server_capabilities)
     }
 }
 
@@ -1,12 +1,14 @@
+use anyhow::Result;
+use quick_xml::se::to_string;
 use serde::Serialize;
 
 use crate::{
     common::XMLNS,
-    message_validation::{validate_datastore_capability, validate_url},
+    message_validation::{validate_usages, Operation, Usage},
     types::{tag_wrapper::TagWrapper, Capability, Datastore, SimpleResponse},
 };
 
-use super::NetconfRequest;
+use super::{NetconfRequest, ToPrettyXml, ToRawXml};
 
 /// Source of the configuration to be validated by [`ValidateRequest`].
 #[derive(Debug, Clone)]
@@ -20,8 +22,7 @@ pub enum ValidateSource {
 }
 
 /// Representation of NETCONF `<validate>` operation request.
-#[derive(Debug, Clone, Serialize)]
-#[serde(into = "ValidateRequestRpc")]
+#[derive(Debug, Clone)]
 pub struct ValidateRequest {
     message_id: String,
     source: ValidateSource,
@@ -34,27 +35,43 @@ impl ValidateRequest {
     }
 }
 
+impl ToRawXml for ValidateRequest {
+    fn to_raw_xml(&self) -> Result<String> {
+        const TOKEN: &str = "MAGIC_TOKEN";
+
+        // serialize RPC with a TOKEN in place of <config> contents, to be replaced later
+        // (auto would have escaped tags to html &lt; / &gt;)
+        let (request, config) = match &self.source {
+            ValidateSource::Config(config) => (
+                Self::new(
+                    self.message_id.clone(),
+                    ValidateSource::Config(TOKEN.to_string()),
+                ),
+                Some(config),
+            ),
+            _ => (self.clone(), None),
+        };
+        let rpc: ValidateRequestRpc = request.into();
+        let mut rpc_str = to_string(&rpc)?;
+        if let Some(config) = config {
+            rpc_str = rpc_str.replace(TOKEN, config);
+        }
+        Ok(rpc_str)
+    }
+}
+
+impl ToPrettyXml for ValidateRequest {}
+
 impl NetconfRequest for ValidateRequest {
     type Response = ValidateResponse;
 
-    fn validate_request(
-        &self,
-        server_capabilities: &[crate::types::Capability],
-    ) -> anyhow::Result<()> {
-        match &self.source {
-            ValidateSource::Datastore(datastore) => {
-                validate_datastore_capability(
-                    datastore,
-                    &Datastore::Candidate,
-                    &Capability::Candidate,
-                    server_capabilities,
-                )?;
-            }
-            ValidateSource::Config(_) => {} // TODO - might check for valid/paired XML tags or something?
-            ValidateSource::Url(url) => validate_url(url, server_capabilities)?,
+    fn validate_request(&self, server_capabilities: &[Capability]) -> Result<()> {
+        let usages: Vec<Usage> = match &self.source {
+            ValidateSource::Datastore(datastore) => vec![Usage::Datastore("source", datastore)],
+            ValidateSource::Config(_) => vec![], // TODO - might check for valid/paired XML tags or something?
+            ValidateSource::Url(url) => vec![Usage::Url("source", url)],
         };
-
-        Ok(())
+        validate_usages(Operation::Validate, &usages, server_capabilities)
     }
 }
 
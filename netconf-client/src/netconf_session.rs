@@ -1,25 +1,47 @@
+use std::io::{Read, Write};
 use std::net::IpAddr;
+use std::time::Instant;
 
+use crate::common::{parse_root_attributes, rpc_operation};
+use crate::framing::{encode_message, BaseCapability, FrameDecoder};
 use crate::messages::hello::HelloResponse;
-use crate::ssh_client::BaseCapability;
+use crate::trace::{Direction, WireTrace};
+use crate::transcript::{TranscriptMessage, TranscriptRecorder};
+use crate::transport::Transport;
 
 use super::messages::*;
 
+use super::backup::{ConfigBackup, ModuleRevision, RestoreMethod, RestoreOptions, RestoreReport};
+use super::diff::ConfigDiff;
 use super::ssh_client::SshClient;
-use super::types::{Capability, RpcReply};
+use super::transaction::Transaction;
+use super::types::{
+    Capability, ConfigWaypoint, Conformance, Datastore, Filter, FilterPayload, RpcReply,
+    ServerCapabilities, XmlNode, YangLibrary, YANG_LIBRARY_XMLNS,
+};
 use super::SshAuthentication;
 
 use anyhow::{bail, Result};
+use tracing::{debug, debug_span, field, info, info_span, warn, Span};
 
 /// The core NETCONF client. Simple blocking implementation that allows streaming
 /// NETCONF RPC messages to a target server.
+///
+/// Instrumented by [`tracing`] - events of the session are within the `netconf_session` span (target & session-id),
+/// and each request has its own `rpc` span (message-id & operation), closed by an event of its duration & sizes.
 pub struct NetconfSession {
-    ssh: SshClient,
+    transport: Box<dyn Transport>,
+    decoder: FrameDecoder,
     last_message_id: u32,
     session_id: Option<u32>,
     client_capabilities: Vec<Capability>,
-    server_capabilities: Option<Vec<Capability>>,
+    server_capabilities: Option<ServerCapabilities>,
     validate_capabilities: bool,
+    span: Span,
+    wire_trace: Option<WireTrace>,
+    recorder: Option<TranscriptRecorder>,
+    /// Messages of the last `<hello>` exchange, starting each transcript.
+    hello_messages: Vec<TranscriptMessage>,
 }
 
 impl NetconfSession {
@@ -31,19 +53,46 @@ impl NetconfSession {
         auth: SshAuthentication,
         client_capabilities: Vec<Capability>,
     ) -> Self {
+        Self::with_transport(
+            Box::new(SshClient::new(address, port, auth)),
+            client_capabilities,
+        )
+    }
+
+    /// Create new instance of NETCONF client using custom `transport`, without connecting to the server.
+    /// Invoke [`Self::connect()`] to establish the connection, prior to other requests.
+    pub fn with_transport(
+        transport: Box<dyn Transport>,
+        client_capabilities: Vec<Capability>,
+    ) -> Self {
+        let span = info_span!(
+            "netconf_session",
+            target = %transport.target_string(),
+            session_id = field::Empty
+        );
         Self {
-            ssh: SshClient::new(address, port, auth),
+            transport,
+            decoder: FrameDecoder::new(BaseCapability::Base),
             last_message_id: 0,
             session_id: None,
             client_capabilities,
             server_capabilities: None,
             validate_capabilities: true,
+            span,
+            wire_trace: None,
+            recorder: None,
+            hello_messages: vec![],
         }
     }
 
     /// String representation of the target NETCONF server, in the form of "host:port".
     pub fn target_string(&self) -> String {
-        self.ssh.target_string()
+        self.transport.target_string()
+    }
+
+    /// List of capabilities advertised by the client in its `<hello>` message.
+    pub fn client_capabilities(&self) -> &[Capability] {
+        &self.client_capabilities
     }
 
     /// Current `session-id` as advertised by NETCONF server.
@@ -70,20 +119,50 @@ impl NetconfSession {
         self.validate_capabilities = do_validate;
     }
 
-    /// Establish SSH connection to target server.
+    /// Trace the raw framed bytes exchanged with server into the `wire_trace` sink, or stop tracing with `None`.
+    pub fn set_wire_trace(&mut self, wire_trace: Option<WireTrace>) {
+        self.wire_trace = wire_trace;
+    }
+
+    /// Record transcript of the session by the `recorder`, or stop recording with `None`.
+    ///
+    /// Recording started after the `<hello>` exchange gets the `<hello>` messages first, so that the transcript can be replayed.
+    pub fn set_recorder(&mut self, recorder: Option<TranscriptRecorder>) -> Result<()> {
+        self.recorder = recorder;
+        if let Some(recorder) = self.recorder.as_mut() {
+            for message in self.hello_messages.iter() {
+                recorder.record(message)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Establish transport connection (SSH by default) to target server.
     pub fn connect(&mut self) -> Result<()> {
         if self.session_id.is_some() {
             bail!("Already connected to server!");
         }
-        self.ssh.connect()?;
+        let _entered = self.span.enter();
+        if let Err(err) = self.transport.connect() {
+            warn!(error = %err, "connection failed");
+            return Err(err);
+        }
+        info!("connected");
+        self.decoder = FrameDecoder::new(BaseCapability::Base);
         Ok(())
     }
 
     /// Perform exchange of `<hello>` messages & capabilities advertisement.
-    /// Requires previously established SSH connection done using [NetconfSession::connect()] method.
+    /// Requires previously established connection done using [NetconfSession::connect()] method.
     pub fn exchange_hello(&mut self) -> Result<FullResponse<HelloResponse>> {
         let request = hello::HelloRequest::new(self.client_capabilities.clone());
-        let response = self.dispatch_request(request)?;
+        let raw_request = request.to_raw_xml()?;
+        // no server capabilities yet to validate against
+        let response = self.dispatch_unvalidated(request)?;
+        self.hello_messages = vec![
+            TranscriptMessage::now(Direction::Sent, &raw_request),
+            TranscriptMessage::now(Direction::Received, &response.dump),
+        ];
         self.update_on_hello(&response.typed)?;
         Ok(response)
     }
@@ -94,6 +173,12 @@ impl NetconfSession {
         self.last_message_id.to_string()
     }
 
+    /// String representation of the message-id to be returned by the next [`Self::new_message_id()`] call,
+    /// without increasing the internal counter.
+    pub fn next_message_id(&self) -> String {
+        self.last_message_id.saturating_add(1).to_string()
+    }
+
     /// All-in-one constructor that connects to the target NETCONF server at the moment of creation,
     /// and performs the <hello> capability exchange.
     pub fn initialize(
@@ -110,13 +195,13 @@ impl NetconfSession {
 
     /// List of capabilities advertised by NETCONF server on `<hello>` messages exchange.
     /// Returns `None` if no connection established yet.
-    pub fn server_capabilities(&self) -> Option<&[Capability]> {
-        self.server_capabilities.as_deref()
+    pub fn server_capabilities(&self) -> Option<&ServerCapabilities> {
+        self.server_capabilities.as_ref()
     }
 
     /// A `:base` capability that specifies type of message exchange between client & server.
     pub fn base_capability(&self) -> BaseCapability {
-        self.ssh.base_capability()
+        self.decoder.base()
     }
 
     /// Core method for dispatching NETCONF requests to server.
@@ -124,15 +209,112 @@ impl NetconfSession {
         &mut self,
         request: R,
     ) -> Result<FullResponse<R::Response>> {
+        if self.session_id.is_none() {
+            bail!("Session not connected! Connect & exchange <hello> first.");
+        }
         if self.validate_capabilities {
             match self.server_capabilities() {
                 Some(caps) => request.validate_request(caps)?,
                 None => bail!("No server capabilities! Session not initiated yet?"),
             };
         }
-        let dump = self.ssh.dispatch_netconf_request(request)?;
+        self.dispatch_unvalidated(request)
+    }
+
+    /// Dispatch the request without any capabilities validation.
+    fn dispatch_unvalidated<R: NetconfRequest>(
+        &mut self,
+        request: R,
+    ) -> Result<FullResponse<R::Response>> {
+        // TODO - max length check?
+        let raw_dump = request.to_raw_xml()?;
+        let (message_id, operation) = rpc_operation(&raw_dump);
+        let span = debug_span!(parent: &self.span, "rpc", %message_id, %operation);
+        let _entered = span.enter();
+
+        let started = Instant::now();
+        let (dump, bytes_sent, bytes_received) = self
+            .exchange(&raw_dump)
+            .inspect_err(|err| warn!(error = %err, "request failed"))?;
         let typed = R::Response::from_netconf_rpc(&dump)?;
-        Ok(FullResponse { typed, dump })
+        debug!(
+            duration_ms = started.elapsed().as_millis() as u64,
+            bytes_sent,
+            bytes_received,
+            succeeded = typed.succeeded(),
+            "reply received"
+        );
+        // the reply itself was parsed already, attributes are informative only
+        let attributes = parse_root_attributes(&dump).unwrap_or_else(|err| {
+            warn!(error = %err, "unparsable attributes of reply ignored");
+            vec![]
+        });
+        Ok(FullResponse {
+            typed,
+            dump,
+            attributes,
+        })
+    }
+
+    /// Send the `message` & receive the reply, along with the sizes of framed data sent & received.
+    fn exchange(&mut self, message: &str) -> Result<(String, usize, usize)> {
+        let bytes_sent = self.send_message(message)?;
+        self.record(Direction::Sent, message);
+        let mut received = vec![];
+        let result = self.receive_message(&mut received);
+        if !received.is_empty() {
+            self.trace(Direction::Received, &received);
+        }
+        let reply = result?;
+        self.record(Direction::Received, &reply);
+        Ok((reply, bytes_sent, received.len()))
+    }
+
+    /// Write the whole framed message into transport, returning size of the framed data.
+    fn send_message(&mut self, message: &str) -> Result<usize> {
+        let framed = encode_message(message, self.decoder.base());
+        self.transport.write_all(&framed)?;
+        self.transport.flush()?;
+        self.trace(Direction::Sent, &framed);
+        Ok(framed.len())
+    }
+
+    /// Blocking receive of the transport data, up until the complete framed message is decoded.
+    /// Data read from transport are appended to `received`.
+    fn receive_message(&mut self, received: &mut Vec<u8>) -> Result<String> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            if let Some(message) = self.decoder.next_message()? {
+                return Ok(message);
+            }
+            let bytes_read = self.transport.read(&mut buffer)?;
+            received.extend_from_slice(&buffer[..bytes_read]);
+            if bytes_read == 0 {
+                bail!(
+                    "Connection unexpectedly closed by server.\nData read before encountering the problem: {}",
+                    String::from_utf8_lossy(self.decoder.pending())
+                );
+            }
+            self.decoder.feed(&buffer[..bytes_read]);
+        }
+    }
+
+    /// Record the data into wire trace, if any. Failure of the trace is only logged, not to break the session.
+    fn trace(&mut self, direction: Direction, bytes: &[u8]) {
+        if let Some(wire_trace) = self.wire_trace.as_mut() {
+            if let Err(err) = wire_trace.record(direction, bytes) {
+                warn!(error = %err, "wire trace failed");
+            }
+        }
+    }
+
+    /// Record the message into transcript, if any. Failure of the recorder is only logged, not to break the session.
+    fn record(&mut self, direction: Direction, message: &str) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(err) = recorder.record(&TranscriptMessage::now(direction, message)) {
+                warn!(error = %err, "transcript recording failed");
+            }
+        }
     }
 
     /// Update internal state of [`NetconfSession`] according to the hello capabilities exchange results.
@@ -145,16 +327,306 @@ impl NetconfSession {
             common_highest_base_capability(&self.client_capabilities, &response.capabilities);
 
         match base_capability {
-            Some(base_capability) => self.ssh.set_base_capability(base_capability),
+            Some(base_capability) => self.decoder.set_base(base_capability),
             None => bail!("No common base capability found!".to_string()),
         };
 
         self.session_id = Some(response.session_id);
-        self.server_capabilities = Some(response.capabilities.clone());
+        self.server_capabilities = Some(response.capabilities.clone().into());
+        self.span.record("session_id", response.session_id);
+        info!(
+            parent: &self.span,
+            base = ?self.decoder.base(),
+            capabilities = response.capabilities.len(),
+            "hello exchanged"
+        );
 
         Ok(())
     }
 
+    /// Retrieve the YANG library of server - [RFC 8525](https://datatracker.ietf.org/doc/html/rfc8525), via the `<get>` request.
+    ///
+    /// The `/yang-library` container is requested first, with a fall back to the legacy `/modules-state`
+    /// for servers implementing older revision of the `ietf-yang-library` module.
+    /// Only the `/modules-state` is requested, if server advertises the `:yang-library:1.0` capability.
+    pub fn get_yang_library(&mut self) -> Result<YangLibrary> {
+        let legacy_only = match self
+            .server_capabilities()
+            .and_then(|caps| caps.yang_library())
+        {
+            Some(library) => library.version == "1.0",
+            None => false,
+        };
+        let containers: &[&str] = match legacy_only {
+            true => &["modules-state"],
+            false => &["yang-library", "modules-state"],
+        };
+
+        let mut errors = vec![];
+        for container in containers {
+            match self.get_yang_library_container(container) {
+                Ok(library) => return Ok(library),
+                Err(err) => errors.push(format!("<{}>: {}", container, err)),
+            }
+        }
+        bail!("Failed to retrieve YANG library - {}", errors.join("; "))
+    }
+
+    fn get_yang_library_container(&mut self, container: &str) -> Result<YangLibrary> {
+        let filter = FilterPayload {
+            filter: Filter::Subtree(format!("<{} xmlns=\"{}\"/>", container, YANG_LIBRARY_XMLNS)),
+            namespaces: vec![],
+        };
+        let request = get::GetRequest::new(self.new_message_id(), Some(filter));
+        let response = self.dispatch_request(request)?;
+        if let RpcReply::Error(errors) = &response.typed.reply {
+            let tags: Vec<&str> = errors.iter().map(|x| x.error_tag.as_str()).collect();
+            bail!("error reply \"{}\"", tags.join("\", \""));
+        }
+        let data = XmlNode::parse(response.data()?)?;
+        YangLibrary::from_data(&data)
+    }
+
+    /// Begin the [`Transaction`] - lock the datastores to be edited, by "candidate" or "writable-running" capability of server.
+    ///
+    /// ```no_run
+    /// # fn example(session: &mut netconf_client::NetconfSession) -> anyhow::Result<()> {
+    /// use netconf_client::messages::edit_config::EditConfigContent;
+    ///
+    /// let mut transaction = session.transaction()?;
+    /// transaction.edit(EditConfigContent::Config("<system xmlns=\"urn:example:sys\"/>".to_string()), None)?;
+    /// transaction.commit()
+    /// # }
+    /// ```
+    pub fn transaction(&mut self) -> Result<Transaction<'_>> {
+        Transaction::begin(self)
+    }
+
+    /// Revisions of the YANG modules implemented by server - advertised in `<hello>`,
+    /// or retrieved from the YANG library of servers advertising none of them (e.g. NMDA ones).
+    pub fn module_revisions(&mut self) -> Result<Vec<ModuleRevision>> {
+        let capabilities = match self.server_capabilities() {
+            Some(caps) => caps,
+            None => bail!("No server capabilities! Session not initiated yet?"),
+        };
+        let modules: Vec<ModuleRevision> = capabilities
+            .modules()
+            .map(|x| ModuleRevision {
+                name: x.module.clone(),
+                revision: x.revision.clone(),
+            })
+            .collect();
+        if !modules.is_empty() || capabilities.yang_library().is_none() {
+            return Ok(modules);
+        }
+
+        let library = self.get_yang_library()?;
+        Ok(library
+            .modules()
+            .filter(|x| x.conformance == Conformance::Implement)
+            .map(|x| ModuleRevision {
+                name: x.name.clone(),
+                revision: x.revision.clone(),
+            })
+            .collect())
+    }
+
+    /// Retrieve the configuration of the `datastore` via `<get-config>`, along with the server metadata.
+    ///
+    /// The `timestamp` of the backup is up to the caller, e.g. the current time in RFC 3339 format.
+    pub fn backup(&mut self, datastore: Datastore, timestamp: &str) -> Result<ConfigBackup> {
+        let request =
+            get_config::GetConfigRequest::new(self.new_message_id(), datastore.clone(), None);
+        let response = self.dispatch_request(request)?;
+        check_reply("get-config", &response.typed.reply)?;
+        let config = XmlNode::parse(response.data()?)?.children;
+
+        let capabilities = self
+            .server_capabilities()
+            .map(|caps| caps.iter().map(|x| x.get_urn()).collect())
+            .unwrap_or_default();
+        Ok(ConfigBackup {
+            device: self.target_string(),
+            datastore,
+            timestamp: timestamp.to_string(),
+            capabilities,
+            modules: self.module_revisions()?,
+            config,
+        })
+    }
+
+    /// Restore the configuration `backup` via the candidate datastore.
+    ///
+    /// Modules of the server are checked against the backup first, see [`ConfigBackup::incompatible_modules()`].
+    /// The backup is loaded into the candidate datastore, validated (`:validate` capability) and committed.
+    /// Servers supporting the `:confirmed-commit` capability get the confirmed commit, confirmed by this session
+    /// only after `<get-config>` of the "running" datastore matches the backup (lists lined up by [`RestoreOptions::list_hints`]).
+    /// Otherwise the commit is cancelled (`:confirmed-commit:1.1`), or rolled back by server after the confirm timeout -
+    /// same as if the session breaks in the meantime.
+    ///
+    /// Both "running" & "candidate" datastores are locked for the whole restore, same as by the [`Transaction`].
+    /// Changes of the candidate datastore are discarded on failure, and the locks released in any case.
+    pub fn restore(
+        &mut self,
+        backup: &ConfigBackup,
+        options: &RestoreOptions,
+    ) -> Result<RestoreReport> {
+        let capabilities = match self.server_capabilities() {
+            Some(caps) => caps,
+            None => bail!("No server capabilities! Session not initiated yet?"),
+        };
+        if !capabilities.contains(&Capability::Candidate) {
+            bail!("Server does not support the :candidate capability, required for restore!");
+        }
+        let validate = capabilities.contains(&Capability::Validate11)
+            || capabilities.contains(&Capability::Validate);
+        let confirmed_commit = capabilities.contains(&Capability::ConfirmedCommit)
+            || capabilities.contains(&Capability::ConfirmedCommit10);
+
+        let incompatible_modules = backup.incompatible_modules(&self.module_revisions()?);
+        if !incompatible_modules.is_empty() && !options.force {
+            bail!(
+                "Backup is not compatible with modules of server: {}",
+                incompatible_modules.join("; ")
+            );
+        }
+
+        let mut locked = vec![];
+        let mut result = Ok(());
+        for datastore in [Datastore::Running, Datastore::Candidate] {
+            let request = lock::LockRequest::new(self.new_message_id(), datastore.clone());
+            result = self
+                .dispatch_request(request)
+                .and_then(|response| check_reply("lock", &response.typed.reply));
+            if result.is_err() {
+                break;
+            }
+            locked.push(datastore);
+        }
+        if result.is_ok() {
+            result = self.restore_candidate(backup, options, validate, confirmed_commit);
+        }
+        // changes of "candidate" are discarded only when locked - not to discard changes of others
+        if result.is_err() && locked.contains(&Datastore::Candidate) {
+            let request = discard_changes::DiscardChangesRequest::new(self.new_message_id());
+            let _ = self.dispatch_request(request);
+        }
+        while let Some(datastore) = locked.pop() {
+            let request = unlock::UnlockRequest::new(self.new_message_id(), datastore);
+            let unlocked = self
+                .dispatch_request(request)
+                .and_then(|response| check_reply("unlock", &response.typed.reply));
+            if result.is_ok() {
+                result = unlocked;
+            }
+        }
+        result?;
+
+        Ok(RestoreReport {
+            incompatible_modules,
+            validated: validate,
+            confirmed_commit,
+        })
+    }
+
+    fn restore_candidate(
+        &mut self,
+        backup: &ConfigBackup,
+        options: &RestoreOptions,
+        validate: bool,
+        confirmed_commit: bool,
+    ) -> Result<()> {
+        let config = XmlNode::fragment_to_xml(&backup.config)?;
+        match options.method {
+            RestoreMethod::Replace => {
+                let params = edit_config::EditConfigParams {
+                    target: Datastore::Candidate,
+                    default_operation: Some(edit_config::DefaultOperation::Replace),
+                    test_option: None,
+                    error_option: None,
+                    config: edit_config::EditConfigContent::Config(config),
+                };
+                let request = edit_config::EditConfigRequest::new(self.new_message_id(), params);
+                let response = self.dispatch_request(request)?;
+                check_reply("edit-config", &response.typed.reply)?;
+            }
+            RestoreMethod::Copy => {
+                let request = copy_config::CopyConfigRequest::new(
+                    self.new_message_id(),
+                    ConfigWaypoint::Datastore(Datastore::Candidate),
+                    ConfigWaypoint::Config(config),
+                );
+                let response = self.dispatch_request(request)?;
+                check_reply("copy-config", &response.typed.reply)?;
+            }
+        }
+
+        if validate {
+            let request = validate::ValidateRequest::new(
+                self.new_message_id(),
+                validate::ValidateSource::Datastore(Datastore::Candidate),
+            );
+            let response = self.dispatch_request(request)?;
+            check_reply("validate", &response.typed.reply)?;
+        }
+
+        if confirmed_commit {
+            let params = commit::ConfirmedCommitParams {
+                confirm_timeout: Some(options.confirm_timeout),
+                persist: None,
+                persist_id: None,
+            };
+            let request = commit::CommitRequest::new(
+                self.new_message_id(),
+                commit::CommitType::Confirmed(params),
+            );
+            let response = self.dispatch_request(request)?;
+            check_reply("commit", &response.typed.reply)?;
+            self.check_restored(backup, options)?;
+        }
+        let request = commit::CommitRequest::new(self.new_message_id(), commit::CommitType::Plain);
+        let response = self.dispatch_request(request)?;
+        check_reply("commit", &response.typed.reply)
+    }
+
+    /// Check the "running" datastore of the pending confirmed commit against the `backup`, cancelling the commit on mismatch.
+    fn check_restored(&mut self, backup: &ConfigBackup, options: &RestoreOptions) -> Result<()> {
+        let request =
+            get_config::GetConfigRequest::new(self.new_message_id(), Datastore::Running, None);
+        let response = self.dispatch_request(request)?;
+        check_reply("get-config", &response.typed.reply)?;
+        let running = XmlNode::parse(response.data()?)?.children;
+        let diff = ConfigDiff::new(&backup.config, &running, &options.list_hints);
+        if diff.is_empty() {
+            return Ok(());
+        }
+
+        let cancel = self
+            .server_capabilities()
+            .is_some_and(|caps| caps.contains(&Capability::ConfirmedCommit));
+        let rollback = match cancel {
+            true => {
+                let request = cancel_commit::CancelCommitRequest::new(self.new_message_id(), None);
+                let response = self.dispatch_request(request)?;
+                check_reply("cancel-commit", &response.typed.reply)?;
+                "commit cancelled".to_string()
+            }
+            false => format!(
+                "rolled back by server in {} seconds",
+                options.confirm_timeout
+            ),
+        };
+        bail!(
+            "Committed configuration does not match the backup, {}:\n{}",
+            rollback,
+            diff.to_unified("backup", "running", 2)
+        )
+    }
+
+    /// Close the session by `<close-session>`, disconnecting the transport on `<ok>` reply.
+    ///
+    /// The closed session has no session-id & server capabilities - any further request fails,
+    /// until connected & `<hello>` exchanged again.
     pub fn request_close_session(
         &mut self,
     ) -> Result<FullResponse<close_session::CloseSessionResponse>> {
@@ -162,13 +634,37 @@ impl NetconfSession {
         let response = self.dispatch_request(request)?;
 
         if RpcReply::Ok == response.typed.reply {
-            self.ssh.disconnect()?;
+            self.session_id = None;
+            self.server_capabilities = None;
+            self.transport.disconnect()?;
+            info!(parent: &self.span, "session closed");
         }
 
         Ok(response)
     }
 }
 
+/// Fail on the `<rpc-error>` reply of the `operation`, describing all the errors.
+pub(crate) fn check_reply(operation: &str, reply: &RpcReply) -> Result<()> {
+    match reply {
+        RpcReply::Ok => Ok(()),
+        RpcReply::Error(errors) => {
+            let described: Vec<String> = errors
+                .iter()
+                .map(|err| match &err.error_message {
+                    Some(message) => format!("\"{}\": {}", err.error_tag, message),
+                    None => format!("\"{}\"", err.error_tag),
+                })
+                .collect();
+            bail!(
+                "<{}> failed with error reply {}",
+                operation,
+                described.join(", ")
+            )
+        }
+    }
+}
+
 /// Compares two sets of capabilities, and finds the highest version of `:base` capability to specify
 /// type of SSH communication to be used for further requests, as defined in [RFC 6242](https://datatracker.ietf.org/doc/html/rfc6242).
 fn common_highest_base_capability(
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::diff::ListHints;
+
+/// Built-in YANG type of a leaf, as far as its JSON encoding is concerned - [RFC 7951 - section 6](https://datatracker.ietf.org/doc/html/rfc7951#section-6).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LeafType {
+    /// Integers up to 32 bits, encoded as JSON numbers.
+    Number,
+    /// The `boolean` type, encoded as JSON `true` or `false`.
+    Boolean,
+    /// The `empty` type, encoded as `[null]`.
+    Empty,
+    /// The `identityref` type, encoded as identity name qualified by the module name.
+    IdentityRef,
+    /// Any other type, incl. 64-bit integers & `decimal64`, encoded as JSON string.
+    String,
+}
+
+impl LeafType {
+    /// Type of the built-in YANG type name, `None` for the derived ones (typedefs).
+    pub fn from_builtin(name: &str) -> Option<Self> {
+        Some(match name {
+            "int8" | "int16" | "int32" | "uint8" | "uint16" | "uint32" => LeafType::Number,
+            "boolean" => LeafType::Boolean,
+            "empty" => LeafType::Empty,
+            "identityref" => LeafType::IdentityRef,
+            "int64"
+            | "uint64"
+            | "decimal64"
+            | "string"
+            | "binary"
+            | "bits"
+            | "enumeration"
+            | "instance-identifier"
+            | "leafref"
+            | "union" => LeafType::String,
+            _ => return None,
+        })
+    }
+}
+
+/// Schema-less hints of the data encoding - YANG modules by namespace, lists & types of leaves by element name.
+///
+/// Built from the YANG modules via [`Self::from_yang()`], or by hand. The leaves without any hint
+/// are encoded as strings.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaHints {
+    lists: ListHints,
+    leaves: HashMap<String, LeafType>,
+    modules: HashMap<String, String>,
+}
+
+impl SchemaHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the YANG module of given `namespace`.
+    pub fn module(mut self, namespace: &str, name: &str) -> Self {
+        self.modules.insert(namespace.to_string(), name.to_string());
+        self
+    }
+
+    /// Add the list of given `keys`.
+    pub fn list(mut self, name: &str, keys: &[&str]) -> Self {
+        self.lists = self.lists.list(name, keys);
+        self
+    }
+
+    /// Add the leaf-list of given type.
+    pub fn leaf_list(mut self, name: &str, leaf_type: LeafType) -> Self {
+        self.lists = self.lists.leaf_list(name);
+        self.leaf(name, leaf_type)
+    }
+
+    /// Add the leaf of given type.
+    pub fn leaf(mut self, name: &str, leaf_type: LeafType) -> Self {
+        self.leaves.insert(name.to_string(), leaf_type);
+        self
+    }
+
+    /// Lists & leaf-lists known to the hints.
+    pub fn list_hints(&self) -> &ListHints {
+        &self.lists
+    }
+
+    pub fn leaf_type(&self, name: &str) -> Option<LeafType> {
+        self.leaves.get(name).copied()
+    }
+
+    /// Name of the YANG module of given `namespace`.
+    pub fn module_name(&self, namespace: &str) -> Option<&str> {
+        self.modules.get(namespace).map(String::as_str)
+    }
+
+    /// Namespace of the YANG module of given `name`.
+    pub fn module_namespace(&self, name: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .find(|(_, module)| *module == name)
+            .map(|(namespace, _)| namespace.as_str())
+    }
+
+    /// Hints of the YANG modules (and submodules) `sources` - namespaces of the modules, lists & leaf types.
+    ///
+    /// The YANG statements are only scanned, not validated nor compiled - nodes are identified by their name,
+    /// regardless of the groupings, augments or deviations. Typedefs are resolved across all the `sources`,
+    /// so modules of the imported typedefs should be given as well - otherwise their leaves are encoded as strings.
+    pub fn from_yang(sources: &[&str]) -> Result<Self> {
+        let mut modules = vec![];
+        for source in sources {
+            modules.extend(parse_statements(&tokenize(source)?, &mut 0)?);
+        }
+
+        let mut typedefs = HashMap::new();
+        let mut leaves = vec![];
+        let mut hints = Self::new();
+        for module in modules.iter() {
+            if module.keyword == "module" {
+                if let (Some(name), Some(namespace)) = (&module.arg, module.child_arg("namespace"))
+                {
+                    hints = hints.module(namespace, name);
+                }
+            }
+            module.walk(&mut |statement| match statement.keyword.as_str() {
+                "typedef" => {
+                    if let (Some(name), Some(base)) = (&statement.arg, statement.child_arg("type"))
+                    {
+                        typedefs.insert(name.clone(), base.to_string());
+                    }
+                }
+                "leaf" | "leaf-list" => leaves.push(statement),
+                "list" => {
+                    if let (Some(name), Some(keys)) = (&statement.arg, statement.child_arg("key")) {
+                        let keys: Vec<&str> = keys.split_whitespace().collect();
+                        hints.lists = std::mem::take(&mut hints.lists).list(name, &keys);
+                    }
+                }
+                _ => {}
+            });
+        }
+
+        for leaf in leaves {
+            let (name, type_name) = match (&leaf.arg, leaf.child_arg("type")) {
+                (Some(name), Some(type_name)) => (name, type_name),
+                _ => continue,
+            };
+            let leaf_type = resolve_type(type_name, &typedefs);
+            hints = match leaf.keyword.as_str() {
+                "leaf-list" => hints.leaf_list(name, leaf_type),
+                _ => hints.leaf(name, leaf_type),
+            };
+        }
+        Ok(hints)
+    }
+}
+
+/// Built-in type of the (derived) YANG type, strings for typedefs unknown.
+fn resolve_type(type_name: &str, typedefs: &HashMap<String, String>) -> LeafType {
+    let mut name = type_name;
+    // bounded, in case of (invalid) circular typedefs
+    for _ in 0..16 {
+        let unprefixed = name.rsplit(':').next().unwrap_or(name);
+        if !name.contains(':') {
+            if let Some(leaf_type) = LeafType::from_builtin(name) {
+                return leaf_type;
+            }
+        }
+        match typedefs.get(unprefixed) {
+            Some(base) => name = base,
+            None => break,
+        }
+    }
+    LeafType::String
+}
+
+/// Generic YANG statement - [RFC 7950 - section 6.3](https://datatracker.ietf.org/doc/html/rfc7950#section-6.3).
+#[derive(Debug)]
+struct Statement {
+    keyword: String,
+    arg: Option<String>,
+    children: Vec<Statement>,
+}
+
+impl Statement {
+    fn child_arg(&self, keyword: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .find(|x| x.keyword == keyword)
+            .and_then(|x| x.arg.as_deref())
+    }
+
+    /// Visit the statement & all its substatements, depth-first.
+    fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a Statement)) {
+        visit(self);
+        for child in self.children.iter() {
+            child.walk(visit);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// Unquoted string, incl. keywords.
+    Word(String),
+    /// Quoted string, concatenated already.
+    Quoted(String),
+    Semicolon,
+    Open,
+    Close,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' => tokens.push(Token::Semicolon),
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => last = c,
+                        None => bail!("Unterminated comment of YANG module"),
+                    }
+                }
+            }
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') if c == '"' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(escaped) => value.push(escaped),
+                            None => bail!("Unterminated string of YANG module"),
+                        },
+                        Some(end) if end == c => break,
+                        Some(other) => value.push(other),
+                        None => bail!("Unterminated string of YANG module"),
+                    }
+                }
+                // "a" + "b" concatenation
+                if let [.., Token::Quoted(previous), Token::Word(plus)] = tokens.as_mut_slice() {
+                    if plus == "+" {
+                        previous.push_str(&value);
+                        tokens.pop();
+                        continue;
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            c => {
+                let mut value = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, ';' | '{' | '}') {
+                        break;
+                    }
+                    value.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Word(value));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Statements of the `tokens` from the `position`, up to the closing brace of the parent statement.
+fn parse_statements(tokens: &[Token], position: &mut usize) -> Result<Vec<Statement>> {
+    let mut statements = vec![];
+    while let Some(token) = tokens.get(*position) {
+        *position += 1;
+        let keyword = match token {
+            Token::Word(keyword) => keyword.clone(),
+            Token::Close => return Ok(statements),
+            token => bail!("Unexpected {:?} in YANG module, keyword expected", token),
+        };
+        let arg = match tokens.get(*position) {
+            Some(Token::Word(arg)) | Some(Token::Quoted(arg)) => {
+                *position += 1;
+                Some(arg.clone())
+            }
+            _ => None,
+        };
+        let children = match tokens.get(*position) {
+            Some(Token::Semicolon) => {
+                *position += 1;
+                vec![]
+            }
+            Some(Token::Open) => {
+                *position += 1;
+                parse_statements(tokens, position)?
+            }
+            _ => bail!(
+                "Missing \";\" or \"{{\" of the YANG statement \"{}\"",
+                keyword
+            ),
+        };
+        statements.push(Statement {
+            keyword,
+            arg,
+            children,
+        });
+    }
+    Ok(statements)
+}
@@ -1,53 +1,65 @@
 //! Simple blocking implementation of SSH client, that allows exchanging RPC
 //! requests/responses with target NETCONF server.
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use ssh2::{Channel, Session};
+use tracing::{debug, warn};
 
 use std::{
-    fmt::Display,
+    fmt::Debug,
     io::{self, Read, Write},
     net::{IpAddr, SocketAddr, TcpStream},
+    path::PathBuf,
+    time::Duration,
 };
+use zeroize::Zeroizing;
 
-use super::messages::NetconfRequest;
+use crate::transport::Transport;
 
-/// Type of a `:base` capability defining the type of SSH communication between the endpoints.
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum BaseCapability {
-    /// NETCONF 1.0 raw message processing.
-    Base,
-    // NETCONF 1.1 chunked message processing.
-    Base11,
+/// Default timeout of the connection & blocking SSH operations.
+pub const SSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Secret used for SSH authentication - password or passphrase of the key.
+///
+/// Its memory is wiped out when dropped, and the content is never revealed by the [`Debug`] output.
+#[derive(Clone, Default, PartialEq)]
+pub struct Password(Zeroizing<String>);
+
+impl Password {
+    /// Plain-text content of the secret, to be handed over to the authentication.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
 }
 
-impl BaseCapability {
-    /// [RFC 6242](https://datatracker.ietf.org/doc/html/rfc6242) specified end-of-message separator.
-    pub fn eom_separator(&self) -> &'static str {
-        match self {
-            BaseCapability::Base => "]]>]]>",
-            BaseCapability::Base11 => "\n##\n",
-        }
+impl From<String> for Password {
+    fn from(s: String) -> Self {
+        Self(Zeroizing::new(s))
     }
 }
 
-impl Display for BaseCapability {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            BaseCapability::Base => "plain :base:1.0",
-            BaseCapability::Base11 => "chunked :base:1.1",
-        };
-        write!(f, "{}", s)
+impl From<&str> for Password {
+    fn from(s: &str) -> Self {
+        Self::from(s.to_string())
     }
 }
 
-const SSH_TIMEOUT: u32 = 5000;
+impl Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Password(***)")
+    }
+}
 
 /// Type of authentication used for SSH connection.
 #[derive(Debug)]
 pub enum SshAuthentication {
-    /// Plain old username & password access. Please note plain-text data kept in memory during runtime.
-    UserPassword(String, String),
+    /// Plain old username & password access.
+    UserPassword(String, Password),
+    /// Username & path to the private key file, with optional passphrase of the key.
+    /// Public key is expected in the file of the same path with `.pub` suffix, if present.
+    KeyFile(String, PathBuf, Option<Password>),
+    /// Username authenticated by the keys of running SSH agent.
+    Agent(String),
 }
 
 /// SSH client for streaming messages between caller - client, and NETCONF server.
@@ -55,15 +67,8 @@ pub struct SshClient {
     address: IpAddr,
     port: u16,
     auth: SshAuthentication,
+    timeout: Duration,
     channel: Option<Channel>,
-    base_capability: BaseCapability,
-}
-
-macro_rules! regex {
-    ($re:literal $(,)?) => {{
-        static RE: once_cell::sync::OnceCell<regex::Regex> = once_cell::sync::OnceCell::new();
-        RE.get_or_init(|| regex::Regex::new($re).unwrap())
-    }};
 }
 
 impl SshClient {
@@ -74,20 +79,15 @@ impl SshClient {
             address,
             port,
             auth,
+            timeout: SSH_TIMEOUT,
             channel: None,
-            base_capability: BaseCapability::Base,
         }
     }
 
-    /// Return the message exchange mode set for the pending session.
-    pub fn base_capability(&self) -> BaseCapability {
-        self.base_capability
-    }
-
-    /// Set the message exchange mode/base capability, as a result of the <hello> capabilities exchange
-    /// defined by [RFC 6241](https://datatracker.ietf.org/doc/html/rfc6241#section-8.1).
-    pub fn set_base_capability(&mut self, base_capability: BaseCapability) {
-        self.base_capability = base_capability;
+    /// Set the timeout of connecting to the server & of any blocking SSH operation,
+    /// [`SSH_TIMEOUT`] by default. Applied on the next [`Self::connect()`].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
     }
 
     /// String representing the target NETCONF server address & port.
@@ -100,18 +100,34 @@ impl SshClient {
     pub fn connect(&mut self) -> Result<()> {
         let mut session = Session::new()?;
         session.set_blocking(true);
-        session.set_timeout(SSH_TIMEOUT);
+        session.set_timeout(self.timeout.as_millis().try_into().unwrap_or(u32::MAX));
         let socket_address = SocketAddr::from((self.address, self.port));
-        let tcp_stream = TcpStream::connect(socket_address)?;
+        let tcp_stream = TcpStream::connect_timeout(&socket_address, self.timeout)?;
         session.set_tcp_stream(tcp_stream);
         session.handshake()?;
+        debug!(server = %self.target_string(), "SSH handshake done");
 
         match &self.auth {
             SshAuthentication::UserPassword(username, password) => {
-                session.userauth_password(username, password)?;
+                session.userauth_password(username, password.expose())?;
+            }
+            SshAuthentication::KeyFile(username, private_key, passphrase) => {
+                let public_key = PathBuf::from(format!("{}.pub", private_key.display()));
+                let public_key = public_key.exists().then_some(public_key);
+                session.userauth_pubkey_file(
+                    username,
+                    public_key.as_deref(),
+                    private_key,
+                    passphrase.as_ref().map(|x| x.expose()),
+                )?;
+            }
+            SshAuthentication::Agent(username) => {
+                session.userauth_agent(username)?;
             }
         }
 
+        debug!(server = %self.target_string(), "SSH authenticated");
+
         let mut channel = session.channel_session()?;
         channel.subsystem("netconf")?;
         self.channel = Some(channel);
@@ -122,7 +138,7 @@ impl SshClient {
     /// Disconnects the instance of [`Self`] from connected NETCONF server.
     /// Instance can be re-used subsequently if needed, with another connect() invocation.
     pub fn disconnect(&mut self) -> Result<()> {
-        let channel = self.channel.as_mut().unwrap();
+        let channel = self.channel()?;
         channel.send_eof()?;
         channel.wait_eof()?;
         match channel.eof() {
@@ -134,67 +150,14 @@ impl SshClient {
         self.channel = None;
         Ok(())
     }
+}
 
-    /// Dispatches the input data over connected SSH stream.
-    /// Returns the String containing whole reponse received from server up to & excluding the NETCONF message separator.
-    pub fn dispatch_netconf_request(&mut self, request: impl NetconfRequest) -> Result<String> {
-        if self.channel.is_some() {
-            // TODO - max length check?
-            let raw_dump = request.to_raw_xml()?;
-            if self.base_capability == BaseCapability::Base11 {
-                self.write_all(format!("\n#{}\n", raw_dump.len()).as_bytes())?;
-            }
-            self.write_all(raw_dump.as_bytes())?;
-            self.write_all(self.base_capability.eom_separator().as_bytes())?;
-
-            self.get_reply()
-        } else {
-            bail!("request: Channel not connected!");
-        }
-    }
-
-    /// Blocking receive of the stream data. Buffers all the incoming data up until NETCONF message terminator.
-    fn get_reply(&mut self) -> Result<String> {
-        if self.channel.is_none() {
-            bail!("response: Channel not connected!");
-        }
-
-        let mut result = String::new();
-        let mut buffer = [0u8; 4096];
-
-        loop {
-            let bytes_read = self.read(&mut buffer)?;
-            let s = String::from_utf8_lossy(&buffer[..bytes_read]);
-
-            match &self.base_capability {
-                BaseCapability::Base => {
-                    result.push_str(&s);
-                }
-                BaseCapability::Base11 => {
-                    let chunk_prefix: &regex::Regex = regex!(r"^\n#\d+\n");
-                    let slice = if let Some(index) = chunk_prefix.shortest_match(&s) {
-                        &s[index..]
-                    } else {
-                        &s
-                    };
-                    result.push_str(slice);
-                }
-            };
-
-            if result.ends_with(self.base_capability.eom_separator()) {
-                break;
-            }
-
-            if bytes_read == 0 || self.channel.as_ref().unwrap().eof() {
-                bail!("Buffer is unexpectedly empty, SSH channel read terminated.\nData read before encountering the problem: {}", result);
-            }
-        }
-
-        if let Some(stripped) = result.strip_suffix(self.base_capability.eom_separator()) {
-            result = stripped.to_string();
-        }
-
-        Ok(result)
+impl SshClient {
+    /// The NETCONF channel, failing when not connected.
+    fn channel(&mut self) -> io::Result<&mut Channel> {
+        self.channel
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "SSH channel not connected"))
     }
 }
 
@@ -202,24 +165,38 @@ impl Drop for SshClient {
     fn drop(&mut self) {
         if self.channel.is_some() {
             if let Result::Err(err) = self.disconnect() {
-                println!("SSH disconnect error: {}", err);
+                warn!(server = %self.target_string(), error = %err, "SSH disconnect failed");
             }
         }
     }
 }
 
+impl Transport for SshClient {
+    fn connect(&mut self) -> Result<()> {
+        SshClient::connect(self)
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        SshClient::disconnect(self)
+    }
+
+    fn target_string(&self) -> String {
+        SshClient::target_string(self)
+    }
+}
+
 impl Write for SshClient {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.channel.as_mut().unwrap().write(buf)
+        self.channel()?.write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.channel.as_mut().unwrap().flush()
+        self.channel()?.flush()
     }
 }
 
 impl Read for SshClient {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.channel.as_mut().unwrap().read(buf)
+        self.channel()?.read(buf)
     }
 }
@@ -0,0 +1,578 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    common::XMLNS,
+    messages::ToRawXml,
+    types::{Filter, FilterPayload, XmlAttribute, XmlNode},
+};
+
+/// Builder of the subtree filter ([RFC 6241 - section 6](https://datatracker.ietf.org/doc/html/rfc6241#section-6)),
+/// and its evaluator over the data - e.g. to check the filter against saved `<get>` replies.
+///
+/// Namespaces of the nodes are declared on the `<filter>` element of the [`FilterPayload`], by the prefixes given
+/// via [`SubtreeFilter::with_namespace()`], otherwise `ns1`, `ns2`...
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use netconf_client::subtree::{FilterNode, SubtreeFilter};
+///
+/// let filter = SubtreeFilter::new()
+///     .with_namespace("if", "urn:ietf:params:xml:ns:yang:ietf-interfaces")
+///     .with_node(
+///         FilterNode::containment(
+///             "interfaces",
+///             [FilterNode::containment(
+///                 "interface",
+///                 [FilterNode::content_match("name", "eth0"), FilterNode::selection("mtu")],
+///             )],
+///         )
+///         .with_namespace("urn:ietf:params:xml:ns:yang:ietf-interfaces"),
+///     );
+/// let payload = filter.to_payload()?;
+/// assert_eq!(
+///     payload.namespaces,
+///     [("xmlns:if".to_string(), "urn:ietf:params:xml:ns:yang:ietf-interfaces".to_string())]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubtreeFilter {
+    /// Prefixes of the namespaces, as tuples of (prefix, value).
+    namespaces: Vec<(String, String)>,
+    /// Top-level filter nodes, with resolved namespaces.
+    nodes: Vec<XmlNode>,
+}
+
+impl SubtreeFilter {
+    /// Empty filter, selecting no data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter of the raw XML content, e.g. of [`Filter::Subtree`] - namespace prefixes have to be declared within.
+    pub fn parse(xml: &str) -> Result<Self> {
+        Ok(Self {
+            namespaces: vec![],
+            nodes: XmlNode::parse_fragment(xml)?,
+        })
+    }
+
+    /// Filter of the subtree `payload`, with its namespace declarations.
+    pub fn from_payload(payload: &FilterPayload) -> Result<Self> {
+        if let Filter::Xpath(_) = payload.filter {
+            bail!("Not a subtree filter, evaluate XPath by xpath::XPath instead");
+        }
+        let filter = XmlNode::parse(&payload.to_raw_xml()?)?;
+        Ok(Self {
+            namespaces: vec![],
+            nodes: filter.children,
+        })
+    }
+
+    /// Declare the namespace by the `prefix` in the [`FilterPayload`].
+    pub fn with_namespace(mut self, prefix: &str, namespace: &str) -> Self {
+        self.namespaces
+            .push((prefix.to_string(), namespace.to_string()));
+        self
+    }
+
+    /// Append the top-level filter node.
+    pub fn with_node(mut self, node: FilterNode) -> Self {
+        self.nodes.push(node.resolve(None));
+        self
+    }
+
+    /// Top-level filter nodes, with resolved namespaces.
+    pub fn nodes(&self) -> &[XmlNode] {
+        &self.nodes
+    }
+
+    /// The `<filter>` of requests, with the namespaces used by the nodes declared.
+    pub fn to_payload(&self) -> Result<FilterPayload> {
+        let mut namespaces = vec![];
+        let mut nodes = self.nodes.clone();
+        for node in nodes.iter_mut() {
+            self.assign_prefixes(node, &mut namespaces);
+        }
+        let declared: Vec<(Option<String>, String)> = namespaces
+            .iter()
+            .map(|(prefix, namespace)| (Some(prefix.clone()), namespace.clone()))
+            .collect();
+        Ok(FilterPayload {
+            filter: Filter::Subtree(XmlNode::fragment_to_xml_within(&nodes, &declared)?),
+            namespaces: namespaces
+                .into_iter()
+                .map(|(prefix, namespace)| (format!("xmlns:{}", prefix), namespace))
+                .collect(),
+        })
+    }
+
+    /// Qualify the node & its attributes by prefixes of their namespaces - those declared, or new ones
+    /// added to `used` namespaces of the payload.
+    fn assign_prefixes(&self, node: &mut XmlNode, used: &mut Vec<(String, String)>) {
+        let mut prefix_of = |namespace: &Option<String>| {
+            let namespace = namespace.as_ref()?;
+            if let Some((prefix, _)) = used.iter().find(|(_, x)| x == namespace) {
+                return Some(prefix.clone());
+            }
+            let prefix = match self.namespaces.iter().find(|(_, x)| x == namespace) {
+                Some((prefix, _)) => prefix.clone(),
+                None => (1..)
+                    .map(|x| format!("ns{}", x))
+                    .find(|x| {
+                        !used
+                            .iter()
+                            .chain(self.namespaces.iter())
+                            .any(|(prefix, _)| prefix == x)
+                    })
+                    .unwrap_or_default(),
+            };
+            used.push((prefix.clone(), namespace.clone()));
+            Some(prefix)
+        };
+        node.prefix = prefix_of(&node.namespace);
+        node.namespace_declarations.clear();
+        for attribute in node.attributes.iter_mut() {
+            attribute.prefix = prefix_of(&attribute.namespace);
+        }
+        for child in node.children.iter_mut() {
+            self.assign_prefixes(child, used);
+        }
+    }
+
+    /// Data selected by the filter, see [`filter_subtree()`].
+    pub fn apply(&self, data: &[XmlNode]) -> Vec<XmlNode> {
+        filter_subtree(data, &self.nodes)
+    }
+}
+
+/// Node of the [`SubtreeFilter`].
+///
+/// Nodes without namespace are of the namespace of their parent - top-level ones match elements of any namespace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterNode {
+    node: XmlNode,
+    children: Vec<FilterNode>,
+}
+
+impl FilterNode {
+    /// Selection node ([RFC 6241 - section 6.2.4](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.4)),
+    /// selecting the element with all its descendants.
+    pub fn selection(name: &str) -> Self {
+        Self {
+            node: XmlNode::new(name),
+            children: vec![],
+        }
+    }
+
+    /// Containment node ([RFC 6241 - section 6.2.3](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.3)),
+    /// selecting the element with the descendants selected by the `children`.
+    pub fn containment(name: &str, children: impl IntoIterator<Item = FilterNode>) -> Self {
+        Self {
+            node: XmlNode::new(name),
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Content match node ([RFC 6241 - section 6.2.5](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.5)),
+    /// selecting the sibling nodes of the leaf of the `value` - e.g. the list entry of the key.
+    pub fn content_match(name: &str, value: &str) -> Self {
+        Self {
+            node: XmlNode::new(name).with_text(value),
+            children: vec![],
+        }
+    }
+
+    /// Namespace selection ([RFC 6241 - section 6.2.2](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.2)) -
+    /// the node matches only elements of the `namespace`.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.node.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Append the `child` node, turning the node into containment node.
+    pub fn with_child(mut self, child: FilterNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Attribute match expression ([RFC 6241 - section 6.2.1](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.1)) -
+    /// the element has to have the attribute of the `value`.
+    pub fn with_attribute(self, name: &str, value: &str) -> Self {
+        self.with_attribute_in(None, name, value)
+    }
+
+    /// Attribute match expression of the attribute in the `namespace`.
+    pub fn with_namespaced_attribute(self, namespace: &str, name: &str, value: &str) -> Self {
+        self.with_attribute_in(Some(namespace), name, value)
+    }
+
+    fn with_attribute_in(mut self, namespace: Option<&str>, name: &str, value: &str) -> Self {
+        self.node.set_attribute(XmlAttribute {
+            name: name.to_string(),
+            prefix: None,
+            namespace: namespace.map(str::to_string),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// The filter element, of the `parent_namespace` unless having its own namespace.
+    fn resolve(self, parent_namespace: Option<&str>) -> XmlNode {
+        let mut node = self.node;
+        if node.namespace.is_none() {
+            node.namespace = parent_namespace.map(str::to_string);
+        }
+        for child in self.children {
+            let child = child.resolve(node.namespace.as_deref());
+            node.children.push(child);
+        }
+        node
+    }
+}
+
+/// Whether the data node matches the name & namespace of the filter node.
+///
+/// Filter node without namespace (or inheriting the NETCONF base namespace of `<filter>`) matches any namespace,
+/// as allowed by [RFC 6241 - section 6.2.1](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.1).
+fn matches_name(data: &XmlNode, filter: &XmlNode) -> bool {
+    data.name == filter.name
+        && match filter.namespace.as_deref() {
+            None | Some(XMLNS) => true,
+            namespace => data.namespace.as_deref() == namespace,
+        }
+}
+
+/// Content match node - leaf with text ([RFC 6241 - section 6.2.5](https://datatracker.ietf.org/doc/html/rfc6241#section-6.2.5)).
+fn is_content_match(filter: &XmlNode) -> bool {
+    filter.children.is_empty() && !filter.text().is_empty()
+}
+
+/// Whether the data node satisfies all the attribute match expressions of the filter node.
+fn matches_attributes(data: &XmlNode, filter: &XmlNode) -> bool {
+    filter.attributes.iter().all(|attribute| {
+        data.attributes.iter().any(|x| {
+            x.name == attribute.name
+                && x.namespace == attribute.namespace
+                && x.value == attribute.value
+        })
+    })
+}
+
+/// Evaluate the filter node against the data node of the same name,
+/// returning the selected part of the data, if any.
+fn filter_node(data: &XmlNode, filter: &XmlNode) -> Option<XmlNode> {
+    if !matches_attributes(data, filter) {
+        return None;
+    }
+    if filter.children.is_empty() {
+        return match is_content_match(filter) && data.text() != filter.text() {
+            true => None,
+            false => Some(data.clone()),
+        };
+    }
+
+    let (content_matches, others): (Vec<&XmlNode>, Vec<&XmlNode>) =
+        filter.children.iter().partition(|x| is_content_match(x));
+    let is_matched_content = |child: &XmlNode| {
+        content_matches
+            .iter()
+            .any(|x| matches_name(child, x) && child.text() == x.text())
+    };
+    let all_content_matched = content_matches.iter().all(|content| {
+        data.children
+            .iter()
+            .any(|x| matches_name(x, content) && x.text() == content.text())
+    });
+    if !all_content_matched {
+        return None;
+    }
+
+    let mut result = XmlNode {
+        children: vec![],
+        ..data.clone()
+    };
+    if others.is_empty() {
+        // only content match nodes - all the siblings are selected
+        result.children = data.children.clone();
+        return Some(result);
+    }
+    for child in &data.children {
+        if is_matched_content(child) {
+            result.children.push(child.clone());
+            continue;
+        }
+        let selected = others
+            .iter()
+            .filter(|x| matches_name(child, x))
+            .find_map(|x| filter_node(child, x));
+        if let Some(selected) = selected {
+            result.children.push(selected);
+        }
+    }
+    match result.children.is_empty() {
+        true => None,
+        false => Some(result),
+    }
+}
+
+/// Evaluate the subtree filter ([RFC 6241 - section 6](https://datatracker.ietf.org/doc/html/rfc6241#section-6))
+/// consisting of the top-level `filter` nodes, against the top-level `data` nodes.
+///
+/// Empty filter selects no data.
+pub fn filter_subtree(data: &[XmlNode], filter: &[XmlNode]) -> Vec<XmlNode> {
+    data.iter()
+        .filter_map(|node| {
+            filter
+                .iter()
+                .filter(|x| matches_name(node, x))
+                .find_map(|x| filter_node(node, x))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_NS: &str = "http://example.com/schema/1.2/config";
+
+    /// Data of the examples of [RFC 6241 - section 6.4](https://datatracker.ietf.org/doc/html/rfc6241#section-6.4), trimmed.
+    const DATA: &str = r#"
+<top xmlns="http://example.com/schema/1.2/config">
+  <users>
+    <user><name>root</name><type>superuser</type><full-name>Charlie Root</full-name><company-info><dept>1</dept><id>1</id></company-info></user>
+    <user><name>fred</name><type>admin</type><full-name>Fred Flintstone</full-name><company-info><dept>2</dept><id>2</id></company-info></user>
+    <user><name>barney</name><type>admin</type><full-name>Barney Rubble</full-name><company-info><dept>2</dept><id>3</id></company-info></user>
+  </users>
+</top>
+<interfaces xmlns="urn:example:if" xmlns:x="urn:example:ext">
+  <interface ifName="eth0" x:origin="static"><mtu>1500</mtu></interface>
+  <interface ifName="eth1"><mtu>9000</mtu></interface>
+</interfaces>"#;
+
+    fn select(filter: &SubtreeFilter) -> String {
+        let data = XmlNode::parse_fragment(DATA).unwrap();
+        XmlNode::fragment_to_xml(&filter.apply(&data)).unwrap()
+    }
+
+    /// Filter of the `<user>` entries by the `children` nodes.
+    fn users(children: impl IntoIterator<Item = FilterNode>) -> SubtreeFilter {
+        SubtreeFilter::new().with_node(
+            FilterNode::containment(
+                "top",
+                [FilterNode::containment(
+                    "users",
+                    [FilterNode::containment("user", children)],
+                )],
+            )
+            .with_namespace(CONFIG_NS),
+        )
+    }
+
+    #[test]
+    fn selection() {
+        let filter = SubtreeFilter::new().with_node(FilterNode::selection("interfaces"));
+        assert_eq!(
+            select(&filter),
+            r#"<interfaces xmlns="urn:example:if" xmlns:x="urn:example:ext"><interface ifName="eth0" x:origin="static"><mtu>1500</mtu></interface><interface ifName="eth1"><mtu>9000</mtu></interface></interfaces>"#
+        );
+        let filter = SubtreeFilter::new().with_node(
+            FilterNode::containment("top", [FilterNode::selection("users")])
+                .with_namespace(CONFIG_NS),
+        );
+        assert!(select(&filter).starts_with(
+            r#"<top xmlns="http://example.com/schema/1.2/config"><users><user><name>root</name>"#
+        ));
+        assert!(SubtreeFilter::new()
+            .apply(&XmlNode::parse_fragment(DATA).unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn namespace_selection() {
+        let filter = |namespace: &str| {
+            SubtreeFilter::new()
+                .with_node(FilterNode::selection("interfaces").with_namespace(namespace))
+        };
+        assert!(select(&filter("urn:example:if")).starts_with("<interfaces"));
+        assert_eq!(select(&filter(CONFIG_NS)), "");
+        // children inherit namespace of the parent, unless having their own
+        let filter = |namespace: &str| {
+            SubtreeFilter::new().with_node(
+                FilterNode::containment(
+                    "top",
+                    [FilterNode::selection("users").with_namespace(namespace)],
+                )
+                .with_namespace(CONFIG_NS),
+            )
+        };
+        assert!(select(&filter(CONFIG_NS)).contains("<users>"));
+        assert_eq!(select(&filter("urn:example:if")), "");
+        let filter = SubtreeFilter::new().with_node(
+            FilterNode::containment("interfaces", [FilterNode::selection("mtu")])
+                .with_namespace(CONFIG_NS),
+        );
+        assert_eq!(select(&filter), "");
+    }
+
+    #[test]
+    fn content_match() {
+        // only content match nodes - all the siblings selected
+        assert_eq!(
+            select(&users([FilterNode::content_match("name", "fred")])),
+            concat!(
+                r#"<top xmlns="http://example.com/schema/1.2/config"><users><user><name>fred</name><type>admin</type>"#,
+                r#"<full-name>Fred Flintstone</full-name><company-info><dept>2</dept><id>2</id></company-info></user></users></top>"#,
+            )
+        );
+        // all the content matches have to match
+        let admins = |name: &str| {
+            users([
+                FilterNode::content_match("type", "admin"),
+                FilterNode::content_match("name", name),
+            ])
+        };
+        assert!(select(&admins("barney")).contains("<name>barney</name>"));
+        assert_eq!(select(&admins("root")), "");
+        assert_eq!(
+            select(&users([FilterNode::content_match("name", "wilma")])),
+            ""
+        );
+        // leaves are compared by text
+        assert_eq!(
+            select(&users([FilterNode::content_match("name", "Fred")])),
+            ""
+        );
+    }
+
+    #[test]
+    fn containment_selection_content_match() {
+        let filter = users([
+            FilterNode::content_match("type", "admin"),
+            FilterNode::selection("full-name"),
+            FilterNode::containment("company-info", [FilterNode::selection("id")]),
+        ]);
+        assert_eq!(
+            select(&filter),
+            concat!(
+                r#"<top xmlns="http://example.com/schema/1.2/config"><users>"#,
+                r#"<user><type>admin</type><full-name>Fred Flintstone</full-name><company-info><id>2</id></company-info></user>"#,
+                r#"<user><type>admin</type><full-name>Barney Rubble</full-name><company-info><id>3</id></company-info></user>"#,
+                r#"</users></top>"#,
+            )
+        );
+
+        // content match within the nested containment filters its own siblings only
+        let filter = users([
+            FilterNode::selection("name"),
+            FilterNode::containment(
+                "company-info",
+                [
+                    FilterNode::content_match("dept", "1"),
+                    FilterNode::selection("id"),
+                ],
+            ),
+        ]);
+        assert_eq!(
+            select(&filter),
+            concat!(
+                r#"<top xmlns="http://example.com/schema/1.2/config"><users>"#,
+                r#"<user><name>root</name><company-info><dept>1</dept><id>1</id></company-info></user>"#,
+                r#"<user><name>fred</name></user><user><name>barney</name></user></users></top>"#,
+            )
+        );
+
+        // containment of nothing selected is left out
+        let filter = users([FilterNode::containment(
+            "company-info",
+            [FilterNode::selection("phone")],
+        )]);
+        assert_eq!(select(&filter), "");
+    }
+
+    #[test]
+    fn attribute_match() {
+        let interface = |node: FilterNode| {
+            SubtreeFilter::new().with_node(
+                FilterNode::containment(
+                    "interfaces",
+                    [node.with_child(FilterNode::selection("mtu"))],
+                )
+                .with_namespace("urn:example:if"),
+            )
+        };
+        assert_eq!(
+            select(&interface(
+                FilterNode::containment("interface", []).with_attribute("ifName", "eth1")
+            )),
+            r#"<interfaces xmlns="urn:example:if" xmlns:x="urn:example:ext"><interface ifName="eth1"><mtu>9000</mtu></interface></interfaces>"#
+        );
+        let origin = |namespace: &str| {
+            interface(
+                FilterNode::containment("interface", [])
+                    .with_namespaced_attribute(namespace, "origin", "static"),
+            )
+        };
+        assert!(select(&origin("urn:example:ext")).contains(r#"ifName="eth0""#));
+        assert_eq!(select(&origin("urn:example:if")), "");
+        assert_eq!(
+            select(&interface(
+                FilterNode::containment("interface", []).with_attribute("ifName", "eth2")
+            )),
+            ""
+        );
+    }
+
+    #[test]
+    fn payload() {
+        let filter = SubtreeFilter::new()
+            .with_namespace("ns1", CONFIG_NS)
+            .with_node(
+                FilterNode::containment(
+                    "interfaces",
+                    [
+                        FilterNode::containment("interface", [FilterNode::selection("mtu")])
+                            .with_namespaced_attribute("urn:example:ext", "origin", "static"),
+                    ],
+                )
+                .with_namespace("urn:example:if"),
+            )
+            .with_node(FilterNode::selection("top").with_namespace(CONFIG_NS));
+        let payload = filter.to_payload().unwrap();
+        // declared prefixes are used, the generated ones skip them
+        assert_eq!(
+            payload.namespaces,
+            [
+                ("xmlns:ns2".to_string(), "urn:example:if".to_string()),
+                ("xmlns:ns3".to_string(), "urn:example:ext".to_string()),
+                ("xmlns:ns1".to_string(), CONFIG_NS.to_string()),
+            ]
+        );
+        assert_eq!(
+            payload.to_raw_xml().unwrap(),
+            concat!(
+                r#"<filter xmlns:ns2="urn:example:if" xmlns:ns3="urn:example:ext" xmlns:ns1="http://example.com/schema/1.2/config" type="subtree">"#,
+                r#"<ns2:interfaces><ns2:interface ns3:origin="static"><ns2:mtu/></ns2:interface></ns2:interfaces><ns1:top/></filter>"#,
+            )
+        );
+
+        // the filter parsed back selects the same data
+        let parsed = SubtreeFilter::from_payload(&payload).unwrap();
+        assert_eq!(select(&parsed), select(&filter));
+        assert!(select(&filter).contains(r#"</top><interfaces xmlns="urn:example:if" xmlns:x="urn:example:ext"><interface ifName="eth0" x:origin="static"><mtu>1500</mtu></interface></interfaces>"#));
+
+        let parsed = SubtreeFilter::parse(r#"<t:top xmlns:t="http://example.com/schema/1.2/config"><t:users><t:user><t:name>barney</t:name></t:user></t:users></t:top>"#).unwrap();
+        assert_eq!(
+            select(&parsed),
+            select(&users([FilterNode::content_match("name", "barney")]))
+        );
+
+        let xpath = FilterPayload {
+            filter: Filter::Xpath("/top".to_string()),
+            namespaces: vec![],
+        };
+        assert!(SubtreeFilter::from_payload(&xpath).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Elements whose text content never gets into the wire trace (nor the session transcript, unless asked for) - e.g. passwords
+/// of `ietf-system` users, or keys of `ietf-keystore`. Matched by the local name, regardless of the namespace prefix.
+pub const SECRET_ELEMENTS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "shared-secret",
+    "private-key",
+    "cleartext-key",
+    "cleartext-private-key",
+];
+
+/// Replacement of the redacted content.
+const REDACTED: &str = "***";
+
+const CDATA_START: &str = "<![CDATA[";
+const CDATA_END: &str = "]]>";
+
+/// Direction of the traced data, as seen by the client.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// Sink of the raw framed bytes exchanged with server, see [`crate::NetconfSession::set_wire_trace()`].
+///
+/// Each record starts by the header line of direction, size & time since the trace start, e.g. `>>> sent 245 bytes, +0.012s`,
+/// followed by the data (as UTF-8), with content of the [`SECRET_ELEMENTS`] replaced by `***`.
+/// Chunks of the `:base:1.1` framing are joined before the redaction - server may split the chunks anywhere,
+/// even within the secret element - and their sizes are listed by the header instead, e.g. `<<< received 5012 bytes, chunks 4096+900, +0.020s`.
+/// Sizes in the headers are of the original data.
+pub struct WireTrace {
+    writer: Box<dyn Write + Send>,
+    secret_elements: Vec<String>,
+    started: Instant,
+}
+
+impl WireTrace {
+    /// Trace into the `writer`, flushed after each record.
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer,
+            secret_elements: SECRET_ELEMENTS.iter().map(|x| x.to_string()).collect(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Trace appended to the file of given `path`, created if missing. The trace starts by a line of the current UNIX time.
+    pub fn to_file(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open wire trace file {}", path.display()))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        writeln!(
+            file,
+            "=== trace started at {}.{:03}",
+            now.as_secs(),
+            now.subsec_millis()
+        )?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    /// Redact also the content of elements of given (local) `name`, in addition to the [`SECRET_ELEMENTS`].
+    pub fn with_secret_element(mut self, name: &str) -> Self {
+        self.secret_elements.push(name.to_string());
+        self
+    }
+
+    /// Write the record of `bytes` transferred in the `direction`.
+    pub fn record(&mut self, direction: Direction, bytes: &[u8]) -> Result<()> {
+        let elapsed = self.started.elapsed();
+        let header = match direction {
+            Direction::Sent => ">>> sent",
+            Direction::Received => "<<< received",
+        };
+        let (content, chunks) = unframe(bytes);
+        let content = redact_secrets(&String::from_utf8_lossy(&content), &self.secret_elements);
+        let chunks = match chunks.is_empty() {
+            true => String::new(),
+            false => {
+                let sizes: Vec<String> = chunks.iter().map(|x| x.to_string()).collect();
+                format!(", chunks {}", sizes.join("+"))
+            }
+        };
+        writeln!(
+            self.writer,
+            "{} {} bytes{}, +{}.{:03}s",
+            header,
+            bytes.len(),
+            chunks,
+            elapsed.as_secs(),
+            elapsed.subsec_millis()
+        )?;
+        self.writer.write_all(content.as_bytes())?;
+        if !content.ends_with('\n') {
+            writeln!(self.writer)?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Content of the `:base:1.1` chunks of the framed `bytes` joined, along with the chunk sizes.
+/// Data of the end-of-message framing (or not starting by a chunk) are kept as they are, without any chunks.
+fn unframe(bytes: &[u8]) -> (Vec<u8>, Vec<usize>) {
+    let mut content = vec![];
+    let mut chunks = vec![];
+    let mut rest = bytes;
+    while rest.starts_with(b"\n#") {
+        if rest.starts_with(b"\n##\n") {
+            rest = &rest[4..];
+            continue;
+        }
+        let header_end = match rest[2..].iter().position(|x| *x == b'\n') {
+            Some(end) => end + 2,
+            None => break,
+        };
+        let size = match std::str::from_utf8(&rest[2..header_end])
+            .ok()
+            .and_then(|x| x.parse::<usize>().ok())
+        {
+            Some(size) => size,
+            None => break,
+        };
+        let chunk_end = (header_end + 1 + size).min(rest.len());
+        content.extend_from_slice(&rest[header_end + 1..chunk_end]);
+        chunks.push(size);
+        rest = &rest[chunk_end..];
+    }
+    content.extend_from_slice(rest);
+    (content, chunks)
+}
+
+/// Replace text content of the `elements` (by local name) in the XML `text`, which does not need to be well-formed -
+/// e.g. part of the message. CDATA sections are taken as text content.
+pub(crate) fn redact_secrets(text: &str, elements: &[String]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let (before, tag) = rest.split_at(start);
+        result.push_str(before);
+        rest = tag;
+        let cdata = tag.starts_with(CDATA_START);
+        let end = match cdata {
+            true => tag.find(CDATA_END).map(|x| x + CDATA_END.len()),
+            false => tag.find('>').map(|x| x + 1),
+        };
+        let end = match end {
+            Some(end) => end,
+            None => break,
+        };
+        let (tag, after) = tag.split_at(end);
+        result.push_str(tag);
+        rest = after;
+        if cdata {
+            continue;
+        }
+
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or_default();
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+        let opening = !tag.starts_with("</") && !tag.ends_with("/>");
+        if opening && elements.iter().any(|x| x == local_name) {
+            let content_end = content_end(rest);
+            if content_end > 0 {
+                result.push_str(REDACTED);
+            }
+            rest = &rest[content_end..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// End of the element content at the start of `text` - the next tag, skipping over CDATA sections.
+fn content_end(text: &str) -> usize {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find('<') {
+        let start = offset + start;
+        if !text[start..].starts_with(CDATA_START) {
+            return start;
+        }
+        match text[start..].find(CDATA_END) {
+            Some(end) => offset = start + end + CDATA_END.len(),
+            None => break,
+        }
+    }
+    text.len()
+}
@@ -0,0 +1,221 @@
+use anyhow::{bail, Result};
+
+use crate::{
+    messages::{
+        commit::{CommitRequest, CommitType, ConfirmedCommitParams},
+        discard_changes::DiscardChangesRequest,
+        edit_config::{
+            DefaultOperation, EditConfigContent, EditConfigParams, EditConfigRequest, ErrorOption,
+            TestOption,
+        },
+        lock::LockRequest,
+        unlock::UnlockRequest,
+        validate::{ValidateRequest, ValidateSource},
+        NetconfRequest,
+    },
+    netconf_session::check_reply,
+    types::{Capability, Datastore, SimpleResponse},
+    NetconfSession,
+};
+
+/// Configuration changes applied as a whole - locked, edited, validated & committed, see [`NetconfSession::transaction()`].
+///
+/// The target is chosen by capabilities of server:
+/// - `:candidate` - both "running" & "candidate" datastores are locked (as recommended by [RFC 6241 - section 8.3.5.2](https://datatracker.ietf.org/doc/html/rfc6241#section-8.3.5.2)),
+///   the edits of "candidate" are validated (`:validate` capability) & committed
+/// - `:writable-running` - "running" datastore is locked & edited directly, the edit is tested first (`:validate` capability)
+///   and rolled back on error (`:rollback-on-error` capability). There is nothing to discard the applied edits of,
+///   so the transaction is limited to a single [`Self::edit()`] - combine the changes into one content.
+///
+/// Any failure discards changes of the locked "candidate" and releases the locks, and so does dropping the unfinished transaction,
+/// incl. unwinding on panic.
+pub struct Transaction<'a> {
+    session: &'a mut NetconfSession,
+    target: Datastore,
+    locked: Vec<Datastore>,
+    validate: bool,
+    rollback_on_error: bool,
+    edited: bool,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn begin(session: &'a mut NetconfSession) -> Result<Self> {
+        let capabilities = match session.server_capabilities() {
+            Some(caps) => caps,
+            None => bail!("No server capabilities! Session not initiated yet?"),
+        };
+        let target = if capabilities.contains(&Capability::Candidate) {
+            Datastore::Candidate
+        } else if capabilities.contains(&Capability::WritableRunning) {
+            Datastore::Running
+        } else {
+            bail!("Server supports neither :candidate nor :writable-running capability!");
+        };
+        let validate = capabilities.contains(&Capability::Validate11)
+            || capabilities.contains(&Capability::Validate);
+        let rollback_on_error = capabilities.contains(&Capability::RollbackOnError);
+
+        let mut transaction = Self {
+            session,
+            target: target.clone(),
+            locked: vec![],
+            validate,
+            rollback_on_error,
+            edited: false,
+            finished: false,
+        };
+        let mut datastores = vec![Datastore::Running];
+        if target == Datastore::Candidate {
+            datastores.push(Datastore::Candidate);
+        }
+        for datastore in datastores {
+            let request = LockRequest::new(transaction.session.new_message_id(), datastore.clone());
+            transaction.run("lock", request)?;
+            transaction.locked.push(datastore);
+        }
+        Ok(transaction)
+    }
+
+    /// Datastore being edited - "candidate", or "running" for servers without the `:candidate` capability.
+    pub fn target(&self) -> &Datastore {
+        &self.target
+    }
+
+    /// Apply the `<edit-config>` of the `content` onto the target datastore.
+    ///
+    /// Fails without rollback for the second edit of "running" datastore - the first one could not be undone
+    /// in case the later one fails.
+    pub fn edit(
+        &mut self,
+        content: EditConfigContent,
+        default_operation: Option<DefaultOperation>,
+    ) -> Result<()> {
+        let running = self.target == Datastore::Running;
+        if running && self.edited {
+            bail!("Transaction of the \"running\" datastore allows single <edit-config> only, server lacks :candidate capability!");
+        }
+        self.edited = true;
+        let params = EditConfigParams {
+            target: self.target.clone(),
+            default_operation,
+            test_option: (running && self.validate).then_some(TestOption::TestThenSet),
+            error_option: (running && self.rollback_on_error)
+                .then_some(ErrorOption::RollbackOnError),
+            config: content,
+        };
+        let request = EditConfigRequest::new(self.session.new_message_id(), params);
+        self.run("edit-config", request)
+    }
+
+    /// Validate (if supported) & commit the edits of "candidate", and release the locks.
+    pub fn commit(self) -> Result<()> {
+        self.finish(CommitType::Plain)
+    }
+
+    /// Same as [`Self::commit()`], but with the confirmed commit - rolled back by server unless confirmed
+    /// by subsequent `<commit>` within the `confirm_timeout` seconds, see [RFC 6241 - section 8.4](https://datatracker.ietf.org/doc/html/rfc6241#section-8.4).
+    pub fn commit_confirmed(self, confirm_timeout: u32) -> Result<()> {
+        if !self.session_supports_confirmed_commit() {
+            let mut transaction = self;
+            transaction.rollback();
+            bail!("Server does not support the :confirmed-commit capability!");
+        }
+        let params = ConfirmedCommitParams {
+            confirm_timeout: Some(confirm_timeout),
+            persist: None,
+            persist_id: None,
+        };
+        self.finish(CommitType::Confirmed(params))
+    }
+
+    /// Discard the edits & release the locks.
+    pub fn abort(mut self) -> Result<()> {
+        self.finished = true;
+        if self.locked.contains(&Datastore::Candidate) {
+            let request = DiscardChangesRequest::new(self.session.new_message_id());
+            let response = self.session.dispatch_request(request);
+            if let Err(err) = response.and_then(|x| check_reply("discard-changes", &x.typed.reply))
+            {
+                let _ = self.unlock_all();
+                return Err(err);
+            }
+        }
+        self.unlock_all()
+    }
+
+    fn finish(mut self, commit_type: CommitType) -> Result<()> {
+        if self.target == Datastore::Candidate {
+            if self.validate {
+                let request = ValidateRequest::new(
+                    self.session.new_message_id(),
+                    ValidateSource::Datastore(Datastore::Candidate),
+                );
+                self.run("validate", request)?;
+            }
+            let request = CommitRequest::new(self.session.new_message_id(), commit_type);
+            self.run("commit", request)?;
+        }
+        self.finished = true;
+        self.unlock_all()
+    }
+
+    fn session_supports_confirmed_commit(&self) -> bool {
+        self.target == Datastore::Candidate
+            && self.session.server_capabilities().is_some_and(|caps| {
+                caps.contains(&Capability::ConfirmedCommit)
+                    || caps.contains(&Capability::ConfirmedCommit10)
+            })
+    }
+
+    /// Dispatch the request of the transaction step, rolling back the transaction on failure.
+    fn run<R>(&mut self, operation: &str, request: R) -> Result<()>
+    where
+        R: NetconfRequest<Response = SimpleResponse>,
+    {
+        if self.finished {
+            bail!("Transaction already finished!");
+        }
+        let result = self
+            .session
+            .dispatch_request(request)
+            .and_then(|response| check_reply(operation, &response.typed.reply));
+        if result.is_err() {
+            self.rollback();
+        }
+        result
+    }
+
+    /// Best-effort discard of the changes & unlock, ignoring any errors.
+    /// Changes of "candidate" are discarded only when locked by this transaction - not to discard changes of others.
+    fn rollback(&mut self) {
+        self.finished = true;
+        if self.locked.contains(&Datastore::Candidate) {
+            let request = DiscardChangesRequest::new(self.session.new_message_id());
+            let _ = self.session.dispatch_request(request);
+        }
+        let _ = self.unlock_all();
+    }
+
+    /// Release the locks in reverse order, reporting the first failure.
+    fn unlock_all(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        while let Some(datastore) = self.locked.pop() {
+            let request = UnlockRequest::new(self.session.new_message_id(), datastore);
+            let response = self.session.dispatch_request(request);
+            let unlocked = response.and_then(|x| check_reply("unlock", &x.typed.reply));
+            if result.is_ok() {
+                result = unlocked;
+            }
+        }
+        result
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.rollback();
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::{self, Read, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    framing::{encode_message, BaseCapability},
+    trace::{redact_secrets, Direction, SECRET_ELEMENTS},
+    transport::Transport,
+};
+
+/// Single message of the [`Transcript`], as sent or received by the client - without any framing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    /// Time of the message, in milliseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub direction: Direction,
+    pub message: String,
+}
+
+impl TranscriptMessage {
+    /// Message of the current time.
+    pub fn now(direction: Direction, message: &str) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            .try_into()
+            .unwrap_or(u64::MAX);
+        Self {
+            timestamp,
+            direction,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Full transcript of the session - all the messages in order, starting by the `<hello>` exchange.
+///
+/// Stored in the JSON Lines format - a JSON object of [`TranscriptMessage`] per line, e.g.
+/// `{"timestamp":1651399200000,"direction":"sent","message":"<hello ...>"}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub messages: Vec<TranscriptMessage>,
+}
+
+impl Transcript {
+    /// Transcript of the JSON Lines `content`, empty lines are skipped.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut messages = vec![];
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message = serde_json::from_str(line)
+                .with_context(|| format!("Invalid transcript message on line {}", index + 1))?;
+            messages.push(message);
+        }
+        Ok(Self { messages })
+    }
+
+    /// Transcript of the file written by [`TranscriptRecorder`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read transcript file {}", path.display()))?;
+        Self::parse(&content)
+    }
+
+    /// Content of the transcript in the JSON Lines format.
+    pub fn to_json_lines(&self) -> Result<String> {
+        let mut content = String::new();
+        for message in self.messages.iter() {
+            content.push_str(&serde_json::to_string(message)?);
+            content.push('\n');
+        }
+        Ok(content)
+    }
+}
+
+/// Recorder of the session transcript, see [`crate::NetconfSession::set_recorder()`].
+///
+/// Each message is written as a line of JSON right away, so the transcript survives even the crash of the client.
+/// Content of the [`SECRET_ELEMENTS`] is replaced by `***` same as in the wire trace, unless recorded by [`Self::unredacted()`].
+pub struct TranscriptRecorder {
+    writer: Box<dyn Write + Send>,
+    secret_elements: Vec<String>,
+}
+
+impl TranscriptRecorder {
+    /// Record into the `writer`, flushed after each message.
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer,
+            secret_elements: SECRET_ELEMENTS.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    /// Record into the file of given `path`, replacing any previous content.
+    pub fn to_file(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("Failed to open transcript file {}", path.display()))?;
+        Ok(Self::new(Box::new(file)))
+    }
+
+    /// Redact also the content of elements of given (local) `name`, in addition to the [`SECRET_ELEMENTS`].
+    pub fn with_secret_element(mut self, name: &str) -> Self {
+        self.secret_elements.push(name.to_string());
+        self
+    }
+
+    /// Record the messages exactly as exchanged, without any redaction - e.g. for offline regression fixtures
+    /// of a lab device. Beware of the secrets leaking into the transcript.
+    pub fn unredacted(mut self) -> Self {
+        self.secret_elements.clear();
+        self
+    }
+
+    pub fn record(&mut self, message: &TranscriptMessage) -> Result<()> {
+        let redacted;
+        let message = match self.secret_elements.is_empty() {
+            true => message,
+            false => {
+                redacted = TranscriptMessage {
+                    message: redact_secrets(&message.message, &self.secret_elements),
+                    ..message.clone()
+                };
+                &redacted
+            }
+        };
+        serde_json::to_writer(&mut self.writer, message)?;
+        writeln!(self.writer)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Transport feeding the received messages of the [`Transcript`] back into [`crate::NetconfSession`], e.g. to reproduce
+/// parsing problems offline, or as a fixture of regression tests.
+///
+/// Each read of the client gets the next received message of the transcript, regardless of the requests actually written,
+/// which are discarded. The messages are framed as the last request of client - chunked if it was, end-of-message delimited otherwise.
+/// End of the transcript closes the stream.
+pub struct ReplayTransport {
+    received: VecDeque<String>,
+    written: Vec<u8>,
+    pending: VecDeque<u8>,
+}
+
+impl ReplayTransport {
+    pub fn new(transcript: Transcript) -> Self {
+        let received = transcript
+            .messages
+            .into_iter()
+            .filter(|x| x.direction == Direction::Received)
+            .map(|x| x.message)
+            .collect();
+        Self {
+            received,
+            written: vec![],
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn target_string(&self) -> String {
+        "replay".to_string()
+    }
+}
+
+impl Write for ReplayTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for ReplayTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let message = match self.received.pop_front() {
+                Some(message) => message,
+                None => return Ok(0),
+            };
+            let base = match self.written.starts_with(b"\n#") {
+                true => BaseCapability::Base11,
+                false => BaseCapability::Base,
+            };
+            self.written.clear();
+            self.pending.extend(encode_message(&message, base));
+        }
+        self.pending.read(buf)
+    }
+}
@@ -0,0 +1,21 @@
+use std::io::{Read, Write};
+
+use anyhow::Result;
+
+mod tcp;
+pub use tcp::TcpTransport;
+
+/// Connection-oriented byte stream between client & NETCONF server.
+///
+/// Messages are framed by [`crate::NetconfSession`] itself, transport only moves raw bytes.
+/// Read of zero bytes signals the stream was closed by the other side.
+pub trait Transport: Read + Write + Send {
+    /// Open the underlying connection. Invoked once prior to any data being exchanged.
+    fn connect(&mut self) -> Result<()>;
+
+    /// Close the underlying connection.
+    fn disconnect(&mut self) -> Result<()>;
+
+    /// String representing the target server, e.g. "host:port".
+    fn target_string(&self) -> String;
+}
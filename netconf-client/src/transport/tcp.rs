@@ -0,0 +1,81 @@
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+
+use crate::{Transport, SSH_TIMEOUT};
+
+/// Client side [`Transport`] exchanging NETCONF messages directly over TCP, without SSH layer.
+///
+/// Not a standard NETCONF transport, intended only for testing - e.g. connecting to `MockServer::listen_tcp()`
+/// of the `netconf-mock-server` crate.
+pub struct TcpTransport {
+    address: SocketAddr,
+    stream: Option<TcpStream>,
+    timeout: Option<Duration>,
+}
+
+impl TcpTransport {
+    /// Creates new instance of TCP transport, without initiating any network connection yet.
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            stream: None,
+            timeout: Some(SSH_TIMEOUT),
+        }
+    }
+
+    /// Set the read timeout, [`SSH_TIMEOUT`] by default, `None` blocks indefinitely. Applied on [`Transport::connect()`].
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn stream(&mut self) -> io::Result<&mut TcpStream> {
+        self.stream
+            .as_mut()
+            .ok_or_else(|| io::ErrorKind::NotConnected.into())
+    }
+}
+
+impl Transport for TcpTransport {
+    fn connect(&mut self) -> Result<()> {
+        if self.stream.is_some() {
+            bail!("Already connected to {}", self.address);
+        }
+        let stream = TcpStream::connect(self.address)?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_nodelay(true)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        if let Some(stream) = self.stream.take() {
+            stream.shutdown(Shutdown::Both)?;
+        }
+        Ok(())
+    }
+
+    fn target_string(&self) -> String {
+        self.address.to_string()
+    }
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream()?.read(buf)
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream()?.flush()
+    }
+}
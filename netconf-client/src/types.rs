@@ -2,19 +2,33 @@ mod capability;
 mod config_waypoint;
 mod datastore;
 mod filter;
+mod rpc_attribute;
 mod rpc_error;
 mod rpc_reply;
+mod server_capabilities;
 mod simple_response;
+mod xml_node;
+mod yang_library;
 
-pub use capability::Capability;
+pub use capability::{
+    AdvertisedUrn, Capability, UrlCapability, WithDefaultsCapability, WithDefaultsMode,
+    YangLibraryCapability, YangModuleCapability,
+};
 pub use config_waypoint::ConfigWaypoint;
 pub(crate) use config_waypoint::ConfigWaypointRpc;
 pub use datastore::Datastore;
 pub use filter::{Filter, FilterPayload};
+pub use rpc_attribute::RpcAttribute;
 pub(crate) use rpc_error::RpcErrorRpc;
 pub use rpc_error::{ErrorSeverity, ErrorType, RpcError};
 pub use rpc_reply::RpcReply;
+pub use server_capabilities::ServerCapabilities;
 pub use simple_response::SimpleResponse;
+pub use xml_node::{XmlAttribute, XmlNode};
+pub use yang_library::{
+    Conformance, DatastoreSchema, Module, ModuleSet, Schema, Submodule, YangLibrary,
+    LEGACY_MODULE_SET, YANG_LIBRARY_XMLNS,
+};
 
 pub(crate) mod tag_wrapper;
 
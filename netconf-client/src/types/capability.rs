@@ -1,4 +1,7 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 use anyhow::bail;
 
@@ -13,8 +16,12 @@ pub enum Capability {
     WritableRunning,
     /// `:candidate` capability - [RFC 6241 - section 8.3](https://datatracker.ietf.org/doc/html/rfc6241#section-8.3)
     Candidate,
-    /// `:confirmed-commit` capability - [RFC 6241 - section 8.4](https://datatracker.ietf.org/doc/html/rfc6241#section-8.4)
+    /// `:confirmed-commit:1.1` capability - [RFC 6241 - section 8.4](https://datatracker.ietf.org/doc/html/rfc6241#section-8.4)
     ConfirmedCommit,
+    /// `:confirmed-commit:1.0` capability of the obsoleted NETCONF 1.0 - [RFC 4741 - section 8.4](https://datatracker.ietf.org/doc/html/rfc4741#section-8.4)
+    ///
+    /// Does not support the `<persist>`, `<persist-id>` parameters & `<cancel-commit>` operation.
+    ConfirmedCommit10,
     /// `:rollback-on-error` capability - [RFC 6241 - section 8.5](https://datatracker.ietf.org/doc/html/rfc6241#section-8.5)
     RollbackOnError,
     /// `:validate` capability - [RFC 6241 - section 8.6](https://datatracker.ietf.org/doc/html/rfc6241#section-8.6)
@@ -22,22 +29,404 @@ pub enum Capability {
     /// `:startup` capability - [RFC 6241 - section 8.7](https://datatracker.ietf.org/doc/html/rfc6241#section-8.7)
     Startup,
     /// `:url` capability - [RFC 6241 - section 8.8](https://datatracker.ietf.org/doc/html/rfc6241#section-8.8)
-    Url(Vec<String>),
+    Url(UrlCapability),
     /// `:xpath` capability - [RFC 6241 - section 8.9](https://datatracker.ietf.org/doc/html/rfc6241#section-8.9)
     XPath,
     /// `:validate-1.1` capability for extended <validate> functionality
     Validate11,
+    /// `:notification` capability for event notifications - [RFC 5277 - section 3.1](https://datatracker.ietf.org/doc/html/rfc5277#section-3.1)
+    Notification,
+    /// `:interleave` capability allowing RPCs during notification subscription - [RFC 5277 - section 6](https://datatracker.ietf.org/doc/html/rfc5277#section-6)
+    Interleave,
+    /// `:partial-lock` capability - [RFC 5717 - section 2](https://datatracker.ietf.org/doc/html/rfc5717#section-2)
+    PartialLock,
+    /// `:with-defaults` capability - [RFC 6243 - section 4](https://datatracker.ietf.org/doc/html/rfc6243#section-4)
+    WithDefaults(WithDefaultsCapability),
+    /// `:with-operational-defaults` capability for NMDA `<get-data>` - [RFC 8526 - section 3.1.1.2](https://datatracker.ietf.org/doc/html/rfc8526#section-3.1.1.2)
+    WithOperationalDefaults(WithDefaultsCapability),
+    /// `:time` capability for scheduled operations - [RFC 7758 - section 3](https://datatracker.ietf.org/doc/html/rfc7758#section-3)
+    Time,
+    /// YANG 1.0 module advertised in `<hello>` message - [RFC 6020 - section 5.6.4](https://datatracker.ietf.org/doc/html/rfc6020#section-5.6.4)
+    YangModule(YangModuleCapability),
+    /// `:yang-library` capability advertising the YANG library in use by server -
+    /// [RFC 7950 - section 5.6.4](https://datatracker.ietf.org/doc/html/rfc7950#section-5.6.4),
+    /// [RFC 8526 - section 2](https://datatracker.ietf.org/doc/html/rfc8526#section-2)
+    YangLibrary(YangLibraryCapability),
     /// Fallback non-standardized or un-implemented capability with full URN as argument.
+    ///
+    /// Also standard capabilities without any parameters, advertised with a query.
     Other(String),
 }
 
+/// URN of the capability as advertised by server, rendered back by `get_urn()` as long as the parsed
+/// parameters are not changed - keeping the order of parameters, the repeated & unknown ones.
+///
+/// It is not considered by the equality of capabilities.
+#[derive(Debug, Clone, Default)]
+pub struct AdvertisedUrn(Option<String>);
+
+impl AdvertisedUrn {
+    fn new(urn: &str) -> Self {
+        Self(Some(urn.to_string()))
+    }
+
+    /// The advertised URN, if `parse` of it equals the `current` parameters, or `canonical` URN of them.
+    fn get_urn<T, P, C>(&self, current: &T, parse: P, canonical: C) -> String
+    where
+        T: PartialEq,
+        P: FnOnce(&str) -> Option<T>,
+        C: FnOnce() -> String,
+    {
+        match &self.0 {
+            Some(urn) if parse(urn).as_ref() == Some(current) => urn.clone(),
+            _ => canonical(),
+        }
+    }
+}
+
+impl PartialEq for AdvertisedUrn {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for AdvertisedUrn {}
+
+impl Hash for AdvertisedUrn {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+/// Parameters of the `:url` capability - the supported URL schemes,
+/// listed in a single `scheme` parameter, or in each one of the repeated parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UrlCapability {
+    /// Supported URL schemes, e.g. "http", "ftp", "file".
+    pub schemes: Vec<String>,
+    /// The URN as advertised by server.
+    pub advertised: AdvertisedUrn,
+}
+
+/// Parameters of a YANG 1.0 module capability URI, in the form of
+/// `<namespace>?module=<name>[&revision=<date>][&features=<f1>,...][&deviations=<d1>,...]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YangModuleCapability {
+    /// XML namespace of the module.
+    pub namespace: String,
+    /// Name of the module.
+    pub module: String,
+    /// Revision date of the module, if advertised.
+    pub revision: Option<String>,
+    /// Supported features of the module.
+    pub features: Vec<String>,
+    /// Modules containing deviations to this module.
+    pub deviations: Vec<String>,
+    /// The URN as advertised by server, e.g. with the parameters in other order, or unknown ones.
+    pub advertised: AdvertisedUrn,
+}
+
+/// Parameters of the `:yang-library` capability.
+///
+/// Version `1.0` is identified by the `module-set-id` parameter, version `1.1` by the `content-id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YangLibraryCapability {
+    /// Version of the capability - "1.0" or "1.1".
+    pub version: String,
+    /// Revision of the `ietf-yang-library` module in use.
+    pub revision: Option<String>,
+    /// Identifier of the current module set, for version "1.0".
+    pub module_set_id: Option<String>,
+    /// Identifier of the current YANG library content, for version "1.1".
+    pub content_id: Option<String>,
+    /// The URN as advertised by server.
+    pub advertised: AdvertisedUrn,
+}
+
+/// Retrieval mode of default values, as defined in [RFC 6243 - section 3](https://datatracker.ietf.org/doc/html/rfc6243#section-3).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WithDefaultsMode {
+    /// All data nodes are reported, including any default values.
+    ReportAll,
+    /// Like [`Self::ReportAll`], with default values tagged by `default` attribute.
+    ReportAllTagged,
+    /// Data nodes set to their schema default value are not reported.
+    Trim,
+    /// Data nodes explicitly set by client are reported.
+    Explicit,
+}
+
+impl WithDefaultsMode {
+    /// Provides slice of all the modes in their textual representation.
+    pub fn values() -> Vec<&'static str> {
+        vec!["report-all", "report-all-tagged", "trim", "explicit"]
+    }
+}
+
+impl std::str::FromStr for WithDefaultsMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "report-all" => Ok(Self::ReportAll),
+            "report-all-tagged" => Ok(Self::ReportAllTagged),
+            "trim" => Ok(Self::Trim),
+            "explicit" => Ok(Self::Explicit),
+            s => bail!("Unsupported with-defaults mode: \"{}\"", s),
+        }
+    }
+}
+
+impl Display for WithDefaultsMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            WithDefaultsMode::ReportAll => "report-all",
+            WithDefaultsMode::ReportAllTagged => "report-all-tagged",
+            WithDefaultsMode::Trim => "trim",
+            WithDefaultsMode::Explicit => "explicit",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Parameters of the `:with-defaults` & `:with-operational-defaults` capabilities.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WithDefaultsCapability {
+    /// Mode used by server when no explicit `<with-defaults>` parameter is present in request.
+    pub basic_mode: WithDefaultsMode,
+    /// Additional modes supported in the `<with-defaults>` parameter of request.
+    pub also_supported: Vec<WithDefaultsMode>,
+    /// The URN as advertised by server.
+    pub advertised: AdvertisedUrn,
+}
+
+impl WithDefaultsCapability {
+    fn from_params(params: &[(&str, &str)], urn: &str) -> anyhow::Result<Self> {
+        let mut basic_mode = None;
+        let mut also_supported = vec![];
+        for (name, value) in params {
+            match *name {
+                "basic-mode" => basic_mode = Some(value.parse()?),
+                "also-supported" => {
+                    for mode in split_list(value) {
+                        also_supported.push(mode.parse()?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        match basic_mode {
+            Some(basic_mode) => Ok(Self {
+                basic_mode,
+                also_supported,
+                advertised: AdvertisedUrn::new(urn),
+            }),
+            None => bail!("Missing mandatory \"basic-mode\" parameter of capability: \"{urn}\""),
+        }
+    }
+
+    fn get_urn(&self, base: &str) -> String {
+        let parse = |urn: &str| Self::from_params(&split_query(urn).1, urn).ok();
+        self.advertised.get_urn(self, parse, || {
+            let mut urn = format!("{}?basic-mode={}", base, self.basic_mode);
+            if !self.also_supported.is_empty() {
+                let modes: Vec<String> =
+                    self.also_supported.iter().map(|x| x.to_string()).collect();
+                urn.push_str(&format!("&also-supported={}", modes.join(",")));
+            }
+            urn
+        })
+    }
+}
+
+const URL_CAPABILITY: &str = "urn:ietf:params:netconf:capability:url:1.0";
+
+impl UrlCapability {
+    /// Capability of the given URL `schemes`.
+    pub fn new(schemes: Vec<String>) -> Self {
+        Self {
+            schemes,
+            advertised: AdvertisedUrn::default(),
+        }
+    }
+
+    fn from_params(params: &[(&str, &str)], urn: &str) -> anyhow::Result<Self> {
+        // Format for URL schemes - https://datatracker.ietf.org/doc/html/rfc6241#section-8.8.3
+        // Some servers list each scheme in a standalone `scheme=` parameter.
+        let schemes: Vec<String> = params
+            .iter()
+            .filter(|(name, _)| *name == "scheme")
+            .flat_map(|(_, value)| split_list(value))
+            .collect();
+        if schemes.is_empty() {
+            bail!("Unsupported URL capability string - unable to parse schemes: \"{urn}\"");
+        }
+        Ok(Self {
+            schemes,
+            advertised: AdvertisedUrn::new(urn),
+        })
+    }
+
+    fn get_urn(&self) -> String {
+        let parse = |urn: &str| Self::from_params(&split_query(urn).1, urn).ok();
+        self.advertised.get_urn(self, parse, || {
+            format!("{}?scheme={}", URL_CAPABILITY, self.schemes.join(","))
+        })
+    }
+}
+
+const YANG_LIBRARY_PREFIX: &str = "urn:ietf:params:netconf:capability:yang-library:";
+
+/// Split the URN into the part before `?`, and list of its `name=value` query parameters.
+fn split_query(urn: &str) -> (&str, Vec<(&str, &str)>) {
+    match urn.split_once('?') {
+        Some((base, query)) => {
+            let params = query
+                .split('&')
+                .filter(|param| !param.is_empty())
+                .map(|param| param.split_once('=').unwrap_or((param, "")))
+                .collect();
+            (base, params)
+        }
+        None => (urn, vec![]),
+    }
+}
+
+/// Split comma separated list of values, ignoring empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+impl YangModuleCapability {
+    /// Parse the module capability URI. Returns `None` if there is no `module` query parameter present.
+    pub fn from_urn(urn: &str) -> Option<Self> {
+        let (namespace, params) = split_query(urn);
+        let mut instance = Self {
+            namespace: namespace.to_string(),
+            module: String::new(),
+            revision: None,
+            features: vec![],
+            deviations: vec![],
+            advertised: AdvertisedUrn::new(urn),
+        };
+        for (name, value) in params {
+            match name {
+                "module" => instance.module = value.to_string(),
+                "revision" => instance.revision = Some(value.to_string()),
+                "features" => instance.features = split_list(value),
+                "deviations" => instance.deviations = split_list(value),
+                _ => {}
+            }
+        }
+        match instance.module.is_empty() {
+            true => None,
+            false => Some(instance),
+        }
+    }
+
+    /// Get the capability URI of the module - as advertised by server, unless changed.
+    pub fn get_urn(&self) -> String {
+        self.advertised
+            .get_urn(self, Self::from_urn, || self.canonical_urn())
+    }
+
+    fn canonical_urn(&self) -> String {
+        let mut urn = format!("{}?module={}", self.namespace, self.module);
+        if let Some(revision) = &self.revision {
+            urn.push_str(&format!("&revision={}", revision));
+        }
+        if !self.features.is_empty() {
+            urn.push_str(&format!("&features={}", self.features.join(",")));
+        }
+        if !self.deviations.is_empty() {
+            urn.push_str(&format!("&deviations={}", self.deviations.join(",")));
+        }
+        urn
+    }
+}
+
+impl Display for YangModuleCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.module)?;
+        if let Some(revision) = &self.revision {
+            write!(f, "@{}", revision)?;
+        }
+        if !self.features.is_empty() {
+            write!(f, " features: [{}]", self.features.join(", "))?;
+        }
+        if !self.deviations.is_empty() {
+            write!(f, " deviations: [{}]", self.deviations.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl YangLibraryCapability {
+    /// Parse the `:yang-library` capability URN. Returns `None` for any other URN.
+    pub fn from_urn(urn: &str) -> Option<Self> {
+        let (base, params) = split_query(urn);
+        let version = base.strip_prefix(YANG_LIBRARY_PREFIX)?;
+        let mut instance = Self {
+            version: version.to_string(),
+            revision: None,
+            module_set_id: None,
+            content_id: None,
+            advertised: AdvertisedUrn::new(urn),
+        };
+        for (name, value) in params {
+            match name {
+                "revision" => instance.revision = Some(value.to_string()),
+                "module-set-id" => instance.module_set_id = Some(value.to_string()),
+                "content-id" => instance.content_id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(instance)
+    }
+
+    /// Get the URN of the capability - as advertised by server, unless changed.
+    pub fn get_urn(&self) -> String {
+        self.advertised
+            .get_urn(self, Self::from_urn, || self.canonical_urn())
+    }
+
+    fn canonical_urn(&self) -> String {
+        let mut params = vec![];
+        if let Some(revision) = &self.revision {
+            params.push(format!("revision={}", revision));
+        }
+        if let Some(module_set_id) = &self.module_set_id {
+            params.push(format!("module-set-id={}", module_set_id));
+        }
+        if let Some(content_id) = &self.content_id {
+            params.push(format!("content-id={}", content_id));
+        }
+        let mut urn = format!("{}{}", YANG_LIBRARY_PREFIX, self.version);
+        if !params.is_empty() {
+            urn.push('?');
+            urn.push_str(&params.join("&"));
+        }
+        urn
+    }
+}
+
+const URN_PREFIX: &str = "urn:ietf:params:netconf";
+
 impl Capability {
     /// Get the URN of the capability defined by RFC.
+    ///
+    /// Capabilities parsed by [`Self::from_urn()`] keep the URN exactly as advertised by server, unless their parameters are changed.
+    /// Otherwise the URN is in the canonical form. Either way, [`Self::from_urn()`] of the result returns the equal capability instance.
     pub fn get_urn(&self) -> String {
         match self {
             Capability::Base => "urn:ietf:params:netconf:base:1.0".to_string(),
             Capability::Base11 => "urn:ietf:params:netconf:base:1.1".to_string(),
             Capability::Candidate => "urn:ietf:params:netconf:capability:candidate:1.0".to_string(),
+            Capability::ConfirmedCommit10 => {
+                "urn:ietf:params:netconf:capability:confirmed-commit:1.0".to_string()
+            }
             Capability::ConfirmedCommit => {
                 "urn:ietf:params:netconf:capability:confirmed-commit:1.1".to_string()
             }
@@ -51,21 +440,46 @@ impl Capability {
                 "urn:ietf:params:netconf:capability:writable-running:1.0".to_string()
             }
             Capability::XPath => "urn:ietf:params:netconf:capability:xpath:1.0".to_string(),
-            Capability::Url(schemes) => format!(
-                "urn:ietf:params:netconf:capability:url:1.0?scheme={{{}}}",
-                schemes.join(",")
-            ),
+            Capability::Url(url) => url.get_urn(),
+            Capability::Notification => {
+                "urn:ietf:params:netconf:capability:notification:1.0".to_string()
+            }
+            Capability::Interleave => {
+                "urn:ietf:params:netconf:capability:interleave:1.0".to_string()
+            }
+            Capability::PartialLock => {
+                "urn:ietf:params:netconf:capability:partial-lock:1.0".to_string()
+            }
+            Capability::WithDefaults(params) => {
+                params.get_urn("urn:ietf:params:netconf:capability:with-defaults:1.0")
+            }
+            Capability::WithOperationalDefaults(params) => {
+                params.get_urn("urn:ietf:params:netconf:capability:with-operational-defaults:1.0")
+            }
+            Capability::Time => "urn:ietf:params:netconf:capability:time:1.0".to_string(),
+            Capability::YangModule(module) => module.get_urn(),
+            Capability::YangLibrary(library) => library.get_urn(),
             Capability::Other(s) => s.clone(),
         }
     }
-    // urn:ietf:params:netconf:capability:url:1.0?scheme={name,...}
 
     /// Construct Capability instance from URN text string.
+    ///
+    /// Returns error for malformed standard capabilities, e.g. `:url` capability without any schemes.
+    /// Standard capabilities without any parameters are [`Self::Other`] when advertised with a query.
     pub fn from_urn(urn: &str) -> anyhow::Result<Self> {
-        let instance: Capability = match urn {
+        let (base, params) = split_query(urn);
+        let instance: Capability = match base {
+            "urn:ietf:params:netconf:capability:url:1.0"
+            | "urn:ietf:params:netconf:capability:with-defaults:1.0"
+            | "urn:ietf:params:netconf:capability:with-operational-defaults:1.0" => {
+                Self::from_params(base, &params, urn)?
+            }
+            _ if urn.contains('?') => Self::from_query_urn(urn),
             "urn:ietf:params:netconf:base:1.0" => Self::Base,
             "urn:ietf:params:netconf:base:1.1" => Self::Base11,
             "urn:ietf:params:netconf:capability:candidate:1.0" => Self::Candidate,
+            "urn:ietf:params:netconf:capability:confirmed-commit:1.0" => Self::ConfirmedCommit10,
             "urn:ietf:params:netconf:capability:confirmed-commit:1.1" => Self::ConfirmedCommit,
             "urn:ietf:params:netconf:capability:rollback-on-error:1.0" => Self::RollbackOnError,
             "urn:ietf:params:netconf:capability:startup:1.0" => Self::Startup,
@@ -73,41 +487,112 @@ impl Capability {
             "urn:ietf:params:netconf:capability:xpath:1.0" => Self::XPath,
             "urn:ietf:params:netconf:capability:validate:1.0" => Self::Validate,
             "urn:ietf:params:netconf:capability:validate:1.1" => Self::Validate11,
-            s => {
-                // Format for URL schemes - https://datatracker.ietf.org/doc/html/rfc6241#section-8.8.3
-                if s.starts_with("urn:ietf:params:netconf:capability:url:1.0") {
-                    if let Some(s) = s.split("scheme=").nth(1) {
-                        let schemes = s.split(',').map(|x| x.to_string()).collect();
-                        Self::Url(schemes)
-                    } else {
-                        bail!("Unsupported URL capability string - unable to parse schemes!");
-                    }
-                } else {
-                    Self::Other(s.to_string())
-                }
-            }
+            "urn:ietf:params:netconf:capability:notification:1.0" => Self::Notification,
+            "urn:ietf:params:netconf:capability:interleave:1.0" => Self::Interleave,
+            "urn:ietf:params:netconf:capability:partial-lock:1.0" => Self::PartialLock,
+            "urn:ietf:params:netconf:capability:time:1.0" => Self::Time,
+            _ => Self::from_query_urn(urn),
         };
         Ok(instance)
     }
+
+    /// Standard capability with parameters of the `base` URN.
+    fn from_params(base: &str, params: &[(&str, &str)], urn: &str) -> anyhow::Result<Self> {
+        Ok(match base {
+            URL_CAPABILITY => Self::Url(UrlCapability::from_params(params, urn)?),
+            "urn:ietf:params:netconf:capability:with-defaults:1.0" => {
+                Self::WithDefaults(WithDefaultsCapability::from_params(params, urn)?)
+            }
+            _ => Self::WithOperationalDefaults(WithDefaultsCapability::from_params(params, urn)?),
+        })
+    }
+
+    /// Capability identified by the query parameters - YANG library or module, or any other one.
+    fn from_query_urn(urn: &str) -> Self {
+        if let Some(library) = YangLibraryCapability::from_urn(urn) {
+            Self::YangLibrary(library)
+        } else if let Some(module) = YangModuleCapability::from_urn(urn) {
+            Self::YangModule(module)
+        } else {
+            Self::Other(urn.to_string())
+        }
+    }
+
+    /// URN of the capability without its version and query parameters,
+    /// e.g. `urn:ietf:params:netconf:capability:validate` for `:validate:1.1` capability.
+    ///
+    /// For YANG module capabilities, the module namespace is returned.
+    pub fn identifier(&self) -> String {
+        let urn = self.get_urn();
+        let (base, _) = split_query(&urn);
+        match self.version() {
+            Some(version) => base
+                .strip_suffix(&format!(":{}", version))
+                .unwrap_or(base)
+                .to_string(),
+            None => base.to_string(),
+        }
+    }
+
+    /// Version of the capability, e.g. "1.1" for the `:base:1.1` capability.
+    ///
+    /// Returns `None` for YANG module capabilities and capabilities not ending with version number.
+    pub fn version(&self) -> Option<String> {
+        if let Capability::YangModule(_) = self {
+            return None;
+        }
+        let urn = self.get_urn();
+        let (base, _) = split_query(&urn);
+        let (_, version) = base.rsplit_once(':')?;
+        let is_version = !version.is_empty()
+            && version.split('.').all(|x| x.parse::<u32>().is_ok())
+            && version.contains('.');
+        match is_version {
+            true => Some(version.to_string()),
+            false => None,
+        }
+    }
+
+    /// Check whether the capability has the specified `identifier` (see [`Self::identifier()`]),
+    /// and its version is at least `min_version`.
+    ///
+    /// Identifier can be specified also in its short form, e.g. `:validate` or `:base`.
+    pub fn matches_version(&self, identifier: &str, min_version: &str) -> bool {
+        let own_identifier = self.identifier();
+        let identifier_matches = match identifier.starts_with(':') {
+            true => own_identifier.ends_with(identifier),
+            false => own_identifier == identifier,
+        };
+        identifier_matches
+            && match self.version() {
+                Some(version) => parse_version(&version) >= parse_version(min_version),
+                None => false,
+            }
+    }
+}
+
+/// Convert textual "major.minor" version into comparable numeric components.
+pub(crate) fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|x| x.parse::<u32>().unwrap_or(0))
+        .collect()
 }
 
 impl Display for Capability {
-    // TODO - what about schemes for Url capability?
+    /// Standard capabilities are displayed in their short form, e.g. `:url:1.0?scheme=http,ftp`.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            Capability::Base => ":base:1.0",
-            Capability::Base11 => ":base:1.1",
-            Capability::Candidate => ":candidate:1.0",
-            Capability::ConfirmedCommit => ":confirmed-commit:1.1",
-            Capability::RollbackOnError => ":rollback-on-error:1.0",
-            Capability::Startup => ":startup:1.0",
-            Capability::Url(_) => ":url:1.0",
-            Capability::Validate => ":validate:1.0",
-            Capability::Validate11 => ":validate:1.1",
-            Capability::WritableRunning => ":writable-running:1.0",
-            Capability::XPath => ":xpath:1.0",
-            Capability::Other(other) => other,
-        };
-        write!(f, "{}", s)
+        match self {
+            Capability::YangModule(module) => write!(f, "{}", module),
+            Capability::Other(other) => write!(f, "{}", other),
+            standard => {
+                let urn = standard.get_urn();
+                let short = urn
+                    .strip_prefix(URN_PREFIX)
+                    .map(|s| s.strip_prefix(":capability").unwrap_or(s))
+                    .unwrap_or(&urn);
+                write!(f, "{}", short)
+            }
+        }
     }
 }
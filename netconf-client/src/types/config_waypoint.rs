@@ -1,5 +1,7 @@
 use serde::Serialize;
 
+use crate::message_validation::Usage;
+
 use super::{tag_wrapper::TagWrapper, Datastore};
 
 /// The artificial unifying enumeration of NETCONF datastore and URL.
@@ -12,6 +14,20 @@ pub enum ConfigWaypoint {
     Datastore(Datastore),
     /// URL variant for :url capability enabled client/server.
     Url(String),
+    /// Inline configuration - raw XML content of the `<config>` element, valid as the source of `<copy-config>`.
+    Config(String),
+}
+
+impl ConfigWaypoint {
+    /// Capability validation usage of the waypoint used as the named parameter.
+    /// Inline configuration is the usage of the parameter itself, e.g. forbidden as the `target`.
+    pub(crate) fn to_usage(&self, parameter: &'static str) -> Usage<'_> {
+        match self {
+            ConfigWaypoint::Datastore(datastore) => Usage::Datastore(parameter, datastore),
+            ConfigWaypoint::Url(url) => Usage::Url(parameter, url),
+            ConfigWaypoint::Config(_) => Usage::Parameter(parameter),
+        }
+    }
 }
 
 /// Private representation of [`ConfigWaypoint`] used for RPC serialization.
@@ -19,6 +35,7 @@ pub enum ConfigWaypoint {
 pub(crate) struct ConfigWaypointRpc {
     datastore: Option<Datastore>,
     url: Option<TagWrapper<String>>,
+    config: Option<TagWrapper<String>>,
 }
 
 impl From<ConfigWaypoint> for ConfigWaypointRpc {
@@ -27,10 +44,17 @@ impl From<ConfigWaypoint> for ConfigWaypointRpc {
             ConfigWaypoint::Datastore(datastore) => Self {
                 datastore: Some(datastore),
                 url: None,
+                config: None,
             },
             ConfigWaypoint::Url(url) => Self {
                 datastore: None,
                 url: Some(TagWrapper::new(url)),
+                config: None,
+            },
+            ConfigWaypoint::Config(config) => Self {
+                datastore: None,
+                url: None,
+                config: Some(TagWrapper::new(config)),
             },
         }
     }
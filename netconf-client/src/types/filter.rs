@@ -45,10 +45,7 @@ impl ToRawXml for FilterPayload {
             Filter::Subtree(subtree) => {
                 elem.push_attribute(("type", "subtree"));
                 events.push(Event::Start(elem));
-                let data_tag = b"data";
-                events.push(Event::Start(BytesStart::borrowed(data_tag, data_tag.len())));
-                events.push(Event::Text(BytesText::from_escaped_str(&*subtree)));
-                events.push(Event::End(BytesEnd::borrowed(data_tag)));
+                events.push(Event::Text(BytesText::from_escaped_str(subtree)));
                 events.push(Event::End(BytesEnd::borrowed(filter_tag)));
             }
             Filter::Xpath(xpath) => {
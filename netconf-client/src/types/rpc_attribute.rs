@@ -0,0 +1,53 @@
+use std::fmt::Display;
+
+/// Custom attribute of the `<rpc>` element, as allowed by [RFC 6241 - section 4.1](https://datatracker.ietf.org/doc/html/rfc6241#section-4.1).
+///
+/// Any additional attributes present in the `<rpc>` element are returned unmodified
+/// by the NETCONF server in the `<rpc-reply>` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcAttribute {
+    /// Optional namespace of the attribute, as a tuple of (prefix, value).
+    pub namespace: Option<(String, String)>,
+    /// Local name of the attribute.
+    pub name: String,
+    /// Plain (un-escaped) value of the attribute.
+    pub value: String,
+}
+
+impl RpcAttribute {
+    /// Create attribute without any namespace.
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            namespace: None,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Create attribute bound to a namespace `value`, declared with the `prefix`.
+    pub fn namespaced(prefix: &str, namespace: &str, name: &str, value: &str) -> Self {
+        Self {
+            namespace: Some((prefix.to_string(), namespace.to_string())),
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Attribute name as written in XML, i.e. including the namespace prefix if any.
+    pub fn qualified_name(&self) -> String {
+        match &self.namespace {
+            Some((prefix, _)) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Display for RpcAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}=\"{}\"", self.qualified_name(), self.value)?;
+        if let Some((_, namespace)) = &self.namespace {
+            write!(f, " ({})", namespace)?;
+        }
+        Ok(())
+    }
+}
@@ -5,6 +5,6 @@ use super::RpcError;
 pub enum RpcReply {
     /// Variant corresponding to <ok/> RPC.
     Ok,
-    /// Variant containing the <rpc-error> data.
-    Error(RpcError),
+    /// Variant containing data of all the <rpc-error> elements, in order of the reply - at least one.
+    Error(Vec<RpcError>),
 }
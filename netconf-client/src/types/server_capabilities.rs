@@ -0,0 +1,144 @@
+use std::ops::Deref;
+
+use crate::BaseCapability;
+
+use super::{
+    capability::parse_version, Capability, Datastore, YangLibraryCapability, YangModuleCapability,
+};
+
+/// Collection of capabilities advertised by NETCONF server in its `<hello>` message,
+/// with helpers for lookup of specific capabilities, YANG modules & their features.
+///
+/// Dereferences into the plain slice of [`Capability`] items.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerCapabilities {
+    capabilities: Vec<Capability>,
+}
+
+impl ServerCapabilities {
+    /// Create new collection from the list of capabilities.
+    pub fn new(capabilities: Vec<Capability>) -> Self {
+        Self { capabilities }
+    }
+
+    /// Plain slice of all the capabilities.
+    pub fn as_slice(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// Iterator over all the advertised YANG 1.0 modules.
+    pub fn modules(&self) -> impl Iterator<Item = &YangModuleCapability> {
+        self.capabilities.iter().filter_map(|cap| match cap {
+            Capability::YangModule(module) => Some(module),
+            _ => None,
+        })
+    }
+
+    /// Find the advertised YANG module by its name.
+    pub fn module(&self, name: &str) -> Option<&YangModuleCapability> {
+        self.modules().find(|module| module.module == name)
+    }
+
+    /// Check whether the YANG module with specified name is advertised by server.
+    pub fn supports_module(&self, name: &str) -> bool {
+        self.module(name).is_some()
+    }
+
+    /// Check whether the YANG `module` is advertised by server with the specified `feature`.
+    pub fn has_feature(&self, module: &str, feature: &str) -> bool {
+        match self.module(module) {
+            Some(module) => module.features.iter().any(|x| x == feature),
+            None => false,
+        }
+    }
+
+    /// The `:yang-library` capability, if advertised by server.
+    pub fn yang_library(&self) -> Option<&YangLibraryCapability> {
+        self.capabilities.iter().find_map(|cap| match cap {
+            Capability::YangLibrary(library) => Some(library),
+            _ => None,
+        })
+    }
+
+    /// Check whether server supports the Network Management Datastore Architecture - [RFC 8342](https://datatracker.ietf.org/doc/html/rfc8342).
+    ///
+    /// There is no dedicated `:nmda` capability - as required by [RFC 8526 - section 2](https://datatracker.ietf.org/doc/html/rfc8526#section-2),
+    /// NMDA servers advertise the `:yang-library:1.1` capability (or newer).
+    pub fn supports_nmda(&self) -> bool {
+        self.supports_version(":yang-library", "1.1")
+    }
+
+    /// Configuration datastores supported by server - `running` always, `candidate` & `startup` if the corresponding
+    /// capability is advertised, and `intended` & `operational` for NMDA servers.
+    pub fn datastores(&self) -> Vec<Datastore> {
+        let mut datastores = vec![Datastore::Running];
+        if self.capabilities.contains(&Capability::Candidate) {
+            datastores.push(Datastore::Candidate);
+        }
+        if self.capabilities.contains(&Capability::Startup) {
+            datastores.push(Datastore::Startup);
+        }
+        if self.supports_nmda() {
+            datastores.extend([Datastore::Intended, Datastore::Operational]);
+        }
+        datastores
+    }
+
+    /// All the `:base` protocol versions supported by server, from the most recent one.
+    pub fn base_versions(&self) -> Vec<BaseCapability> {
+        [
+            (Capability::Base11, BaseCapability::Base11),
+            (Capability::Base, BaseCapability::Base),
+        ]
+        .into_iter()
+        .filter(|(cap, _)| self.capabilities.contains(cap))
+        .map(|(_, base)| base)
+        .collect()
+    }
+
+    /// All the advertised versions of capability with the specified `identifier`, from the most recent one.
+    ///
+    /// See [`Capability::identifier()`] for allowed identifier formats.
+    pub fn versions(&self, identifier: &str) -> Vec<String> {
+        let mut versions: Vec<String> = self
+            .capabilities
+            .iter()
+            .filter(|cap| cap.matches_version(identifier, "0"))
+            .filter_map(|cap| cap.version())
+            .collect();
+        versions.sort_by_key(|version| std::cmp::Reverse(parse_version(version)));
+        versions
+    }
+
+    /// Check whether server supports capability with the `identifier` in at least the `min_version`.
+    ///
+    /// See [`Capability::matches_version()`] for details.
+    pub fn supports_version(&self, identifier: &str, min_version: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|cap| cap.matches_version(identifier, min_version))
+    }
+}
+
+impl From<Vec<Capability>> for ServerCapabilities {
+    fn from(capabilities: Vec<Capability>) -> Self {
+        Self::new(capabilities)
+    }
+}
+
+impl Deref for ServerCapabilities {
+    type Target = [Capability];
+
+    fn deref(&self) -> &Self::Target {
+        &self.capabilities
+    }
+}
+
+impl<'a> IntoIterator for &'a ServerCapabilities {
+    type Item = &'a Capability;
+    type IntoIter = std::slice::Iter<'a, Capability>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.capabilities.iter()
+    }
+}
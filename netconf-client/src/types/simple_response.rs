@@ -29,8 +29,8 @@ struct SimpleResponseRpc {
     message_id: String,
     xmlns: String,
     ok: Option<()>,
-    #[serde(rename = "rpc-error")]
-    rpc_error: Option<RpcError>,
+    #[serde(rename = "rpc-error", default)]
+    rpc_errors: Vec<RpcError>,
 }
 
 impl TryFrom<SimpleResponseRpc> for SimpleResponse {
@@ -39,12 +39,10 @@ impl TryFrom<SimpleResponseRpc> for SimpleResponse {
     fn try_from(value: SimpleResponseRpc) -> Result<Self> {
         let message_id = value.message_id;
         let xmlns = value.xmlns;
-        let reply = match value.ok.is_some() {
-            true => RpcReply::Ok,
-            false => match value.rpc_error {
-                Some(err) => RpcReply::Error(err),
-                None => bail!("Missing both <ok/> and <rpc-error> from response"),
-            },
+        let reply = match (value.ok.is_some(), value.rpc_errors.is_empty()) {
+            (true, _) => RpcReply::Ok,
+            (false, false) => RpcReply::Error(value.rpc_errors),
+            (false, true) => bail!("Missing both <ok/> and <rpc-error> from response"),
         };
         Ok(SimpleResponse {
             message_id,
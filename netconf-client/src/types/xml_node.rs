@@ -0,0 +1,431 @@
+use std::io::Cursor;
+
+use anyhow::{bail, Result};
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+
+/// Attribute of the [`XmlNode`] element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlAttribute {
+    /// Local name of the attribute, without prefix.
+    pub name: String,
+    /// Namespace prefix of the attribute, as written in the source document.
+    pub prefix: Option<String>,
+    /// Resolved namespace of the prefixed attribute.
+    pub namespace: Option<String>,
+    /// Plain (un-escaped) value of the attribute.
+    pub value: String,
+}
+
+impl XmlAttribute {
+    /// Attribute name as written in XML, i.e. including the namespace prefix if any.
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Simple owned tree of XML elements with resolved namespaces.
+///
+/// Used for processing of the nested configuration/state data carried by NETCONF messages,
+/// e.g. the `<data>` of `<get>` / `<get-config>` responses.
+/// Mixed content is not preserved - all the text pieces of an element are joined into [`XmlNode::text`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XmlNode {
+    /// Local name of the element, without prefix.
+    pub name: String,
+    /// Namespace prefix of the element, as written in the source document.
+    pub prefix: Option<String>,
+    /// Resolved namespace of the element.
+    pub namespace: Option<String>,
+    /// Namespaces declared on this element, as tuples of (prefix, value). Default namespace has `None` prefix.
+    pub namespace_declarations: Vec<(Option<String>, String)>,
+    /// Attributes of the element, excluding namespace declarations.
+    pub attributes: Vec<XmlAttribute>,
+    /// Nested child elements.
+    pub children: Vec<XmlNode>,
+    /// Trimmed text content of the element, if any.
+    pub text: Option<String>,
+}
+
+type NamespaceScope = Vec<(Option<String>, String)>;
+
+impl XmlNode {
+    /// Create element with the local `name`, without any namespace.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the namespace of the element (as default namespace, without prefix).
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Set the text content of the element.
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    /// Append the `child` element.
+    pub fn with_child(mut self, child: XmlNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Set the un-prefixed attribute of the element.
+    pub fn with_attribute(mut self, name: &str, value: &str) -> Self {
+        self.set_attribute(XmlAttribute {
+            name: name.to_string(),
+            prefix: None,
+            namespace: None,
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// Set the attribute, replacing existing one with the same name & namespace.
+    pub fn set_attribute(&mut self, attribute: XmlAttribute) {
+        self.attributes
+            .retain(|x| !(x.name == attribute.name && x.namespace == attribute.namespace));
+        self.attributes.push(attribute);
+    }
+
+    /// Parse the XML document with single root element.
+    pub fn parse(xml: &str) -> Result<XmlNode> {
+        let mut nodes = Self::parse_fragment(xml)?;
+        match nodes.len() {
+            1 => Ok(nodes.remove(0)),
+            0 => bail!("Missing root element"),
+            n => bail!("Expected single root element, found {}", n),
+        }
+    }
+
+    /// Parse XML fragment, possibly consisting of multiple top-level elements.
+    pub fn parse_fragment(xml: &str) -> Result<Vec<XmlNode>> {
+        let mut reader = Reader::from_str(xml);
+        reader.check_end_names(true);
+
+        let mut roots = vec![];
+        let mut stack: Vec<XmlNode> = vec![];
+        let mut scopes: Vec<NamespaceScope> = vec![];
+        let mut read_buf = Vec::new();
+
+        loop {
+            let event = match reader.read_event(&mut read_buf) {
+                Ok(event) => event,
+                Err(e) => bail!("Error at position {}: {:?}", reader.buffer_position(), e),
+            };
+            match event {
+                Event::Eof => break,
+                Event::Start(elem) => {
+                    let node = Self::from_start(&elem, &reader, &mut scopes)?;
+                    stack.push(node);
+                }
+                Event::Empty(elem) => {
+                    let node = Self::from_start(&elem, &reader, &mut scopes)?;
+                    scopes.pop();
+                    Self::attach(node, &mut stack, &mut roots);
+                }
+                Event::End(_) => {
+                    scopes.pop();
+                    match stack.pop() {
+                        Some(mut node) => {
+                            if !node.children.is_empty() {
+                                Self::drop_whitespace(&mut node);
+                            }
+                            Self::attach(node, &mut stack, &mut roots)
+                        }
+                        None => bail!("Unexpected closing tag"),
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape_and_decode(&reader)?;
+                    Self::append_text(&mut stack, &text)?;
+                }
+                Event::CData(text) => {
+                    let text = String::from_utf8(text.into_inner().to_vec())?;
+                    Self::append_text(&mut stack, &text)?;
+                }
+                _ => {}
+            }
+            read_buf.clear();
+        }
+
+        if !stack.is_empty() {
+            bail!(
+                "Missing closing tag of element <{}>",
+                stack[stack.len() - 1].name
+            );
+        }
+
+        Ok(roots)
+    }
+
+    fn from_start(
+        elem: &BytesStart,
+        reader: &Reader<&[u8]>,
+        scopes: &mut Vec<NamespaceScope>,
+    ) -> Result<XmlNode> {
+        let mut declarations = vec![];
+        let mut raw_attributes = vec![];
+        for attribute in elem.attributes() {
+            let attribute = attribute?;
+            let key = String::from_utf8(attribute.key.to_vec())?;
+            let value = attribute.unescape_and_decode_value(reader)?;
+            if key == "xmlns" {
+                declarations.push((None, value));
+            } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+                declarations.push((Some(prefix.to_string()), value));
+            } else {
+                raw_attributes.push((key, value));
+            }
+        }
+        scopes.push(declarations.clone());
+
+        let (prefix, name) = split_qualified_name(&String::from_utf8(elem.name().to_vec())?);
+        let namespace = resolve_prefix(scopes, prefix.as_deref());
+        if prefix.is_some() && namespace.is_none() {
+            bail!("Undeclared namespace prefix of element <{}>", name);
+        }
+
+        let attributes = raw_attributes
+            .into_iter()
+            .map(|(key, value)| {
+                let (prefix, name) = split_qualified_name(&key);
+                let namespace = prefix
+                    .as_deref()
+                    .and_then(|prefix| resolve_prefix(scopes, Some(prefix)));
+                XmlAttribute {
+                    name,
+                    prefix,
+                    namespace,
+                    value,
+                }
+            })
+            .collect();
+
+        Ok(XmlNode {
+            name,
+            prefix,
+            namespace,
+            namespace_declarations: declarations,
+            attributes,
+            children: vec![],
+            text: None,
+        })
+    }
+
+    fn attach(node: XmlNode, stack: &mut [XmlNode], roots: &mut Vec<XmlNode>) {
+        match stack.last_mut() {
+            Some(parent) => {
+                Self::drop_whitespace(parent);
+                parent.children.push(node);
+            }
+            None => roots.push(node),
+        }
+    }
+
+    /// Drop the whitespace-only text of the element, i.e. the indentation between its child elements.
+    fn drop_whitespace(node: &mut XmlNode) {
+        if node.text.as_deref().is_some_and(|x| x.trim().is_empty()) {
+            node.text = None;
+        }
+    }
+
+    /// Append the raw `text` to the element being parsed. Whitespace-only text is dropped later,
+    /// if it turns out to be between the child elements.
+    fn append_text(stack: &mut [XmlNode], text: &str) -> Result<()> {
+        match stack.last_mut() {
+            Some(node) => {
+                match &mut node.text {
+                    Some(existing) => existing.push_str(text),
+                    None => node.text = Some(text.to_string()),
+                };
+                Ok(())
+            }
+            None if text.trim().is_empty() => Ok(()),
+            None => bail!("Text content outside of root element"),
+        }
+    }
+
+    /// Element name as written in XML, i.e. including the namespace prefix if any.
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Text content of the element, or empty string if there is none.
+    pub fn text(&self) -> &str {
+        self.text.as_deref().unwrap_or("")
+    }
+
+    /// Check whether the element has no child elements.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// First child element with the specified local `name`, irrespective of its namespace.
+    pub fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Mutable reference to the first child element with the specified local `name`.
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut XmlNode> {
+        self.children.iter_mut().find(|child| child.name == name)
+    }
+
+    /// All child elements with the specified local `name`, irrespective of their namespace.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Text content of the first child element with the specified local `name`.
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text())
+    }
+
+    /// Value of the attribute with the specified local `name`, irrespective of its namespace.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Find the first descendant element (including self) with the specified local `name`, in depth-first order.
+    pub fn find(&self, name: &str) -> Option<&XmlNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    /// Serialize the element into raw XML string.
+    ///
+    /// Namespaces of the element & its attributes are declared as needed,
+    /// so the serialized sub-tree is self-contained even when detached from its original document.
+    pub fn to_xml(&self) -> Result<String> {
+        Self::fragment_to_xml(std::slice::from_ref(self))
+    }
+
+    /// Serialize the element into XML string indented by 2 spaces per level.
+    ///
+    /// Unlike [`crate::messages::raw_to_pretty_xml()`], the text content is kept as is - indentation is added only
+    /// between the child elements, where the whitespace is dropped again by [`XmlNode::parse()`].
+    pub fn to_pretty_xml(&self) -> Result<String> {
+        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+        self.write_events(&mut writer, &mut vec![])?;
+        Ok(String::from_utf8(writer.into_inner().into_inner())?)
+    }
+
+    /// Serialize the elements into raw XML string, one after another.
+    pub fn fragment_to_xml(nodes: &[XmlNode]) -> Result<String> {
+        Self::fragment_to_xml_within(nodes, &[])
+    }
+
+    /// Serialize the elements as [`XmlNode::fragment_to_xml()`], as content of an element declaring the `namespaces`
+    /// (tuples of prefix & value) - those are not declared again.
+    pub(crate) fn fragment_to_xml_within(
+        nodes: &[XmlNode],
+        namespaces: &[(Option<String>, String)],
+    ) -> Result<String> {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        for node in nodes {
+            node.write_events(&mut writer, &mut vec![namespaces.to_vec()])?;
+        }
+        Ok(String::from_utf8(writer.into_inner().into_inner())?)
+    }
+
+    fn write_events(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        scopes: &mut Vec<NamespaceScope>,
+    ) -> Result<()> {
+        let name = self.qualified_name();
+        let mut elem = BytesStart::owned_name(name.as_bytes());
+
+        let mut declarations = self.namespace_declarations.clone();
+        let mut require = |prefix: &Option<String>, namespace: &Option<String>| {
+            let namespace = namespace.clone().unwrap_or_default();
+            let in_scope = declarations
+                .iter()
+                .rev()
+                .find(|(declared, _)| declared == prefix)
+                .map(|(_, value)| value.clone())
+                .or_else(|| resolve_prefix(scopes, prefix.as_deref()))
+                .unwrap_or_default();
+            if in_scope != namespace {
+                declarations.push((prefix.clone(), namespace));
+            }
+        };
+        require(&self.prefix, &self.namespace);
+        for attribute in self.attributes.iter() {
+            if attribute.prefix.is_some() {
+                require(&attribute.prefix, &attribute.namespace);
+            }
+        }
+
+        for (prefix, value) in declarations.iter() {
+            match prefix {
+                Some(prefix) => {
+                    elem.push_attribute((format!("xmlns:{}", prefix).as_str(), value.as_str()))
+                }
+                None => elem.push_attribute(("xmlns", value.as_str())),
+            }
+        }
+        for attribute in self.attributes.iter() {
+            elem.push_attribute((
+                attribute.qualified_name().as_str(),
+                attribute.value.as_str(),
+            ));
+        }
+
+        if self.children.is_empty() && self.text.is_none() {
+            writer.write_event(Event::Empty(elem))?;
+            return Ok(());
+        }
+
+        writer.write_event(Event::Start(elem))?;
+        scopes.push(declarations);
+        if let Some(text) = &self.text {
+            writer.write_event(Event::Text(BytesText::from_plain_str(text)))?;
+        }
+        for child in self.children.iter() {
+            child.write_events(writer, scopes)?;
+        }
+        scopes.pop();
+        writer.write_event(Event::End(BytesEnd::owned(name.into_bytes())))?;
+        Ok(())
+    }
+}
+
+fn split_qualified_name(name: &str) -> (Option<String>, String) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix.to_string()), local.to_string()),
+        None => (None, name.to_string()),
+    }
+}
+
+fn resolve_prefix(scopes: &[NamespaceScope], prefix: Option<&str>) -> Option<String> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| {
+            scope
+                .iter()
+                .find(|(declared, _)| declared.as_deref() == prefix)
+                .map(|(_, namespace)| namespace.clone())
+        })
+        .filter(|namespace| !namespace.is_empty())
+}
@@ -0,0 +1,271 @@
+use anyhow::{bail, Result};
+
+use super::{Datastore, XmlNode};
+
+/// Namespace of the `ietf-yang-library` YANG module.
+pub const YANG_LIBRARY_XMLNS: &str = "urn:ietf:params:xml:ns:yang:ietf-yang-library";
+
+/// Name of the single artificial module set, used for data converted from the legacy `modules-state`.
+pub const LEGACY_MODULE_SET: &str = "modules-state";
+
+/// Typed representation of the YANG library - [RFC 8525](https://datatracker.ietf.org/doc/html/rfc8525),
+/// describing the YANG modules, datastores & schemas implemented by server.
+///
+/// Can be built also from the legacy `modules-state` tree - [RFC 7895](https://datatracker.ietf.org/doc/html/rfc7895).
+/// In that case all the modules are listed in the single [`LEGACY_MODULE_SET`] module set,
+/// and there are no schemas nor datastores present.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct YangLibrary {
+    /// Identifier of the YANG library content - `content-id`, or `module-set-id` of the legacy `modules-state`.
+    pub content_id: String,
+    /// Sets of modules & submodules in use by server.
+    pub module_sets: Vec<ModuleSet>,
+    /// Schemas built from the module sets.
+    pub schemas: Vec<Schema>,
+    /// Datastores supported by server with their schemas.
+    pub datastores: Vec<DatastoreSchema>,
+}
+
+/// Set of modules & submodules, as listed in the `module-set` list of YANG library.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModuleSet {
+    /// Name of the module set.
+    pub name: String,
+    /// Modules implemented by server, or imported only.
+    pub modules: Vec<Module>,
+}
+
+/// YANG module entry of the YANG library.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Module {
+    /// Name of the module.
+    pub name: String,
+    /// Revision of the module, if any.
+    pub revision: Option<String>,
+    /// XML namespace of the module.
+    pub namespace: String,
+    /// URLs where the module source can be retrieved.
+    pub locations: Vec<String>,
+    /// Implemented features of the module.
+    pub features: Vec<String>,
+    /// Names of the modules containing deviations of this module.
+    pub deviations: Vec<String>,
+    /// Submodules of the module.
+    pub submodules: Vec<Submodule>,
+    /// Whether the module is implemented by server, or only imported by other modules.
+    pub conformance: Conformance,
+}
+
+/// Submodule of the YANG [`Module`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Submodule {
+    /// Name of the submodule.
+    pub name: String,
+    /// Revision of the submodule, if any.
+    pub revision: Option<String>,
+    /// URLs where the submodule source can be retrieved.
+    pub locations: Vec<String>,
+}
+
+/// Conformance type of the YANG [`Module`].
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Conformance {
+    /// Module is implemented by server.
+    #[default]
+    Implement,
+    /// Module is not implemented, only its groupings/typedefs are used by other modules.
+    Import,
+}
+
+/// Complete schema built from module sets.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    /// Name of the schema.
+    pub name: String,
+    /// Names of the module sets the schema consists of.
+    pub module_sets: Vec<String>,
+}
+
+/// Datastore supported by server along with its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatastoreSchema {
+    /// Datastore identity, with the YANG prefix stripped.
+    pub datastore: Datastore,
+    /// Name of the schema used by the datastore.
+    pub schema: String,
+}
+
+fn texts(node: &XmlNode, name: &str) -> Vec<String> {
+    node.children_named(name)
+        .map(|x| x.text().to_string())
+        .collect()
+}
+
+fn optional_text(node: &XmlNode, name: &str) -> Option<String> {
+    node.child_text(name)
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+}
+
+fn strip_identity_prefix(identity: &str) -> &str {
+    identity.rsplit(':').next().unwrap_or(identity)
+}
+
+impl YangLibrary {
+    /// Parse the `<yang-library>` container of the `ietf-yang-library` module, revision 2019-01-04 and newer.
+    pub fn from_yang_library(node: &XmlNode) -> Result<Self> {
+        if node.name != "yang-library" {
+            bail!("Expected <yang-library> element, found <{}>", node.name);
+        }
+
+        let module_sets = node
+            .children_named("module-set")
+            .map(|set| {
+                let mut modules: Vec<Module> = set
+                    .children_named("module")
+                    .map(|x| Module::from_node(x, Conformance::Implement))
+                    .collect();
+                modules.extend(
+                    set.children_named("import-only-module")
+                        .map(|x| Module::from_node(x, Conformance::Import)),
+                );
+                ModuleSet {
+                    name: set.child_text("name").unwrap_or_default().to_string(),
+                    modules,
+                }
+            })
+            .collect();
+
+        let schemas = node
+            .children_named("schema")
+            .map(|schema| Schema {
+                name: schema.child_text("name").unwrap_or_default().to_string(),
+                module_sets: texts(schema, "module-set"),
+            })
+            .collect();
+
+        let datastores = node
+            .children_named("datastore")
+            .map(|datastore| {
+                let name = datastore.child_text("name").unwrap_or_default();
+                DatastoreSchema {
+                    // infallible parsing
+                    datastore: strip_identity_prefix(name).parse().unwrap(),
+                    schema: datastore
+                        .child_text("schema")
+                        .unwrap_or_default()
+                        .to_string(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            content_id: node
+                .child_text("content-id")
+                .unwrap_or_default()
+                .to_string(),
+            module_sets,
+            schemas,
+            datastores,
+        })
+    }
+
+    /// Parse the deprecated `<modules-state>` container of the `ietf-yang-library` module, revision 2016-06-21.
+    pub fn from_modules_state(node: &XmlNode) -> Result<Self> {
+        if node.name != "modules-state" {
+            bail!("Expected <modules-state> element, found <{}>", node.name);
+        }
+
+        let modules = node
+            .children_named("module")
+            .map(|module| {
+                let conformance = match module.child_text("conformance-type") {
+                    Some("import") => Conformance::Import,
+                    _ => Conformance::Implement,
+                };
+                let mut instance = Module::from_node(module, conformance);
+                instance.locations = optional_text(module, "schema").into_iter().collect();
+                instance.deviations = module
+                    .children_named("deviation")
+                    .filter_map(|x| x.child_text("name"))
+                    .map(|x| x.to_string())
+                    .collect();
+                for (submodule, node) in instance
+                    .submodules
+                    .iter_mut()
+                    .zip(module.children_named("submodule"))
+                {
+                    submodule.locations = optional_text(node, "schema").into_iter().collect();
+                }
+                instance
+            })
+            .collect();
+
+        Ok(Self {
+            content_id: node
+                .child_text("module-set-id")
+                .unwrap_or_default()
+                .to_string(),
+            module_sets: vec![ModuleSet {
+                name: LEGACY_MODULE_SET.to_string(),
+                modules,
+            }],
+            schemas: vec![],
+            datastores: vec![],
+        })
+    }
+
+    /// Parse the YANG library from the `<data>` element of `<get>` reply,
+    /// containing either `<yang-library>` or the legacy `<modules-state>` container.
+    pub fn from_data(data: &XmlNode) -> Result<Self> {
+        if let Some(node) = data.child("yang-library") {
+            return Self::from_yang_library(node);
+        }
+        if let Some(node) = data.child("modules-state") {
+            return Self::from_modules_state(node);
+        }
+        bail!("Neither <yang-library> nor <modules-state> found in data");
+    }
+
+    /// Iterator over the modules of all the module sets.
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.module_sets.iter().flat_map(|set| set.modules.iter())
+    }
+
+    /// Find the module by its name, in any of the module sets.
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules().find(|module| module.name == name)
+    }
+}
+
+impl Module {
+    fn from_node(node: &XmlNode, conformance: Conformance) -> Self {
+        Self {
+            name: node.child_text("name").unwrap_or_default().to_string(),
+            revision: optional_text(node, "revision"),
+            namespace: node.child_text("namespace").unwrap_or_default().to_string(),
+            locations: texts(node, "location"),
+            features: texts(node, "feature"),
+            deviations: texts(node, "deviation"),
+            submodules: node
+                .children_named("submodule")
+                .map(|submodule| Submodule {
+                    name: submodule.child_text("name").unwrap_or_default().to_string(),
+                    revision: optional_text(submodule, "revision"),
+                    locations: texts(submodule, "location"),
+                })
+                .collect(),
+            conformance,
+        }
+    }
+}
+
+impl std::fmt::Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(revision) = &self.revision {
+            write!(f, "@{}", revision)?;
+        }
+        Ok(())
+    }
+}
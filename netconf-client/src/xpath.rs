@@ -0,0 +1,1696 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::types::{XmlAttribute, XmlNode};
+
+/// Client-side [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/) expression, evaluated over the retrieved data.
+///
+/// Namespace prefixes of the expression are bound to the prefixes declared in the data, or by [`XPath::with_namespace()`].
+/// Names without prefix match elements of any namespace - e.g. `/interfaces/interface[name='eth0']/oper-status`
+/// selects the leaf of the `ietf-interfaces` module, without declaring its namespace.
+/// Variables, and the `id()` & `lang()` functions are not supported.
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use netconf_client::{types::XmlNode, xpath::XPath};
+///
+/// let data = XmlNode::parse_fragment(r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+///     <interface><name>eth0</name><enabled>true</enabled></interface>
+/// </interfaces>"#)?;
+/// let xpath = XPath::parse("/interfaces/interface[name='eth0']/enabled")?;
+/// assert_eq!(xpath.select(&data)?.string_value(), "true");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct XPath {
+    expression: Expr,
+    /// Namespaces by prefixes bound explicitly, taking precedence over the declarations in data.
+    namespaces: HashMap<String, String>,
+}
+
+/// Result of the [`XPath`] evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection<'a> {
+    /// Selected nodes, in document order.
+    Nodes(Vec<SelectedNode<'a>>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+/// Node of the data selected by [`XPath`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectedNode<'a> {
+    /// The root node - parent of all the top-level elements.
+    Root,
+    Element(&'a XmlNode),
+    /// Attribute of the element.
+    Attribute(&'a XmlNode, &'a XmlAttribute),
+    /// Text content of the element.
+    Text(&'a XmlNode),
+}
+
+impl XPath {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            position: 0,
+        };
+        let parsed = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {:?} in XPath expression", token);
+        }
+        Ok(Self {
+            expression: parsed,
+            namespaces: HashMap::new(),
+        })
+    }
+
+    /// Bind the namespace `prefix` - e.g. of the declaration on the `<rpc-reply>`, outside the evaluated data.
+    pub fn with_namespace(mut self, prefix: &str, namespace: &str) -> Self {
+        self.namespaces
+            .insert(prefix.to_string(), namespace.to_string());
+        self
+    }
+
+    /// Evaluate the expression over the `roots` - the top-level elements of data, children of the root node.
+    pub fn select<'a>(&self, roots: &'a [XmlNode]) -> Result<Selection<'a>> {
+        let document = Document::new(roots, self.namespaces.clone());
+        let mut prefixes = vec![];
+        self.expression.prefixes(&mut prefixes);
+        for prefix in prefixes {
+            document.namespace(prefix)?;
+        }
+        let context = Context {
+            node: NodeRef::element(0),
+            position: 1,
+            size: 1,
+        };
+        Ok(match document.evaluate(&self.expression, &context)? {
+            Value::Nodes(nodes) => {
+                Selection::Nodes(nodes.iter().map(|x| document.selected(*x)).collect())
+            }
+            Value::Boolean(value) => Selection::Boolean(value),
+            Value::Number(value) => Selection::Number(value),
+            Value::String(value) => Selection::String(value),
+        })
+    }
+}
+
+impl Selection<'_> {
+    /// Result converted to string, as by the XPath `string()` function - string-value of the first node for node-sets.
+    pub fn string_value(&self) -> String {
+        match self {
+            Selection::Nodes(nodes) => nodes.first().map(|x| x.string_value()).unwrap_or_default(),
+            Selection::Boolean(value) => value.to_string(),
+            Selection::Number(value) => number_to_string(*value),
+            Selection::String(value) => value.clone(),
+        }
+    }
+}
+
+impl SelectedNode<'_> {
+    /// Text of the node - concatenated text of all the descendants for elements.
+    pub fn string_value(&self) -> String {
+        match self {
+            SelectedNode::Root => String::new(),
+            SelectedNode::Element(node) => {
+                let mut text = String::new();
+                push_text(node, &mut text);
+                text
+            }
+            SelectedNode::Attribute(_, attribute) => attribute.value.clone(),
+            SelectedNode::Text(node) => node.text().to_string(),
+        }
+    }
+}
+
+fn push_text(node: &XmlNode, text: &mut String) {
+    text.push_str(node.text());
+    for child in node.children.iter() {
+        push_text(child, text);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Slash,
+    DoubleSlash,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Pipe,
+    Plus,
+    Minus,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal(String),
+    Number(f64),
+    /// Name test - `*`, `prefix:*` or (prefixed) name, or name of function, axis & node type.
+    Name(String),
+    Variable(String),
+}
+
+impl Token {
+    /// Whether the token may precede an operand only, not an operator - see [XPath - section 3.7](https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex).
+    fn precedes_operand(&self) -> bool {
+        !matches!(
+            self,
+            Token::RParen
+                | Token::RBracket
+                | Token::Dot
+                | Token::DotDot
+                | Token::Literal(_)
+                | Token::Number(_)
+                | Token::Name(_)
+                | Token::Variable(_)
+        )
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens: Vec<Token> = vec![];
+    let mut i = 0;
+    let read_name = |i: &mut usize| {
+        let start = *i;
+        while *i < chars.len() && is_name_char(chars[*i]) {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let operator_expected = tokens.last().is_some_and(|x| !x.precedes_operand());
+        let (token, length) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '/' if next == Some('/') => (Token::DoubleSlash, 2),
+            '/' => (Token::Slash, 1),
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            '.' if next == Some('.') => (Token::DotDot, 2),
+            '.' if !next.is_some_and(|x| x.is_ascii_digit()) => (Token::Dot, 1),
+            '@' => (Token::At, 1),
+            ',' => (Token::Comma, 1),
+            ':' if next == Some(':') => (Token::ColonColon, 2),
+            '|' => (Token::Pipe, 1),
+            '+' => (Token::Plus, 1),
+            '-' => (Token::Minus, 1),
+            '=' => (Token::Eq, 1),
+            '!' if next == Some('=') => (Token::Neq, 2),
+            '<' if next == Some('=') => (Token::Le, 2),
+            '<' => (Token::Lt, 1),
+            '>' if next == Some('=') => (Token::Ge, 2),
+            '>' => (Token::Gt, 1),
+            '*' if operator_expected => (Token::Multiply, 1),
+            '*' => (Token::Name("*".to_string()), 1),
+            '"' | '\'' => {
+                let end = match chars[i + 1..].iter().position(|x| *x == c) {
+                    Some(end) => i + 1 + end,
+                    None => bail!("Unterminated literal in XPath expression"),
+                };
+                let literal = chars[i + 1..end].iter().collect();
+                (Token::Literal(literal), end + 1 - i)
+            }
+            '$' => {
+                let mut end = i + 1;
+                let name = read_name(&mut end);
+                (Token::Variable(name), end - i)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                let mut end = i;
+                while end < chars.len() && (chars[end].is_ascii_digit() || chars[end] == '.') {
+                    end += 1;
+                }
+                let number: String = chars[start..end].iter().collect();
+                match number.parse() {
+                    Ok(number) => (Token::Number(number), end - i),
+                    Err(_) => bail!("Invalid number \"{}\" in XPath expression", number),
+                }
+            }
+            c if is_name_start(c) => {
+                let mut end = i;
+                let mut name = read_name(&mut end);
+                if chars.get(end) == Some(&':') && chars.get(end + 1) != Some(&':') {
+                    match chars.get(end + 1) {
+                        Some('*') => {
+                            name.push_str(":*");
+                            end += 2;
+                        }
+                        Some(c) if is_name_start(*c) => {
+                            end += 1;
+                            name = format!("{}:{}", name, read_name(&mut end));
+                        }
+                        _ => bail!("Invalid name \"{}:\" in XPath expression", name),
+                    }
+                }
+                let token = match (operator_expected, name.as_str()) {
+                    (true, "and") => Token::And,
+                    (true, "or") => Token::Or,
+                    (true, "mod") => Token::Mod,
+                    (true, "div") => Token::Div,
+                    _ => Token::Name(name),
+                };
+                (token, end - i)
+            }
+            c => bail!("Unexpected character '{}' in XPath expression", c),
+        };
+        tokens.push(token);
+        i += length;
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CompareOp {
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfNode,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfNode,
+            "namespace" => bail!("XPath namespace axis is not supported"),
+            name => bail!("Unknown XPath axis \"{}\"", name),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodeTest {
+    /// `*`, or the `prefix:*`.
+    Any(Option<String>),
+    Name(Option<String>, String),
+    /// `node()`
+    Node,
+    /// `text()`
+    Text,
+    /// `comment()` & `processing-instruction()`, not kept in the data.
+    None,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+enum PathStart {
+    Root,
+    Context,
+    Filter(Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arithmetic(ArithmeticOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    Path(PathStart, Vec<Step>),
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Namespace prefixes of the name tests.
+    fn prefixes<'a>(&'a self, prefixes: &mut Vec<&'a str>) {
+        match self {
+            Expr::Or(left, right)
+            | Expr::And(left, right)
+            | Expr::Compare(_, left, right)
+            | Expr::Arithmetic(_, left, right)
+            | Expr::Union(left, right) => {
+                left.prefixes(prefixes);
+                right.prefixes(prefixes);
+            }
+            Expr::Negate(expr) => expr.prefixes(prefixes),
+            Expr::Path(start, steps) => {
+                if let PathStart::Filter(expr) = start {
+                    expr.prefixes(prefixes);
+                }
+                for step in steps.iter() {
+                    if let NodeTest::Any(Some(prefix)) | NodeTest::Name(Some(prefix), _) =
+                        &step.test
+                    {
+                        prefixes.push(prefix);
+                    }
+                    step.predicates.iter().for_each(|x| x.prefixes(prefixes));
+                }
+            }
+            Expr::Filter(expr, predicates) => {
+                expr.prefixes(prefixes);
+                predicates.iter().for_each(|x| x.prefixes(prefixes));
+            }
+            Expr::Function(_, args) => args.iter().for_each(|x| x.prefixes(prefixes)),
+            Expr::Literal(_) | Expr::Number(_) => {}
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let matched = self.peek() == Some(token);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        match self.eat(&token) {
+            true => Ok(()),
+            false => match self.peek() {
+                Some(other) => bail!(
+                    "Expected {:?} in XPath expression, found {:?}",
+                    token,
+                    other
+                ),
+                None => bail!("Expected {:?} at the end of XPath expression", token),
+            },
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut left = self.and_expr()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let mut left = self.equality_expr()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.equality_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn equality_expr(&mut self) -> Result<Expr> {
+        let mut left = self.relational_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Eq) => CompareOp::Eq,
+                Some(Token::Neq) => CompareOp::Neq,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Compare(op, Box::new(left), Box::new(self.relational_expr()?));
+        }
+    }
+
+    fn relational_expr(&mut self) -> Result<Expr> {
+        let mut left = self.additive_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Lt) => CompareOp::Lt,
+                Some(Token::Le) => CompareOp::Le,
+                Some(Token::Gt) => CompareOp::Gt,
+                Some(Token::Ge) => CompareOp::Ge,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Compare(op, Box::new(left), Box::new(self.additive_expr()?));
+        }
+    }
+
+    fn additive_expr(&mut self) -> Result<Expr> {
+        let mut left = self.multiplicative_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => ArithmeticOp::Add,
+                Some(Token::Minus) => ArithmeticOp::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Arithmetic(op, Box::new(left), Box::new(self.multiplicative_expr()?));
+        }
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Expr> {
+        let mut left = self.unary_expr()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Multiply) => ArithmeticOp::Multiply,
+                Some(Token::Div) => ArithmeticOp::Divide,
+                Some(Token::Mod) => ArithmeticOp::Modulo,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expr::Arithmetic(op, Box::new(left), Box::new(self.unary_expr()?));
+        }
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr> {
+        match self.eat(&Token::Minus) {
+            true => Ok(Expr::Negate(Box::new(self.unary_expr()?))),
+            false => self.union_expr(),
+        }
+    }
+
+    fn union_expr(&mut self) -> Result<Expr> {
+        let mut left = self.path_expr()?;
+        while self.eat(&Token::Pipe) {
+            left = Expr::Union(Box::new(left), Box::new(self.path_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn path_expr(&mut self) -> Result<Expr> {
+        let filter_start = match (self.peek(), self.peek_at(1)) {
+            (
+                Some(Token::Literal(_) | Token::Number(_) | Token::LParen | Token::Variable(_)),
+                _,
+            ) => true,
+            (Some(Token::Name(name)), Some(Token::LParen)) => !is_node_type(name),
+            _ => false,
+        };
+        if !filter_start {
+            return self.location_path();
+        }
+
+        let primary = self.primary_expr()?;
+        let mut predicates = vec![];
+        while self.peek() == Some(&Token::LBracket) {
+            predicates.push(self.predicate()?);
+        }
+        let filter = match predicates.is_empty() {
+            true => primary,
+            false => Expr::Filter(Box::new(primary), predicates),
+        };
+        let mut steps = vec![];
+        match self.peek() {
+            Some(Token::Slash) => {
+                self.position += 1;
+                self.relative_path(&mut steps)?;
+            }
+            Some(Token::DoubleSlash) => {
+                self.position += 1;
+                steps.push(descendant_or_self());
+                self.relative_path(&mut steps)?;
+            }
+            _ => return Ok(filter),
+        }
+        Ok(Expr::Path(PathStart::Filter(Box::new(filter)), steps))
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr> {
+        match self.peek().cloned() {
+            Some(Token::Literal(literal)) => {
+                self.position += 1;
+                Ok(Expr::Literal(literal))
+            }
+            Some(Token::Number(number)) => {
+                self.position += 1;
+                Ok(Expr::Number(number))
+            }
+            Some(Token::LParen) => {
+                self.position += 1;
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Variable(name)) => bail!("XPath variables are not supported: ${}", name),
+            Some(Token::Name(name)) => {
+                self.position += 2;
+                let mut args = vec![];
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        args.push(self.expr()?);
+                        if self.eat(&Token::RParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Function(name, args))
+            }
+            token => bail!("Unexpected {:?} in XPath expression", token),
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Expr> {
+        self.expect(Token::LBracket)?;
+        let expr = self.expr()?;
+        self.expect(Token::RBracket)?;
+        Ok(expr)
+    }
+
+    fn location_path(&mut self) -> Result<Expr> {
+        let mut steps = vec![];
+        let start = match self.peek() {
+            Some(Token::Slash) => {
+                self.position += 1;
+                if self.starts_step() {
+                    self.relative_path(&mut steps)?;
+                }
+                PathStart::Root
+            }
+            Some(Token::DoubleSlash) => {
+                self.position += 1;
+                steps.push(descendant_or_self());
+                self.relative_path(&mut steps)?;
+                PathStart::Root
+            }
+            _ => {
+                self.relative_path(&mut steps)?;
+                PathStart::Context
+            }
+        };
+        Ok(Expr::Path(start, steps))
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Name(_) | Token::Dot | Token::DotDot | Token::At)
+        )
+    }
+
+    fn relative_path(&mut self, steps: &mut Vec<Step>) -> Result<()> {
+        steps.push(self.step()?);
+        loop {
+            match self.peek() {
+                Some(Token::Slash) => self.position += 1,
+                Some(Token::DoubleSlash) => {
+                    self.position += 1;
+                    steps.push(descendant_or_self());
+                }
+                _ => return Ok(()),
+            }
+            steps.push(self.step()?);
+        }
+    }
+
+    fn step(&mut self) -> Result<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfNode,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+
+        let axis = match (self.peek().cloned(), self.peek_at(1)) {
+            (Some(Token::At), _) => {
+                self.position += 1;
+                Axis::Attribute
+            }
+            (Some(Token::Name(name)), Some(Token::ColonColon)) => {
+                self.position += 2;
+                Axis::from_name(&name)?
+            }
+            _ => Axis::Child,
+        };
+        let test = match (self.peek().cloned(), self.peek_at(1)) {
+            (Some(Token::Name(name)), Some(Token::LParen)) if is_node_type(&name) => {
+                self.position += 2;
+                if name == "processing-instruction" {
+                    if let Some(Token::Literal(_)) = self.peek() {
+                        self.position += 1;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                match name.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    _ => NodeTest::None,
+                }
+            }
+            (Some(Token::Name(name)), _) => {
+                self.position += 1;
+                match name.split_once(':') {
+                    _ if name == "*" => NodeTest::Any(None),
+                    Some((prefix, "*")) => NodeTest::Any(Some(prefix.to_string())),
+                    Some((prefix, local)) => {
+                        NodeTest::Name(Some(prefix.to_string()), local.to_string())
+                    }
+                    None => NodeTest::Name(None, name),
+                }
+            }
+            (Some(token), _) => bail!("Unexpected {:?} in XPath expression, step expected", token),
+            (None, _) => bail!("Unexpected end of XPath expression, step expected"),
+        };
+        let mut predicates = vec![];
+        while self.peek() == Some(&Token::LBracket) {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step {
+            axis,
+            test,
+            predicates,
+        })
+    }
+}
+
+fn is_node_type(name: &str) -> bool {
+    matches!(name, "node" | "text" | "comment" | "processing-instruction")
+}
+
+/// The `//` abbreviation.
+fn descendant_or_self() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: vec![],
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum NodeKind {
+    Element,
+    Attribute(usize),
+    Text,
+}
+
+/// Node of the [`Document`] - element (incl. the root), its attribute or text. Ordered by the document order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct NodeRef {
+    /// Index of the (owner) element.
+    element: usize,
+    kind: NodeKind,
+}
+
+impl NodeRef {
+    fn element(element: usize) -> Self {
+        Self {
+            element,
+            kind: NodeKind::Element,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    /// Nodes in document order, without duplicates.
+    Nodes(Vec<NodeRef>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+struct Context {
+    node: NodeRef,
+    position: usize,
+    size: usize,
+}
+
+struct Entry<'a> {
+    /// The element, `None` for the root node.
+    node: Option<&'a XmlNode>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Index following the last descendant.
+    end: usize,
+}
+
+/// Elements of the data indexed in document order, the root node being the first one.
+struct Document<'a> {
+    entries: Vec<Entry<'a>>,
+    /// Namespaces by prefixes declared in the data, the first declaration wins.
+    namespaces: HashMap<String, String>,
+}
+
+impl<'a> Document<'a> {
+    fn new(roots: &'a [XmlNode], namespaces: HashMap<String, String>) -> Self {
+        let mut document = Self {
+            entries: vec![Entry {
+                node: None,
+                parent: None,
+                children: vec![],
+                end: 0,
+            }],
+            namespaces,
+        };
+        for root in roots {
+            let index = document.add(root, 0);
+            document.entries[0].children.push(index);
+        }
+        document.entries[0].end = document.entries.len();
+        document
+    }
+
+    fn add(&mut self, node: &'a XmlNode, parent: usize) -> usize {
+        for (prefix, namespace) in node.namespace_declarations.iter() {
+            if let Some(prefix) = prefix {
+                self.namespaces
+                    .entry(prefix.clone())
+                    .or_insert_with(|| namespace.clone());
+            }
+        }
+        let index = self.entries.len();
+        self.entries.push(Entry {
+            node: Some(node),
+            parent: Some(parent),
+            children: vec![],
+            end: 0,
+        });
+        for child in node.children.iter() {
+            let child_index = self.add(child, index);
+            self.entries[index].children.push(child_index);
+        }
+        self.entries[index].end = self.entries.len();
+        index
+    }
+
+    fn selected(&self, node: NodeRef) -> SelectedNode<'a> {
+        match (self.entries[node.element].node, node.kind) {
+            (None, _) => SelectedNode::Root,
+            (Some(element), NodeKind::Element) => SelectedNode::Element(element),
+            (Some(element), NodeKind::Attribute(index)) => {
+                SelectedNode::Attribute(element, &element.attributes[index])
+            }
+            (Some(element), NodeKind::Text) => SelectedNode::Text(element),
+        }
+    }
+
+    fn has_text(&self, element: usize) -> bool {
+        self.entries[element]
+            .node
+            .is_some_and(|x| x.text.as_ref().is_some_and(|x| !x.is_empty()))
+    }
+
+    /// The element with its text & descendants, in document order.
+    fn push_subtree(&self, element: usize, include_self: bool, nodes: &mut Vec<NodeRef>) {
+        if include_self {
+            nodes.push(NodeRef::element(element));
+        }
+        if self.has_text(element) {
+            nodes.push(NodeRef {
+                element,
+                kind: NodeKind::Text,
+            });
+        }
+        for child in self.entries[element].children.iter() {
+            self.push_subtree(*child, true, nodes);
+        }
+    }
+
+    /// Nodes of the `axis`, in order of the axis - reverse document order for the reverse axes.
+    fn axis(&self, axis: Axis, node: NodeRef) -> Vec<NodeRef> {
+        let element = node.element;
+        let on_element = node.kind == NodeKind::Element;
+        // parent of attributes & text is their element
+        let parent = match on_element {
+            true => self.entries[element].parent,
+            false => Some(element),
+        };
+        let mut nodes = vec![];
+        match axis {
+            Axis::SelfNode => nodes.push(node),
+            Axis::Child if on_element => {
+                if self.has_text(element) {
+                    nodes.push(NodeRef {
+                        element,
+                        kind: NodeKind::Text,
+                    });
+                }
+                nodes.extend(
+                    self.entries[element]
+                        .children
+                        .iter()
+                        .map(|x| NodeRef::element(*x)),
+                );
+            }
+            Axis::Descendant if on_element => self.push_subtree(element, false, &mut nodes),
+            Axis::DescendantOrSelf if on_element => self.push_subtree(element, true, &mut nodes),
+            Axis::DescendantOrSelf => nodes.push(node),
+            Axis::Attribute if on_element => {
+                if let Some(xml) = self.entries[element].node {
+                    nodes.extend((0..xml.attributes.len()).map(|index| NodeRef {
+                        element,
+                        kind: NodeKind::Attribute(index),
+                    }));
+                }
+            }
+            Axis::Parent => nodes.extend(parent.map(NodeRef::element)),
+            Axis::Ancestor | Axis::AncestorOrSelf => {
+                if axis == Axis::AncestorOrSelf {
+                    nodes.push(node);
+                }
+                let mut current = parent;
+                while let Some(index) = current {
+                    nodes.push(NodeRef::element(index));
+                    current = self.entries[index].parent;
+                }
+            }
+            Axis::FollowingSibling | Axis::PrecedingSibling if on_element => {
+                if let Some(parent) = parent {
+                    let siblings = &self.entries[parent].children;
+                    let position = siblings.iter().position(|x| *x == element).unwrap_or(0);
+                    match axis {
+                        Axis::FollowingSibling => nodes.extend(
+                            siblings[position + 1..]
+                                .iter()
+                                .map(|x| NodeRef::element(*x)),
+                        ),
+                        _ => nodes.extend(
+                            siblings[..position]
+                                .iter()
+                                .rev()
+                                .map(|x| NodeRef::element(*x)),
+                        ),
+                    }
+                }
+            }
+            Axis::Following => {
+                let start = match on_element {
+                    true => self.entries[element].end,
+                    false => element + 1,
+                };
+                for index in start..self.entries.len() {
+                    self.push_subtree_flat(index, &mut nodes);
+                }
+            }
+            Axis::Preceding => {
+                let ancestors = self.axis(Axis::Ancestor, node);
+                for index in (1..element).rev() {
+                    if ancestors.contains(&NodeRef::element(index)) {
+                        continue;
+                    }
+                    if self.has_text(index) {
+                        nodes.push(NodeRef {
+                            element: index,
+                            kind: NodeKind::Text,
+                        });
+                    }
+                    nodes.push(NodeRef::element(index));
+                }
+            }
+            _ => {}
+        }
+        nodes
+    }
+
+    /// The element & its text, without descendants.
+    fn push_subtree_flat(&self, element: usize, nodes: &mut Vec<NodeRef>) {
+        nodes.push(NodeRef::element(element));
+        if self.has_text(element) {
+            nodes.push(NodeRef {
+                element,
+                kind: NodeKind::Text,
+            });
+        }
+    }
+
+    fn namespace(&self, prefix: &str) -> Result<&str> {
+        match self.namespaces.get(prefix) {
+            Some(namespace) => Ok(namespace),
+            None => bail!(
+                "Namespace prefix \"{}\" is not declared in the data",
+                prefix
+            ),
+        }
+    }
+
+    fn matches(&self, test: &NodeTest, axis: Axis, node: NodeRef) -> Result<bool> {
+        let element = self.entries[node.element].node;
+        let (name, namespace) = match (test, node.kind, element) {
+            (NodeTest::Node, _, _) => return Ok(true),
+            (NodeTest::Text, kind, _) => return Ok(kind == NodeKind::Text),
+            (NodeTest::None, _, _) | (_, NodeKind::Text, _) | (_, _, None) => return Ok(false),
+            (_, NodeKind::Attribute(index), Some(element)) => {
+                let attribute = &element.attributes[index];
+                (&attribute.name, &attribute.namespace)
+            }
+            (_, NodeKind::Element, Some(element)) => {
+                if axis == Axis::Attribute {
+                    return Ok(false);
+                }
+                (&element.name, &element.namespace)
+            }
+        };
+        let namespace = namespace.as_deref();
+        Ok(match test {
+            NodeTest::Any(None) => true,
+            NodeTest::Any(Some(prefix)) => namespace == Some(self.namespace(prefix)?),
+            NodeTest::Name(None, local) => name == local,
+            NodeTest::Name(Some(prefix), local) => {
+                name == local && namespace == Some(self.namespace(prefix)?)
+            }
+            _ => false,
+        })
+    }
+
+    fn string_value(&self, node: NodeRef) -> String {
+        match self.selected(node) {
+            SelectedNode::Root => {
+                let mut text = String::new();
+                for child in self.entries[0].children.iter() {
+                    if let Some(child) = self.entries[*child].node {
+                        push_text(child, &mut text);
+                    }
+                }
+                text
+            }
+            selected => selected.string_value(),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expr, context: &Context) -> Result<Value> {
+        Ok(match expr {
+            Expr::Or(left, right) => {
+                Value::Boolean(self.boolean(left, context)? || self.boolean(right, context)?)
+            }
+            Expr::And(left, right) => {
+                Value::Boolean(self.boolean(left, context)? && self.boolean(right, context)?)
+            }
+            Expr::Compare(op, left, right) => {
+                let left = self.evaluate(left, context)?;
+                let right = self.evaluate(right, context)?;
+                Value::Boolean(self.compare(*op, &left, &right))
+            }
+            Expr::Arithmetic(op, left, right) => {
+                let left = self.number(left, context)?;
+                let right = self.number(right, context)?;
+                Value::Number(match op {
+                    ArithmeticOp::Add => left + right,
+                    ArithmeticOp::Subtract => left - right,
+                    ArithmeticOp::Multiply => left * right,
+                    ArithmeticOp::Divide => left / right,
+                    ArithmeticOp::Modulo => left % right,
+                })
+            }
+            Expr::Negate(expr) => Value::Number(-self.number(expr, context)?),
+            Expr::Union(left, right) => {
+                let mut nodes = self.nodes(left, context)?;
+                nodes.extend(self.nodes(right, context)?);
+                nodes.sort();
+                nodes.dedup();
+                Value::Nodes(nodes)
+            }
+            Expr::Path(start, steps) => {
+                let mut nodes = match start {
+                    PathStart::Root => vec![NodeRef::element(0)],
+                    PathStart::Context => vec![context.node],
+                    PathStart::Filter(expr) => self.nodes(expr, context)?,
+                };
+                for step in steps.iter() {
+                    nodes = self.step(step, &nodes)?;
+                }
+                Value::Nodes(nodes)
+            }
+            Expr::Filter(expr, predicates) => {
+                let mut nodes = self.nodes(expr, context)?;
+                for predicate in predicates.iter() {
+                    nodes = self.filter(nodes, predicate)?;
+                }
+                Value::Nodes(nodes)
+            }
+            Expr::Literal(literal) => Value::String(literal.clone()),
+            Expr::Number(number) => Value::Number(*number),
+            Expr::Function(name, args) => self.function(name, args, context)?,
+        })
+    }
+
+    fn nodes(&self, expr: &Expr, context: &Context) -> Result<Vec<NodeRef>> {
+        match self.evaluate(expr, context)? {
+            Value::Nodes(nodes) => Ok(nodes),
+            value => bail!("XPath node-set expected, found {:?}", value),
+        }
+    }
+
+    fn boolean(&self, expr: &Expr, context: &Context) -> Result<bool> {
+        Ok(self.to_boolean(&self.evaluate(expr, context)?))
+    }
+
+    fn number(&self, expr: &Expr, context: &Context) -> Result<f64> {
+        Ok(self.to_number(&self.evaluate(expr, context)?))
+    }
+
+    fn string(&self, expr: &Expr, context: &Context) -> Result<String> {
+        Ok(self.to_string(&self.evaluate(expr, context)?))
+    }
+
+    /// String of the optional function argument, defaulting to the context node.
+    fn string_arg(&self, arg: Option<&Expr>, context: &Context) -> Result<String> {
+        match arg {
+            Some(arg) => self.string(arg, context),
+            None => Ok(self.string_value(context.node)),
+        }
+    }
+
+    fn to_boolean(&self, value: &Value) -> bool {
+        match value {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::Boolean(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+        }
+    }
+
+    fn to_number(&self, value: &Value) -> f64 {
+        match value {
+            Value::Boolean(true) => 1.0,
+            Value::Boolean(false) => 0.0,
+            Value::Number(value) => *value,
+            value => string_to_number(&self.to_string(value)),
+        }
+    }
+
+    fn to_string(&self, value: &Value) -> String {
+        match value {
+            Value::Nodes(nodes) => nodes
+                .first()
+                .map(|x| self.string_value(*x))
+                .unwrap_or_default(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Number(value) => number_to_string(*value),
+            Value::String(value) => value.clone(),
+        }
+    }
+
+    fn step(&self, step: &Step, nodes: &[NodeRef]) -> Result<Vec<NodeRef>> {
+        let mut result = vec![];
+        for node in nodes.iter() {
+            let mut selected = vec![];
+            for candidate in self.axis(step.axis, *node) {
+                if self.matches(&step.test, step.axis, candidate)? {
+                    selected.push(candidate);
+                }
+            }
+            for predicate in step.predicates.iter() {
+                selected = self.filter(selected, predicate)?;
+            }
+            result.extend(selected);
+        }
+        result.sort();
+        result.dedup();
+        Ok(result)
+    }
+
+    /// Nodes of the `predicate`, numbers are compared with the position.
+    fn filter(&self, nodes: Vec<NodeRef>, predicate: &Expr) -> Result<Vec<NodeRef>> {
+        let size = nodes.len();
+        let mut result = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            let matched = match self.evaluate(predicate, &context)? {
+                Value::Number(number) => number == context.position as f64,
+                value => self.to_boolean(&value),
+            };
+            if matched {
+                result.push(node);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Comparison of [XPath - section 3.4](https://www.w3.org/TR/1999/REC-xpath-19991116/#booleans) -
+    /// node-sets match if any of their nodes does.
+    fn compare(&self, op: CompareOp, left: &Value, right: &Value) -> bool {
+        let string = |node: &NodeRef| Value::String(self.string_value(*node));
+        match (left, right) {
+            (Value::Nodes(left), Value::Nodes(right)) => left.iter().any(|x| {
+                let left = string(x);
+                right
+                    .iter()
+                    .any(|x| self.compare_atoms(op, &left, &string(x)))
+            }),
+            (Value::Nodes(nodes), Value::Boolean(_)) => {
+                self.compare_atoms(op, &Value::Boolean(!nodes.is_empty()), right)
+            }
+            (Value::Boolean(_), Value::Nodes(nodes)) => {
+                self.compare_atoms(op, left, &Value::Boolean(!nodes.is_empty()))
+            }
+            (Value::Nodes(nodes), _) => nodes
+                .iter()
+                .any(|x| self.compare_atoms(op, &string(x), right)),
+            (_, Value::Nodes(nodes)) => nodes
+                .iter()
+                .any(|x| self.compare_atoms(op, left, &string(x))),
+            _ => self.compare_atoms(op, left, right),
+        }
+    }
+
+    fn compare_atoms(&self, op: CompareOp, left: &Value, right: &Value) -> bool {
+        let either = |f: fn(&Value) -> bool| f(left) || f(right);
+        match op {
+            CompareOp::Eq | CompareOp::Neq => {
+                let equal = if either(|x| matches!(x, Value::Boolean(_))) {
+                    self.to_boolean(left) == self.to_boolean(right)
+                } else if either(|x| matches!(x, Value::Number(_))) {
+                    self.to_number(left) == self.to_number(right)
+                } else {
+                    self.to_string(left) == self.to_string(right)
+                };
+                equal == (op == CompareOp::Eq)
+            }
+            _ => {
+                let (left, right) = (self.to_number(left), self.to_number(right));
+                match op {
+                    CompareOp::Lt => left < right,
+                    CompareOp::Le => left <= right,
+                    CompareOp::Gt => left > right,
+                    _ => left >= right,
+                }
+            }
+        }
+    }
+
+    /// Result of the `local-name()`, `name()` or `namespace-uri()` function of the `node`.
+    fn node_name(&self, function: &str, node: NodeRef) -> String {
+        let (name, qualified_name, namespace) = match self.selected(node) {
+            SelectedNode::Element(element) => (
+                element.name.clone(),
+                element.qualified_name(),
+                element.namespace.clone(),
+            ),
+            SelectedNode::Attribute(_, attribute) => (
+                attribute.name.clone(),
+                attribute.qualified_name(),
+                attribute.namespace.clone(),
+            ),
+            _ => return String::new(),
+        };
+        match function {
+            "local-name" => name,
+            "name" => qualified_name,
+            _ => namespace.unwrap_or_default(),
+        }
+    }
+
+    /// Functions of the [XPath core function library](https://www.w3.org/TR/1999/REC-xpath-19991116/#corelib).
+    fn function(&self, name: &str, args: &[Expr], context: &Context) -> Result<Value> {
+        let arity = |min: usize, max: usize| {
+            if args.len() < min || args.len() > max {
+                bail!("Wrong number of arguments of XPath function {}()", name);
+            }
+            Ok(())
+        };
+        Ok(match name {
+            "last" => {
+                arity(0, 0)?;
+                Value::Number(context.size as f64)
+            }
+            "position" => {
+                arity(0, 0)?;
+                Value::Number(context.position as f64)
+            }
+            "count" => {
+                arity(1, 1)?;
+                Value::Number(self.nodes(&args[0], context)?.len() as f64)
+            }
+            "local-name" | "name" | "namespace-uri" => {
+                arity(0, 1)?;
+                let node = match args.first() {
+                    Some(arg) => self.nodes(arg, context)?.first().copied(),
+                    None => Some(context.node),
+                };
+                Value::String(node.map(|x| self.node_name(name, x)).unwrap_or_default())
+            }
+            "string" => {
+                arity(0, 1)?;
+                Value::String(self.string_arg(args.first(), context)?)
+            }
+            "concat" => {
+                arity(2, usize::MAX)?;
+                let parts = args
+                    .iter()
+                    .map(|x| self.string(x, context))
+                    .collect::<Result<Vec<_>>>()?;
+                Value::String(parts.concat())
+            }
+            "starts-with" | "contains" | "substring-before" | "substring-after" => {
+                arity(2, 2)?;
+                let text = self.string(&args[0], context)?;
+                let pattern = self.string(&args[1], context)?;
+                let found = text.find(&pattern);
+                match name {
+                    "starts-with" => Value::Boolean(text.starts_with(&pattern)),
+                    "contains" => Value::Boolean(found.is_some()),
+                    "substring-before" => {
+                        Value::String(found.map(|x| text[..x].to_string()).unwrap_or_default())
+                    }
+                    _ => Value::String(
+                        found
+                            .map(|x| text[x + pattern.len()..].to_string())
+                            .unwrap_or_default(),
+                    ),
+                }
+            }
+            "substring" => {
+                arity(2, 3)?;
+                let text = self.string(&args[0], context)?;
+                let start = round(self.number(&args[1], context)?);
+                let end = match args.get(2) {
+                    Some(length) => start + round(self.number(length, context)?),
+                    None => f64::INFINITY,
+                };
+                let substring = text
+                    .chars()
+                    .enumerate()
+                    .filter(|(index, _)| {
+                        let position = (*index + 1) as f64;
+                        position >= start && position < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect();
+                Value::String(substring)
+            }
+            "string-length" => {
+                arity(0, 1)?;
+                Value::Number(self.string_arg(args.first(), context)?.chars().count() as f64)
+            }
+            "normalize-space" => {
+                arity(0, 1)?;
+                let text = self.string_arg(args.first(), context)?;
+                Value::String(text.split_whitespace().collect::<Vec<_>>().join(" "))
+            }
+            "translate" => {
+                arity(3, 3)?;
+                let text = self.string(&args[0], context)?;
+                let from: Vec<char> = self.string(&args[1], context)?.chars().collect();
+                let to: Vec<char> = self.string(&args[2], context)?.chars().collect();
+                let translated = text
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|x| *x == c) {
+                        Some(index) => to.get(index).copied(),
+                        None => Some(c),
+                    })
+                    .collect();
+                Value::String(translated)
+            }
+            "boolean" => {
+                arity(1, 1)?;
+                Value::Boolean(self.boolean(&args[0], context)?)
+            }
+            "not" => {
+                arity(1, 1)?;
+                Value::Boolean(!self.boolean(&args[0], context)?)
+            }
+            "true" | "false" => {
+                arity(0, 0)?;
+                Value::Boolean(name == "true")
+            }
+            "number" => {
+                arity(0, 1)?;
+                Value::Number(match args.first() {
+                    Some(arg) => self.number(arg, context)?,
+                    None => string_to_number(&self.string_value(context.node)),
+                })
+            }
+            "sum" => {
+                arity(1, 1)?;
+                let nodes = self.nodes(&args[0], context)?;
+                Value::Number(
+                    nodes
+                        .iter()
+                        .map(|x| string_to_number(&self.string_value(*x)))
+                        .sum(),
+                )
+            }
+            "floor" | "ceiling" | "round" => {
+                arity(1, 1)?;
+                let number = self.number(&args[0], context)?;
+                Value::Number(match name {
+                    "floor" => number.floor(),
+                    "ceiling" => number.ceil(),
+                    _ => round(number),
+                })
+            }
+            "id" | "lang" => bail!("XPath function {}() is not supported", name),
+            _ => bail!("Unknown XPath function {}()", name),
+        })
+    }
+}
+
+/// Rounding of the XPath `round()` function - halves towards positive infinity.
+fn round(number: f64) -> f64 {
+    match number.is_finite() {
+        true => (number + 0.5).floor(),
+        false => number,
+    }
+}
+
+/// Number of the XPath `number()` function - decimal with optional minus sign, `NaN` otherwise.
+fn string_to_number(text: &str) -> f64 {
+    let text = text.trim();
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let valid = !digits.is_empty()
+        && digits != "."
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    match valid {
+        true => text.parse().unwrap_or(f64::NAN),
+        false => f64::NAN,
+    }
+}
+
+/// String of the XPath `string()` function - integers without decimal point.
+fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        "NaN".to_string()
+    } else if number.is_infinite() {
+        match number > 0.0 {
+            true => "Infinity".to_string(),
+            false => "-Infinity".to_string(),
+        }
+    } else if number == number.trunc() && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        number.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = r#"
+<interfaces xmlns="urn:example:if">
+  <interface><name>eth0</name><mtu>1500</mtu><type>ethernet</type><enabled>true</enabled></interface>
+  <interface><name>eth1</name><mtu>9000</mtu><enabled>false</enabled></interface>
+  <interface><name>lo</name><mtu>65536</mtu><description lang="en">  loop  back </description></interface>
+</interfaces>
+<system xmlns="urn:example:sys" xmlns:t="urn:example:types">
+  <hostname>r1</hostname><dns>1.1.1.1</dns><dns>8.8.8.8</dns><t:counter>5</t:counter>
+</system>"#;
+
+    fn select(xpath: XPath) -> Result<Vec<String>> {
+        let data = XmlNode::parse_fragment(DATA)?;
+        Ok(match xpath.select(&data)? {
+            Selection::Nodes(nodes) => nodes.iter().map(|x| x.string_value()).collect(),
+            value => vec![value.string_value()],
+        })
+    }
+
+    /// String values of the selected nodes, or the single value of other results.
+    fn values(expression: &str) -> Vec<String> {
+        select(XPath::parse(expression).unwrap())
+            .unwrap_or_else(|err| panic!("{}: {:?}", expression, err))
+    }
+
+    fn check(cases: &[(&str, &[&str])]) {
+        for (expression, expected) in cases {
+            assert_eq!(values(expression), *expected, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn axes() {
+        check(&[
+            ("/interfaces/interface/name", &["eth0", "eth1", "lo"]),
+            ("//dns", &["1.1.1.1", "8.8.8.8"]),
+            ("/descendant-or-self::hostname", &["r1"]),
+            ("//interface[2]/following-sibling::interface/name", &["lo"]),
+            (
+                "//interface[3]/preceding-sibling::interface/name",
+                &["eth0", "eth1"],
+            ),
+            ("//interface[1]/following::dns", &["1.1.1.1", "8.8.8.8"]),
+            ("//hostname/preceding::name", &["eth0", "eth1", "lo"]),
+            ("//mtu[. > 9000]/parent::interface/name", &["lo"]),
+            ("//mtu[. > 9000]/../name", &["lo"]),
+            ("name(//hostname/ancestor::*)", &["system"]),
+            ("count(//hostname/ancestor-or-self::*)", &["2"]),
+            ("count(//interface[1]/descendant::*)", &["4"]),
+            ("//interface[name='lo']/self::interface/mtu", &["65536"]),
+            ("//interface/child::name[. = 'eth1']", &["eth1"]),
+            ("//description/@lang", &["en"]),
+            ("//description/attribute::lang", &["en"]),
+            ("//name/text()", &["eth0", "eth1", "lo"]),
+            ("count(/interfaces/node())", &["3"]),
+            ("count(//interface/*)", &["10"]),
+        ]);
+    }
+
+    #[test]
+    fn predicates() {
+        check(&[
+            ("//interface[mtu > 1500]/name", &["eth1", "lo"]),
+            ("//interface[not(enabled)]/name", &["lo"]),
+            (
+                "//interface[enabled='false' or description]/name",
+                &["eth1", "lo"],
+            ),
+            ("//interface[enabled][mtu < 9000]/name", &["eth0"]),
+            ("//interface[description/@lang = 'en']/name", &["lo"]),
+            ("//interface[count(*) = 4]/name", &["eth0"]),
+            ("//interface[starts-with(name, 'eth')][2]/name", &["eth1"]),
+        ]);
+    }
+
+    #[test]
+    fn position_and_last() {
+        check(&[
+            ("//interface[1]/name", &["eth0"]),
+            ("//interface[last()]/name", &["lo"]),
+            ("//interface[last() - 1]/name", &["eth1"]),
+            ("//interface[position() < 3]/name", &["eth0", "eth1"]),
+            ("//interface[position() = last()]/name", &["lo"]),
+            ("//dns[2]", &["8.8.8.8"]),
+            ("(//dns)[last()]", &["8.8.8.8"]),
+            ("(//name | //dns)[4]", &["1.1.1.1"]),
+            ("//interface[4]/name", &[]),
+            // reverse axis counts positions from the context node
+            (
+                "//interface[3]/preceding-sibling::interface[1]/name",
+                &["eth1"],
+            ),
+            ("//hostname/ancestor::*[1]/dns[1]", &["1.1.1.1"]),
+        ]);
+    }
+
+    #[test]
+    fn functions() {
+        check(&[
+            ("count(//interface)", &["3"]),
+            ("sum(//mtu)", &["76036"]),
+            ("concat(//hostname, '-', //interface[1]/name)", &["r1-eth0"]),
+            ("string-length(//hostname)", &["2"]),
+            ("normalize-space(//description)", &["loop back"]),
+            ("substring('12345', 1.5, 2.6)", &["234"]),
+            ("substring('12345', 0, 3)", &["12"]),
+            ("substring('12345', 2)", &["2345"]),
+            ("substring-before('1999/04/01', '/')", &["1999"]),
+            ("substring-after('1999/04/01', '/')", &["04/01"]),
+            ("translate('bar', 'abc', 'ABC')", &["BAr"]),
+            ("translate('--aaa--', 'abc-', 'ABC')", &["AAA"]),
+            ("starts-with(//hostname, 'r')", &["true"]),
+            ("contains(//description, 'loop')", &["true"]),
+            ("string(//mtu)", &["1500"]),
+            ("number('12.5') * 2", &["25"]),
+            ("number('abc')", &["NaN"]),
+            ("boolean(//missing)", &["false"]),
+            ("boolean('x') and not(false()) and true()", &["true"]),
+            ("round(2.5)", &["3"]),
+            ("round(-2.5)", &["-2"]),
+            ("floor(-1.5)", &["-2"]),
+            ("ceiling(1.2)", &["2"]),
+            ("local-name(/interfaces)", &["interfaces"]),
+            ("name(//t:counter)", &["t:counter"]),
+            ("namespace-uri(//hostname)", &["urn:example:sys"]),
+            ("//interface[string-length(name) = 2]/mtu", &["65536"]),
+        ]);
+    }
+
+    #[test]
+    fn union() {
+        check(&[
+            // document order, regardless of the order of operands
+            ("//hostname | //interface[1]/name", &["eth0", "r1"]),
+            ("count(//dns | //dns)", &["2"]),
+            ("count(//mtu | //interface/mtu | //name)", &["6"]),
+            ("(//dns | //hostname)[1]", &["r1"]),
+        ]);
+    }
+
+    #[test]
+    fn comparisons() {
+        check(&[
+            ("1 + 2 * 3 - -1", &["8"]),
+            ("7 mod 3", &["1"]),
+            ("1 div 0", &["Infinity"]),
+            ("-1 div 0", &["-Infinity"]),
+            ("0.1 + 0.2 = 0.3", &["false"]),
+            ("2 >= 2 and 1 <= 1", &["true"]),
+            // node-set compared by any of its nodes
+            ("//mtu = 9000", &["true"]),
+            ("//mtu != 9000", &["true"]),
+            ("//mtu > 60000", &["true"]),
+            ("//mtu < 1000", &["false"]),
+            ("//dns = '8.8.8.8'", &["true"]),
+            ("//interface/name = //dns", &["false"]),
+            ("//enabled = true()", &["true"]),
+            ("//missing = false()", &["true"]),
+            // strings compared as numbers by relational operators
+            ("'10' < '9'", &["false"]),
+            ("'2' < '10'", &["true"]),
+            ("'abc' < 'abd'", &["false"]),
+            ("'abc' = 'abc'", &["true"]),
+            ("1 = '1.0'", &["true"]),
+            ("true() = 'x'", &["true"]),
+        ]);
+    }
+
+    #[test]
+    fn namespace_prefixes() {
+        // bound by the declaration in data
+        check(&[("//t:counter", &["5"])]);
+        // bound explicitly, taking precedence over the data
+        let xpath = XPath::parse("//s:hostname").unwrap();
+        assert_eq!(
+            select(xpath.with_namespace("s", "urn:example:sys")).unwrap(),
+            ["r1"]
+        );
+        let xpath = XPath::parse("//t:counter").unwrap();
+        assert!(select(xpath.with_namespace("t", "urn:other"))
+            .unwrap()
+            .is_empty());
+        let xpath = XPath::parse("/s:interfaces/s:interface").unwrap();
+        assert!(select(xpath.with_namespace("s", "urn:example:sys"))
+            .unwrap()
+            .is_empty());
+        // names without prefix match any namespace
+        check(&[("//counter", &["5"]), ("count(/*)", &["2"])]);
+
+        let err = select(XPath::parse("//x:hostname").unwrap()).unwrap_err();
+        assert!(err.to_string().contains("x"), "{}", err);
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        for expression in [
+            "",
+            "//",
+            "/interfaces[",
+            "/interfaces]",
+            "//interface[]",
+            "'unterminated",
+            "1.2.3",
+            "count(",
+            "count(//a,)",
+            "$variable",
+            "//a/namespace::b",
+            "//a/bogus::b",
+            "//a/@",
+            "x:",
+            "a ! b",
+            "1 +",
+        ] {
+            assert!(XPath::parse(expression).is_err(), "{:?}", expression);
+        }
+
+        for expression in [
+            "unknown()",
+            "id('x')",
+            "lang('en')",
+            "count()",
+            "count(//a, //b)",
+            "last(1)",
+            "concat('a')",
+            "1 | 2",
+            "count('x')",
+            "('a')[1]",
+        ] {
+            let xpath = XPath::parse(expression).unwrap();
+            assert!(select(xpath).is_err(), "{:?}", expression);
+        }
+    }
+}
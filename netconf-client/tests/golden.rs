@@ -0,0 +1,984 @@
+//! Golden-file tests of the NETCONF messages serialization & parsing.
+//!
+//! - `to_raw_xml()` of every request is compared with its pretty-printed golden file in `golden/requests/`,
+//!   checked to round-trip through [`XmlNode`], and validated by `xmllint` (if installed)
+//!   against the RFC 6241 (Appendix B) schema in `golden/schema/`.
+//! - `validate_request()` of requests reports all the violations of server capabilities.
+//! - Vendor replies in `golden/replies/<vendor>/` are parsed by the corresponding response types.
+//!
+//! After an intended change of the requests serialization, regenerate the golden files with
+//! `GOLDEN_UPDATE=1 cargo test -p netconf-client --test golden`, and review the diff.
+
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use netconf_client::{
+    messages::{
+        cancel_commit::CancelCommitRequest,
+        close_session::CloseSessionRequest,
+        commit::{CommitRequest, CommitType, ConfirmedCommitParams},
+        copy_config::CopyConfigRequest,
+        delete_config::DeleteConfigRequest,
+        discard_changes::DiscardChangesRequest,
+        edit_config::{
+            DefaultOperation, EditConfigContent, EditConfigParams, EditConfigRequest, ErrorOption,
+            TestOption,
+        },
+        get::{GetRequest, GetResponse},
+        get_config::{GetConfigRequest, GetConfigResponse},
+        hello::{HelloRequest, HelloResponse},
+        kill_session::KillSessionRequest,
+        lock::LockRequest,
+        raw_to_pretty_xml,
+        unlock::UnlockRequest,
+        validate::{ValidateRequest, ValidateSource},
+        FullResponse, NetconfRequest, NetconfResponse, ToRawXml,
+    },
+    subtree::{FilterNode, SubtreeFilter},
+    types::{
+        Capability, ConfigWaypoint, Datastore, ErrorSeverity, ErrorType, Filter, FilterPayload,
+        RpcAttribute, RpcError, RpcReply, ServerCapabilities, SimpleResponse, UrlCapability,
+        WithDefaultsCapability, WithDefaultsMode, XmlNode,
+    },
+    xpath::{SelectedNode, Selection, XPath},
+    BaseCapability,
+};
+
+const NETCONF_XMLNS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+const MESSAGE_ID: &str = "101";
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn message_id() -> String {
+    MESSAGE_ID.to_string()
+}
+
+fn filter(filter: Filter) -> Option<FilterPayload> {
+    Some(FilterPayload {
+        filter,
+        namespaces: vec![(
+            "xmlns:t".to_string(),
+            "http://example.com/schema/1.2/config".to_string(),
+        )],
+    })
+}
+
+fn edit_params(target: Datastore, config: EditConfigContent) -> EditConfigParams {
+    EditConfigParams {
+        target,
+        default_operation: None,
+        test_option: None,
+        error_option: None,
+        config,
+    }
+}
+
+/// Golden file name & the raw XML of every request variant.
+fn request_cases() -> Vec<(&'static str, String)> {
+    let cases: Vec<(&str, anyhow::Result<String>)> = vec![
+        (
+            "hello",
+            HelloRequest::new(vec![Capability::Base, Capability::Base11]).to_raw_xml(),
+        ),
+        ("get", GetRequest::new(message_id(), None).to_raw_xml()),
+        (
+            "get-subtree-filter",
+            SubtreeFilter::new()
+                .with_namespace("t", "http://example.com/schema/1.2/config")
+                .with_node(
+                    FilterNode::containment(
+                        "top",
+                        [FilterNode::containment(
+                            "users",
+                            [FilterNode::containment(
+                                "user",
+                                [FilterNode::content_match("name", "fred")],
+                            )],
+                        )],
+                    )
+                    .with_namespace("http://example.com/schema/1.2/config"),
+                )
+                .to_payload()
+                .and_then(|x| GetRequest::new(message_id(), Some(x)).to_raw_xml()),
+        ),
+        (
+            "get-with-attributes",
+            GetRequest::new(message_id(), None)
+                .with_attributes(vec![
+                    RpcAttribute::new("user-id", "fred"),
+                    RpcAttribute::namespaced(
+                        "ex",
+                        "http://example.net/content/1.0",
+                        "app",
+                        "a<b & \"c\"",
+                    ),
+                ])
+                .to_raw_xml(),
+        ),
+        (
+            "get-config",
+            GetConfigRequest::new(message_id(), Datastore::Running, None).to_raw_xml(),
+        ),
+        (
+            "get-config-xpath-filter",
+            GetConfigRequest::new(
+                message_id(),
+                Datastore::Candidate,
+                filter(Filter::Xpath(
+                    "/t:top/t:users/t:user[t:name='fred' and t:id<100]".to_string(),
+                )),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "edit-config",
+            EditConfigRequest::new(
+                message_id(),
+                edit_params(
+                    Datastore::Running,
+                    EditConfigContent::Config(
+                        r#"<top xmlns="http://example.com/schema/1.2/config"><interface><name>Ethernet0/0</name><mtu>1500</mtu></interface></top>"#
+                            .to_string(),
+                    ),
+                ),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "edit-config-options",
+            EditConfigRequest::new(
+                message_id(),
+                EditConfigParams {
+                    default_operation: Some(DefaultOperation::None),
+                    test_option: Some(TestOption::TestThenSet),
+                    error_option: Some(ErrorOption::RollbackOnError),
+                    ..edit_params(
+                        Datastore::Candidate,
+                        EditConfigContent::Config(
+                            r#"<top xmlns="http://example.com/schema/1.2/config" xmlns:xc="urn:ietf:params:xml:ns:netconf:base:1.0"><interface xc:operation="delete"><name>Ethernet0/0</name></interface></top>"#
+                                .to_string(),
+                        ),
+                    )
+                },
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "edit-config-url",
+            EditConfigRequest::new(
+                message_id(),
+                edit_params(
+                    Datastore::Candidate,
+                    EditConfigContent::Url("ftp://example.com/edit.xml?user=fred&pass=x".to_string()),
+                ),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "copy-config",
+            CopyConfigRequest::new(
+                message_id(),
+                ConfigWaypoint::Datastore(Datastore::Startup),
+                ConfigWaypoint::Datastore(Datastore::Running),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "copy-config-url",
+            CopyConfigRequest::new(
+                message_id(),
+                ConfigWaypoint::Url("file:///backup/running.xml".to_string()),
+                ConfigWaypoint::Datastore(Datastore::Running),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "delete-config",
+            DeleteConfigRequest::new(message_id(), ConfigWaypoint::Datastore(Datastore::Startup))
+                .to_raw_xml(),
+        ),
+        (
+            "delete-config-url",
+            DeleteConfigRequest::new(
+                message_id(),
+                ConfigWaypoint::Url("file:///backup/old.xml".to_string()),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "lock",
+            LockRequest::new(message_id(), Datastore::Candidate).to_raw_xml(),
+        ),
+        (
+            "unlock",
+            UnlockRequest::new(message_id(), Datastore::Candidate).to_raw_xml(),
+        ),
+        (
+            "close-session",
+            CloseSessionRequest::new(message_id()).to_raw_xml(),
+        ),
+        (
+            "kill-session",
+            KillSessionRequest::new(message_id(), 4).to_raw_xml(),
+        ),
+        (
+            "commit",
+            CommitRequest::new(message_id(), CommitType::Plain).to_raw_xml(),
+        ),
+        (
+            "commit-confirmed",
+            CommitRequest::new(
+                message_id(),
+                CommitType::Confirmed(ConfirmedCommitParams {
+                    confirm_timeout: Some(120),
+                    persist: Some("release-1".to_string()),
+                    persist_id: None,
+                }),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "commit-confirmed-persist-id",
+            CommitRequest::new(
+                message_id(),
+                CommitType::Confirmed(ConfirmedCommitParams {
+                    confirm_timeout: None,
+                    persist: None,
+                    persist_id: Some("release-1".to_string()),
+                }),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "cancel-commit",
+            CancelCommitRequest::new(message_id(), None).to_raw_xml(),
+        ),
+        (
+            "cancel-commit-persist-id",
+            CancelCommitRequest::new(message_id(), Some(42)).to_raw_xml(),
+        ),
+        (
+            "discard-changes",
+            DiscardChangesRequest::new(message_id()).to_raw_xml(),
+        ),
+        (
+            "validate",
+            ValidateRequest::new(message_id(), ValidateSource::Datastore(Datastore::Candidate))
+                .to_raw_xml(),
+        ),
+        (
+            "validate-config",
+            ValidateRequest::new(
+                message_id(),
+                ValidateSource::Config(
+                    r#"<top xmlns="http://example.com/schema/1.2/config"><mtu>1500</mtu></top>"#
+                        .to_string(),
+                ),
+            )
+            .to_raw_xml(),
+        ),
+        (
+            "validate-url",
+            ValidateRequest::new(
+                message_id(),
+                ValidateSource::Url("https://example.com/config.xml?v=1&w=2".to_string()),
+            )
+            .to_raw_xml(),
+        ),
+    ];
+
+    cases
+        .into_iter()
+        .map(|(name, raw)| {
+            let raw = raw.unwrap_or_else(|e| panic!("{}: serialization failed: {:?}", name, e));
+            (name, raw)
+        })
+        .collect()
+}
+
+#[test]
+fn requests_match_golden_files() {
+    let dir = golden_dir().join("requests");
+    let update = std::env::var_os("GOLDEN_UPDATE").is_some();
+    let mut mismatches = vec![];
+
+    let cases = request_cases();
+    for (name, raw) in &cases {
+        let path = dir.join(format!("{}.xml", name));
+        let actual = raw_to_pretty_xml(raw).unwrap() + "\n";
+        if update {
+            std::fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => mismatches.push(format!(
+                "{}:\n--- expected\n{}--- actual\n{}",
+                path.display(),
+                expected,
+                actual
+            )),
+            Err(e) => mismatches.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    let names: BTreeSet<String> = cases.iter().map(|(x, _)| format!("{}.xml", x)).collect();
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let file_name = entry.unwrap().file_name().to_string_lossy().to_string();
+        if !names.contains(&file_name) {
+            mismatches.push(format!("{}: stale golden file", file_name));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Golden files mismatch (run with GOLDEN_UPDATE=1 to regenerate):\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn requests_round_trip() {
+    for (name, raw) in request_cases() {
+        let node = XmlNode::parse(&raw).unwrap_or_else(|e| panic!("{}: {:?}\n{}", name, e, raw));
+        assert_eq!(node.namespace.as_deref(), Some(NETCONF_XMLNS), "{}", name);
+        match node.name.as_str() {
+            "hello" => {}
+            "rpc" => {
+                assert_eq!(node.attribute("message-id"), Some(MESSAGE_ID), "{}", name);
+                assert_eq!(node.children.len(), 1, "{}: single operation", name);
+                assert_eq!(
+                    node.children[0].namespace.as_deref(),
+                    Some(NETCONF_XMLNS),
+                    "{}",
+                    name
+                );
+            }
+            other => panic!("{}: unexpected root element <{}>", name, other),
+        }
+
+        let reparsed = XmlNode::parse(&node.to_xml().unwrap()).unwrap();
+        assert_eq!(reparsed, node, "{}", name);
+    }
+}
+
+#[test]
+fn requests_conform_to_schema() {
+    let schema = golden_dir().join("schema/netconf-operations.xsd");
+    if Command::new("xmllint").arg("--version").output().is_err() {
+        eprintln!("xmllint not found, skipping XSD validation of requests");
+        return;
+    }
+
+    let mut failures = vec![];
+    for (name, raw) in request_cases() {
+        let mut child = Command::new("xmllint")
+            .args(["--noout", "--nonet", "--schema"])
+            .arg(&schema)
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(raw.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        if !output.status.success() {
+            failures.push(format!(
+                "{}: {}\n{}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim(),
+                raw
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// Violations of the request validation against the server `capabilities`, empty if valid.
+fn violations(request: &impl NetconfRequest, capabilities: &[Capability]) -> Vec<String> {
+    match request.validate_request(capabilities) {
+        Ok(()) => vec![],
+        Err(err) => err
+            .to_string()
+            .lines()
+            .skip(1)
+            .map(|x| x.trim_start_matches(" - ").to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn requests_validated() {
+    use Capability::*;
+    let url = |schemes: &[&str]| {
+        Url(UrlCapability::new(
+            schemes.iter().map(|x| x.to_string()).collect(),
+        ))
+    };
+    let xpath = || filter(Filter::Xpath("/t:top".to_string()));
+    let config = || EditConfigContent::Config("<top/>".to_string());
+    let confirmed = |persist: Option<&str>, persist_id: Option<&str>| {
+        CommitType::Confirmed(ConfirmedCommitParams {
+            confirm_timeout: None,
+            persist: persist.map(str::to_string),
+            persist_id: persist_id.map(str::to_string),
+        })
+    };
+    let datastore = ConfigWaypoint::Datastore;
+
+    // violations of the request against server capabilities, the expected ones
+    let cases: Vec<(&str, Vec<String>, Vec<&str>)> = vec![
+        (
+            "get",
+            violations(&GetRequest::new(message_id(), None), &[]),
+            vec![],
+        ),
+        (
+            "get xpath",
+            violations(&GetRequest::new(message_id(), xpath()), &[]),
+            vec!["parameter \"xpath-filter\" requires capability :xpath:1.0"],
+        ),
+        (
+            "get-config candidate xpath",
+            violations(
+                &GetConfigRequest::new(message_id(), Datastore::Candidate, xpath()),
+                &[Candidate, XPath],
+            ),
+            vec![],
+        ),
+        (
+            "get-config operational",
+            violations(
+                &GetConfigRequest::new(message_id(), Datastore::Operational, None),
+                &[],
+            ),
+            vec!["datastore \"operational\" as <source> is not allowed - the operational state datastore is accessible only via NMDA operations"],
+        ),
+        (
+            "edit-config running",
+            violations(
+                &EditConfigRequest::new(message_id(), edit_params(Datastore::Running, config())),
+                &[WritableRunning],
+            ),
+            vec![],
+        ),
+        (
+            "edit-config running options",
+            violations(
+                &EditConfigRequest::new(
+                    message_id(),
+                    EditConfigParams {
+                        test_option: Some(TestOption::TestOnly),
+                        error_option: Some(ErrorOption::RollbackOnError),
+                        ..edit_params(Datastore::Running, config())
+                    },
+                ),
+                &[Validate],
+            ),
+            vec![
+                "datastore \"running\" as <target> requires capability :writable-running:1.0",
+                "parameter \"rollback-on-error\" requires capability :rollback-on-error:1.0",
+                "parameter \"test-only\" requires capability :validate:1.1",
+            ],
+        ),
+        (
+            "edit-config url",
+            violations(
+                &EditConfigRequest::new(
+                    message_id(),
+                    edit_params(
+                        Datastore::Candidate,
+                        EditConfigContent::Url("ftp://example.com/edit.xml".to_string()),
+                    ),
+                ),
+                &[Candidate, url(&["file", "https"])],
+            ),
+            vec!["URL \"ftp://example.com/edit.xml\" as <url> has scheme \"ftp\" not included in server URL capability schemes: [file, https]"],
+        ),
+        (
+            "copy-config startup",
+            violations(
+                &CopyConfigRequest::new(message_id(), datastore(Datastore::Startup), datastore(Datastore::Running)),
+                &[Startup],
+            ),
+            vec![],
+        ),
+        (
+            "copy-config url & inline target",
+            violations(
+                &CopyConfigRequest::new(
+                    message_id(),
+                    ConfigWaypoint::Config("<top/>".to_string()),
+                    ConfigWaypoint::Url("file:///backup.xml".to_string()),
+                ),
+                &[],
+            ),
+            vec![
+                "parameter \"target\" is not allowed - inline <config> is valid only as the <source>",
+                "URL \"file:///backup.xml\" as <source> requires capability :url:1.0",
+            ],
+        ),
+        (
+            "delete-config running",
+            violations(
+                &DeleteConfigRequest::new(message_id(), datastore(Datastore::Running)),
+                &[WritableRunning],
+            ),
+            vec!["datastore \"running\" as <target> is not allowed - the running configuration datastore cannot be deleted"],
+        ),
+        (
+            "lock running",
+            violations(&LockRequest::new(message_id(), Datastore::Running), &[]),
+            vec!["datastore \"running\" as <target> requires capability :writable-running:1.0 or :candidate:1.0"],
+        ),
+        (
+            "lock running writable",
+            violations(
+                &LockRequest::new(message_id(), Datastore::Running),
+                &[WritableRunning],
+            ),
+            vec![],
+        ),
+        (
+            "unlock running of candidate",
+            violations(
+                &UnlockRequest::new(message_id(), Datastore::Running),
+                &[Candidate],
+            ),
+            vec![],
+        ),
+        (
+            "lock intended",
+            violations(&LockRequest::new(message_id(), Datastore::Intended), &[]),
+            vec!["datastore \"intended\" as <target> is not allowed - the intended configuration datastore is read-only"],
+        ),
+        (
+            "unlock startup",
+            violations(&UnlockRequest::new(message_id(), Datastore::Startup), &[]),
+            vec!["datastore \"startup\" as <target> requires capability :startup:1.0"],
+        ),
+        (
+            "commit",
+            violations(&CommitRequest::new(message_id(), CommitType::Plain), &[]),
+            vec!["operation requires capability :candidate:1.0"],
+        ),
+        (
+            "commit confirmed persist",
+            violations(
+                &CommitRequest::new(message_id(), confirmed(Some("x"), None)),
+                &[Candidate, ConfirmedCommit10],
+            ),
+            vec!["parameter \"persist\" requires capability :confirmed-commit:1.1"],
+        ),
+        (
+            "commit confirmed persist-id",
+            violations(
+                &CommitRequest::new(message_id(), confirmed(None, Some("x"))),
+                &[],
+            ),
+            vec![
+                "operation requires capability :candidate:1.0",
+                "parameter \"confirmed\" requires capability :confirmed-commit:1.1 or :confirmed-commit:1.0",
+                "parameter \"persist-id\" requires capability :confirmed-commit:1.1",
+            ],
+        ),
+        (
+            "cancel-commit",
+            violations(
+                &CancelCommitRequest::new(message_id(), None),
+                &[Candidate, ConfirmedCommit10],
+            ),
+            vec!["operation requires capability :confirmed-commit:1.1"],
+        ),
+        (
+            "discard-changes",
+            violations(&DiscardChangesRequest::new(message_id()), &[Candidate]),
+            vec![],
+        ),
+        (
+            "validate candidate",
+            violations(
+                &ValidateRequest::new(message_id(), ValidateSource::Datastore(Datastore::Candidate)),
+                &[Validate11],
+            ),
+            vec!["datastore \"candidate\" as <source> requires capability :candidate:1.0"],
+        ),
+        (
+            "validate url",
+            violations(
+                &ValidateRequest::new(
+                    message_id(),
+                    ValidateSource::Url("https://example.com/config.xml".to_string()),
+                ),
+                &[Validate, url(&["https"])],
+            ),
+            vec![],
+        ),
+    ];
+
+    for (name, found, expected) in cases {
+        assert_eq!(found, expected, "{}", name);
+    }
+}
+
+fn reply(path: &str) -> String {
+    std::fs::read_to_string(golden_dir().join("replies").join(path)).unwrap()
+}
+
+fn parse_reply<R: NetconfResponse>(path: &str) -> FullResponse<R> {
+    let dump = reply(path);
+    let typed = R::from_netconf_rpc(&dump).unwrap_or_else(|e| panic!("{}: {:?}", path, e));
+    FullResponse {
+        typed,
+        dump,
+        attributes: vec![],
+    }
+}
+
+fn expect_errors(path: &str) -> Vec<RpcError> {
+    let response = parse_reply::<SimpleResponse>(path).typed;
+    assert!(!response.succeeded(), "{}", path);
+    match response.reply {
+        RpcReply::Error(errors) => errors,
+        RpcReply::Ok => unreachable!(),
+    }
+}
+
+fn expect_error(path: &str) -> RpcError {
+    let mut errors = expect_errors(path);
+    assert_eq!(errors.len(), 1, "{}", path);
+    errors.remove(0)
+}
+
+#[test]
+fn hello_replies() {
+    let junos = HelloResponse::from_netconf_rpc(&reply("junos/hello.xml")).unwrap();
+    assert_eq!(junos.session_id, 27700);
+    assert!(junos.capabilities.contains(&Capability::Base));
+    assert!(junos.capabilities.contains(&Capability::Candidate));
+    assert!(!junos.capabilities.contains(&Capability::Base11));
+
+    let iosxe = HelloResponse::from_netconf_rpc(&reply("iosxe/hello.xml")).unwrap();
+    assert_eq!(iosxe.session_id, 21);
+    assert!(iosxe.capabilities.contains(&Capability::Base11));
+    assert!(iosxe.capabilities.contains(&Capability::WritableRunning));
+    assert!(iosxe.capabilities.contains(&Capability::XPath));
+    // capability URNs are kept intact, including the escaped query parameters
+    for capability in &iosxe.capabilities {
+        assert_eq!(
+            &Capability::from_urn(&capability.get_urn()).unwrap(),
+            capability
+        );
+    }
+
+    // URNs are rendered exactly as advertised
+    for (path, hello) in [("junos/hello.xml", &junos), ("iosxe/hello.xml", &iosxe)] {
+        let advertised = XmlNode::parse(&reply(path)).unwrap();
+        let advertised: Vec<&str> = advertised
+            .child("capabilities")
+            .unwrap()
+            .children
+            .iter()
+            .map(|x| x.text().trim())
+            .collect();
+        let urns: Vec<String> = hello.capabilities.iter().map(|x| x.get_urn()).collect();
+        assert_eq!(urns, advertised, "{}", path);
+    }
+    let cases = [
+        (
+            "urn:ietf:params:netconf:capability:url:1.0?scheme=http&scheme=ftp",
+            Capability::Url(UrlCapability::new(vec!["http".to_string(), "ftp".to_string()])),
+        ),
+        (
+            "urn:ietf:params:netconf:capability:with-defaults:1.0?also-supported=trim&basic-mode=explicit&x=1",
+            Capability::WithDefaults(WithDefaultsCapability {
+                basic_mode: WithDefaultsMode::Explicit,
+                also_supported: vec![WithDefaultsMode::Trim],
+                advertised: Default::default(),
+            }),
+        ),
+        (
+            "urn:ietf:params:netconf:capability:candidate:1.0?x=1",
+            Capability::Other("urn:ietf:params:netconf:capability:candidate:1.0?x=1".to_string()),
+        ),
+    ];
+    for (urn, expected) in cases.clone() {
+        let capability = Capability::from_urn(urn).unwrap();
+        assert_eq!(capability, expected, "{}", urn);
+        assert_eq!(capability.get_urn(), urn);
+    }
+    // changed parameters are rendered in the canonical form
+    let mut url = Capability::from_urn(cases[0].0).unwrap();
+    if let Capability::Url(url) = &mut url {
+        url.schemes.push("file".to_string());
+    }
+    assert_eq!(
+        url.get_urn(),
+        "urn:ietf:params:netconf:capability:url:1.0?scheme=http,ftp,file"
+    );
+
+    // malformed capability does not fail the <hello>
+    let hello = format!(
+        r#"<hello xmlns="{}"><capabilities>
+            <capability>urn:ietf:params:netconf:base:1.1</capability>
+            <capability>urn:ietf:params:netconf:capability:url:1.0</capability>
+        </capabilities><session-id>4</session-id></hello>"#,
+        NETCONF_XMLNS
+    );
+    let hello = HelloResponse::from_netconf_rpc(&hello).unwrap();
+    assert_eq!(
+        hello.capabilities,
+        [
+            Capability::Base11,
+            Capability::Other("urn:ietf:params:netconf:capability:url:1.0".to_string())
+        ]
+    );
+}
+
+#[test]
+fn hello_server_capabilities() {
+    let hello = |path: &str| {
+        let hello = HelloResponse::from_netconf_rpc(&reply(path)).unwrap();
+        ServerCapabilities::new(hello.capabilities)
+    };
+
+    let iosxe = hello("iosxe/hello.xml");
+    let interfaces = iosxe.module("ietf-interfaces").unwrap();
+    assert_eq!(
+        interfaces.namespace,
+        "urn:ietf:params:xml:ns:yang:ietf-interfaces"
+    );
+    assert_eq!(interfaces.revision.as_deref(), Some("2014-05-08"));
+    assert_eq!(interfaces.deviations, ["ietf-ip-devs"]);
+    assert!(iosxe.has_feature("ietf-interfaces", "if-mib"));
+    assert!(!iosxe.has_feature("ietf-interfaces", "if-mib-2"));
+    assert!(!iosxe.has_feature("Cisco-IOS-XE-native", "if-mib"));
+    assert_eq!(
+        iosxe
+            .module("Cisco-IOS-XE-native")
+            .unwrap()
+            .revision
+            .as_deref(),
+        Some("2021-07-01")
+    );
+    assert!(!iosxe.supports_module("tailf-actions"));
+    assert_eq!(iosxe.modules().count(), 2);
+    let library = iosxe.yang_library().unwrap();
+    assert_eq!(library.revision.as_deref(), Some("2016-06-21"));
+    assert_eq!(
+        library.module_set_id.as_deref(),
+        Some("730825758336af65af9606c071685c05")
+    );
+    assert!(!iosxe.supports_nmda());
+    assert_eq!(iosxe.versions(":validate"), ["1.1", "1.0"]);
+    assert_eq!(iosxe.versions(":notification"), ["1.1", "1.0"]);
+    assert_eq!(
+        iosxe.base_versions(),
+        [BaseCapability::Base11, BaseCapability::Base]
+    );
+    assert_eq!(iosxe.datastores(), [Datastore::Running]);
+
+    let junos = hello("junos/hello.xml");
+    assert_eq!(junos.modules().count(), 0);
+    assert!(junos.yang_library().is_none());
+    assert!(junos.supports_version(":confirmed-commit", "1.0"));
+    assert!(!junos.supports_version(":confirmed-commit", "1.1"));
+    assert_eq!(junos.base_versions(), [BaseCapability::Base]);
+    assert_eq!(
+        junos.datastores(),
+        [Datastore::Running, Datastore::Candidate]
+    );
+
+    // module parameters in any order, and unknown ones, are kept as advertised
+    let urn = "urn:example:m?revision=2020-01-01&module=m&features=a,b&x-vendor=1";
+    let capabilities = ServerCapabilities::new(vec![Capability::from_urn(urn).unwrap()]);
+    let module = capabilities.module("m").unwrap();
+    assert_eq!(module.revision.as_deref(), Some("2020-01-01"));
+    assert!(capabilities.has_feature("m", "b"));
+    assert_eq!(module.get_urn(), urn);
+    let mut module = module.clone();
+    module.revision = Some("2021-01-01".to_string());
+    assert_eq!(
+        module.get_urn(),
+        "urn:example:m?module=m&revision=2021-01-01&features=a,b"
+    );
+}
+
+#[test]
+fn data_replies() {
+    let cases = [
+        ("junos/get-config.xml", "configuration", "101"),
+        ("sros/get-config.xml", "configure", "302"),
+        ("rfc6241/attributes-echo.xml", "top", "101"),
+    ];
+    for (path, top, message_id) in cases {
+        let response = parse_reply::<GetConfigResponse>(path);
+        assert!(response.typed.succeeded(), "{}", path);
+        assert_eq!(response.typed.message_id, message_id, "{}", path);
+        let data = XmlNode::parse(response.data().unwrap()).unwrap();
+        assert_eq!(data.name, "data", "{}", path);
+        assert_eq!(data.children[0].name, top, "{}", path);
+    }
+
+    let response = parse_reply::<GetResponse>("iosxe/get.xml");
+    assert!(response.typed.succeeded());
+    let data = XmlNode::parse(response.data().unwrap()).unwrap();
+    assert_eq!(
+        data.find("interface").unwrap().child_text("oper-status"),
+        Some("up")
+    );
+
+    // text is kept as is, only the whitespace between elements is dropped
+    let node =
+        XmlNode::parse("<a>\n  <b> two  words </b>\n  <c><![CDATA[ <x> ]]></c>\n  <d> </d>\n</a>")
+            .unwrap();
+    assert_eq!(node.text, None);
+    assert_eq!(node.child_text("b"), Some(" two  words "));
+    assert_eq!(node.child_text("c"), Some(" <x> "));
+    assert_eq!(node.child_text("d"), Some(" "));
+}
+
+#[test]
+fn data_replies_xpath() {
+    let select = |data: &[XmlNode], expression: &str| {
+        XPath::parse(expression)
+            .unwrap()
+            .with_namespace("junos", "http://xml.juniper.net/junos/21.4R0/junos")
+            .select(data)
+            .unwrap()
+            .string_value()
+    };
+    let data = XmlNode::parse(&reply("iosxe/get.xml")).unwrap().children;
+    let data = &data[0].children;
+    let cases = [
+        (
+            "/interfaces-state/interface[name='GigabitEthernet1']/oper-status",
+            "up",
+        ),
+        ("//interface[admin-status = 'down']/name", ""),
+        ("count(//interface/*)", "4"),
+        ("local-name(/*/*/*[last()])", "oper-status"),
+        (
+            "namespace-uri(//type)",
+            "urn:ietf:params:xml:ns:yang:ietf-interfaces",
+        ),
+        ("substring-after(//type, ':')", "ethernetCsmacd"),
+        (
+            "//name/following-sibling::*[1] = 'ianaift:ethernetCsmacd'",
+            "true",
+        ),
+        (
+            "//oper-status/preceding-sibling::*[last()]",
+            "GigabitEthernet1",
+        ),
+        ("string-length(//name) * 2 - 1 div 2", "31.5"),
+        ("concat(//admin-status, '/', //oper-status)", "up/up"),
+        ("not(//interface[2]) and //interface[1]", "true"),
+        ("ancestor-or-self::node()", ""),
+        ("name(//oper-status/..)", "interface"),
+    ];
+    for (expression, expected) in cases {
+        assert_eq!(select(data, expression), expected, "{}", expression);
+    }
+
+    let data = XmlNode::parse(&reply("junos/get-config.xml"))
+        .unwrap()
+        .children;
+    let configuration = &data[0].children;
+    assert_eq!(
+        select(configuration, "/configuration/@junos:commit-user"),
+        "admin"
+    );
+    assert_eq!(
+        select(configuration, "sum(//@junos:commit-seconds) mod 60"),
+        "40"
+    );
+    let selection = XPath::parse("//system/host-name/text() | //configuration/@*")
+        .unwrap()
+        .with_namespace("junos", "http://xml.juniper.net/junos/21.4R0/junos")
+        .select(configuration)
+        .unwrap();
+    let nodes = match selection {
+        Selection::Nodes(nodes) => nodes,
+        other => panic!("Unexpected {:?}", other),
+    };
+    assert_eq!(nodes.len(), 4);
+    assert!(matches!(nodes[0], SelectedNode::Attribute(_, x) if x.name == "commit-seconds"));
+    assert!(matches!(nodes[3], SelectedNode::Text(x) if x.text() == "vmx-01"));
+
+    for invalid in ["//interface[", "/a/b:c", "$x", "foo()", "1 +", "/a/'b'"] {
+        let result = XPath::parse(invalid).and_then(|x| x.select(configuration).map(|_| ()));
+        assert!(result.is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn ok_replies() {
+    for (path, message_id) in [("junos/commit-ok.xml", "102"), ("sros/ok.xml", "301")] {
+        let response = parse_reply::<SimpleResponse>(path).typed;
+        assert_eq!(response.reply, RpcReply::Ok, "{}", path);
+        assert_eq!(response.message_id, message_id, "{}", path);
+    }
+}
+
+#[test]
+fn error_replies() {
+    let lock_denied = expect_error("junos/lock-denied.xml");
+    assert_eq!(lock_denied.error_type, ErrorType::Protocol);
+    assert_eq!(lock_denied.error_tag, "lock-denied");
+    assert_eq!(lock_denied.error_severity, ErrorSeverity::Error);
+    assert!(lock_denied
+        .error_message
+        .unwrap()
+        .starts_with("configuration database locked by:"));
+
+    let invalid_value = expect_error("iosxe/edit-config-error.xml");
+    assert_eq!(invalid_value.error_type, ErrorType::Application);
+    assert_eq!(invalid_value.error_tag, "invalid-value");
+    assert_eq!(
+        invalid_value.error_path.as_deref(),
+        Some("/nc:rpc/nc:edit-config/nc:config/ios:native/ios:hostname")
+    );
+    assert_eq!(
+        invalid_value.error_message.as_deref(),
+        Some("\"r1 r2\" is an invalid value.")
+    );
+
+    let multiple = expect_errors("rfc6241/multiple-errors.xml");
+    let messages: Vec<_> = multiple
+        .iter()
+        .map(|x| (x.error_tag.as_str(), x.error_message.as_deref()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "invalid-value",
+                Some("MTU value 25000 is not within range 256..9192")
+            ),
+            (
+                "invalid-value",
+                Some("Invalid IP address for interface Ethernet1/0")
+            ),
+        ]
+    );
+
+    let data_error = parse_reply::<GetConfigResponse>("rfc6241/multiple-errors.xml");
+    assert!(matches!(&data_error.typed.reply, RpcReply::Error(errors) if errors.len() == 2));
+    assert!(data_error.data().is_err());
+    let data_error = parse_reply::<GetResponse>("rfc6241/multiple-errors.xml");
+    assert!(matches!(&data_error.typed.reply, RpcReply::Error(errors) if errors.len() == 2));
+}
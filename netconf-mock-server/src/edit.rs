@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use netconf_client::types::{ErrorType, RpcError, XmlNode};
+
+use crate::{reply::rpc_error, NETCONF_XMLNS};
+
+/// Result of the simulated operation, failing with the `<rpc-error>` to be replied.
+pub(crate) type SimResult<T> = Result<T, Box<RpcError>>;
+
+pub(crate) fn boxed_error(
+    error_type: ErrorType,
+    error_tag: &str,
+    error_message: &str,
+) -> Box<RpcError> {
+    Box::new(rpc_error(error_type, error_tag, Some(error_message)))
+}
+
+/// Value of the `operation` attribute of the `<config>` content - [RFC 6241 - section 7.2](https://datatracker.ietf.org/doc/html/rfc6241#section-7.2),
+/// or the `<default-operation>` parameter (including "none").
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum EditOperation {
+    Merge,
+    Replace,
+    Create,
+    Delete,
+    Remove,
+    None,
+}
+
+impl EditOperation {
+    pub(crate) fn parse(value: &str) -> SimResult<Self> {
+        Ok(match value {
+            "merge" => EditOperation::Merge,
+            "replace" => EditOperation::Replace,
+            "create" => EditOperation::Create,
+            "delete" => EditOperation::Delete,
+            "remove" => EditOperation::Remove,
+            "none" => EditOperation::None,
+            other => {
+                return Err(boxed_error(
+                    ErrorType::Protocol,
+                    "bad-attribute",
+                    &format!("Invalid operation \"{}\"", other),
+                ))
+            }
+        })
+    }
+}
+
+/// Kind of the YANG list-like node, identifying its entries among siblings of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ListKind {
+    /// List identified by values of the key leaves.
+    List(Vec<String>),
+    /// Leaf-list identified by the value.
+    LeafList,
+}
+
+/// Schema-less hints for the edit engine, identifying entries of lists & leaf-lists by element name.
+/// All the other elements are treated as containers or leaves.
+pub(crate) type ListHints = HashMap<String, ListKind>;
+
+/// The `operation` attribute of the edited node, if any.
+fn node_operation(node: &XmlNode) -> SimResult<Option<EditOperation>> {
+    node.attributes
+        .iter()
+        .find(|x| x.name == "operation" && x.namespace.as_deref() == Some(NETCONF_XMLNS))
+        .map(|x| EditOperation::parse(&x.value))
+        .transpose()
+}
+
+/// Copy of the node stripped of `operation` attributes and source document namespace prefixes/declarations.
+pub(crate) fn normalize(node: &XmlNode) -> XmlNode {
+    XmlNode {
+        name: node.name.clone(),
+        prefix: None,
+        namespace: node.namespace.clone(),
+        namespace_declarations: vec![],
+        attributes: node
+            .attributes
+            .iter()
+            .filter(|x| !(x.name == "operation" && x.namespace.as_deref() == Some(NETCONF_XMLNS)))
+            .cloned()
+            .collect(),
+        children: node.children.iter().map(normalize).collect(),
+        text: node.text.clone(),
+    }
+}
+
+/// Whether the existing `data` node is the same instance as the `edit` node.
+fn is_same_instance(data: &XmlNode, edit: &XmlNode, hints: &ListHints) -> bool {
+    if data.name != edit.name || data.namespace != edit.namespace {
+        return false;
+    }
+    match hints.get(&edit.name) {
+        Some(ListKind::List(keys)) => keys
+            .iter()
+            .all(|key| data.child_text(key) == edit.child_text(key)),
+        Some(ListKind::LeafList) => data.text() == edit.text(),
+        None => true,
+    }
+}
+
+/// Human readable path of the edited node, used in error messages.
+fn describe(path: &str, edit: &XmlNode) -> String {
+    format!("{}/{}", path, edit.name)
+}
+
+/// Apply the `edits` into the `data` siblings, using the inherited `operation`.
+///
+/// Stops on the first error, leaving the partial changes in `data`.
+pub(crate) fn apply_edits(
+    data: &mut Vec<XmlNode>,
+    edits: &[XmlNode],
+    operation: EditOperation,
+    hints: &ListHints,
+    path: &str,
+) -> SimResult<()> {
+    for edit in edits {
+        apply_edit(data, edit, operation, hints, path)?;
+    }
+    Ok(())
+}
+
+fn apply_edit(
+    data: &mut Vec<XmlNode>,
+    edit: &XmlNode,
+    inherited: EditOperation,
+    hints: &ListHints,
+    path: &str,
+) -> SimResult<()> {
+    let operation = node_operation(edit)?.unwrap_or(inherited);
+    let position = data.iter().position(|x| is_same_instance(x, edit, hints));
+    let path = describe(path, edit);
+
+    match (operation, position) {
+        (EditOperation::Create, Some(_)) => Err(boxed_error(
+            ErrorType::Application,
+            "data-exists",
+            &format!("Data already exists: {}", path),
+        )),
+        (EditOperation::Delete, None) => Err(boxed_error(
+            ErrorType::Application,
+            "data-missing",
+            &format!("Data does not exist: {}", path),
+        )),
+        (EditOperation::Delete | EditOperation::Remove, Some(index)) => {
+            data.remove(index);
+            Ok(())
+        }
+        (EditOperation::Remove, None) => Ok(()),
+        (EditOperation::Create | EditOperation::Replace, None) => {
+            data.push(normalize(edit));
+            Ok(())
+        }
+        (EditOperation::Replace, Some(index)) => {
+            data[index] = normalize(edit);
+            Ok(())
+        }
+        (EditOperation::Merge | EditOperation::None, Some(index)) => {
+            let node = &mut data[index];
+            if edit.children.is_empty() {
+                if operation == EditOperation::Merge {
+                    node.text = edit.text.clone();
+                }
+                return Ok(());
+            }
+            apply_edits(&mut node.children, &edit.children, operation, hints, &path)
+        }
+        (EditOperation::Merge | EditOperation::None, None) => {
+            let mut node = XmlNode {
+                children: vec![],
+                ..normalize(edit)
+            };
+            // list entry is always created along with its keys
+            if let Some(ListKind::List(keys)) = hints.get(&edit.name) {
+                node.children = edit
+                    .children
+                    .iter()
+                    .filter(|x| keys.contains(&x.name))
+                    .map(normalize)
+                    .collect();
+            }
+            let keys_count = node.children.len();
+            apply_edits(&mut node.children, &edit.children, operation, hints, &path)?;
+            // with "none" operation, the node is created only if some descendant was
+            if operation == EditOperation::Merge || node.children.len() > keys_count {
+                data.push(node);
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Scriptable mock NETCONF server, for testing [`netconf_client::NetconfSession`] and its users offline.
+//!
+//! ```
+//! use netconf_client::{types::Capability, NetconfSession};
+//! use netconf_mock_server::{MockServer, Reply};
+//!
+//! let (transport, server) = MockServer::new()
+//!     .reply("get", Reply::data("<top xmlns=\"urn:example\"/>"))
+//!     .spawn_in_memory();
+//! let mut session = NetconfSession::with_transport(Box::new(transport), vec![Capability::Base]);
+//! session.connect().unwrap();
+//! session.exchange_hello().unwrap();
+//! assert_eq!(session.session_id(), Some(1));
+//! drop(session);
+//! server.join().unwrap();
+//! ```
+
+mod edit;
+
+mod memory;
+pub use memory::{MemoryTransport, DEFAULT_TIMEOUT};
+
+mod reply;
+pub use reply::Reply;
+
+mod server;
+pub use server::{CloseHandler, Handler, MockServer, MockServerHandle, RpcRequest, NETCONF_XMLNS};
+
+mod simulator;
+pub use simulator::Simulator;
+
+pub use netconf_client::transport::TcpTransport;
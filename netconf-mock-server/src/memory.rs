@@ -0,0 +1,138 @@
+use std::{
+    io::{self, Read, Write},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+    time::Duration,
+};
+
+use anyhow::Result;
+use netconf_client::Transport;
+
+/// Default read timeout of the client side [`MemoryTransport`], the same as of the SSH transport.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One end of the in-memory bidirectional byte stream.
+///
+/// Reading returns zero bytes once the other end is dropped or disconnected.
+pub(crate) struct DuplexStream {
+    sender: Option<Sender<Vec<u8>>>,
+    receiver: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    timeout: Option<Duration>,
+}
+
+/// Create connected pair of the in-memory streams.
+pub(crate) fn duplex() -> (DuplexStream, DuplexStream) {
+    let (left_sender, right_receiver) = channel();
+    let (right_sender, left_receiver) = channel();
+    let create = |sender, receiver| DuplexStream {
+        sender: Some(sender),
+        receiver,
+        pending: vec![],
+        timeout: None,
+    };
+    (
+        create(left_sender, left_receiver),
+        create(right_sender, right_receiver),
+    )
+}
+
+impl DuplexStream {
+    /// Close the sending half of the stream.
+    pub(crate) fn close(&mut self) {
+        self.sender = None;
+    }
+}
+
+impl Read for DuplexStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let received = match self.timeout {
+                Some(timeout) => self.receiver.recv_timeout(timeout),
+                None => self
+                    .receiver
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(data) => self.pending = data,
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "in-memory stream read timed out",
+                    ))
+                }
+            }
+        }
+        let count = buf.len().min(self.pending.len());
+        buf[..count].copy_from_slice(&self.pending[..count]);
+        self.pending.drain(..count);
+        Ok(count)
+    }
+}
+
+impl Write for DuplexStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return Err(io::ErrorKind::NotConnected.into()),
+        };
+        match sender.send(buf.to_vec()) {
+            Ok(_) => Ok(buf.len()),
+            Err(_) => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Client side [`Transport`] connected to the mock server running in the same process,
+/// created by [`crate::MockServer::spawn_in_memory()`].
+pub struct MemoryTransport {
+    stream: DuplexStream,
+}
+
+impl MemoryTransport {
+    pub(crate) fn new(mut stream: DuplexStream) -> Self {
+        stream.timeout = Some(DEFAULT_TIMEOUT);
+        Self { stream }
+    }
+
+    /// Set the read timeout, `None` blocks indefinitely.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.stream.timeout = timeout;
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.stream.close();
+        Ok(())
+    }
+
+    fn target_string(&self) -> String {
+        "in-memory".to_string()
+    }
+}
+
+impl Read for MemoryTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for MemoryTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
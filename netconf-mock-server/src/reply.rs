@@ -0,0 +1,115 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::Result;
+use netconf_client::types::{ErrorSeverity, ErrorType, RpcError};
+
+/// Scripted server behavior in response to a single received message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// Default behavior of the mock server - the generated `<hello>` message,
+    /// or the built-in handling of the RPC operation (see [`crate::MockServer`]).
+    Default,
+    /// `<ok/>` reply.
+    Ok,
+    /// `<data>` reply, with the inner content as-is.
+    Data(String),
+    /// `<rpc-error>` reply.
+    Error(RpcError),
+    /// The `<rpc-reply>` element with the inner content as-is.
+    Raw(String),
+    /// Whole message content as-is, without the `<rpc-reply>` wrapper - e.g. malformed XML, or mismatched `message-id`.
+    /// Still framed properly.
+    Message(String),
+    /// Raw bytes written into the stream, without any framing - e.g. to inject framing errors.
+    Bytes(Vec<u8>),
+    /// The nested reply, sent after the delay.
+    Delayed(Duration, Box<Reply>),
+    /// Close the connection without any reply.
+    Disconnect,
+    /// Do not reply at all, keep the connection open.
+    Ignore,
+}
+
+impl Reply {
+    /// `<data>` reply with the inner content.
+    pub fn data(content: &str) -> Self {
+        Reply::Data(content.to_string())
+    }
+
+    /// `<rpc-error>` reply of the "error" severity.
+    pub fn error(error_type: ErrorType, error_tag: &str, error_message: Option<&str>) -> Self {
+        Reply::Error(rpc_error(error_type, error_tag, error_message))
+    }
+
+    /// The `reply` sent after the `delay`.
+    pub fn delayed(delay: Duration, reply: Reply) -> Self {
+        Reply::Delayed(delay, Box::new(reply))
+    }
+
+    /// [`Reply::Raw`] loaded from the fixture file, containing the inner content of `<rpc-reply>`.
+    pub fn fixture(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Reply::Raw(std::fs::read_to_string(path)?))
+    }
+}
+
+/// [`RpcError`] of the "error" severity.
+pub(crate) fn rpc_error(
+    error_type: ErrorType,
+    error_tag: &str,
+    error_message: Option<&str>,
+) -> RpcError {
+    RpcError {
+        error_type,
+        error_tag: error_tag.to_string(),
+        error_severity: ErrorSeverity::Error,
+        error_app_tag: None,
+        error_path: None,
+        error_message: error_message.map(|x| x.to_string()),
+        error_info: None,
+    }
+}
+
+/// Escape the special characters of XML text & attribute values.
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Serialize the [`RpcError`] into the `<rpc-error>` element.
+pub(crate) fn rpc_error_to_xml(error: &RpcError) -> String {
+    let error_type = match error.error_type {
+        ErrorType::Transport => "transport",
+        ErrorType::Rpc => "rpc",
+        ErrorType::Protocol => "protocol",
+        ErrorType::Application => "application",
+    };
+    let error_severity = match error.error_severity {
+        ErrorSeverity::Error => "error",
+        ErrorSeverity::Warning => "warning",
+    };
+
+    let mut result = format!(
+        "<rpc-error><error-type>{}</error-type><error-tag>{}</error-tag><error-severity>{}</error-severity>",
+        error_type,
+        escape(&error.error_tag),
+        error_severity
+    );
+    let optional = [
+        ("error-app-tag", &error.error_app_tag),
+        ("error-path", &error.error_path),
+        ("error-message", &error.error_message),
+    ];
+    for (tag, value) in optional {
+        if let Some(value) = value {
+            result.push_str(&format!("<{tag}>{}</{tag}>", escape(value)));
+        }
+    }
+    if let Some(info) = &error.error_info {
+        result.push_str(&format!("<error-info>{}</error-info>", info));
+    }
+    result.push_str("</rpc-error>");
+    result
+}
@@ -0,0 +1,561 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use netconf_client::{
+    framing::{encode_message, BaseCapability, FrameDecoder},
+    types::{Capability, ErrorType, XmlNode},
+};
+
+use crate::{
+    memory::{duplex, MemoryTransport},
+    reply::{escape, rpc_error_to_xml},
+    Reply,
+};
+
+/// Namespace of the NETCONF base protocol.
+pub const NETCONF_XMLNS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+
+/// Closure producing the reply for the received RPC.
+pub type Handler = Box<dyn FnMut(&RpcRequest) -> Reply + Send>;
+
+/// Closure invoked with the session-id of the closed connection.
+pub type CloseHandler = Box<dyn FnMut(u32) + Send>;
+
+/// RPC received by the mock server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcRequest {
+    /// Session-id assigned by server to the connection the RPC was received on.
+    pub session_id: u32,
+    /// Value of the `message-id` attribute, if present.
+    pub message_id: Option<String>,
+    /// Name of the operation, i.e. the first child element of `<rpc>`.
+    pub operation: String,
+    /// The whole parsed `<rpc>` element.
+    pub rpc: XmlNode,
+    /// The message as received, without framing.
+    pub raw: String,
+}
+
+impl RpcRequest {
+    /// Element of the requested operation, e.g. `<get-config>`.
+    pub fn operation_node(&self) -> Option<&XmlNode> {
+        self.rpc.children.first()
+    }
+}
+
+/// Shared state of the server, accessed by all the served connections.
+struct State {
+    capabilities: Vec<Capability>,
+    next_session_id: Arc<AtomicU32>,
+    hello_reply: Reply,
+    script: VecDeque<Reply>,
+    handlers: HashMap<String, Handler>,
+    close_handlers: Vec<CloseHandler>,
+    requests: Vec<RpcRequest>,
+    max_chunk_size: Option<usize>,
+}
+
+impl State {
+    fn reply_for(&mut self, request: &RpcRequest) -> Reply {
+        if let Some(reply) = self.script.pop_front() {
+            return reply;
+        }
+        match self.handlers.get_mut(&request.operation) {
+            Some(handler) => handler(request),
+            None => Reply::Default,
+        }
+    }
+}
+
+/// Scriptable NETCONF server for testing the clients without a real device.
+///
+/// Sends configurable `<hello>` and answers the RPCs by [`Reply`] items resolved in the following order:
+/// 1. one-shot replies queued by [`Self::script()`], consumed by any operation in the order of queueing
+/// 2. handler of the operation registered by [`Self::reply()`] or [`Self::handle()`]
+/// 3. built-in handling - `<ok/>` for `<close-session>` (closing the connection afterwards) and `<kill-session>`,
+///    "operation-not-supported" `<rpc-error>` for any other operation
+///
+/// `<rpc-reply>` echoes all the attributes of `<rpc>`, as required by [RFC 6241 - section 4.2](https://datatracker.ietf.org/doc/html/rfc6241#section-4.2).
+/// The framing is switched to chunked one, if both sides advertise `:base:1.1` capability.
+pub struct MockServer {
+    state: State,
+}
+
+impl Default for MockServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockServer {
+    /// Create new server advertising both `:base` capabilities, with session-id starting from 1.
+    pub fn new() -> Self {
+        Self {
+            state: State {
+                capabilities: vec![Capability::Base, Capability::Base11],
+                next_session_id: Arc::new(AtomicU32::new(1)),
+                hello_reply: Reply::Default,
+                script: VecDeque::new(),
+                handlers: HashMap::new(),
+                close_handlers: vec![],
+                requests: vec![],
+                max_chunk_size: None,
+            },
+        }
+    }
+
+    /// Split the messages of the chunked framing into chunks of at most `size` bytes, regardless of the XML content -
+    /// e.g. in the middle of tags, as real servers may do. Whole messages are sent in single chunk by default.
+    pub fn max_chunk_size(mut self, size: usize) -> Self {
+        self.state.max_chunk_size = Some(size.max(1));
+        self
+    }
+
+    /// Set the capabilities advertised in `<hello>`. The `:base` capabilities must be listed explicitly.
+    pub fn capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.state.capabilities = capabilities;
+        self
+    }
+
+    /// Set the session-id of the first connection, incremented for each subsequent one.
+    pub fn session_id(self, session_id: u32) -> Self {
+        self.state
+            .next_session_id
+            .store(session_id, Ordering::SeqCst);
+        self
+    }
+
+    /// Share the counter of the next session-id with other servers,
+    /// e.g. to simulate multiple sessions of the same device served over multiple in-memory streams.
+    pub fn session_counter(mut self, counter: Arc<AtomicU32>) -> Self {
+        self.state.next_session_id = counter;
+        self
+    }
+
+    /// Override the `<hello>` sent by server.
+    ///
+    /// [`Reply::Raw`] replaces the content of the `<hello>` element, [`Reply::Ok`], [`Reply::Data`]
+    /// and [`Reply::Error`] behave as [`Reply::Default`]. Other variants work the same as for RPCs.
+    pub fn hello_reply(mut self, reply: Reply) -> Self {
+        self.state.hello_reply = reply;
+        self
+    }
+
+    /// Always reply with clone of the `reply` to the `operation`, e.g. "get-config".
+    pub fn reply(self, operation: &str, reply: Reply) -> Self {
+        self.handle(operation, move |_| reply.clone())
+    }
+
+    /// Reply to the `operation` by result of the `handler` closure.
+    pub fn handle<F>(mut self, operation: &str, handler: F) -> Self
+    where
+        F: FnMut(&RpcRequest) -> Reply + Send + 'static,
+    {
+        self.state
+            .handlers
+            .insert(operation.to_string(), Box::new(handler));
+        self
+    }
+
+    /// Invoke the `handler` whenever connection is closed - by client, server, or on error.
+    pub fn on_close<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(u32) + Send + 'static,
+    {
+        self.state.close_handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Queue the one-shot `reply` for the next RPC of any operation, taking precedence over handlers.
+    pub fn script(mut self, reply: Reply) -> Self {
+        self.state.script.push_back(reply);
+        self
+    }
+
+    /// Serve single connection over in-memory stream, in a background thread.
+    ///
+    /// Returns the client side transport to be used by [`netconf_client::NetconfSession::with_transport()`].
+    pub fn spawn_in_memory(self) -> (MemoryTransport, MockServerHandle) {
+        let (client, server) = duplex();
+        let state = Arc::new(Mutex::new(self.state));
+        let thread = {
+            let state = state.clone();
+            thread::spawn(move || serve_connection(server, state))
+        };
+        let handle = MockServerHandle {
+            state,
+            stop: Arc::new(AtomicBool::new(false)),
+            thread: Some(thread),
+            local_addr: None,
+        };
+        (MemoryTransport::new(client), handle)
+    }
+
+    /// Listen on the TCP `address` and serve all the incoming connections, in background threads.
+    ///
+    /// NETCONF messages are exchanged directly over TCP, see [`crate::TcpTransport`].
+    /// Use port 0 to bind any free port, see [`MockServerHandle::local_addr()`].
+    pub fn listen_tcp(self, address: SocketAddr) -> Result<MockServerHandle> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(self.state));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let state = state.clone();
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut connections = vec![];
+                while !stop.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            stream.set_nonblocking(false)?;
+                            let state = state.clone();
+                            connections
+                                .push(thread::spawn(move || serve_connection(stream, state)));
+                        }
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(10))
+                        }
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                join_all(connections)
+            })
+        };
+
+        Ok(MockServerHandle {
+            state,
+            stop,
+            thread: Some(thread),
+            local_addr: Some(local_addr),
+        })
+    }
+}
+
+/// Handle of the running [`MockServer`].
+pub struct MockServerHandle {
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<()>>>,
+    local_addr: Option<SocketAddr>,
+}
+
+impl MockServerHandle {
+    /// Address of the TCP listener, `None` for in-memory server.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// All the RPCs received so far, from all the connections.
+    pub fn requests(&self) -> Vec<RpcRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Stop accepting new connections, and wait until all the served ones are closed by client or server.
+    ///
+    /// Returns the first server side error encountered, e.g. malformed framing sent by client.
+    pub fn join(mut self) -> Result<()> {
+        self.stop.store(true, Ordering::SeqCst);
+        match self.thread.take() {
+            Some(thread) => join_all(vec![thread]),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for MockServerHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+fn join_all(threads: Vec<JoinHandle<Result<()>>>) -> Result<()> {
+    let mut result = Ok(());
+    for thread in threads {
+        let thread_result = match thread.join() {
+            Ok(thread_result) => thread_result,
+            Err(_) => Err(anyhow::anyhow!("Mock server thread panicked")),
+        };
+        if result.is_ok() {
+            result = thread_result;
+        }
+    }
+    result
+}
+
+/// Context of the sent reply.
+enum Context<'a> {
+    Hello(&'a str),
+    Rpc(&'a RpcRequest),
+}
+
+/// Outcome of the sent reply - whether the connection should be kept open.
+#[derive(PartialEq)]
+enum Outcome {
+    Open,
+    Closed,
+}
+
+fn hello_content(capabilities: &[Capability], session_id: u32) -> String {
+    let capabilities: String = capabilities
+        .iter()
+        .map(|cap| format!("<capability>{}</capability>", escape(&cap.get_urn())))
+        .collect();
+    format!(
+        "<capabilities>{}</capabilities><session-id>{}</session-id>",
+        capabilities, session_id
+    )
+}
+
+/// Wrap the `content` into `<rpc-reply>` echoing all the attributes of `<rpc>`.
+fn wrap_reply(rpc: &XmlNode, content: &str) -> String {
+    let mut result = String::from("<rpc-reply");
+    let mut default_namespace = false;
+    for (prefix, namespace) in &rpc.namespace_declarations {
+        match prefix {
+            Some(prefix) => {
+                result.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape(namespace)))
+            }
+            None => {
+                default_namespace = true;
+                result.push_str(&format!(" xmlns=\"{}\"", escape(namespace)));
+            }
+        }
+    }
+    if !default_namespace {
+        result.push_str(&format!(" xmlns=\"{}\"", NETCONF_XMLNS));
+    }
+    for attribute in &rpc.attributes {
+        result.push_str(&format!(
+            " {}=\"{}\"",
+            attribute.qualified_name(),
+            escape(&attribute.value)
+        ));
+    }
+    format!("{}>{}</rpc-reply>", result, content)
+}
+
+fn default_reply(request: &RpcRequest) -> Reply {
+    match request.operation.as_str() {
+        "close-session" | "kill-session" => Reply::Ok,
+        _ => Reply::error(
+            ErrorType::Protocol,
+            "operation-not-supported",
+            Some(&format!(
+                "Operation <{}> not supported by mock server",
+                request.operation
+            )),
+        ),
+    }
+}
+
+/// Framing of the sent messages.
+#[derive(Copy, Clone)]
+struct Framing {
+    base: BaseCapability,
+    max_chunk_size: Option<usize>,
+}
+
+fn write_message<S: Write>(stream: &mut S, message: &str, framing: Framing) -> Result<()> {
+    let framed = match (framing.base, framing.max_chunk_size) {
+        (BaseCapability::Base11, Some(size)) if !message.is_empty() => {
+            let mut framed = vec![];
+            for chunk in message.as_bytes().chunks(size) {
+                framed.extend_from_slice(format!("\n#{}\n", chunk.len()).as_bytes());
+                framed.extend_from_slice(chunk);
+            }
+            framed.extend_from_slice(framing.base.eom_separator().as_bytes());
+            framed
+        }
+        (base, _) => encode_message(message, base),
+    };
+    stream.write_all(&framed)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn send_reply<S: Write>(
+    stream: &mut S,
+    reply: &Reply,
+    framing: Framing,
+    context: &Context,
+) -> Result<Outcome> {
+    let content = match (reply, context) {
+        (
+            Reply::Default | Reply::Ok | Reply::Data(_) | Reply::Error(_),
+            Context::Hello(content),
+        ) => return send_reply(stream, &Reply::Raw(content.to_string()), framing, context),
+        (Reply::Raw(content), Context::Hello(_)) => {
+            format!("<hello xmlns=\"{}\">{}</hello>", NETCONF_XMLNS, content)
+        }
+        (Reply::Default, Context::Rpc(request)) => {
+            return send_reply(stream, &default_reply(request), framing, context)
+        }
+        (Reply::Ok, Context::Rpc(request)) => wrap_reply(&request.rpc, "<ok/>"),
+        (Reply::Data(data), Context::Rpc(request)) => {
+            wrap_reply(&request.rpc, &format!("<data>{}</data>", data))
+        }
+        (Reply::Error(error), Context::Rpc(request)) => {
+            wrap_reply(&request.rpc, &rpc_error_to_xml(error))
+        }
+        (Reply::Raw(content), Context::Rpc(request)) => wrap_reply(&request.rpc, content),
+        (Reply::Message(message), _) => message.clone(),
+        (Reply::Bytes(bytes), _) => {
+            stream.write_all(bytes)?;
+            stream.flush()?;
+            return Ok(Outcome::Open);
+        }
+        (Reply::Delayed(delay, reply), _) => {
+            thread::sleep(*delay);
+            return send_reply(stream, reply, framing, context);
+        }
+        (Reply::Disconnect, _) => return Ok(Outcome::Closed),
+        (Reply::Ignore, _) => return Ok(Outcome::Open),
+    };
+    write_message(stream, &content, framing)?;
+    Ok(Outcome::Open)
+}
+
+/// Read the next complete message, `None` if the connection was closed by client.
+fn read_message<S: Read>(stream: &mut S, decoder: &mut FrameDecoder) -> Result<Option<String>> {
+    let mut buffer = [0u8; 4096];
+    loop {
+        if let Some(message) = decoder.next_message()? {
+            return Ok(Some(message));
+        }
+        let bytes_read = stream.read(&mut buffer)?;
+        if bytes_read == 0 {
+            if decoder.pending().is_empty() {
+                return Ok(None);
+            }
+            bail!(
+                "Connection closed by client in the middle of message: {}",
+                String::from_utf8_lossy(decoder.pending())
+            );
+        }
+        decoder.feed(&buffer[..bytes_read]);
+    }
+}
+
+/// Highest `:base` capability advertised by both client & server.
+fn common_base(
+    client_hello: &XmlNode,
+    server_capabilities: &[Capability],
+) -> Result<BaseCapability> {
+    if client_hello.name != "hello" {
+        bail!("Expected client <hello>, found <{}>", client_hello.name);
+    }
+    let client_capabilities: Vec<&str> = client_hello
+        .child("capabilities")
+        .map(|caps| {
+            caps.children_named("capability")
+                .map(|x| x.text())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let common = [
+        (Capability::Base11, BaseCapability::Base11),
+        (Capability::Base, BaseCapability::Base),
+    ]
+    .into_iter()
+    .find(|(cap, _)| {
+        server_capabilities.contains(cap) && client_capabilities.contains(&cap.get_urn().as_str())
+    });
+    match common {
+        Some((_, base)) => Ok(base),
+        None => bail!("No common base capability found in client <hello>"),
+    }
+}
+
+fn serve_connection<S: Read + Write>(stream: S, state: Arc<Mutex<State>>) -> Result<()> {
+    let session_id = state
+        .lock()
+        .unwrap()
+        .next_session_id
+        .fetch_add(1, Ordering::SeqCst);
+    let result = serve_session(stream, session_id, &state);
+    for handler in state.lock().unwrap().close_handlers.iter_mut() {
+        handler(session_id);
+    }
+    result
+}
+
+fn serve_session<S: Read + Write>(
+    mut stream: S,
+    session_id: u32,
+    state: &Mutex<State>,
+) -> Result<()> {
+    let (hello, hello_reply, capabilities, max_chunk_size) = {
+        let state = state.lock().unwrap();
+        (
+            hello_content(&state.capabilities, session_id),
+            state.hello_reply.clone(),
+            state.capabilities.clone(),
+            state.max_chunk_size,
+        )
+    };
+
+    // <hello> is always framed by end-of-message separator
+    let mut decoder = FrameDecoder::new(BaseCapability::Base);
+    let context = Context::Hello(&hello);
+    let framing = Framing {
+        base: BaseCapability::Base,
+        max_chunk_size,
+    };
+    if send_reply(&mut stream, &hello_reply, framing, &context)? == Outcome::Closed {
+        return Ok(());
+    }
+    let client_hello = match read_message(&mut stream, &mut decoder)? {
+        Some(message) => XmlNode::parse(&message)?,
+        None => return Ok(()),
+    };
+    let base = common_base(&client_hello, &capabilities)?;
+    decoder.set_base(base);
+    let framing = Framing {
+        base,
+        max_chunk_size,
+    };
+
+    while let Some(message) = read_message(&mut stream, &mut decoder)? {
+        let rpc = XmlNode::parse(&message)?;
+        if rpc.name != "rpc" {
+            bail!("Expected <rpc>, found <{}>", rpc.name);
+        }
+        let request = RpcRequest {
+            session_id,
+            message_id: rpc.attribute("message-id").map(|x| x.to_string()),
+            operation: rpc
+                .children
+                .first()
+                .map(|x| x.name.clone())
+                .unwrap_or_default(),
+            rpc,
+            raw: message,
+        };
+
+        let reply = {
+            let mut state = state.lock().unwrap();
+            state.requests.push(request.clone());
+            state.reply_for(&request)
+        };
+        let closing = request.operation == "close-session" && reply == Reply::Default;
+        let outcome = send_reply(&mut stream, &reply, framing, &Context::Rpc(&request))?;
+        if outcome == Outcome::Closed || closing {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
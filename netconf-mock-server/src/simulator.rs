@@ -0,0 +1,598 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicU32, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use netconf_client::{
+    subtree::filter_subtree,
+    types::{Capability, Datastore, ErrorType, RpcError, XmlNode},
+};
+
+use crate::{
+    edit::{apply_edits, boxed_error, normalize, EditOperation, ListHints, ListKind, SimResult},
+    reply::escape,
+    MockServer, Reply, RpcRequest,
+};
+
+/// Default `<confirm-timeout>` of the confirmed commit in seconds - [RFC 6241 - section 8.4.5.1](https://datatracker.ietf.org/doc/html/rfc6241#section-8.4.5.1).
+const DEFAULT_CONFIRM_TIMEOUT: u64 = 600;
+
+/// Operations handled by [`Simulator`], any other operation is left to the [`MockServer`] built-in handling.
+const OPERATIONS: &[&str] = &[
+    "get",
+    "get-config",
+    "edit-config",
+    "copy-config",
+    "delete-config",
+    "lock",
+    "unlock",
+    "commit",
+    "cancel-commit",
+    "discard-changes",
+    "validate",
+    "kill-session",
+];
+
+/// Configuration datastore of the simulator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Target {
+    Running,
+    Candidate,
+    Startup,
+}
+
+impl Target {
+    fn from_datastore(datastore: &Datastore) -> Option<Self> {
+        match datastore {
+            Datastore::Running => Some(Target::Running),
+            Datastore::Candidate => Some(Target::Candidate),
+            Datastore::Startup => Some(Target::Startup),
+            _ => None,
+        }
+    }
+}
+
+/// Source of the configuration data - either datastore, or inline `<config>`.
+enum Source {
+    Target(Target),
+    Config(Vec<XmlNode>),
+}
+
+/// Pending confirmed commit, rolled back unless confirmed in time.
+struct PendingCommit {
+    rollback: Vec<XmlNode>,
+    deadline: Instant,
+    session_id: u32,
+    persist: Option<String>,
+}
+
+struct SimState {
+    datastores: HashMap<Target, Vec<XmlNode>>,
+    locks: HashMap<Target, u32>,
+    pending_commit: Option<PendingCommit>,
+    hints: ListHints,
+    writable_running: bool,
+}
+
+fn protocol_error(error_tag: &str, error_message: &str) -> Box<RpcError> {
+    boxed_error(ErrorType::Protocol, error_tag, error_message)
+}
+
+/// Selected datastore element of the parameter, e.g. `<target><running/></target>`.
+fn parse_target(operation: &XmlNode, parameter: &str) -> SimResult<Target> {
+    match parse_source(operation, parameter)? {
+        Source::Target(target) => Ok(target),
+        Source::Config(_) => Err(protocol_error(
+            "invalid-value",
+            &format!("<config> not allowed as <{}>", parameter),
+        )),
+    }
+}
+
+fn parse_source(operation: &XmlNode, parameter: &str) -> SimResult<Source> {
+    let node = operation
+        .child(parameter)
+        .and_then(|x| x.children.first())
+        .ok_or_else(|| {
+            protocol_error(
+                "missing-element",
+                &format!("Missing <{}> parameter", parameter),
+            )
+        })?;
+    match node.name.as_str() {
+        "config" => Ok(Source::Config(
+            node.children.iter().map(normalize).collect(),
+        )),
+        "url" => Err(protocol_error(
+            "operation-not-supported",
+            "URL datastores not supported by simulator",
+        )),
+        // infallible parsing
+        name => Target::from_datastore(&name.parse().unwrap())
+            .map(Source::Target)
+            .ok_or_else(|| {
+                protocol_error(
+                    "invalid-value",
+                    &format!("Unsupported datastore <{}>", name),
+                )
+            }),
+    }
+}
+
+/// Data selected by the optional `<filter>` parameter.
+fn apply_filter(operation: &XmlNode, data: &[XmlNode]) -> SimResult<Vec<XmlNode>> {
+    let filter = match operation.child("filter") {
+        Some(filter) => filter,
+        None => return Ok(data.to_vec()),
+    };
+    match filter.attribute("type").unwrap_or("subtree") {
+        "subtree" => Ok(filter_subtree(data, &filter.children)),
+        other => Err(protocol_error(
+            "operation-not-supported",
+            &format!("Filter type \"{}\" not supported by simulator", other),
+        )),
+    }
+}
+
+impl SimState {
+    fn datastore(&self, target: Target) -> &Vec<XmlNode> {
+        // all the datastores are always present
+        &self.datastores[&target]
+    }
+
+    fn set_datastore(&mut self, target: Target, data: Vec<XmlNode>) {
+        self.datastores.insert(target, data);
+    }
+
+    /// Roll back the pending confirmed commit after its timeout.
+    fn expire_pending_commit(&mut self) {
+        let expired =
+            matches!(&self.pending_commit, Some(pending) if pending.deadline <= Instant::now());
+        if expired {
+            self.rollback();
+        }
+    }
+
+    /// Revert both "running" and "candidate" to the state prior to the pending confirmed commit.
+    fn rollback(&mut self) {
+        if let Some(pending) = self.pending_commit.take() {
+            self.set_datastore(Target::Running, pending.rollback.clone());
+            self.set_datastore(Target::Candidate, pending.rollback);
+        }
+    }
+
+    /// Release all the resources of the terminated session.
+    fn end_session(&mut self, session_id: u32) {
+        self.locks.retain(|_, owner| *owner != session_id);
+        let non_persistent = matches!(
+            &self.pending_commit,
+            Some(pending) if pending.session_id == session_id && pending.persist.is_none()
+        );
+        if non_persistent {
+            self.rollback();
+        }
+    }
+
+    /// Check the `target` is not locked by another session.
+    fn check_lock(&self, target: Target, session_id: u32) -> SimResult<()> {
+        match self.locks.get(&target) {
+            Some(owner) if *owner != session_id => Err(protocol_error(
+                "in-use",
+                &format!("Datastore locked by session {}", owner),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the `target` can be written by the session.
+    fn check_writable(&self, target: Target, session_id: u32) -> SimResult<()> {
+        if target == Target::Running && !self.writable_running {
+            return Err(protocol_error(
+                "operation-not-supported",
+                "Datastore \"running\" is not writable, use \"candidate\"",
+            ));
+        }
+        self.check_lock(target, session_id)
+    }
+
+    fn handle(&mut self, request: &RpcRequest) -> SimResult<Option<Vec<XmlNode>>> {
+        self.expire_pending_commit();
+
+        let operation = match request.operation_node() {
+            Some(operation) => operation,
+            None => return Err(protocol_error("missing-element", "Missing operation")),
+        };
+        let session_id = request.session_id;
+        match request.operation.as_str() {
+            "get" => Ok(Some(apply_filter(
+                operation,
+                self.datastore(Target::Running),
+            )?)),
+            "get-config" => {
+                let source = parse_target(operation, "source")?;
+                Ok(Some(apply_filter(operation, self.datastore(source))?))
+            }
+            "edit-config" => self.edit_config(operation, session_id).map(|_| None),
+            "copy-config" => {
+                let target = parse_target(operation, "target")?;
+                let data = match parse_source(operation, "source")? {
+                    Source::Target(source) if source == target => {
+                        return Err(protocol_error(
+                            "invalid-value",
+                            "Source & target datastores must differ",
+                        ))
+                    }
+                    Source::Target(source) => self.datastore(source).clone(),
+                    Source::Config(data) => data,
+                };
+                self.check_writable(target, session_id)?;
+                self.set_datastore(target, data);
+                Ok(None)
+            }
+            "delete-config" => {
+                let target = parse_target(operation, "target")?;
+                if target == Target::Running {
+                    return Err(protocol_error(
+                        "operation-failed",
+                        "Datastore \"running\" cannot be deleted",
+                    ));
+                }
+                self.check_lock(target, session_id)?;
+                self.set_datastore(target, vec![]);
+                Ok(None)
+            }
+            "lock" => {
+                let target = parse_target(operation, "target")?;
+                if let Some(owner) = self.locks.get(&target) {
+                    let mut error = protocol_error("lock-denied", "Lock already held");
+                    error.error_info = Some(format!("<session-id>{}</session-id>", owner));
+                    return Err(error);
+                }
+                if target == Target::Candidate
+                    && self.datastore(Target::Candidate) != self.datastore(Target::Running)
+                {
+                    let mut error = protocol_error(
+                        "lock-denied",
+                        "Datastore \"candidate\" has uncommitted changes",
+                    );
+                    error.error_info = Some("<session-id>0</session-id>".to_string());
+                    return Err(error);
+                }
+                self.locks.insert(target, session_id);
+                Ok(None)
+            }
+            "unlock" => {
+                let target = parse_target(operation, "target")?;
+                match self.locks.get(&target) {
+                    Some(owner) if *owner == session_id => {
+                        self.locks.remove(&target);
+                        Ok(None)
+                    }
+                    _ => Err(protocol_error(
+                        "operation-failed",
+                        "Lock not held by this session",
+                    )),
+                }
+            }
+            "commit" => self.commit(operation, session_id).map(|_| None),
+            "cancel-commit" => {
+                self.check_pending_commit(operation.child_text("persist-id"), session_id)?;
+                self.rollback();
+                Ok(None)
+            }
+            "discard-changes" => {
+                self.check_lock(Target::Candidate, session_id)?;
+                let running = self.datastore(Target::Running).clone();
+                self.set_datastore(Target::Candidate, running);
+                Ok(None)
+            }
+            "validate" => {
+                parse_source(operation, "source")?;
+                Ok(None)
+            }
+            "kill-session" => {
+                let killed: u32 = operation
+                    .child_text("session-id")
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| protocol_error("missing-element", "Missing <session-id>"))?;
+                if killed == session_id {
+                    return Err(protocol_error(
+                        "invalid-value",
+                        "Cannot kill the current session",
+                    ));
+                }
+                self.end_session(killed);
+                Ok(None)
+            }
+            other => Err(protocol_error(
+                "operation-not-supported",
+                &format!("Operation <{}> not supported by simulator", other),
+            )),
+        }
+    }
+
+    fn edit_config(&mut self, operation: &XmlNode, session_id: u32) -> SimResult<()> {
+        let target = parse_target(operation, "target")?;
+        if target == Target::Startup {
+            return Err(protocol_error(
+                "invalid-value",
+                "Datastore \"startup\" cannot be edited",
+            ));
+        }
+        self.check_writable(target, session_id)?;
+
+        let default_operation = match operation.child_text("default-operation") {
+            Some(value) => EditOperation::parse(value)?,
+            None => EditOperation::Merge,
+        };
+        if default_operation == EditOperation::Create
+            || default_operation == EditOperation::Delete
+            || default_operation == EditOperation::Remove
+        {
+            return Err(protocol_error(
+                "invalid-value",
+                "Invalid <default-operation>",
+            ));
+        }
+        let edits = match operation.child("config") {
+            Some(config) => &config.children,
+            None => {
+                return Err(protocol_error(
+                    "operation-not-supported",
+                    "Only inline <config> supported by simulator",
+                ))
+            }
+        };
+        let test_only = operation.child_text("test-option") == Some("test-only");
+        let error_option = operation
+            .child_text("error-option")
+            .unwrap_or("stop-on-error");
+
+        let mut data = self.datastore(target).clone();
+        let result = match error_option {
+            "continue-on-error" => {
+                let mut first_error = None;
+                for edit in edits {
+                    let result = apply_edits(
+                        &mut data,
+                        std::slice::from_ref(edit),
+                        default_operation,
+                        &self.hints,
+                        "",
+                    );
+                    if let Err(err) = result {
+                        first_error.get_or_insert(err);
+                    }
+                }
+                first_error.map_or(Ok(()), Err)
+            }
+            _ => apply_edits(&mut data, edits, default_operation, &self.hints, ""),
+        };
+
+        // "rollback-on-error" keeps the datastore untouched on error, "stop-on-error" keeps the partial changes
+        let keep = !test_only && (result.is_ok() || error_option != "rollback-on-error");
+        if keep {
+            self.set_datastore(target, data);
+        }
+        result
+    }
+
+    /// Check the pending confirmed commit can be confirmed or cancelled by the session.
+    fn check_pending_commit(&self, persist_id: Option<&str>, session_id: u32) -> SimResult<()> {
+        let pending = match &self.pending_commit {
+            Some(pending) => pending,
+            None => {
+                return Err(protocol_error(
+                    "operation-failed",
+                    "No confirmed commit pending",
+                ))
+            }
+        };
+        match (&pending.persist, persist_id) {
+            (Some(persist), Some(persist_id)) if persist == persist_id => Ok(()),
+            (Some(_), _) => Err(protocol_error(
+                "invalid-value",
+                "Missing or invalid <persist-id> of the pending confirmed commit",
+            )),
+            (None, Some(_)) => Err(protocol_error(
+                "invalid-value",
+                "Pending confirmed commit is not persistent",
+            )),
+            (None, None) if pending.session_id != session_id => Err(protocol_error(
+                "operation-failed",
+                "Confirmed commit pending from another session",
+            )),
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn commit(&mut self, operation: &XmlNode, session_id: u32) -> SimResult<()> {
+        self.check_lock(Target::Running, session_id)?;
+        if self.pending_commit.is_some() {
+            self.check_pending_commit(operation.child_text("persist-id"), session_id)?;
+        }
+
+        let previous = self.datastore(Target::Running).clone();
+        let candidate = self.datastore(Target::Candidate).clone();
+        self.set_datastore(Target::Running, candidate);
+
+        if operation.child("confirmed").is_none() {
+            self.pending_commit = None;
+            return Ok(());
+        }
+        let timeout = match operation.child_text("confirm-timeout") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| protocol_error("invalid-value", "Invalid <confirm-timeout>"))?,
+            None => DEFAULT_CONFIRM_TIMEOUT,
+        };
+        let rollback = match self.pending_commit.take() {
+            // follow-up confirmed commit keeps the original rollback
+            Some(pending) => pending.rollback,
+            None => previous,
+        };
+        self.pending_commit = Some(PendingCommit {
+            rollback,
+            deadline: Instant::now() + Duration::from_secs(timeout),
+            session_id,
+            persist: operation.child_text("persist").map(|x| x.to_string()),
+        });
+        Ok(())
+    }
+}
+
+/// Simulated NETCONF server keeping in-memory "running", "candidate" & "startup" configuration datastores.
+///
+/// Implements `<get>`, `<get-config>` (including subtree filters), `<edit-config>`, `<copy-config>`, `<delete-config>`,
+/// `<lock>`, `<unlock>`, `<commit>` (including confirmed commit with rollback on timeout or session termination),
+/// `<cancel-commit>`, `<discard-changes>`, `<validate>` and `<kill-session>` on top of [`MockServer`].
+///
+/// There is no YANG schema - lists & leaf-lists, whose entries are identified by keys or values,
+/// must be declared via [`Self::list()`] & [`Self::leaf_list()`]. Any other element is treated as a container or a leaf.
+/// Only inline `<config>` is supported, URLs are rejected.
+///
+/// Instances are cheap clones sharing the same datastores, so the state can be inspected while the server runs.
+#[derive(Clone)]
+pub struct Simulator {
+    state: Arc<Mutex<SimState>>,
+    session_counter: Arc<AtomicU32>,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    /// Create new simulator with empty datastores and writable "running".
+    pub fn new() -> Self {
+        let datastores = [Target::Running, Target::Candidate, Target::Startup]
+            .into_iter()
+            .map(|target| (target, vec![]))
+            .collect();
+        Self {
+            state: Arc::new(Mutex::new(SimState {
+                datastores,
+                locks: HashMap::new(),
+                pending_commit: None,
+                hints: HashMap::new(),
+                writable_running: true,
+            })),
+            session_counter: Arc::new(AtomicU32::new(1)),
+        }
+    }
+
+    /// Set the initial configuration of all the datastores from XML fragment.
+    pub fn with_config(self, xml: &str) -> Result<Self> {
+        let data: Vec<XmlNode> = XmlNode::parse_fragment(xml)?
+            .iter()
+            .map(normalize)
+            .collect();
+        {
+            let mut state = self.state.lock().unwrap();
+            for target in [Target::Running, Target::Candidate, Target::Startup] {
+                state.set_datastore(target, data.clone());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Declare elements of the `name` as list entries identified by the `keys` leaves.
+    pub fn list(self, name: &str, keys: &[&str]) -> Self {
+        let keys = keys.iter().map(|x| x.to_string()).collect();
+        self.state
+            .lock()
+            .unwrap()
+            .hints
+            .insert(name.to_string(), ListKind::List(keys));
+        self
+    }
+
+    /// Declare elements of the `name` as leaf-list entries identified by their values.
+    pub fn leaf_list(self, name: &str) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .hints
+            .insert(name.to_string(), ListKind::LeafList);
+        self
+    }
+
+    /// Set whether the "running" datastore can be written directly - `:writable-running` capability.
+    pub fn writable_running(self, writable: bool) -> Self {
+        self.state.lock().unwrap().writable_running = writable;
+        self
+    }
+
+    /// Capabilities of the simulated server.
+    pub fn capabilities(&self) -> Vec<Capability> {
+        let mut capabilities = vec![
+            Capability::Base,
+            Capability::Base11,
+            Capability::Candidate,
+            Capability::ConfirmedCommit,
+            Capability::RollbackOnError,
+            Capability::Startup,
+            Capability::Validate11,
+        ];
+        if self.state.lock().unwrap().writable_running {
+            capabilities.push(Capability::WritableRunning);
+        }
+        capabilities
+    }
+
+    /// Mock server advertising [`Self::capabilities()`], with handlers of all the simulated operations.
+    ///
+    /// All the servers of the simulator share the datastores, and assign unique session-ids.
+    pub fn server(&self) -> MockServer {
+        let mut server = MockServer::new()
+            .capabilities(self.capabilities())
+            .session_counter(self.session_counter.clone());
+        for operation in OPERATIONS {
+            let simulator = self.clone();
+            server = server.handle(operation, move |request| simulator.handle(request));
+        }
+        let simulator = self.clone();
+        server.on_close(move |session_id| simulator.state.lock().unwrap().end_session(session_id))
+    }
+
+    /// Current content of the `datastore`, `None` for datastores not simulated.
+    pub fn config(&self, datastore: &Datastore) -> Option<Vec<XmlNode>> {
+        let mut state = self.state.lock().unwrap();
+        state.expire_pending_commit();
+        Target::from_datastore(datastore).map(|target| state.datastore(target).clone())
+    }
+
+    /// Session-id of the current `datastore` lock owner, if any.
+    pub fn lock_owner(&self, datastore: &Datastore) -> Option<u32> {
+        let state = self.state.lock().unwrap();
+        Target::from_datastore(datastore).and_then(|target| state.locks.get(&target).copied())
+    }
+
+    /// Whether a confirmed commit is pending confirmation.
+    pub fn is_commit_pending(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.expire_pending_commit();
+        state.pending_commit.is_some()
+    }
+
+    /// Handle the simulated operation request.
+    pub fn handle(&self, request: &RpcRequest) -> Reply {
+        let result = self.state.lock().unwrap().handle(request);
+        match result {
+            Ok(None) => Reply::Ok,
+            Ok(Some(data)) => match XmlNode::fragment_to_xml(&data) {
+                Ok(xml) => Reply::Data(xml),
+                Err(err) => Reply::error(
+                    ErrorType::Application,
+                    "operation-failed",
+                    Some(&escape(&err.to_string())),
+                ),
+            },
+            Err(err) => Reply::Error(*err),
+        }
+    }
+}
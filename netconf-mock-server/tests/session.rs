@@ -0,0 +1,657 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use netconf_client::{
+    framing::FrameDecoder,
+    messages::{
+        get::GetRequest, get_config::GetConfigRequest, lock::LockRequest, NetconfRequest, ToRawXml,
+    },
+    types::{
+        Capability, Conformance, Datastore, ErrorType, RpcAttribute, RpcReply, XmlNode,
+        YangLibrary, LEGACY_MODULE_SET,
+    },
+    BaseCapability, NetconfSession,
+};
+use netconf_mock_server::{MockServer, MockServerHandle, Reply, TcpTransport};
+
+fn connect(
+    server: MockServer,
+    client_capabilities: Vec<Capability>,
+) -> (NetconfSession, MockServerHandle) {
+    let (transport, handle) = server.spawn_in_memory();
+    let mut session = NetconfSession::with_transport(Box::new(transport), client_capabilities);
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+    (session, handle)
+}
+
+fn get_config(session: &mut NetconfSession) -> anyhow::Result<String> {
+    let request = GetConfigRequest::new(session.new_message_id(), Datastore::Running, None);
+    let response = session.dispatch_request(request)?;
+    Ok(response.data()?.to_string())
+}
+
+#[test]
+fn hello_exchange_negotiates_chunked_framing() {
+    let server = MockServer::new()
+        .capabilities(vec![
+            Capability::Base,
+            Capability::Base11,
+            Capability::Candidate,
+        ])
+        .session_id(42);
+    let (session, handle) = connect(server, vec![Capability::Base, Capability::Base11]);
+
+    assert_eq!(session.session_id(), Some(42));
+    assert_eq!(session.base_capability(), BaseCapability::Base11);
+    let capabilities = session.server_capabilities().unwrap();
+    assert!(capabilities.contains(&Capability::Candidate));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn hello_exchange_falls_back_to_base_10() {
+    let (session, handle) = connect(MockServer::new(), vec![Capability::Base]);
+    assert_eq!(session.base_capability(), BaseCapability::Base);
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn hello_without_common_base_fails() {
+    let server = MockServer::new().capabilities(vec![Capability::Base11]);
+    let (transport, handle) = server.spawn_in_memory();
+    let mut session = NetconfSession::with_transport(Box::new(transport), vec![Capability::Base]);
+    session.connect().unwrap();
+    assert!(session.exchange_hello().is_err());
+    drop(session);
+    assert!(handle.join().is_err());
+}
+
+#[test]
+fn canned_data_reply() {
+    for base in [Capability::Base, Capability::Base11] {
+        let server = MockServer::new().reply(
+            "get-config",
+            Reply::data("<top xmlns=\"urn:example\"><a>1</a></top>"),
+        );
+        let (mut session, handle) = connect(server, vec![base]);
+
+        let data = get_config(&mut session).unwrap();
+        let data = XmlNode::parse(&data).unwrap();
+        assert_eq!(data.find("a").unwrap().text(), "1");
+
+        let requests = handle.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].operation, "get-config");
+        assert_eq!(requests[0].message_id.as_deref(), Some("1"));
+        let source = requests[0]
+            .operation_node()
+            .unwrap()
+            .child("source")
+            .unwrap();
+        assert!(source.child("running").is_some());
+
+        drop(session);
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn error_reply() {
+    let server = MockServer::new()
+        .capabilities(vec![
+            Capability::Base,
+            Capability::Base11,
+            Capability::WritableRunning,
+        ])
+        .reply(
+            "lock",
+            Reply::error(
+                ErrorType::Protocol,
+                "lock-denied",
+                Some("Locked by other session"),
+            ),
+        );
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+
+    let request = LockRequest::new(session.new_message_id(), Datastore::Running);
+    let response = session.dispatch_request(request).unwrap();
+    match response.typed.reply {
+        RpcReply::Error(errors) => {
+            let err = &errors[0];
+            assert_eq!(err.error_tag, "lock-denied");
+            assert_eq!(err.error_type, ErrorType::Protocol);
+            assert_eq!(
+                err.error_message.as_deref(),
+                Some("Locked by other session")
+            );
+        }
+        RpcReply::Ok => panic!("Expected error reply"),
+    }
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn unsupported_operation_default_reply() {
+    let server = MockServer::new().capabilities(vec![
+        Capability::Base,
+        Capability::Base11,
+        Capability::WritableRunning,
+    ]);
+    let (mut session, handle) = connect(server, vec![Capability::Base]);
+    let request = LockRequest::new(session.new_message_id(), Datastore::Running);
+    let response = session.dispatch_request(request).unwrap();
+    match response.typed.reply {
+        RpcReply::Error(errors) => assert_eq!(errors[0].error_tag, "operation-not-supported"),
+        RpcReply::Ok => panic!("Expected error reply"),
+    }
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn closure_handler_and_script() {
+    let counter = Arc::new(AtomicU32::new(0));
+    let server = {
+        let counter = counter.clone();
+        MockServer::new()
+            .handle("get-config", move |request| {
+                let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Reply::data(&format!(
+                    "<count>{}:{}</count>",
+                    count,
+                    request.message_id.clone().unwrap()
+                ))
+            })
+            .script(Reply::data("<scripted/>"))
+    };
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+
+    assert_eq!(
+        get_config(&mut session).unwrap(),
+        "<data><scripted/></data>"
+    );
+    assert_eq!(
+        get_config(&mut session).unwrap(),
+        "<data><count>1:2</count></data>"
+    );
+    assert_eq!(
+        get_config(&mut session).unwrap(),
+        "<data><count>2:3</count></data>"
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn rpc_attributes_echoed() {
+    let server = MockServer::new().reply("get", Reply::data(""));
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+
+    let attributes = vec![
+        RpcAttribute::new("user-id", "fred"),
+        RpcAttribute::namespaced("ex", "urn:example", "trace", "a<b"),
+    ];
+    let request =
+        GetRequest::new(session.new_message_id(), None).with_attributes(attributes.clone());
+    let response = session.dispatch_request(request).unwrap();
+    assert_eq!(response.attributes, attributes);
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn rpc_attributes_clashing() {
+    let server = MockServer::new().reply("get", Reply::data(""));
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+
+    let clashing = [
+        RpcAttribute::new("message-id", "1"),
+        RpcAttribute::new("xmlns", "urn:example"),
+        RpcAttribute::new("xmlns:ex", "urn:example"),
+        RpcAttribute::new("ex:trace", "1"),
+        RpcAttribute::namespaced("xmlns", "urn:example", "ex", "1"),
+        RpcAttribute::namespaced("xml", "urn:example", "lang", "en"),
+    ];
+    for attribute in clashing {
+        let request = GetRequest::new(session.next_message_id(), None)
+            .with_attributes(vec![attribute.clone()]);
+        assert!(request.to_raw_xml().is_err(), "{}", attribute);
+        assert!(session.dispatch_request(request).is_err(), "{}", attribute);
+    }
+
+    // prefix bound to another namespace by another attribute, or attributes of the same name
+    let clashing = [
+        vec![
+            RpcAttribute::namespaced("ex", "urn:example", "trace", "1"),
+            RpcAttribute::namespaced("ex", "urn:other", "user", "fred"),
+        ],
+        vec![
+            RpcAttribute::new("user-id", "fred"),
+            RpcAttribute::new("user-id", "barney"),
+        ],
+    ];
+    for attributes in clashing {
+        let request =
+            GetRequest::new(session.next_message_id(), None).with_attributes(attributes.clone());
+        assert!(request.to_raw_xml().is_err(), "{:?}", attributes);
+    }
+
+    // the same namespace of several attributes is declared once
+    let attributes = vec![
+        RpcAttribute::namespaced("ex", "urn:example", "trace", "1"),
+        RpcAttribute::namespaced("ex", "urn:example", "user", "fred"),
+    ];
+    let request =
+        GetRequest::new(session.new_message_id(), None).with_attributes(attributes.clone());
+    assert_eq!(
+        request.to_raw_xml().unwrap().matches("xmlns:ex=").count(),
+        1
+    );
+    let response = session.dispatch_request(request).unwrap();
+    assert_eq!(response.attributes, attributes);
+    assert_eq!(handle.requests().len(), 1);
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+const YANG_LIBRARY: &str = r#"
+<yang-library xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-library"
+    xmlns:ds="urn:ietf:params:xml:ns:yang:ietf-datastores">
+  <module-set>
+    <name>config</name>
+    <module>
+      <name>ietf-interfaces</name>
+      <revision>2018-02-20</revision>
+      <namespace>urn:ietf:params:xml:ns:yang:ietf-interfaces</namespace>
+      <feature>if-mib</feature>
+      <deviation>example-deviations</deviation>
+    </module>
+    <module>
+      <name>example-system</name>
+      <namespace>urn:example:sys</namespace>
+      <location>https://example.com/example-system.yang</location>
+      <submodule><name>example-system-dns</name><revision>2020-01-01</revision></submodule>
+    </module>
+    <import-only-module>
+      <name>ietf-yang-types</name>
+      <revision>2013-07-15</revision>
+      <namespace>urn:ietf:params:xml:ns:yang:ietf-yang-types</namespace>
+    </import-only-module>
+  </module-set>
+  <schema><name>complete</name><module-set>config</module-set></schema>
+  <datastore><name>ds:running</name><schema>complete</schema></datastore>
+  <datastore><name>ds:operational</name><schema>complete</schema></datastore>
+  <content-id>42</content-id>
+</yang-library>
+"#;
+
+const MODULES_STATE: &str = r#"
+<modules-state xmlns="urn:ietf:params:xml:ns:yang:ietf-yang-library">
+  <module-set-id>7</module-set-id>
+  <module>
+    <name>ietf-interfaces</name>
+    <revision>2014-05-08</revision>
+    <schema>https://example.com/ietf-interfaces.yang</schema>
+    <namespace>urn:ietf:params:xml:ns:yang:ietf-interfaces</namespace>
+    <deviation><name>example-deviations</name><revision>2020-01-01</revision></deviation>
+    <conformance-type>implement</conformance-type>
+  </module>
+  <module>
+    <name>ietf-yang-types</name>
+    <revision>2013-07-15</revision>
+    <namespace>urn:ietf:params:xml:ns:yang:ietf-yang-types</namespace>
+    <conformance-type>import</conformance-type>
+  </module>
+</modules-state>
+"#;
+
+/// Server replying the `<get>` of the YANG library `container` by the `data`, or error.
+fn yang_library_server(capability: Option<&str>, container: &str, data: &str) -> MockServer {
+    let mut capabilities = vec![Capability::Base, Capability::Base11];
+    capabilities.extend(capability.map(|x| Capability::from_urn(x).unwrap()));
+    let container = container.to_string();
+    let data = data.to_string();
+    MockServer::new()
+        .capabilities(capabilities)
+        .handle("get", move |request| {
+            match request.operation_node().unwrap().find(&container) {
+                Some(_) => Reply::data(&data),
+                None => Reply::error(ErrorType::Application, "unknown-element", None),
+            }
+        })
+}
+
+/// Containers requested by the `<get>` requests of the server.
+fn requested_containers(handle: &MockServerHandle) -> Vec<String> {
+    handle
+        .requests()
+        .iter()
+        .filter_map(|x| x.operation_node())
+        .filter_map(|x| x.child("filter"))
+        .filter_map(|x| x.children.first().map(|x| x.name.clone()))
+        .collect()
+}
+
+#[test]
+fn yang_library_retrieved() {
+    // NMDA server
+    let server = yang_library_server(
+        Some(
+            "urn:ietf:params:netconf:capability:yang-library:1.1?revision=2019-01-04&content-id=42",
+        ),
+        "yang-library",
+        YANG_LIBRARY,
+    );
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    let library = session.get_yang_library().unwrap();
+    assert_eq!(library.content_id, "42");
+    assert_eq!(library.module_sets.len(), 1);
+    assert_eq!(library.module_sets[0].name, "config");
+    let interfaces = library.module("ietf-interfaces").unwrap();
+    assert_eq!(interfaces.revision.as_deref(), Some("2018-02-20"));
+    assert_eq!(interfaces.features, ["if-mib"]);
+    assert_eq!(interfaces.deviations, ["example-deviations"]);
+    let system = library.module("example-system").unwrap();
+    assert_eq!(system.revision, None);
+    assert_eq!(
+        system.locations,
+        ["https://example.com/example-system.yang"]
+    );
+    assert_eq!(system.submodules[0].name, "example-system-dns");
+    assert_eq!(
+        library.module("ietf-yang-types").unwrap().conformance,
+        Conformance::Import
+    );
+    assert_eq!(library.schemas[0].module_sets, ["config"]);
+    let datastores: Vec<&Datastore> = library.datastores.iter().map(|x| &x.datastore).collect();
+    assert_eq!(datastores, [&Datastore::Running, &Datastore::Operational]);
+    // modules of the backup come from the library, if none advertised in <hello>
+    let revisions: Vec<String> = session
+        .module_revisions()
+        .unwrap()
+        .iter()
+        .map(|x| format!("{}@{}", x.name, x.revision.as_deref().unwrap_or("")))
+        .collect();
+    assert_eq!(revisions, ["ietf-interfaces@2018-02-20", "example-system@"]);
+    drop(session);
+    assert_eq!(
+        requested_containers(&handle),
+        ["yang-library", "yang-library"]
+    );
+    handle.join().unwrap();
+
+    // legacy server advertising :yang-library:1.0 is asked for <modules-state> only
+    let server = yang_library_server(
+        Some("urn:ietf:params:netconf:capability:yang-library:1.0?revision=2016-06-21&module-set-id=7"),
+        "modules-state",
+        MODULES_STATE,
+    );
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    let library = session.get_yang_library().unwrap();
+    assert_eq!(library.content_id, "7");
+    assert_eq!(library.module_sets[0].name, LEGACY_MODULE_SET);
+    let interfaces = library.module("ietf-interfaces").unwrap();
+    assert_eq!(
+        interfaces.locations,
+        ["https://example.com/ietf-interfaces.yang"]
+    );
+    assert_eq!(interfaces.deviations, ["example-deviations"]);
+    assert_eq!(
+        library.module("ietf-yang-types").unwrap().conformance,
+        Conformance::Import
+    );
+    assert!(library.datastores.is_empty());
+    drop(session);
+    assert_eq!(requested_containers(&handle), ["modules-state"]);
+    handle.join().unwrap();
+
+    // <modules-state> is the fall back of server without the capability
+    let server = yang_library_server(None, "modules-state", MODULES_STATE);
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    assert_eq!(session.get_yang_library().unwrap().content_id, "7");
+    drop(session);
+    assert_eq!(
+        requested_containers(&handle),
+        ["yang-library", "modules-state"]
+    );
+    handle.join().unwrap();
+
+    // `show modules` falls back to the modules of <hello>
+    let module = "urn:example:sys?module=example-system&revision=2020-01-01";
+    let server = yang_library_server(Some(module), "none", "");
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    let err = session.get_yang_library().unwrap_err().to_string();
+    assert!(err.contains("<yang-library>: error reply \"unknown-element\""));
+    assert!(err.contains("<modules-state>: error reply \"unknown-element\""));
+    let modules: Vec<String> = session
+        .server_capabilities()
+        .unwrap()
+        .modules()
+        .map(|x| x.to_string())
+        .collect();
+    assert_eq!(modules, ["example-system@2020-01-01"]);
+    drop(session);
+    handle.join().unwrap();
+
+    // parsed from the <data> of any <get> reply
+    let data = XmlNode::parse(&format!("<data>{}</data>", MODULES_STATE)).unwrap();
+    assert_eq!(YangLibrary::from_data(&data).unwrap().content_id, "7");
+    let data = XmlNode::parse("<data><other/></data>").unwrap();
+    assert!(YangLibrary::from_data(&data).is_err());
+}
+
+#[test]
+fn capability_validation_prevents_dispatch() {
+    let (mut session, handle) = connect(MockServer::new(), vec![Capability::Base]);
+    let request = GetConfigRequest::new(session.new_message_id(), Datastore::Candidate, None);
+    assert!(session.dispatch_request(request).is_err());
+    assert!(handle.requests().is_empty());
+
+    session.set_validate_capabilities(false);
+    let request = GetConfigRequest::new(session.new_message_id(), Datastore::Candidate, None);
+    assert!(session.dispatch_request(request).is_ok());
+    assert_eq!(handle.requests().len(), 1);
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn delayed_reply() {
+    let server = MockServer::new().script(Reply::delayed(
+        Duration::from_millis(50),
+        Reply::data("<late/>"),
+    ));
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    assert_eq!(get_config(&mut session).unwrap(), "<data><late/></data>");
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn reply_timeout() {
+    let server = MockServer::new().script(Reply::Ignore);
+    let (mut transport, handle) = server.spawn_in_memory();
+    transport.set_timeout(Some(Duration::from_millis(50)));
+    let mut session = NetconfSession::with_transport(Box::new(transport), vec![Capability::Base11]);
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+
+    let err = get_config(&mut session).unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{}", err);
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn disconnect_during_rpc() {
+    let server = MockServer::new().script(Reply::Disconnect);
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    let err = get_config(&mut session).unwrap_err();
+    assert!(err.to_string().contains("closed"), "{}", err);
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn disconnect_before_hello() {
+    let server = MockServer::new().hello_reply(Reply::Disconnect);
+    let (transport, handle) = server.spawn_in_memory();
+    let mut session = NetconfSession::with_transport(Box::new(transport), vec![Capability::Base]);
+    session.connect().unwrap();
+    assert!(session.exchange_hello().is_err());
+    assert_eq!(session.session_id(), None);
+    handle.join().unwrap();
+}
+
+#[test]
+fn delayed_hello() {
+    let server =
+        MockServer::new().hello_reply(Reply::delayed(Duration::from_millis(50), Reply::Default));
+    let (session, handle) = connect(server, vec![Capability::Base]);
+    assert_eq!(session.session_id(), Some(1));
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn multiple_chunks_reassembled() {
+    let reply = "<rpc-reply xmlns=\"urn:ietf:params:xml:ns:netconf:base:1.0\" message-id=\"1\"><data><x/></data></rpc-reply>";
+    let (head, tail) = reply.split_at(20);
+    let framed = format!(
+        "\n#{}\n{}\n#{}\n{}\n##\n",
+        head.len(),
+        head,
+        tail.len(),
+        tail
+    );
+    let server = MockServer::new().script(Reply::Bytes(framed.into_bytes()));
+    let (mut session, handle) = connect(server, vec![Capability::Base11]);
+    assert_eq!(get_config(&mut session).unwrap(), "<data><x/></data>");
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn frame_decoder_fed_in_pieces() {
+    let hello = "<hello/>";
+    let replies = [
+        "<rpc-reply message-id=\"1\"><ok/></rpc-reply>",
+        "<rpc-reply/>",
+    ];
+    let mut stream = format!("{}]]>]]>", hello);
+    for reply in replies {
+        let (head, tail) = reply.split_at(5);
+        stream += &format!(
+            "\n#{}\n{}\n#{}\n{}\n##\n",
+            head.len(),
+            head,
+            tail.len(),
+            tail
+        );
+    }
+
+    for piece_size in [1, 2, 3, 7, stream.len()] {
+        let mut decoder = FrameDecoder::new(BaseCapability::Base);
+        let mut messages = vec![];
+        for piece in stream.as_bytes().chunks(piece_size) {
+            decoder.feed(piece);
+            while let Some(message) = decoder.next_message().unwrap() {
+                messages.push(message);
+                decoder.set_base(BaseCapability::Base11);
+            }
+        }
+        assert_eq!(messages, [hello, replies[0], replies[1]], "{}", piece_size);
+        assert!(decoder.pending().is_empty());
+    }
+}
+
+#[test]
+fn framing_errors() {
+    let invalid = [
+        "\n#abc\n<rpc-reply/>\n##\n",
+        "\n#012\n<rpc-reply/>\n##\n",
+        "<rpc-reply/>]]>]]>",
+        "\n##\n",
+    ];
+    for bytes in invalid {
+        let server = MockServer::new().script(Reply::Bytes(bytes.as_bytes().to_vec()));
+        let (mut session, handle) = connect(server, vec![Capability::Base11]);
+        assert!(get_config(&mut session).is_err(), "{:?}", bytes);
+        drop(session);
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn malformed_reply() {
+    let server = MockServer::new().script(Reply::Message(
+        "<rpc-reply message-id=\"1\"><data>".to_string(),
+    ));
+    let (mut session, handle) = connect(server, vec![Capability::Base]);
+    assert!(get_config(&mut session).is_err());
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn close_session_closes_connection() {
+    let (mut session, handle) = connect(MockServer::new(), vec![Capability::Base11]);
+    let response = session.request_close_session().unwrap();
+    assert_eq!(response.typed.reply, RpcReply::Ok);
+    handle.join().unwrap();
+}
+
+#[test]
+fn dispatch_after_close_session_fails() {
+    let (mut session, handle) = connect(MockServer::new(), vec![Capability::Base11]);
+    session.request_close_session().unwrap();
+    assert_eq!(session.session_id(), None);
+    assert!(session.server_capabilities().is_none());
+
+    assert!(get_config(&mut session).is_err());
+    session.set_validate_capabilities(false);
+    assert!(get_config(&mut session).is_err());
+    assert!(session.transaction().is_err());
+    assert!(session.request_close_session().is_err());
+    handle.join().unwrap();
+}
+
+#[test]
+fn tcp_listener_serves_multiple_sessions() {
+    let server = MockServer::new()
+        .session_id(7)
+        .reply("get-config", Reply::data("<tcp/>"));
+    let handle = server.listen_tcp("127.0.0.1:0".parse().unwrap()).unwrap();
+    let address = handle.local_addr().unwrap();
+
+    for session_id in [7, 8] {
+        let transport = TcpTransport::new(address);
+        let mut session =
+            NetconfSession::with_transport(Box::new(transport), vec![Capability::Base11]);
+        session.connect().unwrap();
+        session.exchange_hello().unwrap();
+        assert_eq!(session.session_id(), Some(session_id));
+        assert_eq!(session.target_string(), address.to_string());
+        assert_eq!(get_config(&mut session).unwrap(), "<data><tcp/></data>");
+        session.request_close_session().unwrap();
+    }
+
+    assert_eq!(handle.requests().len(), 4);
+    handle.join().unwrap();
+}
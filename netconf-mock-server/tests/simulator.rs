@@ -0,0 +1,1118 @@
+use std::{
+    io::Write,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use netconf_client::{
+    backup::{ConfigBackup, RestoreMethod, RestoreOptions},
+    diff::{ConfigDiff, ListHints},
+    messages::{
+        cancel_commit::CancelCommitRequest,
+        commit::{CommitRequest, CommitType, ConfirmedCommitParams},
+        copy_config::CopyConfigRequest,
+        delete_config::DeleteConfigRequest,
+        discard_changes::DiscardChangesRequest,
+        edit_config::{
+            EditConfigContent, EditConfigParams, EditConfigRequest, ErrorOption, TestOption,
+        },
+        get_config::GetConfigRequest,
+        lock::LockRequest,
+        unlock::UnlockRequest,
+    },
+    trace::{Direction, WireTrace},
+    transcript::{ReplayTransport, Transcript, TranscriptRecorder},
+    types::{Capability, ConfigWaypoint, Datastore, Filter, FilterPayload, RpcReply, XmlNode},
+    BaseCapability, NetconfSession,
+};
+use netconf_mock_server::{MockServerHandle, Reply, Simulator};
+
+const CONFIG: &str = r#"
+<interfaces xmlns="urn:example:if">
+  <interface><name>eth0</name><mtu>1500</mtu><enabled>true</enabled></interface>
+  <interface><name>eth1</name><mtu>1500</mtu><enabled>false</enabled></interface>
+</interfaces>
+<system xmlns="urn:example:sys"><hostname>router</hostname><dns>1.1.1.1</dns></system>
+"#;
+
+fn simulator() -> Simulator {
+    Simulator::new()
+        .with_config(CONFIG)
+        .unwrap()
+        .list("interface", &["name"])
+        .leaf_list("dns")
+}
+
+fn connect(simulator: &Simulator) -> (NetconfSession, MockServerHandle) {
+    let (transport, handle) = simulator.server().spawn_in_memory();
+    let mut session = NetconfSession::with_transport(
+        Box::new(transport),
+        vec![Capability::Base, Capability::Base11],
+    );
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+    (session, handle)
+}
+
+/// Error tag of the reply, `None` for `<ok/>`.
+fn error_tag(reply: &RpcReply) -> Option<String> {
+    match reply {
+        RpcReply::Ok => None,
+        RpcReply::Error(errors) => Some(errors[0].error_tag.clone()),
+    }
+}
+
+fn edit(
+    session: &mut NetconfSession,
+    target: Datastore,
+    config: &str,
+    error_option: Option<ErrorOption>,
+    test_option: Option<TestOption>,
+) -> Option<String> {
+    let params = EditConfigParams {
+        target,
+        default_operation: None,
+        test_option,
+        error_option,
+        config: EditConfigContent::Config(config.to_string()),
+    };
+    let request = EditConfigRequest::new(session.new_message_id(), params);
+    error_tag(&session.dispatch_request(request).unwrap().typed.reply)
+}
+
+fn commit(session: &mut NetconfSession, commit_type: CommitType) -> Option<String> {
+    let request = CommitRequest::new(session.new_message_id(), commit_type);
+    error_tag(&session.dispatch_request(request).unwrap().typed.reply)
+}
+
+fn confirmed(confirm_timeout: u32, persist: Option<&str>) -> CommitType {
+    CommitType::Confirmed(ConfirmedCommitParams {
+        confirm_timeout: Some(confirm_timeout),
+        persist: persist.map(|x| x.to_string()),
+        persist_id: None,
+    })
+}
+
+fn get_config(
+    session: &mut NetconfSession,
+    source: Datastore,
+    subtree: Option<&str>,
+) -> Vec<XmlNode> {
+    let filter = subtree.map(|x| FilterPayload {
+        filter: Filter::Subtree(x.to_string()),
+        namespaces: vec![],
+    });
+    let request = GetConfigRequest::new(session.new_message_id(), source, filter);
+    let response = session.dispatch_request(request).unwrap();
+    let data = XmlNode::parse(response.data().unwrap()).unwrap();
+    data.children
+}
+
+fn text_at<'a>(nodes: &'a [XmlNode], path: &[&str]) -> Option<&'a str> {
+    let (first, rest) = path.split_first()?;
+    let mut node = nodes.iter().find(|x| x.name == *first)?;
+    for name in rest {
+        node = node.child(name)?;
+    }
+    Some(node.text())
+}
+
+fn interface<'a>(nodes: &'a [XmlNode], name: &str) -> Option<&'a XmlNode> {
+    nodes
+        .iter()
+        .find(|x| x.name == "interfaces")?
+        .children_named("interface")
+        .find(|x| x.child_text("name") == Some(name))
+}
+
+#[test]
+fn edit_config_operations() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let merge = r#"<interfaces xmlns="urn:example:if">
+        <interface><name>eth0</name><mtu>9000</mtu></interface>
+        <interface><name>eth2</name><mtu>1400</mtu></interface>
+    </interfaces>
+    <system xmlns="urn:example:sys"><dns>8.8.8.8</dns></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Running, merge, None, None),
+        None
+    );
+
+    let running = simulator.config(&Datastore::Running).unwrap();
+    let eth0 = interface(&running, "eth0").unwrap();
+    assert_eq!(eth0.child_text("mtu"), Some("9000"));
+    assert_eq!(eth0.child_text("enabled"), Some("true"));
+    assert_eq!(
+        interface(&running, "eth2").unwrap().child_text("mtu"),
+        Some("1400")
+    );
+    let system = running.iter().find(|x| x.name == "system").unwrap();
+    assert_eq!(system.children_named("dns").count(), 2);
+
+    let replace = r#"<interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <interface nc:operation="replace"><name>eth1</name><mtu>1600</mtu></interface>
+        <interface nc:operation="delete"><name>eth2</name></interface>
+    </interfaces>
+    <system xmlns="urn:example:sys" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <dns nc:operation="remove">1.1.1.1</dns><hostname nc:operation="remove"/>
+    </system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Running, replace, None, None),
+        None
+    );
+
+    let running = simulator.config(&Datastore::Running).unwrap();
+    let eth1 = interface(&running, "eth1").unwrap();
+    assert_eq!(eth1.child_text("mtu"), Some("1600"));
+    assert_eq!(eth1.child("enabled"), None);
+    assert!(interface(&running, "eth2").is_none());
+    assert_eq!(text_at(&running, &["system", "dns"]), Some("8.8.8.8"));
+    assert_eq!(text_at(&running, &["system", "hostname"]), None);
+    assert!(running
+        .iter()
+        .flat_map(|x| x.children.iter())
+        .all(|x| x.attributes.is_empty()));
+
+    let create_existing = r#"<interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <interface nc:operation="create"><name>eth0</name></interface>
+    </interfaces>"#;
+    let tag = edit(
+        &mut session,
+        Datastore::Running,
+        create_existing,
+        None,
+        None,
+    );
+    assert_eq!(tag.as_deref(), Some("data-exists"));
+
+    let delete_missing = r#"<interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <interface nc:operation="delete"><name>eth9</name></interface>
+    </interfaces>"#;
+    let tag = edit(&mut session, Datastore::Running, delete_missing, None, None);
+    assert_eq!(tag.as_deref(), Some("data-missing"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn edit_config_error_and_test_options() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let partial = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>
+    <interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <interface nc:operation="create"><name>eth0</name></interface>
+    </interfaces>"#;
+    let hostname = |simulator: &Simulator| {
+        let running = simulator.config(&Datastore::Running).unwrap();
+        text_at(&running, &["system", "hostname"]).map(|x| x.to_string())
+    };
+
+    let tag = edit(
+        &mut session,
+        Datastore::Running,
+        partial,
+        Some(ErrorOption::RollbackOnError),
+        None,
+    );
+    assert_eq!(tag.as_deref(), Some("data-exists"));
+    assert_eq!(hostname(&simulator).as_deref(), Some("router"));
+
+    let valid = r#"<system xmlns="urn:example:sys"><hostname>tested</hostname></system>"#;
+    let tag = edit(
+        &mut session,
+        Datastore::Running,
+        valid,
+        None,
+        Some(TestOption::TestOnly),
+    );
+    assert_eq!(tag, None);
+    assert_eq!(hostname(&simulator).as_deref(), Some("router"));
+
+    let tag = edit(
+        &mut session,
+        Datastore::Running,
+        partial,
+        Some(ErrorOption::StopOnError),
+        None,
+    );
+    assert_eq!(tag.as_deref(), Some("data-exists"));
+    assert_eq!(hostname(&simulator).as_deref(), Some("edited"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn subtree_filter() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    // content match selecting single list entry with all its content
+    let data = get_config(
+        &mut session,
+        Datastore::Running,
+        Some(
+            r#"<interfaces xmlns="urn:example:if"><interface><name>eth1</name></interface></interfaces>"#,
+        ),
+    );
+    assert_eq!(data.len(), 1);
+    let interfaces = data[0].children_named("interface").collect::<Vec<_>>();
+    assert_eq!(interfaces.len(), 1);
+    assert_eq!(interfaces[0].child_text("enabled"), Some("false"));
+
+    // selection nodes of all the entries
+    let data = get_config(
+        &mut session,
+        Datastore::Running,
+        Some(
+            r#"<interfaces xmlns="urn:example:if"><interface><name/><mtu/></interface></interfaces>"#,
+        ),
+    );
+    let interfaces = data[0].children_named("interface").collect::<Vec<_>>();
+    assert_eq!(interfaces.len(), 2);
+    assert!(interfaces
+        .iter()
+        .all(|x| x.children.len() == 2 && x.child("enabled").is_none()));
+
+    // content match with selection of specific leaf
+    let data = get_config(
+        &mut session,
+        Datastore::Running,
+        Some(
+            r#"<interfaces xmlns="urn:example:if"><interface><name>eth0</name><enabled/></interface></interfaces>"#,
+        ),
+    );
+    let eth0 = interface(&data, "eth0").unwrap();
+    assert_eq!(eth0.child_text("enabled"), Some("true"));
+    assert!(eth0.child("mtu").is_none());
+
+    // namespace mismatch & no match
+    let data = get_config(
+        &mut session,
+        Datastore::Running,
+        Some(r#"<system xmlns="urn:example:other"/>"#),
+    );
+    assert!(data.is_empty());
+    let data = get_config(
+        &mut session,
+        Datastore::Running,
+        Some(
+            r#"<interfaces xmlns="urn:example:if"><interface><name>eth7</name></interface></interfaces>"#,
+        ),
+    );
+    assert!(data.is_empty());
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn candidate_commit_and_discard() {
+    let simulator = simulator().writable_running(false);
+    let (mut session, handle) = connect(&simulator);
+    assert!(!session
+        .server_capabilities()
+        .unwrap()
+        .contains(&Capability::WritableRunning));
+
+    let config = r#"<system xmlns="urn:example:sys"><hostname>candidate</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    assert_eq!(
+        text_at(&candidate, &["system", "hostname"]),
+        Some("candidate")
+    );
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("router"));
+
+    let request = DiscardChangesRequest::new(session.new_message_id());
+    assert_eq!(
+        session.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    assert_eq!(text_at(&candidate, &["system", "hostname"]), Some("router"));
+
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, CommitType::Plain), None);
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(
+        text_at(&running, &["system", "hostname"]),
+        Some("candidate")
+    );
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn locks() {
+    let simulator = simulator();
+    let (mut first, first_handle) = connect(&simulator);
+    let (mut second, second_handle) = connect(&simulator);
+
+    let request = LockRequest::new(first.new_message_id(), Datastore::Running);
+    assert_eq!(
+        first.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    assert_eq!(simulator.lock_owner(&Datastore::Running), Some(1));
+
+    let request = LockRequest::new(second.new_message_id(), Datastore::Running);
+    match second.dispatch_request(request).unwrap().typed.reply {
+        RpcReply::Error(errors) => assert_eq!(errors[0].error_tag, "lock-denied"),
+        RpcReply::Ok => panic!("Lock should be denied"),
+    }
+    let config = r#"<system xmlns="urn:example:sys"><hostname>second</hostname></system>"#;
+    let tag = edit(&mut second, Datastore::Running, config, None, None);
+    assert_eq!(tag.as_deref(), Some("in-use"));
+    let request = UnlockRequest::new(second.new_message_id(), Datastore::Running);
+    let reply = second.dispatch_request(request).unwrap().typed.reply;
+    assert_eq!(error_tag(&reply).as_deref(), Some("operation-failed"));
+
+    // lock owner can edit, lock is released on session termination
+    let config = r#"<system xmlns="urn:example:sys"><hostname>first</hostname></system>"#;
+    assert_eq!(
+        edit(&mut first, Datastore::Running, config, None, None),
+        None
+    );
+    drop(first);
+    first_handle.join().unwrap();
+    assert_eq!(simulator.lock_owner(&Datastore::Running), None);
+
+    // dirty candidate cannot be locked
+    let config = r#"<system xmlns="urn:example:sys"><hostname>dirty</hostname></system>"#;
+    assert_eq!(
+        edit(&mut second, Datastore::Candidate, config, None, None),
+        None
+    );
+    let request = LockRequest::new(second.new_message_id(), Datastore::Candidate);
+    let reply = second.dispatch_request(request).unwrap().typed.reply;
+    assert_eq!(error_tag(&reply).as_deref(), Some("lock-denied"));
+
+    drop(second);
+    second_handle.join().unwrap();
+}
+
+#[test]
+fn copy_and_delete_config() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let config = r#"<system xmlns="urn:example:sys"><hostname>copied</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Running, config, None, None),
+        None
+    );
+    let request = CopyConfigRequest::new(
+        session.new_message_id(),
+        ConfigWaypoint::Datastore(Datastore::Startup),
+        ConfigWaypoint::Datastore(Datastore::Running),
+    );
+    assert_eq!(
+        session.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    let startup = simulator.config(&Datastore::Startup).unwrap();
+    assert_eq!(text_at(&startup, &["system", "hostname"]), Some("copied"));
+
+    let request = DeleteConfigRequest::new(
+        session.new_message_id(),
+        ConfigWaypoint::Datastore(Datastore::Startup),
+    );
+    assert_eq!(
+        session.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    assert!(simulator.config(&Datastore::Startup).unwrap().is_empty());
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn confirmed_commit_confirmed() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let config = r#"<system xmlns="urn:example:sys"><hostname>confirmed</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, confirmed(60, None)), None);
+    assert!(simulator.is_commit_pending());
+    assert_eq!(commit(&mut session, CommitType::Plain), None);
+    assert!(!simulator.is_commit_pending());
+
+    drop(session);
+    handle.join().unwrap();
+    let running = simulator.config(&Datastore::Running).unwrap();
+    assert_eq!(
+        text_at(&running, &["system", "hostname"]),
+        Some("confirmed")
+    );
+}
+
+#[test]
+fn confirmed_commit_timeout_rollback() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let config = r#"<system xmlns="urn:example:sys"><hostname>timeout</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, confirmed(1, None)), None);
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("timeout"));
+
+    thread::sleep(Duration::from_millis(1100));
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("router"));
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    assert_eq!(text_at(&candidate, &["system", "hostname"]), Some("router"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn confirmed_commit_session_termination() {
+    let simulator = simulator();
+    let config = r#"<system xmlns="urn:example:sys"><hostname>terminated</hostname></system>"#;
+
+    // non-persistent confirmed commit is rolled back on session termination
+    let (mut session, handle) = connect(&simulator);
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, confirmed(60, None)), None);
+    drop(session);
+    handle.join().unwrap();
+    let running = simulator.config(&Datastore::Running).unwrap();
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("router"));
+
+    // persistent one survives, and can be cancelled by other session
+    let (mut session, handle) = connect(&simulator);
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, config, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, confirmed(60, Some("42"))), None);
+    drop(session);
+    handle.join().unwrap();
+    assert!(simulator.is_commit_pending());
+
+    let (mut session, handle) = connect(&simulator);
+    let request = CancelCommitRequest::new(session.new_message_id(), Some(7));
+    let reply = session.dispatch_request(request).unwrap().typed.reply;
+    assert_eq!(error_tag(&reply).as_deref(), Some("invalid-value"));
+    let request = CancelCommitRequest::new(session.new_message_id(), Some(42));
+    assert_eq!(
+        session.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    assert!(!simulator.is_commit_pending());
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("router"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn config_diff_patch() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let changes = r#"<interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <interface><name>eth1</name><mtu>9000</mtu></interface>
+        <interface nc:operation="delete"><name>eth0</name></interface>
+        <interface><name>eth2</name><mtu>1400</mtu></interface>
+    </interfaces>
+    <system xmlns="urn:example:sys" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <dns nc:operation="delete">1.1.1.1</dns><dns>9.9.9.9</dns>
+    </system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, changes, None, None),
+        None
+    );
+
+    let hints = ListHints::new()
+        .list("interface", &["name"])
+        .leaf_list("dns");
+    let running = get_config(&mut session, Datastore::Running, None);
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    let diff = ConfigDiff::new(&running, &candidate, &hints);
+    assert_eq!(
+        diff.to_tree(),
+        "  interfaces
+-   interface[name='eth0']
+-     name: eth0
+-     mtu: 1500
+-     enabled: true
+    interface[name='eth1']
+~     mtu: 1500 -> 9000
++   interface[name='eth2']
++     name: eth2
++     mtu: 1400
+  system
+-   dns[.='1.1.1.1']
++   dns[.='9.9.9.9']
+"
+    );
+    assert!(ConfigDiff::new(&diff.old_side(), &running, &hints).is_empty());
+    assert!(ConfigDiff::new(&diff.new_side(), &candidate, &hints).is_empty());
+
+    let unified = diff.to_unified("running", "candidate", 1);
+    assert!(unified
+        .starts_with("--- running\n+++ candidate\n@@ /interfaces/interface[name='eth0'] @@\n"));
+    assert!(unified.contains("\n-    <mtu>1500</mtu>\n+    <mtu>9000</mtu>\n"));
+
+    let patch = XmlNode::fragment_to_xml(&diff.to_edit_config()).unwrap();
+    assert_eq!(
+        edit(&mut session, Datastore::Running, &patch, None, None),
+        None
+    );
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert!(ConfigDiff::new(&running, &candidate, &hints).is_empty());
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn backup_and_restore() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let hints = ListHints::new()
+        .list("interface", &["name"])
+        .leaf_list("dns");
+    // whitespace of the text is significant
+    let banner = "<system xmlns=\"urn:example:sys\"><banner>\n  Authorized use only\n</banner><motd> hi </motd></system>";
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, banner, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, CommitType::Plain), None);
+
+    let backup = session
+        .backup(Datastore::Running, "2022-05-01T10:00:00Z")
+        .unwrap();
+    assert_eq!(backup.device, session.target_string());
+    assert!(backup
+        .capabilities
+        .contains(&Capability::Candidate.get_urn()));
+    let xml = backup.to_xml().unwrap();
+    assert!(
+        xml.contains("\n  <datastore>running</datastore>"),
+        "{}",
+        xml
+    );
+    let parsed = ConfigBackup::from_xml(&xml).unwrap();
+    assert_eq!(parsed, backup);
+    let backup = parsed;
+    assert_eq!(backup.datastore, Datastore::Running);
+    assert_eq!(backup.timestamp, "2022-05-01T10:00:00Z");
+    assert_eq!(
+        text_at(&backup.config, &["system", "banner"]),
+        Some("\n  Authorized use only\n")
+    );
+    assert_eq!(text_at(&backup.config, &["system", "motd"]), Some(" hi "));
+
+    for method in [RestoreMethod::Replace, RestoreMethod::Copy] {
+        let changes = r#"<interfaces xmlns="urn:example:if" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+            <interface nc:operation="delete"><name>eth0</name></interface>
+            <interface><name>eth2</name><mtu>1400</mtu></interface>
+        </interfaces>"#;
+        assert_eq!(
+            edit(&mut session, Datastore::Candidate, changes, None, None),
+            None
+        );
+        assert_eq!(commit(&mut session, CommitType::Plain), None);
+        let running = get_config(&mut session, Datastore::Running, None);
+        assert!(!ConfigDiff::new(&backup.config, &running, &hints).is_empty());
+
+        let options = RestoreOptions {
+            method,
+            ..Default::default()
+        };
+        let report = session.restore(&backup, &options).unwrap();
+        assert!(report.validated);
+        assert!(report.confirmed_commit);
+        let running = get_config(&mut session, Datastore::Running, None);
+        assert!(ConfigDiff::new(&backup.config, &running, &hints).is_empty());
+        assert_eq!(
+            text_at(&running, &["system", "banner"]),
+            Some("\n  Authorized use only\n")
+        );
+        assert!(lockable(&simulator, Datastore::Running));
+        assert!(lockable(&simulator, Datastore::Candidate));
+    }
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn restore_mismatch_cancelled() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let backup = session
+        .backup(Datastore::Running, "2022-05-01T10:00:00Z")
+        .unwrap();
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, hostname, None, None),
+        None
+    );
+    assert_eq!(commit(&mut session, CommitType::Plain), None);
+    let edited = simulator.config(&Datastore::Running).unwrap();
+    drop(session);
+    handle.join().unwrap();
+
+    // server not reporting the restored configuration
+    let (transport, handle) = simulator
+        .server()
+        .reply("get-config", Reply::Data(hostname.to_string()))
+        .spawn_in_memory();
+    let mut session = NetconfSession::with_transport(
+        Box::new(transport),
+        vec![Capability::Base, Capability::Base11],
+    );
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+    let options = RestoreOptions {
+        list_hints: ListHints::new()
+            .list("interface", &["name"])
+            .leaf_list("dns"),
+        ..Default::default()
+    };
+    let err = session.restore(&backup, &options).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("does not match the backup, commit cancelled"));
+    assert!(!simulator.is_commit_pending());
+    assert_eq!(simulator.config(&Datastore::Running).unwrap(), edited);
+    assert_eq!(simulator.config(&Datastore::Candidate).unwrap(), edited);
+
+    // inline configuration is not a valid target
+    let request = CopyConfigRequest::new(
+        session.new_message_id(),
+        ConfigWaypoint::Config(hostname.to_string()),
+        ConfigWaypoint::Datastore(Datastore::Running),
+    );
+    let err = session.dispatch_request(request).unwrap_err();
+    assert!(err.to_string().contains("inline <config> is valid only"));
+    let request = DeleteConfigRequest::new(
+        session.new_message_id(),
+        ConfigWaypoint::Config(hostname.to_string()),
+    );
+    assert!(session.dispatch_request(request).is_err());
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn restore_locked() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let backup = session
+        .backup(Datastore::Running, "2022-05-01T10:00:00Z")
+        .unwrap();
+
+    // uncommitted changes of another session are neither overwritten, nor discarded
+    let (mut other, other_handle) = connect(&simulator);
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>other</hostname></system>"#;
+    assert_eq!(
+        edit(&mut other, Datastore::Candidate, hostname, None, None),
+        None
+    );
+    let candidate = simulator.config(&Datastore::Candidate).unwrap();
+    let err = session
+        .restore(&backup, &RestoreOptions::default())
+        .unwrap_err();
+    assert!(err.to_string().contains("lock-denied"), "{}", err);
+    assert_eq!(simulator.config(&Datastore::Candidate).unwrap(), candidate);
+    assert!(lockable(&simulator, Datastore::Running));
+    let request = DiscardChangesRequest::new(other.new_message_id());
+    assert_eq!(
+        other.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    drop(other);
+    other_handle.join().unwrap();
+
+    // locked by another session
+    let (mut other, other_handle) = connect(&simulator);
+    let request = LockRequest::new(other.new_message_id(), Datastore::Running);
+    assert_eq!(
+        other.dispatch_request(request).unwrap().typed.reply,
+        RpcReply::Ok
+    );
+    assert!(session
+        .restore(&backup, &RestoreOptions::default())
+        .is_err());
+    drop(other);
+    other_handle.join().unwrap();
+
+    session
+        .restore(&backup, &RestoreOptions::default())
+        .unwrap();
+    assert!(lockable(&simulator, Datastore::Running));
+    assert!(lockable(&simulator, Datastore::Candidate));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+/// Whether the datastore is not locked by any session, i.e. lockable by a new one.
+fn lockable(simulator: &Simulator, datastore: Datastore) -> bool {
+    let (mut session, handle) = connect(simulator);
+    let request = LockRequest::new(session.new_message_id(), datastore);
+    let reply = session.dispatch_request(request).unwrap().typed.reply;
+    drop(session);
+    handle.join().unwrap();
+    reply == RpcReply::Ok
+}
+
+#[test]
+fn transaction_commit() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+
+    let mut transaction = session.transaction().unwrap();
+    assert_eq!(transaction.target(), &Datastore::Candidate);
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>"#;
+    let mtu = r#"<interfaces xmlns="urn:example:if"><interface><name>eth0</name><mtu>9000</mtu></interface></interfaces>"#;
+    for config in [hostname, mtu] {
+        transaction
+            .edit(EditConfigContent::Config(config.to_string()), None)
+            .unwrap();
+    }
+    assert!(!lockable(&simulator, Datastore::Running));
+    assert!(!lockable(&simulator, Datastore::Candidate));
+    transaction.commit().unwrap();
+
+    let running = get_config(&mut session, Datastore::Running, None);
+    let running = XmlNode::fragment_to_xml(&running).unwrap();
+    assert!(running.contains("<hostname>edited</hostname>"));
+    assert!(running.contains("<mtu>9000</mtu>"));
+    assert!(lockable(&simulator, Datastore::Running));
+    assert!(lockable(&simulator, Datastore::Candidate));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn transaction_rollback() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let running = get_config(&mut session, Datastore::Running, None);
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>"#;
+    let missing = r#"<system xmlns="urn:example:sys" xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+        <domain nc:operation="delete">example.net</domain>
+    </system>"#;
+
+    // failed edit discards the previous ones
+    let mut transaction = session.transaction().unwrap();
+    transaction
+        .edit(EditConfigContent::Config(hostname.to_string()), None)
+        .unwrap();
+    let err = transaction
+        .edit(EditConfigContent::Config(missing.to_string()), None)
+        .unwrap_err();
+    assert!(err.to_string().contains("data-missing"));
+    assert!(transaction.commit().is_err());
+    assert_eq!(
+        get_config(&mut session, Datastore::Candidate, None),
+        running
+    );
+    assert!(lockable(&simulator, Datastore::Running));
+    assert!(lockable(&simulator, Datastore::Candidate));
+
+    // so does dropping the unfinished transaction
+    let mut transaction = session.transaction().unwrap();
+    transaction
+        .edit(EditConfigContent::Config(hostname.to_string()), None)
+        .unwrap();
+    drop(transaction);
+    assert_eq!(
+        get_config(&mut session, Datastore::Candidate, None),
+        running
+    );
+    assert!(lockable(&simulator, Datastore::Running));
+    assert!(lockable(&simulator, Datastore::Candidate));
+    assert_eq!(get_config(&mut session, Datastore::Running, None), running);
+
+    // and unwinding on panic
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut transaction = session.transaction().unwrap();
+        transaction
+            .edit(EditConfigContent::Config(hostname.to_string()), None)
+            .unwrap();
+        panic!("interrupted");
+    }));
+    assert!(result.is_err());
+    assert_eq!(
+        get_config(&mut session, Datastore::Candidate, None),
+        running
+    );
+    assert!(lockable(&simulator, Datastore::Running));
+    assert!(lockable(&simulator, Datastore::Candidate));
+
+    // uncommitted changes of other session are not discarded, when "candidate" cannot be locked
+    let (mut other, other_handle) = connect(&simulator);
+    assert_eq!(
+        edit(&mut other, Datastore::Candidate, hostname, None, None),
+        None
+    );
+    let err = session.transaction().err().unwrap();
+    assert!(err.to_string().contains("lock-denied"));
+    let candidate = get_config(&mut other, Datastore::Candidate, None);
+    assert_eq!(text_at(&candidate, &["system", "hostname"]), Some("edited"));
+    assert!(lockable(&simulator, Datastore::Running));
+    drop(other);
+    other_handle.join().unwrap();
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn transaction_writable_running() {
+    let simulator = simulator().writable_running(true);
+    let capabilities = vec![
+        Capability::Base,
+        Capability::Base11,
+        Capability::WritableRunning,
+        Capability::RollbackOnError,
+        Capability::Validate11,
+    ];
+    let (transport, handle) = simulator
+        .server()
+        .capabilities(capabilities)
+        .spawn_in_memory();
+    let mut session = NetconfSession::with_transport(
+        Box::new(transport),
+        vec![Capability::Base, Capability::Base11],
+    );
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>"#;
+    let mtu = r#"<interfaces xmlns="urn:example:if"><interface><name>eth0</name><mtu>9000</mtu></interface></interfaces>"#;
+
+    let mut transaction = session.transaction().unwrap();
+    assert_eq!(transaction.target(), &Datastore::Running);
+    transaction
+        .edit(EditConfigContent::Config(hostname.to_string()), None)
+        .unwrap();
+    assert!(!lockable(&simulator, Datastore::Running));
+    // the first edit could not be undone on failure of the second one
+    let err = transaction
+        .edit(EditConfigContent::Config(mtu.to_string()), None)
+        .unwrap_err();
+    assert!(err.to_string().contains("single <edit-config>"));
+    transaction.commit().unwrap();
+
+    let running = get_config(&mut session, Datastore::Running, None);
+    assert_eq!(text_at(&running, &["system", "hostname"]), Some("edited"));
+    assert!(!XmlNode::fragment_to_xml(&running)
+        .unwrap()
+        .contains("<mtu>9000</mtu>"));
+    assert!(lockable(&simulator, Datastore::Running));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+/// Wire trace sink shared with the test.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn wire_trace_redacted() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let buffer = SharedBuffer::default();
+    session.set_wire_trace(Some(
+        WireTrace::new(Box::new(buffer.clone())).with_secret_element("community"),
+    ));
+
+    let user = r#"<system xmlns="urn:example:sys"><user><name>admin</name><password>s3cret</password>
+        <community>public</community></user></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, user, None, None),
+        None
+    );
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    assert!(XmlNode::fragment_to_xml(&candidate)
+        .unwrap()
+        .contains("<password>s3cret</password>"));
+    session.set_wire_trace(None);
+
+    let trace = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(trace.matches(">>> sent ").count(), 2);
+    assert_eq!(trace.matches("<<< received ").count(), 2);
+    assert!(trace.contains("<edit-config>"));
+    assert_eq!(trace.matches("<password>***</password>").count(), 2);
+    assert_eq!(trace.matches("<community>***</community>").count(), 2);
+    assert!(!trace.contains("s3cret"));
+    assert!(!trace.contains("public"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn wire_trace_redacted_within_chunks() {
+    let simulator = simulator();
+    let (transport, handle) = simulator.server().max_chunk_size(7).spawn_in_memory();
+    let mut session = NetconfSession::with_transport(
+        Box::new(transport),
+        vec![Capability::Base, Capability::Base11],
+    );
+    session.connect().unwrap();
+    session.exchange_hello().unwrap();
+    let buffer = SharedBuffer::default();
+    session.set_wire_trace(Some(WireTrace::new(Box::new(buffer.clone()))));
+
+    let user = r#"<system xmlns="urn:example:sys"><user><name>admin</name>
+        <password><![CDATA[s3cret<>]]></password></user></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, user, None, None),
+        None
+    );
+    let candidate = get_config(&mut session, Datastore::Candidate, None);
+    assert!(XmlNode::fragment_to_xml(&candidate)
+        .unwrap()
+        .contains("<password>s3cret&lt;&gt;</password>"));
+    session.set_wire_trace(None);
+
+    let trace = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(trace.contains(", chunks 7+7+"));
+    assert_eq!(trace.matches("<password>***</password>").count(), 2);
+    assert!(!trace.contains("s3cret"));
+    assert!(!trace.contains("\n#7\n"));
+
+    drop(session);
+    handle.join().unwrap();
+}
+
+#[test]
+fn transcript_record_and_replay() {
+    let simulator = simulator();
+    let (mut session, handle) = connect(&simulator);
+    let buffer = SharedBuffer::default();
+    session
+        .set_recorder(Some(TranscriptRecorder::new(Box::new(buffer.clone()))))
+        .unwrap();
+    let hostname = r#"<system xmlns="urn:example:sys"><hostname>edited</hostname></system>"#;
+    assert_eq!(
+        edit(&mut session, Datastore::Candidate, hostname, None, None),
+        None
+    );
+    let request = GetConfigRequest::new(session.new_message_id(), Datastore::Candidate, None);
+    let recorded = session.dispatch_request(request).unwrap();
+    session.set_recorder(None).unwrap();
+    drop(session);
+    handle.join().unwrap();
+
+    let content = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let transcript = Transcript::parse(&content).unwrap();
+    assert_eq!(transcript.to_json_lines().unwrap(), content);
+    let directions: Vec<Direction> = transcript.messages.iter().map(|x| x.direction).collect();
+    assert_eq!(directions, [Direction::Sent, Direction::Received].repeat(3));
+    assert!(transcript.messages[0].message.contains("<hello"));
+    assert!(transcript.messages[1].message.contains("<session-id>"));
+    assert!(transcript.messages[2].message.contains("<edit-config>"));
+    assert_eq!(transcript.messages[5].message, recorded.dump);
+
+    // replayed offline, framed as negotiated by the recorded <hello>
+    let mut replay = NetconfSession::with_transport(
+        Box::new(ReplayTransport::new(transcript)),
+        vec![Capability::Base, Capability::Base11],
+    );
+    replay.connect().unwrap();
+    replay.exchange_hello().unwrap();
+    assert_eq!(replay.target_string(), "replay");
+    assert_eq!(replay.base_capability(), BaseCapability::Base11);
+    assert_eq!(
+        edit(&mut replay, Datastore::Candidate, hostname, None, None),
+        None
+    );
+    let request = GetConfigRequest::new(replay.new_message_id(), Datastore::Candidate, None);
+    let replayed = replay.dispatch_request(request).unwrap();
+    assert_eq!(replayed.dump, recorded.dump);
+    assert_eq!(replayed.data().unwrap(), recorded.data().unwrap());
+
+    // end of the transcript closes the stream
+    let request = GetConfigRequest::new(replay.new_message_id(), Datastore::Running, None);
+    assert!(replay.dispatch_request(request).is_err());
+}
+
+#[test]
+fn transcript_redacted() {
+    let simulator = simulator();
+    let user = r#"<system xmlns="urn:example:sys"><user><name>admin</name><password>s3cret</password></user></system>"#;
+    let record = |recorder: fn(Box<dyn Write + Send>) -> TranscriptRecorder| {
+        let (mut session, handle) = connect(&simulator);
+        let buffer = SharedBuffer::default();
+        session
+            .set_recorder(Some(recorder(Box::new(buffer.clone()))))
+            .unwrap();
+        assert_eq!(
+            edit(&mut session, Datastore::Candidate, user, None, None),
+            None
+        );
+        get_config(&mut session, Datastore::Candidate, None);
+        session.set_recorder(None).unwrap();
+        drop(session);
+        handle.join().unwrap();
+        let content = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        Transcript::parse(&content).unwrap()
+    };
+
+    let transcript = record(TranscriptRecorder::new);
+    let messages: Vec<&str> = transcript
+        .messages
+        .iter()
+        .map(|x| x.message.as_str())
+        .collect();
+    assert!(messages[2].contains("<password>***</password>"));
+    assert!(messages[5].contains("<password>***</password>"));
+    assert!(!messages.iter().any(|x| x.contains("s3cret")));
+
+    // kept for the offline regression fixtures
+    let transcript = record(|writer| TranscriptRecorder::new(writer).unredacted());
+    assert!(transcript.messages[2].message.contains(user));
+    assert!(transcript.messages[5]
+        .message
+        .contains("<password>s3cret</password>"));
+}